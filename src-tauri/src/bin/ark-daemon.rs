@@ -0,0 +1,6 @@
+// Headless server manager: owns the server processes, the scheduler, the
+// monitor and the Discord bot. The desktop app connects to it when running.
+
+fn main() {
+    ark_server_manager_lib::run_daemon()
+}
@@ -0,0 +1,6 @@
+// Command-line client for scripting the manager: list, start, stop, backups,
+// RCON and schedules, with `--json` output for scripts and cron jobs.
+
+fn main() {
+    ark_server_manager_lib::run_arkctl()
+}
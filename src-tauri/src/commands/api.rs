@@ -0,0 +1,30 @@
+use crate::models::{ApiToken, NewApiToken};
+use crate::services::api_tokens;
+use crate::AppState;
+use tauri::State;
+
+// Tokens for the HTTP API. The API itself is switched on with the
+// `http_api_enabled` and `http_api_bind` settings and starts with the app
+// or daemon that owns the servers.
+
+#[tauri::command]
+pub async fn list_api_tokens(state: State<'_, AppState>) -> Result<Vec<ApiToken>, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let conn = db.get_connection().map_err(|e| e.to_string())?;
+    api_tokens::list(&conn).map_err(|e| e.to_string())
+}
+
+/// The secret in the result is shown once; only its hash is kept.
+#[tauri::command]
+pub async fn create_api_token(state: State<'_, AppState>, name: String, scopes: Vec<String>) -> Result<NewApiToken, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let conn = db.get_connection().map_err(|e| e.to_string())?;
+    api_tokens::create(&conn, &name, &scopes)
+}
+
+#[tauri::command]
+pub async fn revoke_api_token(state: State<'_, AppState>, token_id: i64) -> Result<(), String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let conn = db.get_connection().map_err(|e| e.to_string())?;
+    api_tokens::revoke(&conn, token_id)
+}
@@ -1,4 +1,4 @@
-use crate::models::{Backup, BackupType};
+use crate::models::{Backup, BackupType, WebhookEvent};
 use crate::AppState;
 use tauri::State;
 use std::fs;
@@ -6,6 +6,7 @@ use std::path::{Path, PathBuf};
 use chrono::Local;
 use walkdir::WalkDir;
 use serde::Serialize;
+use crate::services::{platform, server_status, servers, webhooks};
 
 #[derive(Serialize)]
 pub struct BackupFileInfo {
@@ -45,8 +46,24 @@ fn calculate_dir_size(path: &Path) -> u64 {
 
 #[tauri::command]
 pub async fn create_backup(state: State<'_, AppState>, server_id: i64, backup_type: String, note: Option<String>) -> Result<Backup, String> {
+    backup_server(&state, server_id, backup_type, note).await
+}
+
+/// Body of `create_backup`, callable without a Tauri `State` (scheduler, daemon).
+pub async fn backup_server(state: &AppState, server_id: i64, backup_type: String, note: Option<String>) -> Result<Backup, String> {
+    let result = take_backup(state, server_id, &backup_type, note).await;
+    if let Err(e) = &result {
+        webhooks::fire(state, WebhookEvent::BackupFailed, server_id, serde_json::json!({
+            "backupType": backup_type,
+            "error": e
+        }));
+    }
+    result
+}
+
+async fn take_backup(state: &AppState, server_id: i64, backup_type: &str, note: Option<String>) -> Result<Backup, String> {
     // 1. Validate backup type
-    let backup_type_enum = match backup_type.as_str() {
+    let backup_type_enum = match backup_type {
         "auto" => BackupType::Auto,
         "manual" => BackupType::Manual,
         "pre-update" => BackupType::PreUpdate,
@@ -54,16 +71,16 @@ pub async fn create_backup(state: State<'_, AppState>, server_id: i64, backup_ty
     };
 
     // 2. Get server details
-    let (install_path, server_name) = {
+    let (install_path, server_name, server_type) = {
         let db = state.db.lock().map_err(|e| e.to_string())?;
         let conn = db.get_connection().map_err(|e| e.to_string())?;
         // Ensure table has note column (simple migration attempt)
         let _ = conn.execute("ALTER TABLE backups ADD COLUMN note TEXT", []);
 
-        let mut stmt = conn.prepare("SELECT install_path, name FROM servers WHERE id = ?1").map_err(|e| e.to_string())?;
+        let mut stmt = conn.prepare("SELECT install_path, name, server_type FROM servers WHERE id = ?1").map_err(|e| e.to_string())?;
         
         stmt.query_row([server_id], |row| {
-            Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+            Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?, row.get::<_, String>(2)?))
         }).map_err(|e| e.to_string())?
     };
 
@@ -94,8 +111,8 @@ pub async fn create_backup(state: State<'_, AppState>, server_id: i64, backup_ty
         false
     };
 
-    // Backup Config (Settings) -> ShooterGame/Saved/Config/WindowsServer
-    let config_src = save_path.join("Config/WindowsServer");
+    // Backup Config (Settings) -> ShooterGame/Saved/Config/<WindowsServer|LinuxServer>
+    let config_src = platform::config_dir(Path::new(&install_path), &server_type);
     let config_dst = destination.join("Config");
     let includes_configs = if config_src.exists() {
         copy_dir_recursive(&config_src, &config_dst).map_err(|e| e.to_string())?;
@@ -167,6 +184,11 @@ pub async fn create_backup(state: State<'_, AppState>, server_id: i64, backup_ty
 
 #[tauri::command]
 pub async fn get_backups(state: State<'_, AppState>, server_id: i64) -> Result<Vec<Backup>, String> {
+    backups_for(&state, server_id)
+}
+
+/// Body of `get_backups`, callable without a Tauri `State` (arkctl).
+pub fn backups_for(state: &AppState, server_id: i64) -> Result<Vec<Backup>, String> {
     let db = state.db.lock().map_err(|e| e.to_string())?;
     let conn = db.get_connection().map_err(|e| e.to_string())?;
 
@@ -265,6 +287,11 @@ pub async fn view_backup_content(_state: State<'_, AppState>, backup_path: Strin
 
 #[tauri::command]
 pub async fn restore_backup(state: State<'_, AppState>, backup_id: i64) -> Result<(), String> {
+    restore(&state, backup_id)
+}
+
+/// Body of `restore_backup`, callable without a Tauri `State` (arkctl).
+pub fn restore(state: &AppState, backup_id: i64) -> Result<(), String> {
      let (file_path, server_id, includes_configs) = {
         let db = state.db.lock().map_err(|e| e.to_string())?;
         let conn = db.get_connection().map_err(|e| e.to_string())?;
@@ -277,11 +304,19 @@ pub async fn restore_backup(state: State<'_, AppState>, backup_id: i64) -> Resul
         ).map_err(|e| e.to_string())?
     };
 
+    {
+        let db = state.db.lock().map_err(|e| e.to_string())?;
+        let conn = db.get_connection().map_err(|e| e.to_string())?;
+        if servers::is_active(&server_status::current(&conn, server_id)?) {
+            return Err("Stop the server before restoring a backup".to_string());
+        }
+    }
+
     // Get install path
-    let install_path: String = {
+    let (install_path, server_type): (String, String) = {
         let db = state.db.lock().map_err(|e| e.to_string())?;
         let conn = db.get_connection().map_err(|e| e.to_string())?;
-        conn.query_row("SELECT install_path FROM servers WHERE id = ?1", [server_id], |row| row.get(0))
+        conn.query_row("SELECT install_path, server_type FROM servers WHERE id = ?1", [server_id], |row| Ok((row.get(0)?, row.get(1)?)))
             .map_err(|e| e.to_string())?
     };
 
@@ -306,7 +341,7 @@ pub async fn restore_backup(state: State<'_, AppState>, backup_id: i64) -> Resul
 
     // Restore Configs if available
     if includes_configs {
-         let target_config_dir = server_root.join("ShooterGame/Saved/Config/WindowsServer");
+         let target_config_dir = platform::config_dir(&server_root, &server_type);
          let backup_config_dir = backup_root.join("Config");
          
          if backup_config_dir.exists() {
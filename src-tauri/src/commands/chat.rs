@@ -0,0 +1,19 @@
+use crate::models::ChatMessage;
+use crate::services::chat;
+use crate::AppState;
+use tauri::State;
+
+/// Captured chat, newest first. Pass the smallest `id` seen as `before_id`
+/// for the page before it.
+#[tauri::command]
+pub async fn get_chat_messages(
+    state: State<'_, AppState>,
+    server_id: Option<i64>,
+    query: Option<String>,
+    before_id: Option<i64>,
+    limit: Option<usize>,
+) -> Result<Vec<ChatMessage>, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let conn = db.get_connection().map_err(|e| e.to_string())?;
+    chat::search(&conn, server_id, query.as_deref(), before_id, limit.unwrap_or(100).min(1000)).map_err(|e| e.to_string())
+}
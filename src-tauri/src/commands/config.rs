@@ -3,23 +3,40 @@ use crate::AppState;
 use std::fs;
 use std::path::PathBuf;
 use std::time::UNIX_EPOCH;
+use crate::services::platform;
 
-#[tauri::command]
-pub async fn read_config(state: State<'_, AppState>, server_id: i64, config_type: String) -> Result<String, String> {
-    // Get server install path from DB
-    let install_path: String = {
+/// Resolve the INI directory the server actually reads (WindowsServer or LinuxServer)
+fn server_config_dir(state: &AppState, server_id: i64) -> Result<PathBuf, String> {
+    let (install_path, server_type): (String, String) = {
         let db = state.db.lock().map_err(|e| e.to_string())?;
         let conn = db.get_connection().map_err(|e| e.to_string())?;
         conn.query_row(
-            "SELECT install_path FROM servers WHERE id = ?1",
+            "SELECT install_path, server_type FROM servers WHERE id = ?1",
             [server_id],
-            |row| row.get(0),
+            |row| Ok((row.get(0)?, row.get(1)?)),
         ).map_err(|e| e.to_string())?
     };
 
-    let path = PathBuf::from(install_path)
-        .join("ShooterGame/Saved/Config/WindowsServer")
-        .join(format!("{}.ini", config_type));
+    Ok(platform::config_dir(&PathBuf::from(install_path), &server_type))
+}
+
+/// `<config_type>.ini` in the server's config directory. The name must be
+/// a plain word such as `GameUserSettings`, never a path.
+fn config_path(state: &AppState, server_id: i64, config_type: &str) -> Result<PathBuf, String> {
+    if config_type.is_empty() || !config_type.chars().all(|c| c.is_ascii_alphanumeric()) {
+        return Err(format!("Invalid config file \"{}\"", config_type));
+    }
+    Ok(server_config_dir(state, server_id)?.join(format!("{}.ini", config_type)))
+}
+
+#[tauri::command]
+pub async fn read_config(state: State<'_, AppState>, server_id: i64, config_type: String) -> Result<String, String> {
+    read(&state, server_id, &config_type)
+}
+
+/// Body of `read_config`, callable without a Tauri `State` (HTTP API).
+pub fn read(state: &AppState, server_id: i64, config_type: &str) -> Result<String, String> {
+    let path = config_path(state, server_id, config_type)?;
 
     if path.exists() {
         fs::read_to_string(path).map_err(|e| e.to_string())
@@ -31,43 +48,23 @@ pub async fn read_config(state: State<'_, AppState>, server_id: i64, config_type
 
 #[tauri::command]
 pub async fn save_config(state: State<'_, AppState>, server_id: i64, config_type: String, content: String) -> Result<(), String> {
-    // Get server install path from DB
-    let install_path: String = {
-        let db = state.db.lock().map_err(|e| e.to_string())?;
-        let conn = db.get_connection().map_err(|e| e.to_string())?;
-        conn.query_row(
-            "SELECT install_path FROM servers WHERE id = ?1",
-            [server_id],
-            |row| row.get(0),
-        ).map_err(|e| e.to_string())?
-    };
+    save(&state, server_id, &config_type, &content)
+}
 
-    let dir_path = PathBuf::from(install_path)
-        .join("ShooterGame/Saved/Config/WindowsServer");
-        
-    fs::create_dir_all(&dir_path).map_err(|e| e.to_string())?;
-    
-    let file_path = dir_path.join(format!("{}.ini", config_type));
+/// Body of `save_config`, callable without a Tauri `State` (HTTP API).
+pub fn save(state: &AppState, server_id: i64, config_type: &str, content: &str) -> Result<(), String> {
+    let file_path = config_path(state, server_id, config_type)?;
+
+    if let Some(config_dir) = file_path.parent() {
+        fs::create_dir_all(config_dir).map_err(|e| e.to_string())?;
+    }
     
     fs::write(file_path, content).map_err(|e| e.to_string())
 }
 
 #[tauri::command]
 pub async fn get_config_modified_time(state: State<'_, AppState>, server_id: i64, config_type: String) -> Result<u64, String> {
-    // Get server install path from DB
-    let install_path: String = {
-        let db = state.db.lock().map_err(|e| e.to_string())?;
-        let conn = db.get_connection().map_err(|e| e.to_string())?;
-        conn.query_row(
-            "SELECT install_path FROM servers WHERE id = ?1",
-            [server_id],
-            |row| row.get(0),
-        ).map_err(|e| e.to_string())?
-    };
-
-    let path = PathBuf::from(install_path)
-        .join("ShooterGame/Saved/Config/WindowsServer")
-        .join(format!("{}.ini", config_type));
+    let path = config_path(&state, server_id, &config_type)?;
 
     if path.exists() {
         let metadata = fs::metadata(&path).map_err(|e| e.to_string())?;
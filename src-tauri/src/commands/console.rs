@@ -0,0 +1,62 @@
+use crate::services::console::ConsoleLine;
+use crate::AppState;
+use tauri::State;
+
+const DEFAULT_HISTORY_LIMIT: usize = 500;
+const DEFAULT_SEARCH_LIMIT: usize = 200;
+
+/// Recent console output of a server, oldest first. Pass the `seq` of the
+/// last line you have as `since` to get only what came after it.
+#[tauri::command]
+pub async fn get_console_history(
+    state: State<'_, AppState>,
+    server_id: i64,
+    since: Option<u64>,
+    limit: Option<usize>,
+) -> Result<Vec<ConsoleLine>, String> {
+    if let Some(daemon) = &state.daemon {
+        let lines = daemon.call("get_console_history", serde_json::json!({
+            "serverId": server_id,
+            "since": since,
+            "limit": limit
+        })).await?;
+        return serde_json::from_value(lines).map_err(|e| e.to_string());
+    }
+    Ok(history(&state, server_id, since, limit))
+}
+
+/// Body of `get_console_history`, callable without a Tauri `State` (daemon).
+pub fn history(state: &AppState, server_id: i64, since: Option<u64>, limit: Option<usize>) -> Vec<ConsoleLine> {
+    state.console.history(server_id, since, limit.unwrap_or(DEFAULT_HISTORY_LIMIT))
+}
+
+/// Console lines containing `query`, searched through the whole on-disk log.
+#[tauri::command]
+pub async fn search_console_history(
+    state: State<'_, AppState>,
+    server_id: i64,
+    query: String,
+    limit: Option<usize>,
+) -> Result<Vec<ConsoleLine>, String> {
+    if let Some(daemon) = &state.daemon {
+        let lines = daemon.call("search_console_history", serde_json::json!({
+            "serverId": server_id,
+            "query": query,
+            "limit": limit
+        })).await?;
+        return serde_json::from_value(lines).map_err(|e| e.to_string());
+    }
+    search(&state, server_id, query, limit).await
+}
+
+/// Body of `search_console_history`, callable without a Tauri `State` (daemon).
+pub async fn search(state: &AppState, server_id: i64, query: String, limit: Option<usize>) -> Result<Vec<ConsoleLine>, String> {
+    if query.trim().is_empty() {
+        return Err("Search query can't be empty".to_string());
+    }
+    let console = state.console.clone();
+    let limit = limit.unwrap_or(DEFAULT_SEARCH_LIMIT);
+    tauri::async_runtime::spawn_blocking(move || console.search(server_id, &query, limit))
+        .await
+        .map_err(|e| e.to_string())
+}
@@ -0,0 +1,134 @@
+use crate::models::{CrashBundle, CrashBundleDetail, CrashBundleFile};
+use crate::services::crash_bundle;
+use crate::services::file_manager::FileManager;
+use crate::AppState;
+use rusqlite::Connection;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tauri::State;
+use walkdir::WalkDir;
+
+/// Bigger files (minidumps, long logs) are exported rather than shown.
+const MAX_VIEW_SIZE: u64 = 5 * 1024 * 1024;
+
+const BUNDLE_COLUMNS: &str = "id, server_id, exit_code, bundle_path, size, created_at";
+
+fn bundle_from_row(row: &rusqlite::Row) -> rusqlite::Result<CrashBundle> {
+    Ok(CrashBundle {
+        id: row.get(0)?,
+        server_id: row.get(1)?,
+        exit_code: row.get(2)?,
+        bundle_path: PathBuf::from(row.get::<_, String>(3)?),
+        size: row.get(4)?,
+        created_at: row.get(5)?,
+    })
+}
+
+fn load_bundle(conn: &Connection, bundle_id: i64) -> Result<CrashBundle, String> {
+    conn.query_row(
+        &format!("SELECT {} FROM crash_bundles WHERE id = ?1", BUNDLE_COLUMNS),
+        [bundle_id],
+        bundle_from_row,
+    ).map_err(|e| e.to_string())
+}
+
+/// Crash bundles, newest first; all servers when `server_id` is omitted.
+#[tauri::command]
+pub async fn get_crash_bundles(state: State<'_, AppState>, server_id: Option<i64>) -> Result<Vec<CrashBundle>, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let conn = db.get_connection().map_err(|e| e.to_string())?;
+
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM crash_bundles WHERE ?1 IS NULL OR server_id = ?1 ORDER BY id DESC",
+        BUNDLE_COLUMNS
+    )).map_err(|e| e.to_string())?;
+
+    let bundles = stmt.query_map([server_id], bundle_from_row)
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    Ok(bundles)
+}
+
+#[tauri::command]
+pub async fn get_crash_bundle(state: State<'_, AppState>, bundle_id: i64) -> Result<CrashBundleDetail, String> {
+    let bundle = {
+        let db = state.db.lock().map_err(|e| e.to_string())?;
+        let conn = db.get_connection().map_err(|e| e.to_string())?;
+        load_bundle(&conn, bundle_id)?
+    };
+
+    let summary = fs::read_to_string(bundle.bundle_path.join("summary.json"))
+        .ok()
+        .and_then(|s| serde_json::from_str(&s).ok())
+        .unwrap_or(serde_json::Value::Null);
+
+    let files = WalkDir::new(&bundle.bundle_path)
+        .min_depth(1)
+        .sort_by_file_name()
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_file())
+        .filter_map(|e| {
+            let relative = e.path().strip_prefix(&bundle.bundle_path).ok()?;
+            Some(CrashBundleFile {
+                path: relative.to_string_lossy().replace('\\', "/"),
+                size: e.metadata().ok()?.len(),
+            })
+        })
+        .collect();
+
+    Ok(CrashBundleDetail { bundle, summary, files })
+}
+
+/// Contents of one text file in a bundle, such as `console.log` or a log from `Logs/`.
+#[tauri::command]
+pub async fn read_crash_bundle_file(state: State<'_, AppState>, bundle_id: i64, path: String) -> Result<String, String> {
+    let bundle = {
+        let db = state.db.lock().map_err(|e| e.to_string())?;
+        let conn = db.get_connection().map_err(|e| e.to_string())?;
+        load_bundle(&conn, bundle_id)?
+    };
+
+    let file = crash_bundle::bundle_file(&bundle.bundle_path, &path).map_err(|e| e.to_string())?;
+    let size = fs::metadata(&file).map_err(|e| e.to_string())?.len();
+    if size > MAX_VIEW_SIZE {
+        return Err(format!("{} is too large to view ({} MB); export the bundle instead", path, size / (1024 * 1024)));
+    }
+
+    let bytes = fs::read(&file).map_err(|e| e.to_string())?;
+    Ok(String::from_utf8_lossy(&bytes).to_string())
+}
+
+/// Zip the whole bundle to `destination`, e.g. to attach to a bug report.
+#[tauri::command]
+pub async fn export_crash_bundle(state: State<'_, AppState>, bundle_id: i64, destination: String) -> Result<(), String> {
+    let bundle = {
+        let db = state.db.lock().map_err(|e| e.to_string())?;
+        let conn = db.get_connection().map_err(|e| e.to_string())?;
+        load_bundle(&conn, bundle_id)?
+    };
+
+    tauri::async_runtime::spawn_blocking(move || {
+        FileManager::create_zip(&bundle.bundle_path, Path::new(&destination))
+    })
+    .await
+    .map_err(|e| e.to_string())?
+    .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn delete_crash_bundle(state: State<'_, AppState>, bundle_id: i64) -> Result<(), String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let conn = db.get_connection().map_err(|e| e.to_string())?;
+    let bundle = load_bundle(&conn, bundle_id)?;
+
+    if bundle.bundle_path.exists() {
+        fs::remove_dir_all(&bundle.bundle_path).map_err(|e| e.to_string())?;
+    }
+    conn.execute("DELETE FROM crash_bundles WHERE id = ?1", [bundle_id])
+        .map_err(|e| e.to_string())?;
+
+    Ok(())
+}
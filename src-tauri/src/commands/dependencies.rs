@@ -1,28 +1,29 @@
-use tauri::{command, AppHandle};
+use tauri::{command, State};
+use crate::AppState;
 use crate::services::steamcmd::SteamCmdService;
 
 #[command]
-pub async fn check_steamcmd_installed(app_handle: AppHandle) -> Result<bool, String> {
-    let service = SteamCmdService::new(app_handle);
+pub async fn check_steamcmd_installed(state: State<'_, AppState>) -> Result<bool, String> {
+    let service = SteamCmdService::new(&state);
     Ok(service.check_installation())
 }
 
 #[command]
-pub async fn install_steamcmd(app_handle: AppHandle) -> Result<(), String> {
-    let service = SteamCmdService::new(app_handle);
+pub async fn install_steamcmd(state: State<'_, AppState>) -> Result<(), String> {
+    let service = SteamCmdService::new(&state);
     service.install().await.map_err(|e| e.to_string())
 }
 
 #[command]
-pub async fn get_steamcmd_path(app_handle: AppHandle) -> Result<String, String> {
-    let service = SteamCmdService::new(app_handle);
+pub async fn get_steamcmd_path(state: State<'_, AppState>) -> Result<String, String> {
+    let service = SteamCmdService::new(&state);
     let path = service.get_steamcmd_dir().map_err(|e| e.to_string())?;
     Ok(path.to_string_lossy().to_string())
 }
 
 #[command]
-pub async fn check_all_dependencies(app_handle: AppHandle) -> Result<DependencyStatus, String> {
-    let service = SteamCmdService::new(app_handle);
+pub async fn check_all_dependencies(state: State<'_, AppState>) -> Result<DependencyStatus, String> {
+    let service = SteamCmdService::new(&state);
     
     Ok(DependencyStatus {
         steamcmd_installed: service.check_installation(),
@@ -60,14 +61,14 @@ fn check_vcredist() -> bool {
                 }
             }
         }
+
+        false
     }
     
     #[cfg(not(target_os = "windows"))]
     {
         true // Non-Windows platforms don't need VC++ redistributables
     }
-    
-    false
 }
 
 fn check_dotnet() -> bool {
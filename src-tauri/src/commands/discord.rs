@@ -1,7 +1,7 @@
 use crate::services::discord_bot;
 use crate::AppState;
 use serde::{Deserialize, Serialize};
-use tauri::{AppHandle, State, Manager};
+use tauri::State;
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct DiscordBotConfig {
@@ -21,13 +21,12 @@ pub struct BotStatus {
 /// Start the Discord bot with the given token
 #[tauri::command]
 pub async fn start_discord_bot(
-    app: AppHandle,
     state: State<'_, AppState>,
     token: String,
     guild_id: Option<String>,
 ) -> Result<(), String> {
-    // Check if bot is already running
-    {
+    // Check if bot is already running (the daemon checks its own)
+    if state.daemon.is_none() {
         let bot_handle = state.discord_bot.lock().map_err(|e| e.to_string())?;
         if bot_handle.is_running {
             return Err("Discord bot is already running".to_string());
@@ -62,12 +61,19 @@ pub async fn start_discord_bot(
         ).map_err(|e| e.to_string())?;
     }
 
+    if let Some(daemon) = &state.daemon {
+        return daemon
+            .call("start_discord_bot", serde_json::json!({ "token": token, "guildId": guild_id_parsed }))
+            .await
+            .map(|_| ());
+    }
+
     // Start the bot in the background
-    let app_handle = app.clone();
+    let core = state.inner().clone();
     let token_clone = token.clone();
     
     tauri::async_runtime::spawn(async move {
-        if let Err(e) = discord_bot::start_bot(app_handle, token_clone, guild_id_parsed).await {
+        if let Err(e) = discord_bot::start_bot(core, token_clone, guild_id_parsed).await {
             eprintln!("Discord bot error: {}", e);
         }
     });
@@ -79,13 +85,17 @@ pub async fn start_discord_bot(
 /// Stop the Discord bot
 #[tauri::command]
 pub async fn stop_discord_bot(state: State<'_, AppState>) -> Result<(), String> {
-    let mut bot_handle = state.discord_bot.lock().map_err(|e| e.to_string())?;
-    
-    if !bot_handle.is_running {
-        return Err("Discord bot is not running".to_string());
-    }
+    if let Some(daemon) = &state.daemon {
+        daemon.call("stop_discord_bot", serde_json::Value::Null).await?;
+    } else {
+        let mut bot_handle = state.discord_bot.lock().map_err(|e| e.to_string())?;
+        
+        if !bot_handle.is_running {
+            return Err("Discord bot is not running".to_string());
+        }
 
-    bot_handle.stop();
+        bot_handle.stop();
+    }
 
     // Update settings
     {
@@ -104,6 +114,11 @@ pub async fn stop_discord_bot(state: State<'_, AppState>) -> Result<(), String>
 /// Get the current status of the Discord bot
 #[tauri::command]
 pub async fn get_discord_bot_status(state: State<'_, AppState>) -> Result<BotStatus, String> {
+    if let Some(daemon) = &state.daemon {
+        let status = daemon.call("discord_bot_status", serde_json::Value::Null).await?;
+        return serde_json::from_value(status).map_err(|e| e.to_string());
+    }
+
     let bot_handle = state.discord_bot.lock().map_err(|e| e.to_string())?;
     
     Ok(BotStatus {
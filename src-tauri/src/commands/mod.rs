@@ -1,16 +1,22 @@
+pub mod api;
 pub mod app_updater;
 pub mod backup;
+pub mod chat;
 pub mod cluster;
 pub mod config;
+pub mod console;
+pub mod crash;
 pub mod dependencies;
 pub mod discord;
 pub mod files;
 pub mod map;
 pub mod mods;
 pub mod network;
+pub mod players;
 pub mod rcon;
 pub mod scheduler;
 pub mod security;
 pub mod server;
 pub mod system;
 pub mod tribe;
+pub mod webhooks;
@@ -14,14 +14,15 @@ use tauri::State;
 use crate::AppState;
 use std::path::PathBuf;
 use std::fs;
+use crate::services::platform;
 
 // ============================================================================
 // HELPER FUNCTIONS
 // ============================================================================
 
 /// Get the path to GameUserSettings.ini
-fn get_ini_path(install_path: &str) -> PathBuf {
-    PathBuf::from(install_path).join("ShooterGame/Saved/Config/WindowsServer/GameUserSettings.ini")
+fn get_ini_path(install_path: &str, server_type: &str) -> PathBuf {
+    platform::config_dir(&PathBuf::from(install_path), server_type).join("GameUserSettings.ini")
 }
 
 /// Get the path to the Mods folder
@@ -30,8 +31,8 @@ fn get_mods_folder(install_path: &str) -> PathBuf {
 }
 
 /// Read ActiveMods from INI file
-fn read_active_mods(install_path: &str) -> Vec<String> {
-    let ini_path = get_ini_path(install_path);
+fn read_active_mods(install_path: &str, server_type: &str) -> Vec<String> {
+    let ini_path = get_ini_path(install_path, server_type);
     
     if !ini_path.exists() {
         println!("  ⚠️ INI file not found: {:?}", ini_path);
@@ -73,8 +74,8 @@ fn read_active_mods(install_path: &str) -> Vec<String> {
 }
 
 /// Write ActiveMods to INI file
-fn write_active_mods(install_path: &str, mod_ids: &[String]) -> Result<(), String> {
-    let ini_path = get_ini_path(install_path);
+fn write_active_mods(install_path: &str, server_type: &str, mod_ids: &[String]) -> Result<(), String> {
+    let ini_path = get_ini_path(install_path, server_type);
     
     if !ini_path.exists() {
         return Err("INI file not found".to_string());
@@ -127,25 +128,30 @@ fn write_active_mods(install_path: &str, mod_ids: &[String]) -> Result<(), Strin
 // TAURI COMMANDS
 // ============================================================================
 
-/// Get server install path from database (only DB access we need)
-fn get_server_path(state: &State<'_, AppState>, server_id: i64) -> Result<String, String> {
+/// Get server install path and type from database (only DB access we need)
+fn get_server_path(state: &AppState, server_id: i64) -> Result<(String, String), String> {
     let db = state.db.lock().map_err(|e| e.to_string())?;
     let conn = db.get_connection().map_err(|e| e.to_string())?;
     
     conn.query_row(
-        "SELECT install_path FROM servers WHERE id = ?1",
+        "SELECT install_path, server_type FROM servers WHERE id = ?1",
         [server_id],
-        |row| row.get(0),
+        |row| Ok((row.get(0)?, row.get(1)?)),
     ).map_err(|e| format!("Server not found: {}", e))
 }
 
 /// LIST: Get all mods installed on a server (verified against file system)
 #[tauri::command]
 pub async fn get_installed_mods(state: State<'_, AppState>, server_id: i64) -> Result<Vec<ModInfo>, String> {
+    installed_mods(&state, server_id)
+}
+
+/// Body of `get_installed_mods`, callable without a Tauri `State` (HTTP API).
+pub fn installed_mods(state: &AppState, server_id: i64) -> Result<Vec<ModInfo>, String> {
     println!("\n📦 GET INSTALLED MODS (server_id: {})", server_id);
     
     // 1. Get server install path
-    let install_path = get_server_path(&state, server_id)?;
+    let (install_path, server_type) = get_server_path(state, server_id)?;
     println!("  📁 Install path: {}", install_path);
     
     // 2. Check if server folder exists
@@ -155,7 +161,7 @@ pub async fn get_installed_mods(state: State<'_, AppState>, server_id: i64) -> R
     }
     
     // 3. Read ActiveMods from INI
-    let active_mods = read_active_mods(&install_path);
+    let active_mods = read_active_mods(&install_path, &server_type);
     println!("  📋 ActiveMods in INI: {:?}", active_mods);
     
     if active_mods.is_empty() {
@@ -202,15 +208,25 @@ pub async fn get_installed_mods(state: State<'_, AppState>, server_id: i64) -> R
 /// DELETE: Remove a mod from server
 #[tauri::command]
 pub async fn uninstall_mod(state: State<'_, AppState>, server_id: i64, mod_id: String) -> Result<(), String> {
+    remove_mod(&state, server_id, &mod_id)
+}
+
+/// Body of `uninstall_mod`, callable without a Tauri `State` (HTTP API).
+pub fn remove_mod(state: &AppState, server_id: i64, mod_id: &str) -> Result<(), String> {
+    // The id becomes part of a path under the Mods folder
+    if mod_id.is_empty() || !mod_id.chars().all(|c| c.is_ascii_digit()) {
+        return Err(format!("Invalid mod id \"{}\"", mod_id));
+    }
+
     println!("\n🗑️ UNINSTALL MOD (server_id: {}, mod_id: {})", server_id, mod_id);
     
     // 1. Get server install path
-    let install_path = get_server_path(&state, server_id)?;
+    let (install_path, server_type) = get_server_path(state, server_id)?;
     println!("  📁 Install path: {}", install_path);
     
     // 2. Remove mod files
     let mods_folder = get_mods_folder(&install_path);
-    let mod_folder = mods_folder.join(&mod_id);
+    let mod_folder = mods_folder.join(mod_id);
     let mod_file = mods_folder.join(format!("{}.mod", mod_id));
     
     if mod_folder.exists() {
@@ -226,11 +242,11 @@ pub async fn uninstall_mod(state: State<'_, AppState>, server_id: i64, mod_id: S
     }
     
     // 3. Update INI - remove mod from ActiveMods
-    let mut active_mods = read_active_mods(&install_path);
-    active_mods.retain(|m| m != &mod_id);
+    let mut active_mods = read_active_mods(&install_path, &server_type);
+    active_mods.retain(|m| m != mod_id);
     
     println!("  → Updating ActiveMods: {:?}", active_mods);
-    write_active_mods(&install_path, &active_mods)?;
+    write_active_mods(&install_path, &server_type, &active_mods)?;
     
     println!("  ✅ UNINSTALL COMPLETE");
     Ok(())
@@ -239,11 +255,16 @@ pub async fn uninstall_mod(state: State<'_, AppState>, server_id: i64, mod_id: S
 /// UPDATE: Set the mod load order
 #[tauri::command]
 pub async fn update_active_mods(state: State<'_, AppState>, server_id: i64, mod_ids: Vec<String>) -> Result<(), String> {
+    set_active_mods(&state, server_id, &mod_ids)
+}
+
+/// Body of `update_active_mods`, callable without a Tauri `State` (HTTP API).
+pub fn set_active_mods(state: &AppState, server_id: i64, mod_ids: &[String]) -> Result<(), String> {
     println!("\n📝 UPDATE ACTIVE MODS (server_id: {})", server_id);
     println!("  → New order: {:?}", mod_ids);
     
-    let install_path = get_server_path(&state, server_id)?;
-    write_active_mods(&install_path, &mod_ids)?;
+    let (install_path, server_type) = get_server_path(state, server_id)?;
+    write_active_mods(&install_path, &server_type, mod_ids)?;
     
     println!("  ✅ UPDATED");
     Ok(())
@@ -1,3 +1,6 @@
+use crate::models::{PortConflict, ServerPorts, ServerSettingsUpdate};
+use crate::services::port_allocator::{self, CheckScope};
+use crate::services::servers;
 use crate::AppState;
 use tauri::State;
 use local_ip_address::list_afinet_netifas;
@@ -15,6 +18,8 @@ pub async fn get_local_ips() -> Result<Vec<String>, String> {
     Ok(ips)
 }
 
+/// Set or clear the MultiHome IP. Goes through the settings editor, so the
+/// address is checked and refused while the server runs.
 #[tauri::command]
 pub async fn set_network_settings(
     state: State<'_, AppState>,
@@ -23,12 +28,12 @@ pub async fn set_network_settings(
 ) -> Result<(), String> {
     let db = state.db.lock().map_err(|e| e.to_string())?;
     let conn = db.get_connection().map_err(|e| e.to_string())?;
-    
-    conn.execute(
-        "UPDATE servers SET multihome_ip = ?1 WHERE id = ?2",
-        (multihome_ip, server_id),
-    ).map_err(|e| e.to_string())?;
-    
+
+    let update = ServerSettingsUpdate {
+        multihome_ip: Some(multihome_ip.unwrap_or_default()),
+        ..Default::default()
+    };
+    servers::update_settings(&conn, server_id, update)?;
     Ok(())
 }
 
@@ -48,3 +53,32 @@ pub async fn set_crossplay_enabled(
     
     Ok(())
 }
+
+/// Clashes for a set of ports against every other server and what is bound
+/// on this machine. `server_id` is the server being edited, if any; while it
+/// runs its own ports show as bound.
+#[tauri::command]
+pub async fn check_port_conflicts(
+    state: State<'_, AppState>,
+    server_id: Option<i64>,
+    ports: ServerPorts,
+    multihome_ip: Option<String>,
+) -> Result<Vec<PortConflict>, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let conn = db.get_connection().map_err(|e| e.to_string())?;
+    port_allocator::find_conflicts(&conn, server_id, &ports, multihome_ip.as_deref(), CheckScope::AllServers)
+        .map_err(|e| e.to_string())
+}
+
+/// Free game/query/RCON port blocks for new servers, lowest first.
+#[tauri::command]
+pub async fn suggest_server_ports(
+    state: State<'_, AppState>,
+    multihome_ip: Option<String>,
+    count: Option<usize>,
+) -> Result<Vec<ServerPorts>, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let conn = db.get_connection().map_err(|e| e.to_string())?;
+    port_allocator::suggest_ports(&conn, multihome_ip.as_deref(), count.unwrap_or(1))
+        .map_err(|e| e.to_string())
+}
@@ -0,0 +1,46 @@
+use crate::models::{DailyPeak, PlayerSession, PlayerStats};
+use crate::services::player_sessions;
+use crate::AppState;
+use tauri::State;
+
+// Recorded player sessions. Live lists come from `get_online_players`; these
+// read what the session poller wrote, so they lag it by up to 30 seconds.
+
+/// Everyone online, on every server.
+#[tauri::command]
+pub async fn get_all_online_players(state: State<'_, AppState>) -> Result<Vec<PlayerSession>, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let conn = db.get_connection().map_err(|e| e.to_string())?;
+    player_sessions::online(&conn, None).map_err(|e| e.to_string())
+}
+
+/// Playtime and last seen per player, on one server or all of them.
+#[tauri::command]
+pub async fn get_player_stats(state: State<'_, AppState>, server_id: Option<i64>) -> Result<Vec<PlayerStats>, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let conn = db.get_connection().map_err(|e| e.to_string())?;
+    player_sessions::stats(&conn, server_id).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn get_player_history(
+    state: State<'_, AppState>,
+    platform_id: String,
+    limit: Option<usize>,
+) -> Result<Vec<PlayerSession>, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let conn = db.get_connection().map_err(|e| e.to_string())?;
+    player_sessions::history(&conn, &platform_id, limit.unwrap_or(100)).map_err(|e| e.to_string())
+}
+
+/// Peak concurrent players per UTC day, oldest first; 30 days by default.
+#[tauri::command]
+pub async fn get_daily_player_peaks(
+    state: State<'_, AppState>,
+    server_id: Option<i64>,
+    days: Option<u32>,
+) -> Result<Vec<DailyPeak>, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let conn = db.get_connection().map_err(|e| e.to_string())?;
+    player_sessions::daily_peaks(&conn, server_id, days.unwrap_or(30).min(366)).map_err(|e| e.to_string())
+}
@@ -1,70 +1,97 @@
 use tauri::State;
 use crate::AppState;
-use crate::services::rcon_client::RconClient;
+use crate::models::{Ban, ModerationAction, Player};
+use crate::services::{moderation, rcon_sessions};
+
+/// Recorded in the moderation log for actions taken in the app: the OS user
+/// running it.
+fn actor() -> String {
+    match std::env::var("USERNAME").or_else(|_| std::env::var("USER")) {
+        Ok(user) if !user.is_empty() => format!("GUI ({})", user),
+        _ => "GUI".to_string(),
+    }
+}
 
 #[tauri::command]
 pub async fn send_rcon_command(state: State<'_, AppState>, server_id: i64, command: String) -> Result<String, String> {
-    // 1. Get RCON credentials from database
-    let (rcon_port, admin_password) = {
-        let db = state.db.lock().map_err(|e| e.to_string())?;
-        let conn = db.get_connection().map_err(|e| e.to_string())?;
-        
-        let mut stmt = conn.prepare("SELECT rcon_port, admin_password FROM servers WHERE id = ?1")
-            .map_err(|e| e.to_string())?;
-            
-        stmt.query_row([server_id], |row| {
-            Ok((
-                row.get::<_, u16>(0)?,
-                row.get::<_, String>(1)?
-            ))
-        }).map_err(|e| e.to_string())?
-    };
+    send_to_server(&state, server_id, command).await
+}
 
-    // 2. Execute
-    // Note: Localhost for now since manager runs on same machine
-    RconClient::execute("127.0.0.1", rcon_port, &admin_password, &command).await
+/// Body of `send_rcon_command`, callable without a Tauri `State` (scheduler, daemon).
+pub async fn send_to_server(state: &AppState, server_id: i64, command: String) -> Result<String, String> {
+    rcon_sessions::execute(state, server_id, &command).await
 }
 
 #[tauri::command]
-pub async fn get_online_players(state: State<'_, AppState>, server_id: i64) -> Result<Vec<String>, String> {
-   // 1. Get RCON credentials from database
-    let (rcon_port, admin_password) = {
-        let db = state.db.lock().map_err(|e| e.to_string())?;
-        let conn = db.get_connection().map_err(|e| e.to_string())?;
-        
-        let mut stmt = conn.prepare("SELECT rcon_port, admin_password FROM servers WHERE id = ?1")
-            .map_err(|e| e.to_string())?;
-            
-        stmt.query_row([server_id], |row| {
-            Ok((
-                row.get::<_, u16>(0)?,
-                row.get::<_, String>(1)?
-            ))
-        }).map_err(|e| e.to_string())?
-    };
-
-    // 2. Get Players
-    RconClient::get_players("127.0.0.1", rcon_port, &admin_password).await
+pub async fn get_online_players(state: State<'_, AppState>, server_id: i64) -> Result<Vec<Player>, String> {
+    rcon_sessions::players(&state, server_id).await
 }
 
 #[tauri::command]
 pub async fn destroy_wild_dinos(state: State<'_, AppState>, server_id: i64) -> Result<String, String> {
-     // 1. Get RCON credentials from database
-    let (rcon_port, admin_password) = {
-        let db = state.db.lock().map_err(|e| e.to_string())?;
-        let conn = db.get_connection().map_err(|e| e.to_string())?;
-        
-        let mut stmt = conn.prepare("SELECT rcon_port, admin_password FROM servers WHERE id = ?1")
-            .map_err(|e| e.to_string())?;
-            
-        stmt.query_row([server_id], |row| {
-            Ok((
-                row.get::<_, u16>(0)?,
-                row.get::<_, String>(1)?
-            ))
-        }).map_err(|e| e.to_string())?
-    };
+    rcon_sessions::execute(&state, server_id, "DestroyWildDinos").await
+}
+
+#[tauri::command]
+pub async fn kick_player(
+    state: State<'_, AppState>,
+    server_id: i64,
+    platform_id: String,
+    reason: Option<String>,
+) -> Result<(), String> {
+    moderation::kick(&state, server_id, &platform_id, reason.as_deref(), &actor()).await
+}
+
+/// Ban a player; `expires_at` (RFC 3339) makes the ban temporary.
+#[tauri::command]
+pub async fn ban_player(
+    state: State<'_, AppState>,
+    server_id: i64,
+    platform_id: String,
+    player_name: Option<String>,
+    reason: Option<String>,
+    expires_at: Option<String>,
+) -> Result<Ban, String> {
+    moderation::ban(
+        &state,
+        server_id,
+        &platform_id,
+        player_name.as_deref(),
+        reason.as_deref(),
+        expires_at.as_deref(),
+        &actor(),
+    )
+    .await
+}
+
+#[tauri::command]
+pub async fn unban_player(
+    state: State<'_, AppState>,
+    server_id: i64,
+    platform_id: String,
+    reason: Option<String>,
+) -> Result<(), String> {
+    moderation::unban(&state, server_id, &platform_id, reason.as_deref(), &actor()).await
+}
 
-    // 2. Execute Command
-    RconClient::execute("127.0.0.1", rcon_port, &admin_password, "DestroyWildDinos").await
+#[tauri::command]
+pub async fn list_bans(
+    state: State<'_, AppState>,
+    server_id: i64,
+    include_lifted: Option<bool>,
+) -> Result<Vec<Ban>, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let conn = db.get_connection().map_err(|e| e.to_string())?;
+    moderation::list_bans(&conn, server_id, include_lifted.unwrap_or(false))
+}
+
+#[tauri::command]
+pub async fn get_moderation_log(
+    state: State<'_, AppState>,
+    server_id: Option<i64>,
+    limit: Option<usize>,
+) -> Result<Vec<ModerationAction>, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let conn = db.get_connection().map_err(|e| e.to_string())?;
+    moderation::history(&conn, server_id, limit.unwrap_or(100).min(1000)).map_err(|e| e.to_string())
 }
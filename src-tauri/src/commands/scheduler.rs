@@ -1,9 +1,16 @@
 use crate::models::Schedule;
 use crate::AppState;
+use cron::Schedule as CronSchedule;
+use std::str::FromStr;
 use tauri::State;
 
 #[tauri::command]
 pub async fn get_schedules(state: State<'_, AppState>, server_id: i64) -> Result<Vec<Schedule>, String> {
+    schedules_for(&state, server_id)
+}
+
+/// Body of `get_schedules`, callable without a Tauri `State` (arkctl).
+pub fn schedules_for(state: &AppState, server_id: i64) -> Result<Vec<Schedule>, String> {
     let db = state.db.lock().map_err(|e| e.to_string())?;
     let conn = db.get_connection().map_err(|e| e.to_string())?;
     
@@ -35,6 +42,22 @@ pub async fn create_schedule(
     cron_expression: String,
     payload: Option<String>
 ) -> Result<(), String> {
+    add_schedule(&state, server_id, &task_type, &cron_expression, payload.as_deref()).map(|_| ())
+}
+
+/// Body of `create_schedule`, callable without a Tauri `State` (arkctl).
+/// Returns the new schedule's id.
+pub fn add_schedule(
+    state: &AppState,
+    server_id: i64,
+    task_type: &str,
+    cron_expression: &str,
+    payload: Option<&str>,
+) -> Result<i64, String> {
+    // The scheduler silently skips expressions it can't parse
+    CronSchedule::from_str(cron_expression)
+        .map_err(|e| format!("Invalid cron expression \"{}\": {}", cron_expression, e))?;
+
     let db = state.db.lock().map_err(|e| e.to_string())?;
     let conn = db.get_connection().map_err(|e| e.to_string())?;
     
@@ -43,27 +66,43 @@ pub async fn create_schedule(
         (server_id, task_type, cron_expression, payload),
     ).map_err(|e| e.to_string())?;
     
-    Ok(())
+    Ok(conn.last_insert_rowid())
 }
 
 #[tauri::command]
 pub async fn delete_schedule(state: State<'_, AppState>, id: i64) -> Result<(), String> {
+    remove_schedule(&state, id)
+}
+
+/// Body of `delete_schedule`, callable without a Tauri `State` (arkctl).
+pub fn remove_schedule(state: &AppState, id: i64) -> Result<(), String> {
     let db = state.db.lock().map_err(|e| e.to_string())?;
     let conn = db.get_connection().map_err(|e| e.to_string())?;
     
-    conn.execute("DELETE FROM schedules WHERE id = ?1", [id])
+    let deleted = conn.execute("DELETE FROM schedules WHERE id = ?1", [id])
         .map_err(|e| e.to_string())?;
+    if deleted == 0 {
+        return Err(format!("Schedule {} not found", id));
+    }
     
     Ok(())
 }
 
 #[tauri::command]
 pub async fn toggle_schedule(state: State<'_, AppState>, id: i64, enabled: bool) -> Result<(), String> {
+    set_schedule_enabled(&state, id, enabled)
+}
+
+/// Body of `toggle_schedule`, callable without a Tauri `State` (arkctl).
+pub fn set_schedule_enabled(state: &AppState, id: i64, enabled: bool) -> Result<(), String> {
     let db = state.db.lock().map_err(|e| e.to_string())?;
     let conn = db.get_connection().map_err(|e| e.to_string())?;
     
-    conn.execute("UPDATE schedules SET enabled = ?1 WHERE id = ?2", (enabled, id))
+    let updated = conn.execute("UPDATE schedules SET enabled = ?1 WHERE id = ?2", (enabled, id))
         .map_err(|e| e.to_string())?;
+    if updated == 0 {
+        return Err(format!("Schedule {} not found", id));
+    }
     
     Ok(())
 }
@@ -3,6 +3,7 @@ use tauri::State;
 use std::path::PathBuf;
 use std::fs;
 use std::io::Write;
+use crate::services::platform;
 
 #[tauri::command]
 pub async fn set_battleye(state: State<'_, AppState>, server_id: i64, enabled: bool) -> Result<(), String> {
@@ -17,24 +18,24 @@ pub async fn set_battleye(state: State<'_, AppState>, server_id: i64, enabled: b
     Ok(())
 }
 
-fn get_whitelist_path(install_path: &PathBuf) -> PathBuf {
-    // Both ASE and ASA use similar structure for binaries
-    install_path.join("ShooterGame").join("Binaries").join("Win64").join("PlayersExclusiveJoinList.txt")
+fn get_whitelist_path(install_path: &PathBuf, server_type: &str) -> PathBuf {
+    // The list lives next to the server binary (Win64, or Linux for native ASE)
+    platform::binaries_dir(install_path, server_type).join("PlayersExclusiveJoinList.txt")
 }
 
 #[tauri::command]
 pub async fn get_whitelist(state: State<'_, AppState>, server_id: i64) -> Result<Vec<String>, String> {
-    let install_path = {
+    let (install_path, server_type) = {
         let db = state.db.lock().map_err(|e| e.to_string())?;
         let conn = db.get_connection().map_err(|e| e.to_string())?;
         conn.query_row(
-            "SELECT install_path FROM servers WHERE id = ?1",
+            "SELECT install_path, server_type FROM servers WHERE id = ?1",
             [server_id],
-            |row| row.get::<_, String>(0),
+            |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)),
         ).map_err(|e| e.to_string())?
     };
 
-    let path = get_whitelist_path(&PathBuf::from(install_path));
+    let path = get_whitelist_path(&PathBuf::from(install_path), &server_type);
     
     if !path.exists() {
         return Ok(Vec::new());
@@ -51,17 +52,17 @@ pub async fn get_whitelist(state: State<'_, AppState>, server_id: i64) -> Result
 
 #[tauri::command]
 pub async fn add_to_whitelist(state: State<'_, AppState>, server_id: i64, steam_id: String) -> Result<(), String> {
-    let install_path = {
+    let (install_path, server_type) = {
         let db = state.db.lock().map_err(|e| e.to_string())?;
         let conn = db.get_connection().map_err(|e| e.to_string())?;
         conn.query_row(
-            "SELECT install_path FROM servers WHERE id = ?1",
+            "SELECT install_path, server_type FROM servers WHERE id = ?1",
             [server_id],
-            |row| row.get::<_, String>(0),
+            |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)),
         ).map_err(|e| e.to_string())?
     };
 
-    let path = get_whitelist_path(&PathBuf::from(install_path));
+    let path = get_whitelist_path(&PathBuf::from(install_path), &server_type);
     
     // Ensure parent directory exists
     if let Some(parent) = path.parent() {
@@ -82,17 +83,17 @@ pub async fn add_to_whitelist(state: State<'_, AppState>, server_id: i64, steam_
 
 #[tauri::command]
 pub async fn remove_from_whitelist(state: State<'_, AppState>, server_id: i64, steam_id: String) -> Result<(), String> {
-    let install_path = {
+    let (install_path, server_type) = {
         let db = state.db.lock().map_err(|e| e.to_string())?;
         let conn = db.get_connection().map_err(|e| e.to_string())?;
         conn.query_row(
-            "SELECT install_path FROM servers WHERE id = ?1",
+            "SELECT install_path, server_type FROM servers WHERE id = ?1",
             [server_id],
-            |row| row.get::<_, String>(0),
+            |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)),
         ).map_err(|e| e.to_string())?
     };
 
-    let path = get_whitelist_path(&PathBuf::from(install_path));
+    let path = get_whitelist_path(&PathBuf::from(install_path), &server_type);
     
     if !path.exists() {
         return Ok(())
@@ -1,73 +1,47 @@
-use crate::models::{Server, ServerType, ServerStatus, ServerPorts, ServerConfig};
+use crate::models::{Server, ServerType, ServerStatus, ServerPorts, ServerConfig, ServerSettingsUpdate, CloneServerOptions, DetectedInstall, AsmProfileImport, StatusChange, RunnerConfig, RunnerKind, LaunchProfile, LaunchPreview, ReadinessConfig, RestartPolicy};
 use crate::AppState;
-use tauri::{AppHandle, State, Manager, Emitter};
-use std::path::PathBuf;
+use tauri::State;
+use std::path::{Path, PathBuf};
 use crate::services::steamcmd::SteamCmdService;
-use crate::services::notifications::NotificationService;
+use crate::services::runner;
+use crate::services::launch::{self, LaunchSpec};
+use crate::services::port_allocator::{self, CheckScope};
+use crate::services::{asm_profile, lifecycle, readiness, server_status, restart_policy, server_clone, server_import, servers};
 // use ini::Ini;
 
+/// Status history cause for anything a user does in the app.
+const APP_CAUSE: &str = "requested in the app";
+
 #[tauri::command]
 pub async fn get_all_servers(state: State<'_, AppState>) -> Result<Vec<Server>, String> {
     let db = state.db.lock().map_err(|e| e.to_string())?;
     let conn = db.get_connection().map_err(|e| e.to_string())?;
-    
-    let mut stmt = conn.prepare(
-        "SELECT id, name, server_type, install_path, status, game_port, query_port, rcon_port, 
-         max_players, server_password, admin_password, map_name, session_name, motd, 
-         created_at, last_started, battleye_enabled, multihome_ip, crossplay_enabled, auto_restart, auto_update, pid FROM servers ORDER BY id"
-    ).map_err(|e| e.to_string())?;
-    
-    let servers = stmt.query_map([], |row| {
-        Ok(Server {
-            id: row.get(0)?,
-            name: row.get(1)?,
-            server_type: row.get::<_, String>(2)?.parse().unwrap_or(ServerType::ASE),
-            install_path: PathBuf::from(row.get::<_, String>(3)?),
-            status: match row.get::<_, String>(4)?.as_str() {
-                "stopped" => ServerStatus::Stopped,
-                "starting" => ServerStatus::Starting,
-                "running" => ServerStatus::Running,
-                "crashed" => ServerStatus::Crashed,
-                "updating" => ServerStatus::Updating,
-                _ => ServerStatus::Stopped,
-            },
-            ports: ServerPorts {
-                game_port: row.get(5)?,
-                query_port: row.get(6)?,
-                rcon_port: row.get(7)?,
-            },
-            config: ServerConfig {
-                max_players: row.get(8)?,
-                server_password: row.get(9)?,
-                admin_password: row.get(10)?,
-                map_name: row.get(11)?,
-                session_name: row.get(12)?,
-                motd: row.get(13)?,
-                battleye_enabled: row.get::<_, bool>(16).unwrap_or(false),
-                multihome_ip: row.get(17).unwrap_or(None),
-                crossplay_enabled: row.get::<_, bool>(18).unwrap_or(false),
-                auto_restart: row.get(19).unwrap_or(Some(false)),
-                auto_update: row.get(20).unwrap_or(Some(false)),
-            },
-            created_at: row.get(14)?,
-            last_started: row.get(15)?,
-            pid: row.get(21).unwrap_or(None),
-        })
-    }).map_err(|e| e.to_string())?;
-    
-    servers.collect::<Result<Vec<_>, _>>().map_err(|e| e.to_string())
+    servers::list(&conn).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn get_server_by_id(state: State<'_, AppState>, server_id: i64) -> Result<Option<Server>, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let conn = db.get_connection().map_err(|e| e.to_string())?;
+    servers::load(&conn, server_id).map_err(|e| e.to_string())
 }
 
+/// Change any of a server's settings. Ports, install path, MultiHome IP,
+/// admin password and runner can only change while the server is stopped;
+/// everything else applies on its next start.
 #[tauri::command]
-pub async fn get_server_by_id(_state: State<'_, AppState>, _server_id: i64) -> Result<Option<Server>, String> {
-    // Similar to get_all_servers but with WHERE clause
-    // TODO: Implement
-    Ok(None)
+pub async fn update_server_settings(
+    state: State<'_, AppState>,
+    server_id: i64,
+    update: ServerSettingsUpdate,
+) -> Result<Server, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let conn = db.get_connection().map_err(|e| e.to_string())?;
+    servers::update_settings(&conn, server_id, update)
 }
 
 #[tauri::command]
 pub async fn install_server(
-    app: AppHandle,
     state: State<'_, AppState>,
     server_type: String,
     install_path: String,
@@ -76,11 +50,31 @@ pub async fn install_server(
     game_port: u16,
     query_port: u16,
     rcon_port: u16,
+) -> Result<Server, String> {
+    install(&state, server_type, install_path, name, map_name, ServerPorts { game_port, query_port, rcon_port })
+}
+
+/// Body of `install_server`, callable without a Tauri `State` (HTTP API).
+/// The server is returned `installing`; `server-install-complete` follows.
+pub fn install(
+    state: &AppState,
+    server_type: String,
+    install_path: String,
+    name: String,
+    map_name: String,
+    ports: ServerPorts,
 ) -> Result<Server, String> {
     // 1. Create DB Entry
     
     let db = state.db.lock().map_err(|e| e.to_string())?;
     let conn = db.get_connection().map_err(|e| e.to_string())?;
+
+    // Servers sharing ports could never run side by side
+    let conflicts = port_allocator::find_conflicts(&conn, None, &ports, None, CheckScope::AllServers)
+        .map_err(|e| e.to_string())?;
+    if !conflicts.is_empty() {
+        return Err(port_allocator::describe(&conn, &conflicts, None));
+    }
     
     conn.execute(
         "INSERT INTO servers (name, server_type, install_path, status, game_port, query_port, rcon_port, 
@@ -90,10 +84,10 @@ pub async fn install_server(
             &name,
             &server_type,
             &install_path,
-            "updating", // Set status to updating (as installing is not in DB constraint)
-            game_port,
-            query_port,
-            rcon_port,
+            ServerStatus::Installing.to_string(),
+            ports.game_port,
+            ports.query_port,
+            ports.rcon_port,
             70,
             "admin123", 
             &map_name,
@@ -102,58 +96,51 @@ pub async fn install_server(
     ).map_err(|e| e.to_string())?;
     
     let id = conn.last_insert_rowid();
+    server_status::record_created(&conn, id, ServerStatus::Installing, "installed through SteamCMD")
+        .map_err(|e| e.to_string())?;
 
     // 2. Spawn Background Installation
-    let app_handle = app.clone();
-    let service = SteamCmdService::new(app_handle.clone());
+    let service = SteamCmdService::new(state);
     let server_type_clone = server_type.clone();
     let install_path_clone = PathBuf::from(&install_path);
-    // Determine database path or how to access DB in thread. 
-    // AppState is Send/Sync so we can clone the Arc for DB?
-    // Actually AppState holds Arc<Mutex<Database>>, so we can clone state if we had it as Arc, but here it is tauri::State.
-    // simpler: Get a fresh DB connection in the thread if possible, or just accept we might not update status on failure perfectly without more boilerplate.
-    // BETTER: Use the AppHandle to get the state again inside the thread!
+    // AppState is a set of shared handles, so the install thread gets its own copy
+    let core = state.clone();
     
     tauri::async_runtime::spawn_blocking(move || {
-        // We can access state from app_handle if needed, but for now just run the install
         match service.install_server(&server_type_clone, &install_path_clone) {
             Ok(_) => {
                 println!("✅ Server installation completed successfully!");
                 // Update status to stopped (ready)
-                 if let Some(state) = app_handle.try_state::<AppState>() {
-                    if let Ok(db) = state.db.lock() {
-                        if let Ok(conn) = db.get_connection() {
-                            let _ = conn.execute("UPDATE servers SET status = ?1 WHERE id = ?2", ("stopped", id));
-                        }
+                if let Ok(db) = core.db.lock() {
+                    if let Ok(conn) = db.get_connection() {
+                        let _ = server_status::transition(&conn, id, ServerStatus::Stopped, "installation finished");
                     }
-                 }
+                }
                  // Emit status change event so UI refreshes
-                 let _ = app_handle.emit("server-status-changed", serde_json::json!({
+                 core.events.emit("server-status-changed", serde_json::json!({
                      "id": id,
                      "status": "stopped"
                  }));
                  // Emit Success Event
-                 let _ = app_handle.emit("server-install-complete", serde_json::json!({
+                 core.events.emit("server-install-complete", serde_json::json!({
                      "serverId": id,
                      "success": true
                  }));
             }
             Err(e) => {
                 println!("❌ Installation failed: {}", e);
-                 if let Some(state) = app_handle.try_state::<AppState>() {
-                    if let Ok(db) = state.db.lock() {
-                        if let Ok(conn) = db.get_connection() {
-                            let _ = conn.execute("UPDATE servers SET status = ?1 WHERE id = ?2", ("crashed", id));
-                        }
+                if let Ok(db) = core.db.lock() {
+                    if let Ok(conn) = db.get_connection() {
+                        let _ = server_status::transition(&conn, id, ServerStatus::Crashed, &format!("installation failed: {}", e));
                     }
-                 }
+                }
                  // Emit status change event
-                 let _ = app_handle.emit("server-status-changed", serde_json::json!({
+                 core.events.emit("server-status-changed", serde_json::json!({
                      "id": id,
                      "status": "crashed"
                  }));
                  // Emit Failure Event
-                 let _ = app_handle.emit("server-install-complete", serde_json::json!({
+                 core.events.emit("server-install-complete", serde_json::json!({
                      "serverId": id,
                      "success": false,
                      "error": e.to_string()
@@ -168,7 +155,7 @@ pub async fn install_server(
         server_type: server_type.parse().unwrap_or(ServerType::ASE),
         install_path: PathBuf::from(install_path),
         status: ServerStatus::Installing, // Return as Installing
-        ports: ServerPorts { game_port, query_port, rcon_port },
+        ports,
         config: ServerConfig {
             max_players: 70,
             server_password: None,
@@ -178,231 +165,158 @@ pub async fn install_server(
             motd: None,
             battleye_enabled: false,
             multihome_ip: None,
+            rcon_host: None,
             crossplay_enabled: false,
             auto_restart: Some(false),
             auto_update: Some(false),
+            runner: RunnerConfig::default(),
+            launch_profile: LaunchProfile::default(),
         },
         created_at: chrono::Utc::now().to_rfc3339(),
         last_started: None,
         pid: None,
+        shared_install_of: None,
     })
 }
 
+/// Look at an existing ARK install and report what `import_server` would register.
 #[tauri::command]
-pub async fn start_server(app: AppHandle, state: State<'_, AppState>, server_id: i64) -> Result<(), String> {
-    // Get server details from database
-    let (server_type, install_path, map_name, session_name, game_port, query_port, rcon_port,
-         max_players, server_password, admin_password, use_battleye, multihome_ip, crossplay_enabled) = {
-        let db = state.db.lock().map_err(|e| e.to_string())?;
-        let conn = db.get_connection().map_err(|e| e.to_string())?;
-        
-        let mut stmt = conn.prepare(
-            "SELECT server_type, install_path, map_name, session_name, game_port, query_port, rcon_port,
-             max_players, server_password, admin_password, battleye_enabled, multihome_ip, crossplay_enabled FROM servers WHERE id = ?1"
-        ).map_err(|e| e.to_string())?;
-        
-        stmt.query_row([server_id], |row| {
-            Ok((
-                row.get::<_, String>(0)?,
-                row.get::<_, String>(1)?,
-                row.get::<_, String>(2)?,
-                row.get::<_, String>(3)?,
-                row.get::<_, u16>(4)?,
-                row.get::<_, u16>(5)?,
-                row.get::<_, u16>(6)?,
-                row.get::<_, i32>(7)?,
-                row.get::<_, Option<String>>(8)?,
-                row.get::<_, String>(9)?,
-                row.get::<_, bool>(10).unwrap_or(false),
-                row.get::<_, Option<String>>(11)?,
-                row.get::<_, bool>(12).unwrap_or(false),
-            ))
-        }).map_err(|e| e.to_string())?
-    };
-
-    // SYNC: Write settings to GameUserSettings.ini before starting
-    if let Err(e) = sync_settings_to_ini(
-        &PathBuf::from(&install_path), 
-        &session_name, 
-        &map_name, // Map name usually goes to start args but sometimes INI
-        server_password.as_deref(), 
-        &admin_password, 
-        max_players
-    ) {
-        println!("Warning: Failed to sync INI settings: {}", e);
-        // We continue anyway because command line args will override, but logging it is good
-    }
+pub async fn detect_server_install(path: String) -> Result<DetectedInstall, String> {
+    server_import::detect(Path::new(path.trim())).map_err(|e| e.to_string())
+}
 
-    let pid = state.process_manager.start_server(
-        &app, // Pass AppHandle
-        server_id,
-        &server_type,
-        &PathBuf::from(install_path),
-        &map_name,
-        &session_name,
-        game_port,
-        query_port,
-        rcon_port,
-        max_players,
-        server_password.as_deref(),
-        &admin_password,
-        use_battleye,
-        multihome_ip,
-        crossplay_enabled,
-    ).map_err(|e| e.to_string())?;
+/// Register an ARK install that is already on disk, without running SteamCMD.
+/// Its settings are read from the install; `name` defaults to the session name.
+#[tauri::command]
+pub async fn import_server(state: State<'_, AppState>, path: String, name: Option<String>) -> Result<Server, String> {
+    let detected = server_import::detect(Path::new(path.trim())).map_err(|e| e.to_string())?;
 
-    // Update status in database
-    {
-        let db = state.db.lock().map_err(|e| e.to_string())?;
-        let conn = db.get_connection().map_err(|e| e.to_string())?;
-        conn.execute("UPDATE servers SET status = ?1, pid = ?2 WHERE id = ?3", ("running", pid, server_id))
-            .map_err(|e| e.to_string())?;
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let conn = db.get_connection().map_err(|e| e.to_string())?;
+    let id = server_import::register(&conn, &detected, name.as_deref())?;
+    for warning in &detected.warnings {
+        println!("Import of server {}: {}", id, warning);
     }
 
-    Ok(())
+    servers::load(&conn, id)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| "Imported server disappeared".to_string())
 }
-    
 
-fn sync_settings_to_ini(
-    install_path: &PathBuf,
-    session_name: &str,
-    _map_name: &str,
-    server_password: Option<&str>,
-    admin_password: &str,
-    max_players: i32,
-) -> Result<(), String> {
-    /*
-    let config_path = install_path.join("ShooterGame/Saved/Config/WindowsServer/GameUserSettings.ini");
-    
-    // Create parent dirs if missing
-    if let Some(parent) = config_path.parent() {
-        let _ = std::fs::create_dir_all(parent);
-    }
+/// Read a classic Ark Server Manager `.profile` and report what importing it
+/// would create and what it can't carry over. Nothing is written.
+#[tauri::command]
+pub async fn preview_asm_profile(path: String, install_path: Option<String>) -> Result<AsmProfileImport, String> {
+    let install_path = install_path.as_deref().map(str::trim).filter(|p| !p.is_empty());
+    asm_profile::read(Path::new(path.trim()), install_path.map(Path::new)).map_err(|e| e.to_string())
+}
 
-    // Load existing or create new
-    let mut conf = if config_path.exists() {
-        ini::Ini::load_from_file(&config_path).map_err(|e| e.to_string())?
-    } else {
-        ini::Ini::new()
-    };
+/// Import a classic Ark Server Manager `.profile`: the server, its launch
+/// options, schedules, mods and INI settings. `install_path` overrides the
+/// profile's install directory when the files have moved.
+#[tauri::command]
+pub async fn import_asm_profile(
+    state: State<'_, AppState>,
+    path: String,
+    install_path: Option<String>,
+    name: Option<String>,
+) -> Result<AsmProfileImport, String> {
+    let install_path = install_path.as_deref().map(str::trim).filter(|p| !p.is_empty());
+    let mut import = asm_profile::read(Path::new(path.trim()), install_path.map(Path::new)).map_err(|e| e.to_string())?;
 
-    // Update [ServerSettings]
-    conf.with_section(Some("ServerSettings"))
-        .set("SessionName", session_name)
-        .set("ServerAdminPassword", admin_password)
-        .set("MaxPlayers", max_players.to_string());
-    
-    if let Some(pwd) = server_password {
-        conf.with_section(Some("ServerSettings"))
-            .set("ServerPassword", pwd);
-    }
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let conn = db.get_connection().map_err(|e| e.to_string())?;
+    let id = asm_profile::apply(&conn, &mut import, name.as_deref())?;
+    println!("✅ Imported ASM profile {} as server {} ({} settings not mapped)", path.trim(), id, import.unmapped.len());
 
-    // Write back
-    conf.write_to_file(&config_path).map_err(|e| e.to_string())?;
-    */
-    println!("TODO: INI Sync temporarily disabled due to dependency issue");
-    Ok(())
+    Ok(import)
 }
 
+/// Copy a server into a new one, e.g. a staging copy of production. The
+/// clone is `installing` until its files are copied, then `server-clone-complete`
+/// is emitted.
 #[tauri::command]
-pub async fn stop_server(state: State<'_, AppState>, server_id: i64) -> Result<(), String> {
-    if let Err(e) = state.process_manager.stop_server(server_id) {
-        println!("ProcessManager stop failed: {}. Attempting PID kill...", e);
-        // Fallback: Check PID in DB
-        let pid_opt: Option<u32> = {
-            let db = state.db.lock().map_err(|e| e.to_string())?;
-            let conn = db.get_connection().map_err(|e| e.to_string())?;
-            conn.query_row("SELECT pid FROM servers WHERE id = ?1", [server_id], |row| {
-                let p: Option<i64> = row.get(0)?;
-                Ok(p.map(|v| v as u32))
-            }).ok().flatten()
+pub async fn clone_server(
+    state: State<'_, AppState>,
+    source_id: i64,
+    new_name: String,
+    options: CloneServerOptions,
+) -> Result<Server, String> {
+    let (plan, server) = {
+        let db = state.db.lock().map_err(|e| e.to_string())?;
+        let conn = db.get_connection().map_err(|e| e.to_string())?;
+        let plan = server_clone::prepare(&conn, source_id, &new_name, &options)?;
+        let server = servers::load(&conn, plan.server_id)
+            .map_err(|e| e.to_string())?
+            .ok_or("Clone disappeared")?;
+        (plan, server)
+    };
+
+    let core = state.inner().clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        let id = plan.server_id;
+        let result = server_clone::copy_files(&plan);
+        let (status, cause) = match &result {
+            Ok(()) => (ServerStatus::Stopped, "clone finished".to_string()),
+            Err(e) => (ServerStatus::Crashed, format!("clone failed: {:#}", e)),
         };
 
-        if let Some(pid) = pid_opt {
-             use sysinfo::{System, Pid};
-             let mut sys = System::new_all();
-             sys.refresh_all();
-             if let Some(process) = sys.process(Pid::from(pid as usize)) {
-                 process.kill();
-                 println!("Killed orphaned process PID {}", pid);
-             }
+        if let Ok(db) = core.db.lock() {
+            if let Ok(conn) = db.get_connection() {
+                let _ = server_status::transition(&conn, id, status, &cause);
+            }
         }
-    }
-    
-    // Update status in database
-    {
-        let db = state.db.lock().map_err(|e| e.to_string())?;
-        let conn = db.get_connection().map_err(|e| e.to_string())?;
-        conn.execute("UPDATE servers SET status = ?1 WHERE id = ?2", ("stopped", server_id))
-            .map_err(|e| e.to_string())?;
-    }
-    
-    let _ = NotificationService::send_notification(&state, "Server Stopped", &format!("Server {} has been stopped.", server_id)).await;
+        core.events.emit("server-status-changed", serde_json::json!({
+            "id": id,
+            "status": status.to_string()
+        }));
 
-    Ok(())
+        match result {
+            Ok(()) => {
+                println!("✅ Server {} cloned into {}", source_id, id);
+                core.events.emit("server-clone-complete", serde_json::json!({
+                    "serverId": id,
+                    "sourceId": source_id,
+                    "success": true
+                }));
+            }
+            Err(e) => {
+                println!("❌ Cloning server {} failed: {:#}", source_id, e);
+                core.events.emit("server-clone-complete", serde_json::json!({
+                    "serverId": id,
+                    "sourceId": source_id,
+                    "success": false,
+                    "error": format!("{:#}", e)
+                }));
+            }
+        }
+    });
+
+    Ok(server)
 }
 
 #[tauri::command]
-pub async fn restart_server(app: AppHandle, state: State<'_, AppState>, server_id: i64) -> Result<(), String> {
-    // Get server details from database
-    let (server_type, install_path, map_name, session_name, game_port, query_port, rcon_port,
-         max_players, server_password, admin_password, use_battleye, multihome_ip, crossplay_enabled) = {
-        let db = state.db.lock().map_err(|e| e.to_string())?;
-        let conn = db.get_connection().map_err(|e| e.to_string())?;
-        
-        let mut stmt = conn.prepare(
-            "SELECT server_type, install_path, map_name, session_name, game_port, query_port, rcon_port,
-             max_players, server_password, admin_password, battleye_enabled, multihome_ip, crossplay_enabled FROM servers WHERE id = ?1"
-        ).map_err(|e| e.to_string())?;
-        
-        stmt.query_row([server_id], |row| {
-            Ok((
-                row.get::<_, String>(0)?,
-                row.get::<_, String>(1)?,
-                row.get::<_, String>(2)?,
-                row.get::<_, String>(3)?,
-                row.get::<_, u16>(4)?,
-                row.get::<_, u16>(5)?,
-                row.get::<_, u16>(6)?,
-                row.get::<_, i32>(7)?,
-                row.get::<_, Option<String>>(8)?,
-                row.get::<_, String>(9)?,
-                row.get::<_, bool>(10).unwrap_or(false),
-                row.get::<_, Option<String>>(11)?,
-                row.get::<_, bool>(12).unwrap_or(false),
-            ))
-        }).map_err(|e| e.to_string())?
-    }; // db and conn dropped here
-    
-    let pid = state.process_manager.restart_server(
-        &app, // Pass AppHandle
-        server_id,
-        &server_type,
-        &PathBuf::from(install_path),
-        &map_name,
-        &session_name,
-        game_port,
-        query_port,
-        rcon_port,
-        max_players,
-        server_password.as_deref(),
-        &admin_password,
-        use_battleye,
-        multihome_ip,
-        crossplay_enabled,
-    ).map_err(|e| e.to_string())?;
-    
-    // Update PID in DB
-    {
-        let db = state.db.lock().map_err(|e| e.to_string())?;
-        let conn = db.get_connection().map_err(|e| e.to_string())?;
-        conn.execute("UPDATE servers SET pid = ?1 WHERE id = ?2", (pid, server_id))
-            .map_err(|e| e.to_string())?;
+pub async fn start_server(state: State<'_, AppState>, server_id: i64) -> Result<(), String> {
+    if let Some(daemon) = &state.daemon {
+        return daemon.call("start_server", serde_json::json!({ "serverId": server_id, "cause": APP_CAUSE })).await.map(|_| ());
     }
-    
-    let _ = NotificationService::send_notification(&state, "Server Restarted", &format!("Server {} has been restarted.", server_id)).await;
+    lifecycle::start_server(&state, server_id, APP_CAUSE).await
+}
 
-    Ok(())
+#[tauri::command]
+pub async fn stop_server(state: State<'_, AppState>, server_id: i64) -> Result<(), String> {
+    if let Some(daemon) = &state.daemon {
+        return daemon.call("stop_server", serde_json::json!({ "serverId": server_id, "cause": APP_CAUSE })).await.map(|_| ());
+    }
+    lifecycle::stop_server(&state, server_id, APP_CAUSE).await
+}
+
+#[tauri::command]
+pub async fn restart_server(state: State<'_, AppState>, server_id: i64) -> Result<(), String> {
+    if let Some(daemon) = &state.daemon {
+        return daemon.call("restart_server", serde_json::json!({ "serverId": server_id, "cause": APP_CAUSE })).await.map(|_| ());
+    }
+    lifecycle::restart_server(&state, server_id, APP_CAUSE).await
 }
 
 #[tauri::command]
@@ -414,12 +328,14 @@ pub async fn delete_server(state: State<'_, AppState>, server_id: i64) -> Result
     
     conn.execute("DELETE FROM servers WHERE id = ?1", [server_id])
         .map_err(|e| e.to_string())?;
+    state.rcon.close(server_id);
     
     println!("✅ Server {} deleted from database", server_id);
     Ok(())
 }
 
-/// Reset all servers that are stuck in "updating" or "installing" status
+/// Reset all servers that are stuck in "updating", "installing", "starting"
+/// or "stopping" status
 #[tauri::command]
 pub async fn reset_stuck_servers(state: State<'_, AppState>) -> Result<i32, String> {
     println!("🔄 Resetting stuck servers...");
@@ -427,86 +343,33 @@ pub async fn reset_stuck_servers(state: State<'_, AppState>) -> Result<i32, Stri
     let db = state.db.lock().map_err(|e| e.to_string())?;
     let conn = db.get_connection().map_err(|e| e.to_string())?;
     
-    // Update any server with status "updating" or "installing" to "stopped"
-    let affected = conn.execute(
-        "UPDATE servers SET status = 'stopped' WHERE status IN ('updating', 'installing', 'starting')",
-        [],
-    ).map_err(|e| e.to_string())?;
+    let stuck: Vec<i64> = {
+        let mut stmt = conn
+            .prepare("SELECT id FROM servers WHERE status IN ('updating', 'installing', 'starting', 'stopping')")
+            .map_err(|e| e.to_string())?;
+        let ids = stmt.query_map([], |row| row.get(0)).map_err(|e| e.to_string())?;
+        ids.collect::<Result<_, _>>().map_err(|e| e.to_string())?
+    };
+    let mut affected = 0;
+    for id in stuck {
+        server_status::transition(&conn, id, ServerStatus::Stopped, "reset as stuck")?;
+        affected += 1;
+    }
     
     println!("✅ Reset {} stuck servers to 'stopped' status", affected);
-    Ok(affected as i32)
+    Ok(affected)
 }
 
 #[tauri::command]
-pub async fn update_server(app_handle: AppHandle, state: State<'_, AppState>, server_id: i64) -> Result<(), String> {
-    // 1. Get server details
-    let (server_type, install_path, current_status) = {
-        let db = state.db.lock().map_err(|e| e.to_string())?;
-        let conn = db.get_connection().map_err(|e| e.to_string())?;
-        let mut stmt = conn.prepare("SELECT server_type, install_path, status FROM servers WHERE id = ?1").map_err(|e| e.to_string())?;
-        
-        stmt.query_row([server_id], |row| {
-            Ok((
-                row.get::<_, String>(0)?,
-                row.get::<_, String>(1)?,
-                row.get::<_, String>(2)?,
-            ))
-        }).map_err(|e| e.to_string())?
-    };
-
-    // 2. Stop server if running
-    if current_status == "running" || current_status == "starting" {
-        state.process_manager.stop_server(server_id).map_err(|e| e.to_string())?;
-        // Update status in DB
-        {
-            let db = state.db.lock().map_err(|e| e.to_string())?;
-            let conn = db.get_connection().map_err(|e| e.to_string())?;
-            conn.execute("UPDATE servers SET status = ?1 WHERE id = ?2", ("stopped", server_id))
-                .map_err(|e| e.to_string())?;
-        }
-    }
-
-    // 3. Set status to updating
-    {
-        let db = state.db.lock().map_err(|e| e.to_string())?;
-        let conn = db.get_connection().map_err(|e| e.to_string())?;
-        conn.execute("UPDATE servers SET status = ?1 WHERE id = ?2", ("updating", server_id))
-            .map_err(|e| e.to_string())?;
-    }
-
-    // 4. Run SteamCMD Update (This is blocking for now, ideally should be detached or async transparent)
-    // Since we are in an async command, we can perform the blocking operation? 
-    // Tauri commands run on a separate thread pool, so it shouldn't block the UI *event loop*, 
-    // but the frontend promise will wait.
-    let service = SteamCmdService::new(app_handle.clone());
-    let path = PathBuf::from(&install_path);
-    
-    // We use spawn_blocking for the heavy lifting if needed, but the service method just spawns a process and waits.
-    // That wait is blocking the thread.
-    let result = tauri::async_runtime::spawn_blocking(move || {
-        service.install_server(&server_type, &path)
-    }).await.map_err(|e| e.to_string())?;
-
-    // 5. Handle Result
-    let db = state.db.lock().map_err(|e| e.to_string())?;
-    let conn = db.get_connection().map_err(|e| e.to_string())?;
-    
-    match result {
-        Ok(_) => {
-            conn.execute("UPDATE servers SET status = ?1 WHERE id = ?2", ("stopped", server_id))
-                .map_err(|e| e.to_string())?;
-            Ok(())
-        }
-        Err(e) => {
-            conn.execute("UPDATE servers SET status = ?1 WHERE id = ?2", ("stopped", server_id)) // Or error status
-                .map_err(|e| e.to_string())?;
-            Err(e.to_string())
-        }
+pub async fn update_server(state: State<'_, AppState>, server_id: i64) -> Result<(), String> {
+    if let Some(daemon) = &state.daemon {
+        return daemon.call("update_server", serde_json::json!({ "serverId": server_id, "cause": APP_CAUSE })).await.map(|_| ());
     }
+    lifecycle::update_server(&state, server_id, APP_CAUSE).await
 }
 
 #[tauri::command]
-pub async fn get_server_version(app_handle: AppHandle, state: State<'_, AppState>, server_id: i64) -> Result<String, String> {
+pub async fn get_server_version(state: State<'_, AppState>, server_id: i64) -> Result<String, String> {
      let (server_type, install_path) = {
         let db = state.db.lock().map_err(|e| e.to_string())?;
         let conn = db.get_connection().map_err(|e| e.to_string())?;
@@ -520,7 +383,7 @@ pub async fn get_server_version(app_handle: AppHandle, state: State<'_, AppState
         }).map_err(|e| e.to_string())?
     };
 
-    let service = SteamCmdService::new(app_handle);
+    let service = SteamCmdService::new(&state);
     let path = PathBuf::from(install_path);
     
     service.get_build_id(&server_type, &path).map_err(|e| e.to_string())
@@ -544,7 +407,193 @@ pub async fn set_auto_update(state: State<'_, AppState>, server_id: i64, enabled
     Ok(())
 }
 
+/// Choose how the server binary is launched (direct, Wine, or a Proton install)
+#[tauri::command]
+pub async fn set_server_runner(
+    state: State<'_, AppState>,
+    server_id: i64,
+    kind: RunnerKind,
+    path: Option<String>,
+    prefix: Option<String>,
+) -> Result<(), String> {
+    let runner = RunnerConfig { kind, path, prefix };
+    runner::validate(&runner)?;
+
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let conn = db.get_connection().map_err(|e| e.to_string())?;
+    conn.execute(
+        "UPDATE servers SET runner = ?1, runner_path = ?2, compat_prefix = ?3 WHERE id = ?4",
+        (runner.kind.to_string(), &runner.path, &runner.prefix, server_id),
+    ).map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn update_server_graceful(state: State<'_, AppState>, server_id: i64) -> Result<(), String> {
+    if let Some(daemon) = &state.daemon {
+        return daemon.call("update_server_graceful", serde_json::json!({ "serverId": server_id, "cause": APP_CAUSE })).await.map(|_| ());
+    }
+    crate::services::updater::ServerUpdateService::update_server_graceful(&state, server_id, APP_CAUSE).await
+}
+
+#[tauri::command]
+pub async fn get_launch_profile(state: State<'_, AppState>, server_id: i64) -> Result<LaunchProfile, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let conn = db.get_connection().map_err(|e| e.to_string())?;
+
+    let json: Option<String> = conn
+        .query_row("SELECT launch_profile FROM servers WHERE id = ?1", [server_id], |row| row.get(0))
+        .map_err(|e| e.to_string())?;
+
+    Ok(launch::parse_profile(json))
+}
+
+#[tauri::command]
+pub async fn save_launch_profile(
+    state: State<'_, AppState>,
+    server_id: i64,
+    profile: LaunchProfile,
+) -> Result<(), String> {
+    launch::validate_profile(&profile)?;
+    let json = serde_json::to_string(&profile).map_err(|e| e.to_string())?;
+
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let conn = db.get_connection().map_err(|e| e.to_string())?;
+    conn.execute(
+        "UPDATE servers SET launch_profile = ?1 WHERE id = ?2",
+        (json, server_id),
+    ).map_err(|e| e.to_string())?;
+
+    Ok(())
+}
+
+/// Show the exact command a start would run. Pass `profile` to preview
+/// unsaved edits instead of the stored profile.
+#[tauri::command]
+pub async fn preview_launch_command(
+    state: State<'_, AppState>,
+    server_id: i64,
+    profile: Option<LaunchProfile>,
+) -> Result<LaunchPreview, String> {
+    let mut spec = {
+        let db = state.db.lock().map_err(|e| e.to_string())?;
+        let conn = db.get_connection().map_err(|e| e.to_string())?;
+        LaunchSpec::load(&conn, server_id).map_err(|e| e.to_string())?
+    };
+
+    if let Some(profile) = profile {
+        launch::validate_profile(&profile)?;
+        spec.profile = profile;
+    }
+
+    spec.preview(&state.data_dir).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn get_readiness_config(state: State<'_, AppState>, server_id: i64) -> Result<ReadinessConfig, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let conn = db.get_connection().map_err(|e| e.to_string())?;
+    readiness::load_config(&conn, server_id).map_err(|e| e.to_string())
+}
+
+/// Takes effect from the next start.
+#[tauri::command]
+pub async fn save_readiness_config(
+    state: State<'_, AppState>,
+    server_id: i64,
+    config: ReadinessConfig,
+) -> Result<(), String> {
+    readiness::validate_config(&config)?;
+    let json = serde_json::to_string(&config).map_err(|e| e.to_string())?;
+
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let conn = db.get_connection().map_err(|e| e.to_string())?;
+    conn.execute(
+        "UPDATE servers SET readiness_probe = ?1 WHERE id = ?2",
+        (json, server_id),
+    ).map_err(|e| e.to_string())?;
+
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn get_restart_policy(state: State<'_, AppState>, server_id: i64) -> Result<RestartPolicy, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let conn = db.get_connection().map_err(|e| e.to_string())?;
+    restart_policy::load_policy(&conn, server_id).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn save_restart_policy(
+    state: State<'_, AppState>,
+    server_id: i64,
+    policy: RestartPolicy,
+) -> Result<(), String> {
+    restart_policy::validate_policy(&policy)?;
+    let json = serde_json::to_string(&policy).map_err(|e| e.to_string())?;
+
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let conn = db.get_connection().map_err(|e| e.to_string())?;
+    conn.execute(
+        "UPDATE servers SET restart_policy = ?1 WHERE id = ?2",
+        (json, server_id),
+    ).map_err(|e| e.to_string())?;
+
+    Ok(())
+}
+
+/// Clear a `crash-looping` server back to `stopped`. The monitor then forgets
+/// its crash history, so automatic restarts get a fresh budget.
+#[tauri::command]
+pub async fn reset_crash_loop(state: State<'_, AppState>, server_id: i64) -> Result<(), String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let conn = db.get_connection().map_err(|e| e.to_string())?;
+    let changed = server_status::transition_from(
+        &conn,
+        server_id,
+        &[ServerStatus::CrashLooping],
+        ServerStatus::Stopped,
+        "crash loop reset",
+    )?;
+
+    if !changed {
+        return Err("Server is not crash-looping".to_string());
+    }
+    state.events.emit("server-status-changed", serde_json::json!({
+        "id": server_id,
+        "status": "stopped"
+    }));
+    Ok(())
+}
+
+/// Take a stopped server out of service so nothing starts it, or release it
+/// back to `stopped`.
 #[tauri::command]
-pub async fn update_server_graceful(app: tauri::AppHandle, state: State<'_, AppState>, server_id: i64) -> Result<(), String> {
-    crate::services::updater::ServerUpdateService::update_server_graceful(app, state, server_id).await
+pub async fn set_server_maintenance(state: State<'_, AppState>, server_id: i64, enabled: bool) -> Result<(), String> {
+    let status = if enabled { ServerStatus::Maintenance } else { ServerStatus::Stopped };
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let conn = db.get_connection().map_err(|e| e.to_string())?;
+    if !enabled && server_status::current(&conn, server_id)? != ServerStatus::Maintenance {
+        return Err("Server is not in maintenance".to_string());
+    }
+    let cause = if enabled { "maintenance started" } else { "maintenance ended" };
+    server_status::transition(&conn, server_id, status, cause)?;
+
+    state.events.emit("server-status-changed", serde_json::json!({
+        "id": server_id,
+        "status": status.to_string()
+    }));
+    Ok(())
+}
+
+/// The server's status changes and their causes, newest first.
+#[tauri::command]
+pub async fn get_server_status_history(
+    state: State<'_, AppState>,
+    server_id: i64,
+    limit: Option<usize>,
+) -> Result<Vec<StatusChange>, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let conn = db.get_connection().map_err(|e| e.to_string())?;
+    server_status::history(&conn, server_id, limit.unwrap_or(100)).map_err(|e| e.to_string())
 }
@@ -0,0 +1,42 @@
+use crate::models::{Webhook, WebhookDelivery};
+use crate::services::webhooks;
+use crate::AppState;
+use tauri::State;
+
+#[tauri::command]
+pub async fn list_webhooks(state: State<'_, AppState>) -> Result<Vec<Webhook>, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let conn = db.get_connection().map_err(|e| e.to_string())?;
+    webhooks::list(&conn).map_err(|e| e.to_string())
+}
+
+/// Creates the webhook when it has no id. Returns it with its id and secret.
+#[tauri::command]
+pub async fn save_webhook(state: State<'_, AppState>, webhook: Webhook) -> Result<Webhook, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let conn = db.get_connection().map_err(|e| e.to_string())?;
+    webhooks::save(&conn, webhook)
+}
+
+#[tauri::command]
+pub async fn delete_webhook(state: State<'_, AppState>, webhook_id: i64) -> Result<(), String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let conn = db.get_connection().map_err(|e| e.to_string())?;
+    webhooks::delete(&conn, webhook_id)
+}
+
+#[tauri::command]
+pub async fn test_webhook(state: State<'_, AppState>, webhook_id: i64) -> Result<(), String> {
+    webhooks::test(&state, webhook_id).await
+}
+
+#[tauri::command]
+pub async fn get_webhook_deliveries(
+    state: State<'_, AppState>,
+    webhook_id: i64,
+    limit: Option<usize>,
+) -> Result<Vec<WebhookDelivery>, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let conn = db.get_connection().map_err(|e| e.to_string())?;
+    webhooks::deliveries(&conn, webhook_id, limit.unwrap_or(50)).map_err(|e| e.to_string())
+}
@@ -2,6 +2,24 @@ use rusqlite::{Connection, Result};
 use std::path::PathBuf;
 use std::sync::Mutex;
 
+/// Every value `servers.status` may hold. Adding one here rebuilds the
+/// table's CHECK constraint on the next start; keep `schema.sql` in step.
+const SERVER_STATUSES: &[&str] = &[
+    "stopped",
+    "starting",
+    "running",
+    "crashed",
+    "updating",
+    "failed",
+    "crash-looping",
+    "installing",
+    "stopping",
+    "maintenance",
+];
+
+/// Every value `schedules.task_type` may hold, rebuilt the same way.
+const SCHEDULE_TASK_TYPES: &[&str] = &["restart", "backup", "broadcast", "update"];
+
 pub struct Database {
     conn: Mutex<Connection>,
 }
@@ -13,6 +31,9 @@ impl Database {
         // Enable foreign keys
         conn.execute("PRAGMA foreign_keys = ON", [])?;
 
+        // The GUI and the daemon can have the database open at the same time
+        conn.busy_timeout(std::time::Duration::from_secs(5))?;
+
         // Initialize schema
         Self::init_schema(&conn)?;
 
@@ -45,10 +66,87 @@ impl Database {
             [],
         );
         let _ = conn.execute("ALTER TABLE servers ADD COLUMN pid INTEGER", []);
+        let _ = conn.execute(
+            "ALTER TABLE servers ADD COLUMN runner TEXT DEFAULT 'direct'",
+            [],
+        );
+        let _ = conn.execute("ALTER TABLE servers ADD COLUMN runner_path TEXT", []);
+        let _ = conn.execute("ALTER TABLE servers ADD COLUMN compat_prefix TEXT", []);
+        // Start time of `pid`, to tell our server apart from a reused PID
+        let _ = conn.execute("ALTER TABLE servers ADD COLUMN pid_start_time INTEGER", []);
+        // JSON-encoded LaunchProfile
+        let _ = conn.execute("ALTER TABLE servers ADD COLUMN launch_profile TEXT", []);
+        // JSON-encoded ReadinessConfig
+        let _ = conn.execute("ALTER TABLE servers ADD COLUMN readiness_probe TEXT", []);
+        // JSON-encoded RestartPolicy
+        let _ = conn.execute("ALTER TABLE servers ADD COLUMN restart_policy TEXT", []);
+        let _ = conn.execute("ALTER TABLE servers ADD COLUMN rcon_host TEXT", []);
+        // Source server of a clone whose game files are linked to the source's
+        let _ = conn.execute("ALTER TABLE servers ADD COLUMN shared_install_of INTEGER", []);
+
+        Self::migrate_check(
+            conn,
+            "servers",
+            "status",
+            SERVER_STATUSES,
+            &["CREATE INDEX IF NOT EXISTS idx_servers_status ON servers(status)"],
+        )?;
+        Self::migrate_check(conn, "schedules", "task_type", SCHEDULE_TASK_TYPES, &[])?;
 
         Ok(())
     }
 
+    /// SQLite can't alter a CHECK constraint, so when the allowed values of
+    /// `table.column` have changed the table is rebuilt from its own
+    /// definition with the new list, keeping every row and column. `indexes`
+    /// are recreated afterwards.
+    fn migrate_check(conn: &Connection, table: &str, column: &str, allowed: &[&str], indexes: &[&str]) -> Result<()> {
+        let check_start = format!("CHECK({} IN (", column);
+
+        let sql: String = conn.query_row(
+            "SELECT sql FROM sqlite_master WHERE type = 'table' AND name = ?1",
+            [table],
+            |row| row.get(0),
+        )?;
+
+        let Some(start) = sql.find(&check_start).map(|i| i + check_start.len()) else {
+            return Ok(());
+        };
+        let Some(end) = sql[start..].find("))").map(|i| start + i) else {
+            return Ok(());
+        };
+
+        let values = allowed
+            .iter()
+            .map(|s| format!("'{}'", s))
+            .collect::<Vec<_>>()
+            .join(", ");
+        if sql[start..end] == values {
+            return Ok(());
+        }
+
+        // After a rename SQLite stores the name quoted, so replace everything
+        // up to the column list
+        let columns = sql.find('(').unwrap_or(0);
+        let create = format!("CREATE TABLE {}_new {}{}{}", table, &sql[columns..start], values, &sql[end..]);
+
+        // Dropping the old table must not cascade into the tables referencing it
+        conn.execute("PRAGMA foreign_keys = OFF", [])?;
+        let result = (|| {
+            let tx = conn.unchecked_transaction()?;
+            tx.execute(&create, [])?;
+            tx.execute(&format!("INSERT INTO {0}_new SELECT * FROM {0}", table), [])?;
+            tx.execute(&format!("DROP TABLE {}", table), [])?;
+            tx.execute(&format!("ALTER TABLE {0}_new RENAME TO {0}", table), [])?;
+            for index in indexes {
+                tx.execute(index, [])?;
+            }
+            tx.commit()
+        })();
+        conn.execute("PRAGMA foreign_keys = ON", [])?;
+        result
+    }
+
     pub fn get_connection(&self) -> std::result::Result<std::sync::MutexGuard<Connection>, String> {
         self.conn.lock().map_err(|e| e.to_string())
     }
@@ -75,3 +173,18 @@ impl Database {
         Ok(())
     }
 }
+
+/// In-memory database with the full schema and one ASE server (id 1), for tests.
+#[cfg(test)]
+pub fn test_connection() -> Connection {
+    let conn = Connection::open_in_memory().unwrap();
+    Database::init_schema(&conn).unwrap();
+    conn.execute(
+        "INSERT INTO servers (name, server_type, install_path, game_port, query_port, rcon_port,
+         admin_password, map_name, session_name)
+         VALUES ('Test', 'ASE', '/srv/ark', 7777, 27015, 27020, 'secret', 'TheIsland', 'Test')",
+        [],
+    )
+    .unwrap();
+    conn
+}
@@ -6,7 +6,7 @@ CREATE TABLE IF NOT EXISTS servers (
     name TEXT NOT NULL,
     server_type TEXT NOT NULL CHECK(server_type IN ('ASE', 'ASA')),
     install_path TEXT NOT NULL,
-    status TEXT NOT NULL DEFAULT 'stopped' CHECK(status IN ('stopped', 'starting', 'running', 'crashed', 'updating')),
+    status TEXT NOT NULL DEFAULT 'stopped' CHECK(status IN ('stopped', 'starting', 'running', 'crashed', 'updating', 'failed', 'crash-looping', 'installing', 'stopping', 'maintenance')),
     game_port INTEGER NOT NULL,
     query_port INTEGER NOT NULL,
     rcon_port INTEGER NOT NULL,
@@ -77,7 +77,7 @@ CREATE TABLE IF NOT EXISTS settings (
 CREATE TABLE IF NOT EXISTS schedules (
     id INTEGER PRIMARY KEY AUTOINCREMENT,
     server_id INTEGER NOT NULL,
-    task_type TEXT NOT NULL CHECK(task_type IN ('restart', 'backup', 'broadcast')),
+    task_type TEXT NOT NULL CHECK(task_type IN ('restart', 'backup', 'broadcast', 'update')),
     cron_expression TEXT NOT NULL,
     payload TEXT,
     enabled BOOLEAN DEFAULT 1,
@@ -90,3 +90,130 @@ CREATE TABLE IF NOT EXISTS schedules (
 CREATE INDEX IF NOT EXISTS idx_mods_server_id ON mods(server_id);
 CREATE INDEX IF NOT EXISTS idx_backups_server_id ON backups(server_id);
 CREATE INDEX IF NOT EXISTS idx_servers_status ON servers(status);
+
+-- Crash bundles: logs and dumps collected when a server exits unexpectedly
+CREATE TABLE IF NOT EXISTS crash_bundles (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    server_id INTEGER NOT NULL,
+    exit_code INTEGER,
+    bundle_path TEXT NOT NULL,
+    size INTEGER NOT NULL,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (server_id) REFERENCES servers (id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_crash_bundles_server_id ON crash_bundles(server_id);
+
+-- Every status change of a server and what caused it
+CREATE TABLE IF NOT EXISTS server_status_history (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    server_id INTEGER NOT NULL,
+    from_status TEXT,
+    to_status TEXT NOT NULL,
+    cause TEXT NOT NULL,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (server_id) REFERENCES servers (id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_server_status_history_server_id ON server_status_history(server_id);
+
+-- Tokens for the HTTP API; the secret itself is never stored
+CREATE TABLE IF NOT EXISTS api_tokens (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    name TEXT NOT NULL,
+    token_hash TEXT NOT NULL UNIQUE,
+    scopes TEXT NOT NULL, -- space-separated
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    last_used_at TIMESTAMP
+);
+
+-- Outbound webhooks and the log of their delivery attempts
+CREATE TABLE IF NOT EXISTS webhooks (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    name TEXT NOT NULL,
+    url TEXT NOT NULL,
+    events TEXT NOT NULL, -- space-separated
+    template TEXT,
+    secret TEXT NOT NULL,
+    enabled BOOLEAN NOT NULL DEFAULT 1,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE TABLE IF NOT EXISTS webhook_deliveries (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    webhook_id INTEGER NOT NULL,
+    event TEXT NOT NULL,
+    attempt INTEGER NOT NULL,
+    status_code INTEGER,
+    error TEXT,
+    delivered BOOLEAN NOT NULL,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (webhook_id) REFERENCES webhooks (id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_webhook_deliveries_webhook_id ON webhook_deliveries(webhook_id);
+
+-- Who was on which server when; left_at is NULL while they are online
+CREATE TABLE IF NOT EXISTS player_sessions (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    server_id INTEGER NOT NULL,
+    platform_id TEXT NOT NULL,
+    platform TEXT NOT NULL,
+    name TEXT NOT NULL,
+    joined_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    left_at TIMESTAMP,
+    FOREIGN KEY (server_id) REFERENCES servers (id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_player_sessions_server_id ON player_sessions(server_id, left_at);
+CREATE INDEX IF NOT EXISTS idx_player_sessions_platform_id ON player_sessions(platform_id);
+
+-- In-game chat fetched with GetChat, pruned after chat_retention_days
+CREATE TABLE IF NOT EXISTS chat_messages (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    server_id INTEGER NOT NULL,
+    kind TEXT NOT NULL,
+    sender TEXT NOT NULL,
+    character TEXT,
+    tribe TEXT,
+    message TEXT NOT NULL,
+    sent_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (server_id) REFERENCES servers (id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_chat_messages_server_id ON chat_messages(server_id, id);
+CREATE INDEX IF NOT EXISTS idx_chat_messages_sent_at ON chat_messages(sent_at);
+
+-- Bans made through the manager; expires_at is NULL for permanent bans and
+-- lifted_at is set once the ban is lifted by hand or expires
+CREATE TABLE IF NOT EXISTS bans (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    server_id INTEGER NOT NULL,
+    platform_id TEXT NOT NULL,
+    player_name TEXT,
+    reason TEXT,
+    banned_by TEXT NOT NULL,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    expires_at TIMESTAMP,
+    lifted_at TIMESTAMP,
+    lifted_by TEXT,
+    FOREIGN KEY (server_id) REFERENCES servers (id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_bans_server_id ON bans(server_id, platform_id);
+CREATE INDEX IF NOT EXISTS idx_bans_expires_at ON bans(expires_at) WHERE lifted_at IS NULL;
+
+-- Every kick, ban and unban, with who made it. Kept when the server is deleted.
+CREATE TABLE IF NOT EXISTS moderation_log (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    server_id INTEGER NOT NULL,
+    action TEXT NOT NULL,
+    platform_id TEXT NOT NULL,
+    player_name TEXT,
+    reason TEXT,
+    actor TEXT NOT NULL,
+    error TEXT,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS idx_moderation_log_server_id ON moderation_log(server_id, id);
@@ -4,22 +4,96 @@ mod models;
 mod services;
 
 pub use services::rcon_client;
+pub use services::arkctl::run_arkctl;
+pub use services::daemon::run_daemon;
 
 use db::Database;
+use services::console::ConsoleHistory;
+use services::daemon::DaemonClient;
+use services::events::EventBus;
 use services::process_manager::ProcessManager;
+use services::rcon_sessions::RconSessions;
 use services::steamcmd::SteamCmdService;
 use services::scheduler::SchedulerService;
 use services::discord_bot::DiscordBotHandle;
-use std::sync::Mutex;
-use tauri::Manager;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use tauri::{Emitter, Manager};
 use sysinfo::System;
 use services::notifications::test_discord_webhook;
 
+/// State shared by the commands and the background services. Cheap to clone,
+/// so spawned tasks and the daemon each hold their own handle.
+#[derive(Clone)]
 pub struct AppState {
-    pub db: Mutex<Database>,
-    pub process_manager: ProcessManager,
-    pub sys: Mutex<System>,
-    pub discord_bot: Mutex<DiscordBotHandle>,
+    pub db: Arc<Mutex<Database>>,
+    pub process_manager: Arc<ProcessManager>,
+    pub sys: Arc<Mutex<System>>,
+    pub discord_bot: Arc<Mutex<DiscordBotHandle>>,
+    pub data_dir: PathBuf,
+    pub events: EventBus,
+    pub console: Arc<ConsoleHistory>,
+    pub rcon: Arc<RconSessions>,
+    /// Set when a daemon owns the servers; lifecycle commands are sent to it
+    pub daemon: Option<DaemonClient>,
+}
+
+impl AppState {
+    pub fn open(data_dir: PathBuf) -> Result<Self, String> {
+        std::fs::create_dir_all(&data_dir).map_err(|e| e.to_string())?;
+        let db = Database::new(data_dir.join("ark_manager_v2.db")).map_err(|e| e.to_string())?;
+
+        let mut sys = System::new_all();
+        sys.refresh_all();
+
+        let console = ConsoleHistory::open(data_dir.join("console"));
+
+        Ok(AppState {
+            db: Arc::new(Mutex::new(db)),
+            process_manager: Arc::new(ProcessManager::new()),
+            sys: Arc::new(Mutex::new(sys)),
+            discord_bot: Arc::new(Mutex::new(DiscordBotHandle::new())),
+            data_dir,
+            events: EventBus::new(),
+            console: Arc::new(console),
+            rcon: Arc::new(RconSessions::new()),
+            daemon: None,
+        })
+    }
+}
+
+/// Services that own the servers: SteamCMD check, scheduler, monitor, player
+/// and chat pollers and, when enabled, the HTTP API. Run by the daemon, or by
+/// the GUI when no daemon is running. `api_bind` serves the API regardless of
+/// the settings.
+fn start_core_services(state: &AppState, api_bind: Option<String>) {
+    services::console::spawn_recorder(state);
+
+    // Check and install SteamCMD
+    let steamcmd = SteamCmdService::new(state);
+    tauri::async_runtime::spawn(async move {
+        if !steamcmd.check_installation() {
+            println!("SteamCMD not found, installing...");
+            if let Err(e) = steamcmd.install().await {
+                eprintln!("Failed to install SteamCMD: {}", e);
+            }
+        } else {
+            println!("SteamCMD is already installed.");
+        }
+    });
+
+    // Start Scheduler Service
+    let scheduler = SchedulerService::new(state.clone());
+    scheduler.start();
+
+    // Start Server Monitor Service
+    services::server_monitor::ServerMonitorService::init(state.clone());
+    services::player_sessions::spawn(state.clone());
+    services::chat::spawn(state.clone());
+
+    if let Some(bind) = api_bind.or_else(|| services::http_api::configured_bind(state)) {
+        services::http_api::spawn(state.clone(), bind);
+    }
 }
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
@@ -34,43 +108,40 @@ pub fn run() {
                 .path()
                 .app_data_dir()
                 .expect("failed to get app data dir");
-            std::fs::create_dir_all(&app_dir).expect("failed to create app data dir");
-
-            let db_path = app_dir.join("ark_manager_v2.db");
-            let db = Database::new(db_path).expect("failed to initialize database");
-
-            let mut sys = System::new_all();
-            sys.refresh_all();
+            let mut state = AppState::open(app_dir).expect("failed to initialize database");
 
-            app.manage(AppState {
-                db: Mutex::new(db),
-                process_manager: ProcessManager::new(),
-                sys: Mutex::new(sys),
-                discord_bot: Mutex::new(DiscordBotHandle::new()),
-            });
-
-            // Check and install SteamCMD
+            // Forward service events to the webview
+            let mut events = state.events.subscribe();
             let app_handle = app.handle().clone();
             tauri::async_runtime::spawn(async move {
-                let steamcmd = SteamCmdService::new(app_handle);
-                if !steamcmd.check_installation() {
-                    println!("SteamCMD not found, installing...");
-                    if let Err(e) = steamcmd.install().await {
-                        eprintln!("Failed to install SteamCMD: {}", e);
+                loop {
+                    match events.recv().await {
+                        Ok(event) => {
+                            let _ = app_handle.emit(&event.name, event.payload);
+                        }
+                        Err(tokio::sync::broadcast::error::RecvError::Lagged(_)) => continue,
+                        Err(tokio::sync::broadcast::error::RecvError::Closed) => break,
                     }
-                } else {
-                    println!("SteamCMD is already installed.");
                 }
             });
 
-            // Start Scheduler Service
-            let scheduler_handle = app.handle().clone();
-            let scheduler = SchedulerService::new(scheduler_handle);
-            scheduler.start();
+            // A running daemon owns the servers; otherwise this window does
+            state.daemon = tauri::async_runtime::block_on(DaemonClient::connect(&state.data_dir));
+            match &state.daemon {
+                Some(daemon) => {
+                    println!("Connected to ark-daemon, servers are managed there.");
+                    let daemon = daemon.clone();
+                    let events = state.events.clone();
+                    tauri::async_runtime::spawn(async move {
+                        if let Err(e) = daemon.forward_events(events).await {
+                            eprintln!("Lost connection to ark-daemon: {}", e);
+                        }
+                    });
+                }
+                None => start_core_services(&state, None),
+            }
 
-            // Start Server Monitor Service
-            let monitor_handle = app.handle().clone();
-            services::server_monitor::ServerMonitorService::init(monitor_handle);
+            app.manage(state);
 
             Ok(())
         })
@@ -90,17 +161,17 @@ pub fn run() {
                         tauri::async_runtime::spawn(async move {
                             use tauri_plugin_dialog::DialogExt;
                             let answer = w.dialog()
-                                .message("You have servers running. Are you sure you want to quit? This will stop all servers.")
+                                .message("You have servers running. Are you sure you want to quit? All servers will be saved and shut down first.")
                                 .title("Servers Running")
                                 .blocking_show();
                                 
                             if answer {
-                                w.close(); // Force close logic or graceful shutdown?
-                                // If we call close() again, it might trigger this event again.
-                                // We need to stop servers or exit app.
-                                // Simplest is app_handle.exit(0), but that's abrupt.
-                                // Better: Stop servers then exit.
-                                // For now, let's just exit.
+                                // Save and stop every server before exiting; a plain
+                                // exit would leave them running or lose unsaved progress
+                                if let Some(state) = w.app_handle().try_state::<AppState>() {
+                                    let state = state.inner().clone();
+                                    services::graceful_stop::GracefulStopService::stop_all(&state).await;
+                                }
                                 std::process::exit(0);
                             }
                         });
@@ -122,7 +193,13 @@ pub fn run() {
             // Server commands
             commands::server::get_all_servers,
             commands::server::get_server_by_id,
+            commands::server::update_server_settings,
             commands::server::install_server,
+            commands::server::clone_server,
+            commands::server::detect_server_install,
+            commands::server::import_server,
+            commands::server::preview_asm_profile,
+            commands::server::import_asm_profile,
             commands::server::start_server,
             commands::server::stop_server,
             commands::server::restart_server,
@@ -131,6 +208,27 @@ pub fn run() {
             commands::server::get_server_version,
             commands::server::set_auto_restart,
             commands::server::set_auto_update,
+            commands::server::set_server_runner,
+            commands::server::get_launch_profile,
+            commands::server::save_launch_profile,
+            commands::server::preview_launch_command,
+            commands::server::get_readiness_config,
+            commands::server::save_readiness_config,
+            commands::server::get_restart_policy,
+            commands::server::save_restart_policy,
+            commands::server::reset_crash_loop,
+            commands::server::set_server_maintenance,
+            commands::server::get_server_status_history,
+            // HTTP API commands
+            commands::api::list_api_tokens,
+            commands::api::create_api_token,
+            commands::api::revoke_api_token,
+            // Webhook commands
+            commands::webhooks::list_webhooks,
+            commands::webhooks::save_webhook,
+            commands::webhooks::delete_webhook,
+            commands::webhooks::test_webhook,
+            commands::webhooks::get_webhook_deliveries,
             commands::server::update_server_graceful,
             commands::server::reset_stuck_servers,
             // Mod commands
@@ -148,6 +246,15 @@ pub fn run() {
             commands::backup::delete_backup,
             commands::backup::update_backup,
             commands::backup::view_backup_content,
+            // Crash bundle commands
+            commands::crash::get_crash_bundles,
+            commands::crash::get_crash_bundle,
+            commands::crash::read_crash_bundle_file,
+            commands::crash::export_crash_bundle,
+            commands::crash::delete_crash_bundle,
+            // Console commands
+            commands::console::get_console_history,
+            commands::console::search_console_history,
             // Map commands
             commands::map::update_server_map,
             commands::map::wipe_server_save,
@@ -163,7 +270,17 @@ pub fn run() {
             // RCON commands
             commands::rcon::send_rcon_command,
             commands::rcon::get_online_players,
+            commands::players::get_all_online_players,
+            commands::players::get_player_stats,
+            commands::players::get_player_history,
+            commands::players::get_daily_player_peaks,
+            commands::chat::get_chat_messages,
             commands::rcon::destroy_wild_dinos,
+            commands::rcon::kick_player,
+            commands::rcon::ban_player,
+            commands::rcon::unban_player,
+            commands::rcon::list_bans,
+            commands::rcon::get_moderation_log,
             // Tribe commands
             commands::tribe::get_tribe_files,
             commands::tribe::delete_tribe,
@@ -181,6 +298,8 @@ pub fn run() {
             commands::network::get_local_ips,
             commands::network::set_network_settings,
             commands::network::set_crossplay_enabled,
+            commands::network::check_port_conflicts,
+            commands::network::suggest_server_ports,
             // File commands
             commands::files::list_files,
             commands::files::read_file_content,
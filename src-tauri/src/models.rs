@@ -29,30 +29,79 @@ impl std::str::FromStr for ServerType {
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Lifecycle state of a server. Changes go through `server_status`, which
+/// only allows the moves in its transition table.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
 pub enum ServerStatus {
     Stopped,
+    /// Game files being downloaded or copied for the first time
+    Installing,
     Starting,
     Running,
+    /// Shutting down on request
+    Stopping,
     Crashed,
     Updating,
-    Installing,
+    /// Did not become ready before its startup timeout
+    Failed,
+    /// Crashed too often to keep restarting; waits for an operator
+    #[serde(rename = "crash-looping")]
+    CrashLooping,
+    /// Taken out of service by an operator; can't be started until released
+    Maintenance,
 }
 
 impl ToString for ServerStatus {
     fn to_string(&self) -> String {
         match self {
             ServerStatus::Stopped => "stopped".to_string(),
+            ServerStatus::Installing => "installing".to_string(),
             ServerStatus::Starting => "starting".to_string(),
             ServerStatus::Running => "running".to_string(),
+            ServerStatus::Stopping => "stopping".to_string(),
             ServerStatus::Crashed => "crashed".to_string(),
             ServerStatus::Updating => "updating".to_string(),
-            ServerStatus::Installing => "installing".to_string(),
+            ServerStatus::Failed => "failed".to_string(),
+            ServerStatus::CrashLooping => "crash-looping".to_string(),
+            ServerStatus::Maintenance => "maintenance".to_string(),
         }
     }
 }
 
+impl std::str::FromStr for ServerStatus {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "stopped" => Ok(ServerStatus::Stopped),
+            "installing" => Ok(ServerStatus::Installing),
+            "starting" => Ok(ServerStatus::Starting),
+            "running" => Ok(ServerStatus::Running),
+            "stopping" => Ok(ServerStatus::Stopping),
+            "crashed" => Ok(ServerStatus::Crashed),
+            "updating" => Ok(ServerStatus::Updating),
+            "failed" => Ok(ServerStatus::Failed),
+            "crash-looping" => Ok(ServerStatus::CrashLooping),
+            "maintenance" => Ok(ServerStatus::Maintenance),
+            _ => Err(format!("Invalid server status: {}", s)),
+        }
+    }
+}
+
+/// One entry of a server's status history.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StatusChange {
+    pub id: i64,
+    pub server_id: i64,
+    /// `None` for the status a server was created with
+    pub from_status: Option<ServerStatus>,
+    pub to_status: ServerStatus,
+    pub cause: String,
+    pub created_at: String,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Server {
@@ -66,9 +115,13 @@ pub struct Server {
     pub created_at: String,
     pub last_started: Option<String>,
     pub pid: Option<u32>,
+    /// For a clone sharing its game files with another server: that server.
+    /// Such a clone is updated through its source.
+    #[serde(default)]
+    pub shared_install_of: Option<i64>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ServerPorts {
     pub game_port: u16,
@@ -87,9 +140,223 @@ pub struct ServerConfig {
     pub motd: Option<String>,
     pub battleye_enabled: bool,
     pub multihome_ip: Option<String>,
+    /// Where RCON is reached when it isn't this machine or the MultiHome address
+    #[serde(default)]
+    pub rcon_host: Option<String>,
     pub crossplay_enabled: bool,
     pub auto_restart: Option<bool>,
     pub auto_update: Option<bool>,
+    #[serde(default)]
+    pub runner: RunnerConfig,
+    #[serde(default)]
+    pub launch_profile: LaunchProfile,
+}
+
+/// Changes to a server's settings; fields left out keep their current value.
+/// An empty `server_password`, `motd`, `multihome_ip` or `rcon_host` clears it.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ServerSettingsUpdate {
+    pub name: Option<String>,
+    pub install_path: Option<String>,
+    pub ports: Option<ServerPorts>,
+    pub max_players: Option<i32>,
+    pub server_password: Option<String>,
+    pub admin_password: Option<String>,
+    pub map_name: Option<String>,
+    pub session_name: Option<String>,
+    pub motd: Option<String>,
+    pub battleye_enabled: Option<bool>,
+    pub multihome_ip: Option<String>,
+    pub rcon_host: Option<String>,
+    pub crossplay_enabled: Option<bool>,
+    pub auto_restart: Option<bool>,
+    pub auto_update: Option<bool>,
+    pub runner: Option<RunnerConfig>,
+    pub launch_profile: Option<LaunchProfile>,
+}
+
+/// Settings found in an existing ARK install, before it is registered.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DetectedInstall {
+    pub install_path: PathBuf,
+    pub server_type: ServerType,
+    /// Steam build from the appmanifest, when there is one
+    pub build_id: Option<String>,
+    pub session_name: Option<String>,
+    pub map_name: String,
+    pub ports: ServerPorts,
+    pub max_players: i32,
+    pub server_password: Option<String>,
+    /// Missing when the INI has none; a random one is generated on import
+    pub admin_password: Option<String>,
+    pub motd: Option<String>,
+    pub active_mods: Vec<String>,
+    /// Things that were guessed rather than read
+    pub warnings: Vec<String>,
+}
+
+/// How a clone gets its game files.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CloneInstallMode {
+    /// Full copy of the install; independent of the source
+    #[default]
+    Copy,
+    /// Links to the source's game files with its own `Saved` folder. Only the
+    /// source can be updated, which updates both.
+    Shared,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct CloneServerOptions {
+    /// Where the clone lives; next to the source, named after the clone, when unset
+    pub install_path: Option<String>,
+    pub install_mode: CloneInstallMode,
+    /// Copy the live world saves
+    pub copy_saves: bool,
+    /// Copy the installed mods list
+    pub copy_mods: bool,
+    pub copy_launch_profile: bool,
+    /// Take saves, and configs if it has them, from this backup of the source
+    /// instead of the live server
+    pub from_backup_id: Option<i64>,
+    /// Free ports are picked when unset
+    pub ports: Option<ServerPorts>,
+}
+
+/// A schedule that an imported ASM profile turns into.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportedSchedule {
+    pub task_type: String,
+    pub cron_expression: String,
+    pub payload: Option<String>,
+}
+
+/// One INI value an imported ASM profile sets.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IniSetting {
+    /// `GameUserSettings.ini` or `Game.ini`
+    pub file: String,
+    pub section: String,
+    pub key: String,
+    pub value: String,
+}
+
+/// A profile setting with nowhere to go here.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UnmappedSetting {
+    pub name: String,
+    pub value: String,
+}
+
+/// What a classic Ark Server Manager `.profile` maps to, and what it doesn't.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AsmProfileImport {
+    /// Set once the profile has been imported
+    pub server_id: Option<i64>,
+    pub profile_name: Option<String>,
+    /// Install, ports, passwords, map and mods
+    pub install: DetectedInstall,
+    /// The rest of the server's settings, applied after it is registered
+    pub settings: ServerSettingsUpdate,
+    pub schedules: Vec<ImportedSchedule>,
+    pub ini_settings: Vec<IniSetting>,
+    pub unmapped: Vec<UnmappedSetting>,
+}
+
+/// How the server executable is launched. `Wine` and `Proton` wrap the
+/// Windows binary so ASA can be hosted on Linux.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RunnerKind {
+    #[default]
+    Direct,
+    Wine,
+    Proton,
+}
+
+impl std::fmt::Display for RunnerKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RunnerKind::Direct => write!(f, "direct"),
+            RunnerKind::Wine => write!(f, "wine"),
+            RunnerKind::Proton => write!(f, "proton"),
+        }
+    }
+}
+
+impl std::str::FromStr for RunnerKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "direct" => Ok(RunnerKind::Direct),
+            "wine" => Ok(RunnerKind::Wine),
+            "proton" => Ok(RunnerKind::Proton),
+            _ => Err(format!("Invalid runner: {}", s)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RunnerConfig {
+    pub kind: RunnerKind,
+    /// Wine binary (defaults to `wine` on PATH) or the Proton install directory
+    pub path: Option<String>,
+    /// WINEPREFIX / STEAM_COMPAT_DATA_PATH; a per-server folder in app data when unset
+    pub prefix: Option<String>,
+}
+
+/// Extra launch settings stored per server and merged over the options the
+/// manager builds from the server's own settings. A profile entry with the
+/// same name as a built-in one replaces it in place.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LaunchProfile {
+    /// `?Key=Value` map options, in the order they are passed
+    #[serde(default)]
+    pub options: Vec<LaunchOption>,
+    /// Dash flags such as `-ForceAllowCaveFlyers` or `-clusterid=abc`
+    #[serde(default)]
+    pub flags: Vec<String>,
+    /// Environment variables set for the server process
+    #[serde(default)]
+    pub env: Vec<LaunchEnvVar>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LaunchOption {
+    pub key: String,
+    /// `None` passes a bare `?Key`
+    pub value: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LaunchEnvVar {
+    pub name: String,
+    pub value: String,
+}
+
+/// What `preview_launch_command` reports: exactly what would be spawned.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LaunchPreview {
+    pub program: String,
+    pub args: Vec<String>,
+    pub env: Vec<LaunchEnvVar>,
+    pub working_dir: String,
+    /// Shell-style rendering of program and args, for display
+    pub command_line: String,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -178,9 +445,429 @@ pub struct PerformanceMetrics {
 pub struct Schedule {
     pub id: i64,
     pub server_id: i64,
-    pub task_type: String, // restart, backup, broadcast
+    pub task_type: String, // restart, backup, broadcast, update
     pub cron_expression: String,
     pub payload: Option<String>,
     pub enabled: bool,
     pub last_run: Option<String>,
 }
+
+/// How to tell that a server has finished loading. Until the probe passes
+/// the server stays `starting`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum ReadinessProbe {
+    /// A server log line containing `pattern`, ignoring case
+    LogLine { pattern: String },
+    /// The query port answers a Steam server query. ASA has no query port.
+    QueryPort,
+    /// An RCON command succeeds
+    Rcon,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReadinessConfig {
+    pub probe: ReadinessProbe,
+    /// Seconds the server may take to become ready before it is marked failed
+    pub startup_timeout_secs: u64,
+}
+
+impl Default for ReadinessConfig {
+    fn default() -> Self {
+        Self {
+            probe: ReadinessProbe::LogLine { pattern: "has successfully started".to_string() },
+            // Modded ASA maps can take well over ten minutes to load
+            startup_timeout_secs: 1200,
+        }
+    }
+}
+
+/// Limits on automatic restarts after crashes, so a bad mod or corrupt save
+/// can't restart a server forever.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RestartPolicy {
+    /// Restarts allowed within `window_secs` before the server is marked
+    /// `crash-looping`
+    pub max_restarts: u32,
+    pub window_secs: u64,
+    /// Wait before the first restart; doubles for each further one in the window
+    pub initial_backoff_secs: u64,
+    pub max_backoff_secs: u64,
+}
+
+impl Default for RestartPolicy {
+    fn default() -> Self {
+        Self {
+            max_restarts: 3,
+            window_secs: 3600,
+            initial_backoff_secs: 10,
+            max_backoff_secs: 300,
+        }
+    }
+}
+
+/// Logs, dumps and context collected when a server exits unexpectedly.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CrashBundle {
+    pub id: i64,
+    pub server_id: i64,
+    pub exit_code: Option<i32>,
+    pub bundle_path: PathBuf,
+    /// Total size of the bundle in bytes
+    pub size: i64,
+    pub created_at: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CrashBundleFile {
+    /// Path relative to the bundle directory
+    pub path: String,
+    pub size: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CrashBundleDetail {
+    pub bundle: CrashBundle,
+    pub summary: serde_json::Value,
+    pub files: Vec<CrashBundleFile>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PortProtocol {
+    Udp,
+    Tcp,
+}
+
+impl std::fmt::Display for PortProtocol {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PortProtocol::Udp => write!(f, "UDP"),
+            PortProtocol::Tcp => write!(f, "TCP"),
+        }
+    }
+}
+
+/// A port a server needs that is already taken.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PortConflict {
+    pub port: u16,
+    pub protocol: PortProtocol,
+    /// What the server uses the port for: `game`, `raw UDP`, `query` or `RCON`
+    pub purpose: String,
+    /// The other server configured with this port, if that is the clash
+    pub conflicting_server_id: Option<i64>,
+    pub conflicting_server_name: Option<String>,
+    pub message: String,
+}
+
+/// A token for the HTTP API. Only a hash of the secret is stored.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ApiToken {
+    pub id: i64,
+    pub name: String,
+    /// What the token may do, e.g. `servers:read` or `rcon`; `*` is everything
+    pub scopes: Vec<String>,
+    pub created_at: String,
+    pub last_used_at: Option<String>,
+}
+
+impl ApiToken {
+    pub fn allows(&self, scope: &str) -> bool {
+        self.scopes.iter().any(|s| s == "*" || s == scope)
+    }
+}
+
+/// A freshly created token; `secret` is shown this once and never again.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NewApiToken {
+    pub token: ApiToken,
+    pub secret: String,
+}
+
+/// Which account system a player's ID belongs to: Steam64 IDs on ASE,
+/// Epic Online Services IDs on ASA.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PlayerPlatform {
+    Steam,
+    Eos,
+    Unknown,
+}
+
+impl ToString for PlayerPlatform {
+    fn to_string(&self) -> String {
+        match self {
+            PlayerPlatform::Steam => "steam".to_string(),
+            PlayerPlatform::Eos => "eos".to_string(),
+            PlayerPlatform::Unknown => "unknown".to_string(),
+        }
+    }
+}
+
+impl std::str::FromStr for PlayerPlatform {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "steam" => Ok(PlayerPlatform::Steam),
+            "eos" => Ok(PlayerPlatform::Eos),
+            "unknown" => Ok(PlayerPlatform::Unknown),
+            _ => Err(format!("Invalid player platform: {}", s)),
+        }
+    }
+}
+
+/// A player online on a server, from `ListPlayers`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Player {
+    /// Position in the list, as the server numbers it
+    pub index: u32,
+    pub name: String,
+    pub platform_id: String,
+    pub platform: PlayerPlatform,
+}
+
+/// One stretch of a player being on a server. Times are UTC.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PlayerSession {
+    pub id: i64,
+    pub server_id: i64,
+    pub platform_id: String,
+    pub platform: PlayerPlatform,
+    pub name: String,
+    pub joined_at: String,
+    /// `None` while the player is still online
+    pub left_at: Option<String>,
+}
+
+/// Totals for one player over their recorded sessions.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PlayerStats {
+    pub platform_id: String,
+    pub platform: PlayerPlatform,
+    /// Name in the player's latest session
+    pub name: String,
+    pub sessions: i64,
+    /// Open sessions count up to now
+    pub playtime_seconds: i64,
+    pub last_seen: String,
+    pub online: bool,
+}
+
+/// Most players online at once during one UTC day.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DailyPeak {
+    /// `YYYY-MM-DD`
+    pub date: String,
+    pub peak: u32,
+}
+
+/// Where a line from `GetChat` came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ChatKind {
+    /// Global chat from a player
+    Player,
+    /// Broadcasts and `ServerChat` messages
+    Server,
+    /// Admin commands, when the server logs them to chat
+    Admin,
+    /// Tribe log entries, when the server includes them
+    Tribe,
+}
+
+impl ToString for ChatKind {
+    fn to_string(&self) -> String {
+        match self {
+            ChatKind::Player => "player".to_string(),
+            ChatKind::Server => "server".to_string(),
+            ChatKind::Admin => "admin".to_string(),
+            ChatKind::Tribe => "tribe".to_string(),
+        }
+    }
+}
+
+impl std::str::FromStr for ChatKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "player" => Ok(ChatKind::Player),
+            "server" => Ok(ChatKind::Server),
+            "admin" => Ok(ChatKind::Admin),
+            "tribe" => Ok(ChatKind::Tribe),
+            _ => Err(format!("Invalid chat kind: {}", s)),
+        }
+    }
+}
+
+/// A captured chat line.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChatMessage {
+    pub id: i64,
+    pub server_id: i64,
+    pub kind: ChatKind,
+    /// Player (Steam or EOS display name), `SERVER`, or the admin who ran a command
+    pub sender: String,
+    /// In-game character name, for player messages
+    pub character: Option<String>,
+    pub tribe: Option<String>,
+    pub message: String,
+    /// UTC, when the line was fetched
+    pub sent_at: String,
+}
+
+/// A ban on one server. Bans found only in the server's `BanList.txt`,
+/// made in game or by hand, have no `id` and none of the details.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Ban {
+    pub id: Option<i64>,
+    pub server_id: i64,
+    pub platform_id: String,
+    pub player_name: Option<String>,
+    pub reason: Option<String>,
+    pub banned_by: Option<String>,
+    pub created_at: Option<String>,
+    /// UTC; `None` for a permanent ban
+    pub expires_at: Option<String>,
+    /// UTC, once the ban was lifted by hand or expired
+    pub lifted_at: Option<String>,
+    pub lifted_by: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ModerationKind {
+    Kick,
+    Ban,
+    Unban,
+}
+
+impl ToString for ModerationKind {
+    fn to_string(&self) -> String {
+        match self {
+            ModerationKind::Kick => "kick".to_string(),
+            ModerationKind::Ban => "ban".to_string(),
+            ModerationKind::Unban => "unban".to_string(),
+        }
+    }
+}
+
+impl std::str::FromStr for ModerationKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "kick" => Ok(ModerationKind::Kick),
+            "ban" => Ok(ModerationKind::Ban),
+            "unban" => Ok(ModerationKind::Unban),
+            _ => Err(format!("Invalid moderation action: {}", s)),
+        }
+    }
+}
+
+/// An entry in the moderation audit log.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ModerationAction {
+    pub id: i64,
+    pub server_id: i64,
+    pub action: ModerationKind,
+    pub platform_id: String,
+    pub player_name: Option<String>,
+    pub reason: Option<String>,
+    /// Who made it: `GUI (<OS user>)`, `HTTP API token "<name>"`, or
+    /// `scheduler` for bans lifted on expiry
+    pub actor: String,
+    /// Why the action could not be applied on the server, if it failed
+    pub error: Option<String>,
+    pub created_at: String,
+}
+
+/// What a webhook can be sent for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum WebhookEvent {
+    Crash,
+    /// The server finished loading and is ready for players
+    Start,
+    Stop,
+    /// An update finished, successfully or not
+    Update,
+    BackupFailed,
+    PlayerJoined,
+}
+
+impl ToString for WebhookEvent {
+    fn to_string(&self) -> String {
+        match self {
+            WebhookEvent::Crash => "crash".to_string(),
+            WebhookEvent::Start => "start".to_string(),
+            WebhookEvent::Stop => "stop".to_string(),
+            WebhookEvent::Update => "update".to_string(),
+            WebhookEvent::BackupFailed => "backup-failed".to_string(),
+            WebhookEvent::PlayerJoined => "player-joined".to_string(),
+        }
+    }
+}
+
+impl std::str::FromStr for WebhookEvent {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "crash" => Ok(WebhookEvent::Crash),
+            "start" => Ok(WebhookEvent::Start),
+            "stop" => Ok(WebhookEvent::Stop),
+            "update" => Ok(WebhookEvent::Update),
+            "backup-failed" => Ok(WebhookEvent::BackupFailed),
+            "player-joined" => Ok(WebhookEvent::PlayerJoined),
+            _ => Err(format!("Invalid webhook event: {}", s)),
+        }
+    }
+}
+
+/// An outbound webhook target.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Webhook {
+    /// `None` when creating one
+    pub id: Option<i64>,
+    pub name: String,
+    pub url: String,
+    pub events: Vec<WebhookEvent>,
+    /// JSON body with `{{placeholders}}`; `None` sends the default payload
+    pub template: Option<String>,
+    /// Key for the `X-ASM-Signature` HMAC; generated when left empty
+    pub secret: String,
+    pub enabled: bool,
+}
+
+/// One attempt at delivering a webhook.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WebhookDelivery {
+    pub id: i64,
+    pub webhook_id: i64,
+    pub event: String,
+    pub attempt: i32,
+    pub status_code: Option<u16>,
+    pub error: Option<String>,
+    pub delivered: bool,
+    pub created_at: String,
+}
@@ -0,0 +1,106 @@
+use crate::models::{ApiToken, NewApiToken};
+use rand::RngCore;
+use rusqlite::{Connection, OptionalExtension};
+use sha2::{Digest, Sha256};
+
+/// Every scope a token can be given. `*` grants all of them.
+pub const SCOPES: &[&str] = &[
+    "servers:read",
+    "servers:control",
+    "rcon",
+    "backups:read",
+    "backups:write",
+    "schedules:read",
+    "schedules:write",
+    "mods:read",
+    "mods:write",
+    "config:read",
+    "config:write",
+    "metrics",
+];
+
+/// Secrets start with this, so they are easy to spot in scripts and logs.
+const PREFIX: &str = "asm_";
+
+fn hash(secret: &str) -> String {
+    Sha256::digest(secret.as_bytes()).iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn token_from_row(row: &rusqlite::Row) -> rusqlite::Result<ApiToken> {
+    let scopes: String = row.get(2)?;
+    Ok(ApiToken {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        scopes: scopes.split_whitespace().map(String::from).collect(),
+        created_at: row.get(3)?,
+        last_used_at: row.get(4)?,
+    })
+}
+
+/// Create a token with the given scopes. The secret is returned once.
+pub fn create(conn: &Connection, name: &str, scopes: &[String]) -> Result<NewApiToken, String> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err("Token name can't be empty".to_string());
+    }
+    if scopes.is_empty() {
+        return Err("A token needs at least one scope".to_string());
+    }
+    if let Some(unknown) = scopes.iter().find(|s| *s != "*" && !SCOPES.contains(&s.as_str())) {
+        return Err(format!("Unknown scope \"{}\"; expected * or one of {}", unknown, SCOPES.join(", ")));
+    }
+
+    let mut bytes = [0u8; 32];
+    rand::rngs::OsRng.fill_bytes(&mut bytes);
+    let secret = format!("{}{}", PREFIX, bytes.iter().map(|b| format!("{:02x}", b)).collect::<String>());
+
+    conn.execute(
+        "INSERT INTO api_tokens (name, token_hash, scopes) VALUES (?1, ?2, ?3)",
+        (name, hash(&secret), scopes.join(" ")),
+    )
+    .map_err(|e| e.to_string())?;
+    let id = conn.last_insert_rowid();
+    let token = conn
+        .query_row(
+            "SELECT id, name, scopes, created_at, last_used_at FROM api_tokens WHERE id = ?1",
+            [id],
+            token_from_row,
+        )
+        .map_err(|e| e.to_string())?;
+
+    Ok(NewApiToken { token, secret })
+}
+
+pub fn list(conn: &Connection) -> rusqlite::Result<Vec<ApiToken>> {
+    let mut stmt = conn.prepare("SELECT id, name, scopes, created_at, last_used_at FROM api_tokens ORDER BY id")?;
+    let tokens = stmt.query_map([], token_from_row)?;
+    tokens.collect()
+}
+
+pub fn revoke(conn: &Connection, id: i64) -> Result<(), String> {
+    let deleted = conn
+        .execute("DELETE FROM api_tokens WHERE id = ?1", [id])
+        .map_err(|e| e.to_string())?;
+    if deleted == 0 {
+        return Err(format!("Token {} not found", id));
+    }
+    Ok(())
+}
+
+/// The token `secret` belongs to, marking it as used.
+pub fn authenticate(conn: &Connection, secret: &str) -> rusqlite::Result<Option<ApiToken>> {
+    if !secret.starts_with(PREFIX) {
+        return Ok(None);
+    }
+    let token = conn
+        .query_row(
+            "SELECT id, name, scopes, created_at, last_used_at FROM api_tokens WHERE token_hash = ?1",
+            [hash(secret)],
+            token_from_row,
+        )
+        .optional()?;
+    if let Some(token) = &token {
+        conn.execute("UPDATE api_tokens SET last_used_at = CURRENT_TIMESTAMP WHERE id = ?1", [token.id])?;
+    }
+    Ok(token)
+}
@@ -0,0 +1,343 @@
+use crate::commands::{backup, rcon, scheduler};
+use crate::models::{ApiToken, Backup, Schedule, Server};
+use crate::services::daemon::{self, DaemonClient};
+use crate::services::{api_tokens, lifecycle, servers};
+use crate::AppState;
+use clap::{Parser, Subcommand};
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::path::PathBuf;
+
+// Command-line client for scripts and cron jobs. It opens the same database
+// as the app and the daemon. Starting a server needs a process that stays
+// around to own it, so start and restart need `ark-daemon`. Stop and update
+// use the daemon when it runs; the rest works on the database directly.
+
+/// Recorded as the cause of every status change arkctl makes.
+const CAUSE: &str = "arkctl";
+
+#[derive(Debug, Parser)]
+#[command(name = "arkctl", version, about = "Control the servers of Ark Server Manager")]
+struct Cli {
+    /// Data directory of the manager, if not the app's
+    #[arg(long, global = true)]
+    data_dir: Option<PathBuf>,
+
+    /// Print results as JSON
+    #[arg(long, global = true)]
+    json: bool,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// List servers and their status
+    List,
+    /// Start a server (needs ark-daemon)
+    Start { server_id: i64 },
+    /// Save and stop a server
+    Stop { server_id: i64 },
+    /// Save, stop and start a server again (needs ark-daemon)
+    Restart { server_id: i64 },
+    /// Update a server through SteamCMD; it is left stopped
+    Update {
+        server_id: i64,
+        /// Warn players, update and start the server again (needs ark-daemon)
+        #[arg(long)]
+        restart: bool,
+    },
+    /// Create, list and restore backups
+    #[command(subcommand)]
+    Backup(BackupCommand),
+    /// Send an RCON command to a running server
+    Rcon {
+        server_id: i64,
+        /// The command, e.g. `SaveWorld` or `Broadcast hello`
+        #[arg(required = true, trailing_var_arg = true)]
+        command: Vec<String>,
+    },
+    /// List and edit scheduled tasks
+    #[command(subcommand)]
+    Schedule(ScheduleCommand),
+    /// Manage tokens for the HTTP API
+    #[command(subcommand)]
+    Token(TokenCommand),
+}
+
+#[derive(Debug, Subcommand)]
+enum BackupCommand {
+    /// Back up a server's saves and configs
+    Create {
+        server_id: i64,
+        #[arg(long)]
+        note: Option<String>,
+    },
+    /// List a server's backups, newest first
+    List { server_id: i64 },
+    /// Replace a stopped server's saves and configs with a backup
+    Restore { backup_id: i64 },
+}
+
+#[derive(Debug, Subcommand)]
+enum ScheduleCommand {
+    /// List a server's schedules
+    List { server_id: i64 },
+    /// Add a schedule
+    Add {
+        server_id: i64,
+        #[arg(value_parser = ["restart", "backup", "broadcast", "update"])]
+        task_type: String,
+        /// Cron expression with seconds, e.g. "0 0 4 * * * *" for 04:00 daily
+        cron: String,
+        /// Broadcast message
+        #[arg(long)]
+        payload: Option<String>,
+    },
+    /// Delete a schedule
+    Remove { schedule_id: i64 },
+    /// Let a schedule run again
+    Enable { schedule_id: i64 },
+    /// Keep a schedule without running it
+    Disable { schedule_id: i64 },
+}
+
+#[derive(Debug, Subcommand)]
+enum TokenCommand {
+    /// List tokens and their scopes
+    List,
+    /// Create a token; its secret is printed once
+    Create {
+        name: String,
+        /// A scope such as `servers:read` or `rcon`, or `*` for all; repeatable
+        #[arg(long = "scope", required = true)]
+        scopes: Vec<String>,
+    },
+    /// Revoke a token
+    Revoke { token_id: i64 },
+}
+
+/// What a command prints: `data` with `--json`, `text` otherwise.
+struct Output {
+    data: Value,
+    text: String,
+}
+
+impl Output {
+    fn done(text: String) -> Self {
+        Output { data: json!({ "ok": true }), text }
+    }
+
+    fn of<T: Serialize>(value: &T, text: String) -> Result<Self, String> {
+        Ok(Output { data: serde_json::to_value(value).map_err(|e| e.to_string())?, text })
+    }
+}
+
+fn server_table(servers: &[Server]) -> String {
+    let mut lines = vec![format!("{:<4} {:<28} {:<4} {:<14} {}", "ID", "NAME", "TYPE", "STATUS", "PORTS")];
+    for s in servers {
+        lines.push(format!(
+            "{:<4} {:<28} {:<4} {:<14} {}/{}/{}",
+            s.id,
+            s.name,
+            s.server_type.to_string(),
+            s.status.to_string(),
+            s.ports.game_port,
+            s.ports.query_port,
+            s.ports.rcon_port
+        ));
+    }
+    lines.join("\n")
+}
+
+fn backup_table(backups: &[Backup]) -> String {
+    let mut lines = vec![format!("{:<5} {:<11} {:<26} {:>10}  {}", "ID", "TYPE", "CREATED", "SIZE (MB)", "PATH")];
+    for b in backups {
+        lines.push(format!(
+            "{:<5} {:<11} {:<26} {:>10.1}  {}",
+            b.id,
+            b.backup_type.to_string(),
+            b.created_at,
+            b.size as f64 / 1_048_576.0,
+            b.file_path.display()
+        ));
+    }
+    lines.join("\n")
+}
+
+fn schedule_table(schedules: &[Schedule]) -> String {
+    let mut lines = vec![format!("{:<5} {:<10} {:<22} {:<8} {}", "ID", "TASK", "CRON", "ENABLED", "LAST RUN")];
+    for s in schedules {
+        lines.push(format!(
+            "{:<5} {:<10} {:<22} {:<8} {}",
+            s.id,
+            s.task_type,
+            s.cron_expression,
+            s.enabled,
+            s.last_run.as_deref().unwrap_or("-")
+        ));
+    }
+    lines.join("\n")
+}
+
+fn token_table(tokens: &[ApiToken]) -> String {
+    let mut lines = vec![format!("{:<4} {:<20} {:<20} {}", "ID", "NAME", "LAST USED", "SCOPES")];
+    for t in tokens {
+        lines.push(format!(
+            "{:<4} {:<20} {:<20} {}",
+            t.id,
+            t.name,
+            t.last_used_at.as_deref().unwrap_or("never"),
+            t.scopes.join(" ")
+        ));
+    }
+    lines.join("\n")
+}
+
+fn require_daemon<'a>(client: &'a Option<DaemonClient>, action: &str) -> Result<&'a DaemonClient, String> {
+    client.as_ref().ok_or_else(|| {
+        format!(
+            "ark-daemon is not running. Start it first: a server arkctl {}s would lose its console and monitor when arkctl exits",
+            action
+        )
+    })
+}
+
+async fn run(state: &AppState, client: Option<DaemonClient>, command: Command) -> Result<Output, String> {
+    match command {
+        Command::List => {
+            let servers = {
+                let db = state.db.lock().map_err(|e| e.to_string())?;
+                let conn = db.get_connection().map_err(|e| e.to_string())?;
+                servers::list(&conn).map_err(|e| e.to_string())?
+            };
+            Output::of(&servers, server_table(&servers))
+        }
+        Command::Start { server_id } => {
+            let client = require_daemon(&client, "start")?;
+            client.call("start_server", json!({ "serverId": server_id, "cause": CAUSE })).await?;
+            Ok(Output::done(format!("Server {} is starting", server_id)))
+        }
+        Command::Stop { server_id } => {
+            match &client {
+                Some(client) => {
+                    client.call("stop_server", json!({ "serverId": server_id, "cause": CAUSE })).await?;
+                }
+                // Without a daemon the server is stopped through its recorded PID
+                None => lifecycle::stop_server(state, server_id, CAUSE).await?,
+            }
+            Ok(Output::done(format!("Server {} stopped", server_id)))
+        }
+        Command::Restart { server_id } => {
+            let client = require_daemon(&client, "restart")?;
+            client.call("restart_server", json!({ "serverId": server_id, "cause": CAUSE })).await?;
+            Ok(Output::done(format!("Server {} is restarting", server_id)))
+        }
+        Command::Update { server_id, restart: true } => {
+            let client = require_daemon(&client, "restart")?;
+            client
+                .call("update_server_graceful", json!({ "serverId": server_id, "cause": CAUSE }))
+                .await?;
+            Ok(Output::done(format!("Server {} updated and starting", server_id)))
+        }
+        Command::Update { server_id, restart: false } => {
+            match &client {
+                Some(client) => {
+                    client.call("update_server", json!({ "serverId": server_id, "cause": CAUSE })).await?;
+                }
+                None => lifecycle::update_server(state, server_id, CAUSE).await?,
+            }
+            Ok(Output::done(format!("Server {} updated", server_id)))
+        }
+        Command::Backup(BackupCommand::Create { server_id, note }) => {
+            let backup = backup::backup_server(state, server_id, "manual".to_string(), note).await?;
+            let text = format!("Backup {} created in {}", backup.id, backup.file_path.display());
+            Output::of(&backup, text)
+        }
+        Command::Backup(BackupCommand::List { server_id }) => {
+            let backups = backup::backups_for(state, server_id)?;
+            Output::of(&backups, backup_table(&backups))
+        }
+        Command::Backup(BackupCommand::Restore { backup_id }) => {
+            backup::restore(state, backup_id)?;
+            Ok(Output::done(format!("Backup {} restored", backup_id)))
+        }
+        Command::Rcon { server_id, command } => {
+            let response = rcon::send_to_server(state, server_id, command.join(" ")).await?;
+            Ok(Output { data: json!({ "response": response }), text: response.trim_end().to_string() })
+        }
+        Command::Schedule(ScheduleCommand::List { server_id }) => {
+            let schedules = scheduler::schedules_for(state, server_id)?;
+            Output::of(&schedules, schedule_table(&schedules))
+        }
+        Command::Schedule(ScheduleCommand::Add { server_id, task_type, cron, payload }) => {
+            let id = scheduler::add_schedule(state, server_id, &task_type, &cron, payload.as_deref())?;
+            Ok(Output { data: json!({ "id": id }), text: format!("Schedule {} added", id) })
+        }
+        Command::Schedule(ScheduleCommand::Remove { schedule_id }) => {
+            scheduler::remove_schedule(state, schedule_id)?;
+            Ok(Output::done(format!("Schedule {} removed", schedule_id)))
+        }
+        Command::Schedule(ScheduleCommand::Enable { schedule_id }) => {
+            scheduler::set_schedule_enabled(state, schedule_id, true)?;
+            Ok(Output::done(format!("Schedule {} enabled", schedule_id)))
+        }
+        Command::Schedule(ScheduleCommand::Disable { schedule_id }) => {
+            scheduler::set_schedule_enabled(state, schedule_id, false)?;
+            Ok(Output::done(format!("Schedule {} disabled", schedule_id)))
+        }
+        Command::Token(command) => {
+            let db = state.db.lock().map_err(|e| e.to_string())?;
+            let conn = db.get_connection().map_err(|e| e.to_string())?;
+            match command {
+                TokenCommand::List => {
+                    let tokens = api_tokens::list(&conn).map_err(|e| e.to_string())?;
+                    Output::of(&tokens, token_table(&tokens))
+                }
+                TokenCommand::Create { name, scopes } => {
+                    let created = api_tokens::create(&conn, &name, &scopes)?;
+                    let text = format!("Token {} created. Its secret is shown only now:\n{}", created.token.id, created.secret);
+                    Output::of(&created, text)
+                }
+                TokenCommand::Revoke { token_id } => {
+                    api_tokens::revoke(&conn, token_id)?;
+                    Ok(Output::done(format!("Token {} revoked", token_id)))
+                }
+            }
+        }
+    }
+}
+
+/// Entry point of the `arkctl` binary. Exits with 1 when the command
+/// fails and 2 on bad arguments; with `--json` the error is also printed
+/// to stdout as `{"error": ...}`.
+pub fn run_arkctl() {
+    let Cli { data_dir, json, command } = Cli::parse();
+
+    let Some(data_dir) = data_dir.or_else(daemon::default_data_dir) else {
+        eprintln!("Could not determine the data directory; pass --data-dir");
+        std::process::exit(2);
+    };
+
+    let runtime = tokio::runtime::Runtime::new().expect("failed to start tokio runtime");
+    tauri::async_runtime::set(runtime.handle().clone());
+
+    let result = runtime.block_on(async move {
+        let state = AppState::open(data_dir).map_err(|e| format!("Failed to open data directory: {}", e))?;
+        let client = DaemonClient::connect(&state.data_dir).await;
+        run(&state, client, command).await
+    });
+
+    match result {
+        Ok(output) if json => println!("{}", output.data),
+        Ok(output) => println!("{}", output.text),
+        Err(e) => {
+            if json {
+                println!("{}", json!({ "error": e }));
+            }
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        }
+    }
+}
@@ -0,0 +1,696 @@
+use crate::models::{
+    AsmProfileImport, DetectedInstall, ImportedSchedule, IniSetting, LaunchOption, LaunchProfile, ServerPorts,
+    ServerSettingsUpdate, ServerType, UnmappedSetting,
+};
+use crate::services::server_import::{self, DEFAULT_GAME_PORT, DEFAULT_MAX_PLAYERS, DEFAULT_QUERY_PORT, DEFAULT_RCON_PORT};
+use crate::services::{platform, servers};
+use anyhow::{anyhow, Context, Result};
+use regex::Regex;
+use rusqlite::Connection;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+// Importer for the `.profile` files of the classic Windows Ark Server
+// Manager. A profile is a serialized `ServerProfile` object: one XML element
+// per setting, with lists (engram overrides, dino spawns...) as nested
+// elements.
+
+const GAME_USER_SETTINGS: &str = "GameUserSettings.ini";
+const GAME_INI: &str = "Game.ini";
+const SERVER_SETTINGS: &str = "ServerSettings";
+const GAME_MODE: &str = "/script/shootergame.shootergamemode";
+
+/// Profile settings that are plain INI values: property, file, section, key,
+/// and whether the meaning is inverted.
+const INI_MAPPINGS: &[(&str, &str, &str, &str, bool)] = &[
+    ("EnablePVP", GAME_USER_SETTINGS, SERVER_SETTINGS, "ServerPVE", true),
+    ("EnableHardcore", GAME_USER_SETTINGS, SERVER_SETTINGS, "ServerHardcore", false),
+    ("EnableTributeDownloads", GAME_USER_SETTINGS, SERVER_SETTINGS, "NoTributeDownloads", true),
+    ("SpectatorPassword", GAME_USER_SETTINGS, SERVER_SETTINGS, "SpectatorPassword", false),
+    ("RCONEnabled", GAME_USER_SETTINGS, SERVER_SETTINGS, "RCONEnabled", false),
+    ("RCONServerGameLogBuffer", GAME_USER_SETTINGS, SERVER_SETTINGS, "RCONServerGameLogBuffer", false),
+    ("MOTDDuration", GAME_USER_SETTINGS, "MessageOfTheDay", "Duration", false),
+    ("XPMultiplier", GAME_USER_SETTINGS, SERVER_SETTINGS, "XPMultiplier", false),
+    ("TamingSpeedMultiplier", GAME_USER_SETTINGS, SERVER_SETTINGS, "TamingSpeedMultiplier", false),
+    ("HarvestAmountMultiplier", GAME_USER_SETTINGS, SERVER_SETTINGS, "HarvestAmountMultiplier", false),
+    ("HarvestHealthMultiplier", GAME_USER_SETTINGS, SERVER_SETTINGS, "HarvestHealthMultiplier", false),
+    ("ResourcesRespawnPeriodMultiplier", GAME_USER_SETTINGS, SERVER_SETTINGS, "ResourcesRespawnPeriodMultiplier", false),
+    ("DifficultyOffset", GAME_USER_SETTINGS, SERVER_SETTINGS, "DifficultyOffset", false),
+    ("OverrideOfficialDifficulty", GAME_USER_SETTINGS, SERVER_SETTINGS, "OverrideOfficialDifficulty", false),
+    ("DayCycleSpeedScale", GAME_USER_SETTINGS, SERVER_SETTINGS, "DayCycleSpeedScale", false),
+    ("DayTimeSpeedScale", GAME_USER_SETTINGS, SERVER_SETTINGS, "DayTimeSpeedScale", false),
+    ("NightTimeSpeedScale", GAME_USER_SETTINGS, SERVER_SETTINGS, "NightTimeSpeedScale", false),
+    ("DinoDamageMultiplier", GAME_USER_SETTINGS, SERVER_SETTINGS, "DinoDamageMultiplier", false),
+    ("PlayerDamageMultiplier", GAME_USER_SETTINGS, SERVER_SETTINGS, "PlayerDamageMultiplier", false),
+    ("StructureDamageMultiplier", GAME_USER_SETTINGS, SERVER_SETTINGS, "StructureDamageMultiplier", false),
+    ("PlayerResistanceMultiplier", GAME_USER_SETTINGS, SERVER_SETTINGS, "PlayerResistanceMultiplier", false),
+    ("DinoResistanceMultiplier", GAME_USER_SETTINGS, SERVER_SETTINGS, "DinoResistanceMultiplier", false),
+    ("StructureResistanceMultiplier", GAME_USER_SETTINGS, SERVER_SETTINGS, "StructureResistanceMultiplier", false),
+    ("DinoCountMultiplier", GAME_USER_SETTINGS, SERVER_SETTINGS, "DinoCountMultiplier", false),
+    ("PlayerCharacterWaterDrainMultiplier", GAME_USER_SETTINGS, SERVER_SETTINGS, "PlayerCharacterWaterDrainMultiplier", false),
+    ("PlayerCharacterFoodDrainMultiplier", GAME_USER_SETTINGS, SERVER_SETTINGS, "PlayerCharacterFoodDrainMultiplier", false),
+    ("PlayerCharacterStaminaDrainMultiplier", GAME_USER_SETTINGS, SERVER_SETTINGS, "PlayerCharacterStaminaDrainMultiplier", false),
+    ("PlayerCharacterHealthRecoveryMultiplier", GAME_USER_SETTINGS, SERVER_SETTINGS, "PlayerCharacterHealthRecoveryMultiplier", false),
+    ("AllowThirdPersonPlayer", GAME_USER_SETTINGS, SERVER_SETTINGS, "AllowThirdPersonPlayer", false),
+    ("AlwaysNotifyPlayerJoined", GAME_USER_SETTINGS, SERVER_SETTINGS, "AlwaysNotifyPlayerJoined", false),
+    ("AlwaysNotifyPlayerLeft", GAME_USER_SETTINGS, SERVER_SETTINGS, "AlwaysNotifyPlayerLeft", false),
+    ("ServerCrosshair", GAME_USER_SETTINGS, SERVER_SETTINGS, "ServerCrosshair", false),
+    ("ShowMapPlayerLocation", GAME_USER_SETTINGS, SERVER_SETTINGS, "ShowMapPlayerLocation", false),
+    ("AllowHitMarkers", GAME_USER_SETTINGS, SERVER_SETTINGS, "AllowHitMarkers", false),
+    ("ShowFloatingDamageText", GAME_USER_SETTINGS, SERVER_SETTINGS, "ShowFloatingDamageText", false),
+    ("GlobalVoiceChat", GAME_USER_SETTINGS, SERVER_SETTINGS, "GlobalVoiceChat", false),
+    ("ProximityChat", GAME_USER_SETTINGS, SERVER_SETTINGS, "ProximityChat", false),
+    ("EnablePvPGamma", GAME_USER_SETTINGS, SERVER_SETTINGS, "EnablePvPGamma", false),
+    ("DisablePvEGamma", GAME_USER_SETTINGS, SERVER_SETTINGS, "DisablePvEGamma", false),
+    ("AllowFlyerCarryPvE", GAME_USER_SETTINGS, SERVER_SETTINGS, "AllowFlyerCarryPvE", false),
+    ("AllowCaveBuildingPvE", GAME_USER_SETTINGS, SERVER_SETTINGS, "AllowCaveBuildingPvE", false),
+    ("DisableStructureDecayPvE", GAME_USER_SETTINGS, SERVER_SETTINGS, "DisableStructureDecayPvE", false),
+    ("PvEDinoDecayPeriodMultiplier", GAME_USER_SETTINGS, SERVER_SETTINGS, "PvEDinoDecayPeriodMultiplier", false),
+    ("AutoSavePeriodMinutes", GAME_USER_SETTINGS, SERVER_SETTINGS, "AutoSavePeriodMinutes", false),
+    ("KickIdlePlayersPeriod", GAME_USER_SETTINGS, SERVER_SETTINGS, "KickIdlePlayersPeriod", false),
+    ("MaxTamedDinos", GAME_USER_SETTINGS, SERVER_SETTINGS, "MaxTamedDinos", false),
+    ("TribeNameChangeCooldown", GAME_USER_SETTINGS, SERVER_SETTINGS, "TribeNameChangeCooldown", false),
+    ("PreventDownloadSurvivors", GAME_USER_SETTINGS, SERVER_SETTINGS, "PreventDownloadSurvivors", false),
+    ("PreventDownloadItems", GAME_USER_SETTINGS, SERVER_SETTINGS, "PreventDownloadItems", false),
+    ("PreventDownloadDinos", GAME_USER_SETTINGS, SERVER_SETTINGS, "PreventDownloadDinos", false),
+    ("PreventUploadSurvivors", GAME_USER_SETTINGS, SERVER_SETTINGS, "PreventUploadSurvivors", false),
+    ("PreventUploadItems", GAME_USER_SETTINGS, SERVER_SETTINGS, "PreventUploadItems", false),
+    ("PreventUploadDinos", GAME_USER_SETTINGS, SERVER_SETTINGS, "PreventUploadDinos", false),
+    ("MatingIntervalMultiplier", GAME_INI, GAME_MODE, "MatingIntervalMultiplier", false),
+    ("EggHatchSpeedMultiplier", GAME_INI, GAME_MODE, "EggHatchSpeedMultiplier", false),
+    ("BabyMatureSpeedMultiplier", GAME_INI, GAME_MODE, "BabyMatureSpeedMultiplier", false),
+    ("BabyFoodConsumptionSpeedMultiplier", GAME_INI, GAME_MODE, "BabyFoodConsumptionSpeedMultiplier", false),
+    ("BabyCuddleIntervalMultiplier", GAME_INI, GAME_MODE, "BabyCuddleIntervalMultiplier", false),
+    ("BabyImprintingStatScaleMultiplier", GAME_INI, GAME_MODE, "BabyImprintingStatScaleMultiplier", false),
+    ("CropGrowthSpeedMultiplier", GAME_INI, GAME_MODE, "CropGrowthSpeedMultiplier", false),
+    ("LayEggIntervalMultiplier", GAME_INI, GAME_MODE, "LayEggIntervalMultiplier", false),
+    ("PoopIntervalMultiplier", GAME_INI, GAME_MODE, "PoopIntervalMultiplier", false),
+    ("HairGrowthSpeedMultiplier", GAME_INI, GAME_MODE, "HairGrowthSpeedMultiplier", false),
+    ("MaxNumberOfPlayersInTribe", GAME_INI, GAME_MODE, "MaxNumberOfPlayersInTribe", false),
+    ("SupplyCrateLootQualityMultiplier", GAME_INI, GAME_MODE, "SupplyCrateLootQualityMultiplier", false),
+    ("FishingLootQualityMultiplier", GAME_INI, GAME_MODE, "FishingLootQualityMultiplier", false),
+    ("KillXPMultiplier", GAME_INI, GAME_MODE, "KillXPMultiplier", false),
+    ("HarvestXPMultiplier", GAME_INI, GAME_MODE, "HarvestXPMultiplier", false),
+    ("CraftXPMultiplier", GAME_INI, GAME_MODE, "CraftXPMultiplier", false),
+    ("GenericXPMultiplier", GAME_INI, GAME_MODE, "GenericXPMultiplier", false),
+    ("SpecialXPMultiplier", GAME_INI, GAME_MODE, "SpecialXPMultiplier", false),
+    ("DisableFriendlyFirePvP", GAME_INI, GAME_MODE, "bPvPDisableFriendlyFire", false),
+    ("DisableFriendlyFirePvE", GAME_INI, GAME_MODE, "bPvEDisableFriendlyFire", false),
+    ("AllowUnlimitedRespecs", GAME_INI, GAME_MODE, "bAllowUnlimitedRespecs", false),
+    ("AllowPlatformSaddleMultiFloors", GAME_INI, GAME_MODE, "bAllowPlatformSaddleMultiFloors", false),
+];
+
+/// Profile switches that become launch flags.
+const FLAG_MAPPINGS: &[(&str, &str)] = &[
+    ("UseAllAvailableCores", "-useallavailablecores"),
+    ("UseCache", "-usecache"),
+    ("EnableAllowCaveFlyers", "-ForceAllowCaveFlyers"),
+    ("ForceRespawnDinos", "-ForceRespawnDinos"),
+    ("DisableValveAntiCheatSystem", "-insecure"),
+    ("NoTransferFromFiltering", "-NoTransferFromFiltering"),
+    ("UseNoHangDetection", "-NoHangDetection"),
+    ("EnableExclusiveJoin", "-exclusivejoin"),
+    ("ServerAllowAnsel", "-ServerAllowAnsel"),
+    ("StructureMemoryOptimizations", "-StructureMemOpts"),
+];
+
+/// Profile values that become `-Flag=value` launch flags.
+const VALUE_FLAG_MAPPINGS: &[(&str, &str)] = &[
+    ("CrossArkClusterId", "-clusterid"),
+    ("ClusterDirOverride", "-ClusterDirOverride"),
+];
+
+/// Profile values that become `?Key=value` launch options.
+const OPTION_MAPPINGS: &[&str] = &["AltSaveDirectoryName"];
+
+/// Top-level settings of a profile, in file order. `take` hands each one out
+/// once, so whatever is left afterwards was not mapped.
+struct Properties {
+    values: Vec<(String, Option<String>)>,
+    /// Nested lists, reported as unmapped with their size
+    lists: Vec<(String, usize)>,
+}
+
+impl Properties {
+    fn parse(xml: &str) -> Result<Self> {
+        let tag = Regex::new(r"<(/?)([A-Za-z_][\w.:-]*)((?:\s[^>]*?)?)(/?)>")?;
+        let mut values = Vec::new();
+        let mut lists = Vec::new();
+        let mut depth = 0usize;
+        let mut seen_root = false;
+        // Name, start of its text and number of children of the open top-level element
+        let mut current: Option<(String, usize, usize)> = None;
+
+        for caps in tag.captures_iter(xml) {
+            let whole = caps.get(0).map_or(0..0, |m| m.range());
+            let closing = !caps[1].is_empty();
+            let name = caps[2].rsplit(':').next().unwrap_or_default().to_string();
+            let self_closing = !caps[4].is_empty();
+
+            if closing {
+                depth = depth.saturating_sub(1);
+                if depth == 1 {
+                    if let Some((name, start, children)) = current.take() {
+                        if children == 0 {
+                            values.push((name, Some(unescape(&xml[start..whole.start]))));
+                        } else {
+                            lists.push((name, children));
+                        }
+                    }
+                }
+                continue;
+            }
+
+            match depth {
+                0 if !seen_root => {
+                    if name != "ServerProfile" {
+                        return Err(anyhow!("Not an Ark Server Manager profile: the root element is <{}>", name));
+                    }
+                    seen_root = true;
+                }
+                1 if self_closing => values.push((name, Some(String::new()))),
+                1 => current = Some((name, whole.end, 0)),
+                2 => {
+                    if let Some((_, _, children)) = &mut current {
+                        *children += 1;
+                    }
+                }
+                _ => {}
+            }
+            if !self_closing {
+                depth += 1;
+            }
+        }
+
+        if !seen_root {
+            return Err(anyhow!("Not an Ark Server Manager profile: no <ServerProfile> element"));
+        }
+        Ok(Self { values, lists })
+    }
+
+    /// The trimmed value of `name`, matched case-insensitively; empty means unset.
+    fn take(&mut self, name: &str) -> Option<String> {
+        self.values
+            .iter_mut()
+            .find(|(n, v)| v.is_some() && n.eq_ignore_ascii_case(name))
+            .and_then(|(_, v)| v.take())
+            .map(|v| v.trim().to_string())
+            .filter(|v| !v.is_empty())
+    }
+
+    /// The first of `names` the profile has.
+    fn take_any(&mut self, names: &[&str]) -> Option<String> {
+        let mut found = None;
+        for name in names {
+            let value = self.take(name);
+            if found.is_none() {
+                found = value;
+            }
+        }
+        found
+    }
+
+    fn take_parsed<T: std::str::FromStr>(&mut self, name: &str) -> Option<T> {
+        self.take(name).and_then(|v| v.parse().ok())
+    }
+
+    fn take_bool(&mut self, names: &[&str]) -> Option<bool> {
+        self.take_any(names).and_then(|v| v.to_ascii_lowercase().parse().ok())
+    }
+
+    /// Everything nobody took, skipping empty values.
+    fn unmapped(self) -> Vec<UnmappedSetting> {
+        let values = self.values.into_iter().filter_map(|(name, value)| {
+            let value = value?.trim().to_string();
+            (!value.is_empty()).then_some(UnmappedSetting { name, value })
+        });
+        let lists = self.lists.into_iter().map(|(name, entries)| UnmappedSetting {
+            name,
+            value: format!("list with {} entries", entries),
+        });
+        values.chain(lists).collect()
+    }
+}
+
+fn unescape(text: &str) -> String {
+    let entity = Regex::new(r"&(#x[0-9A-Fa-f]+|#\d+|lt|gt|quot|apos|amp);").expect("valid regex");
+    entity
+        .replace_all(text, |caps: &regex::Captures| {
+            let code = &caps[1];
+            let c = match code {
+                "lt" => Some('<'),
+                "gt" => Some('>'),
+                "quot" => Some('"'),
+                "apos" => Some('\''),
+                "amp" => Some('&'),
+                _ if code.starts_with("#x") => u32::from_str_radix(&code[2..], 16).ok().and_then(char::from_u32),
+                _ => code[1..].parse().ok().and_then(char::from_u32),
+            };
+            c.map_or_else(|| caps[0].to_string(), String::from)
+        })
+        .to_string()
+}
+
+/// ARK writes booleans as `True`/`False`.
+fn ini_value(value: &str, inverted: bool) -> String {
+    match value.to_ascii_lowercase().parse::<bool>() {
+        Ok(b) if b != inverted => "True".to_string(),
+        Ok(_) => "False".to_string(),
+        Err(_) => value.to_string(),
+    }
+}
+
+/// `?Key=Value` options and dash flags typed into ASM's extra arguments box.
+/// Anything else is returned as is.
+fn parse_additional_args(args: &str, profile: &mut LaunchProfile) -> Vec<String> {
+    let mut leftovers = Vec::new();
+    for token in args.split_whitespace() {
+        if token.starts_with('?') {
+            for option in token.split('?').filter(|o| !o.is_empty()) {
+                let (key, value) = match option.split_once('=') {
+                    Some((key, value)) => (key, Some(value.to_string())),
+                    None => (option, None),
+                };
+                profile.options.push(LaunchOption { key: key.to_string(), value });
+            }
+        } else if token.starts_with('-') {
+            profile.flags.push(token.to_string());
+        } else {
+            leftovers.push(token.to_string());
+        }
+    }
+    leftovers
+}
+
+/// `Monday Wednesday` (a .NET flags enum) as a cron day-of-week field.
+fn cron_days(days: Option<&str>) -> String {
+    let Some(days) = days else {
+        return "*".to_string();
+    };
+    let names: Vec<&str> = days
+        .split([' ', ','])
+        .filter_map(|d| match d.trim().to_ascii_lowercase().as_str() {
+            "sunday" => Some("Sun"),
+            "monday" => Some("Mon"),
+            "tuesday" => Some("Tue"),
+            "wednesday" => Some("Wed"),
+            "thursday" => Some("Thu"),
+            "friday" => Some("Fri"),
+            "saturday" => Some("Sat"),
+            _ => None,
+        })
+        .collect();
+    if names.is_empty() || names.len() == 7 {
+        "*".to_string()
+    } else {
+        names.join(",")
+    }
+}
+
+/// ASM's two timed shutdowns. Ours restart or update the server; a shutdown
+/// that leaves it off has no counterpart.
+fn shutdown_schedules(props: &mut Properties, unmapped: &mut Vec<UnmappedSetting>) -> Vec<ImportedSchedule> {
+    let mut schedules = Vec::new();
+    for n in 1..=2 {
+        let enabled = props.take_bool(&[format!("EnableAutoShutdown{}", n).as_str()]).unwrap_or(false);
+        let time = props.take(&format!("AutoShutdownTime{}", n));
+        let days = props.take(&format!("ShutdownDaysOfTheWeek{}", n));
+        let restart = props.take_bool(&[format!("RestartAfterShutdown{}", n).as_str()]).unwrap_or(false);
+        let update = props.take_bool(&[format!("UpdateAfterShutdown{}", n).as_str()]).unwrap_or(false);
+        if !enabled {
+            continue;
+        }
+
+        let time = time.unwrap_or_default();
+        let parsed = time
+            .split_once(':')
+            .and_then(|(h, m)| Some((h.trim().parse::<u32>().ok()?, m.trim().parse::<u32>().ok()?)))
+            .filter(|(h, m)| *h < 24 && *m < 60);
+        let task_type = if update {
+            "update"
+        } else if restart {
+            "restart"
+        } else {
+            ""
+        };
+
+        match parsed {
+            Some((hour, minute)) if !task_type.is_empty() => schedules.push(ImportedSchedule {
+                task_type: task_type.to_string(),
+                cron_expression: format!("0 {} {} * * {} *", minute, hour, cron_days(days.as_deref())),
+                payload: None,
+            }),
+            _ => unmapped.push(UnmappedSetting {
+                name: format!("EnableAutoShutdown{}", n),
+                value: if task_type.is_empty() {
+                    format!("shutdown at {} without a restart", time)
+                } else {
+                    format!("unreadable shutdown time '{}'", time)
+                },
+            }),
+        }
+    }
+    schedules
+}
+
+/// Read a profile and work out what importing it would create. The server
+/// files must already be in the profile's install directory, or in
+/// `install_override` when they have moved.
+pub fn read(path: &Path, install_override: Option<&Path>) -> Result<AsmProfileImport> {
+    let xml = fs::read_to_string(path).with_context(|| format!("Failed to read {}", path.display()))?;
+    let mut props = Properties::parse(&xml)?;
+    let mut warnings = Vec::new();
+    let mut unmapped = Vec::new();
+
+    let profile_name = props.take("ProfileName");
+    let profile_install = props.take("InstallDirectory");
+    let install_path = match install_override {
+        Some(path) => path.to_path_buf(),
+        None => profile_install
+            .map(Into::into)
+            .ok_or_else(|| anyhow!("The profile has no install directory; choose the server's folder"))?,
+    };
+    let (server_type, build_id) = server_import::detect_server_type(&install_path)
+        .ok_or_else(|| anyhow!("No ARK server found in {}", install_path.display()))?;
+
+    let ports = ServerPorts {
+        game_port: props.take_parsed("ServerPort").unwrap_or(DEFAULT_GAME_PORT),
+        query_port: props.take_parsed("QueryPort").unwrap_or(DEFAULT_QUERY_PORT),
+        rcon_port: props.take_parsed("RCONPort").unwrap_or(DEFAULT_RCON_PORT),
+    };
+
+    let map_name = props.take("ServerMap").unwrap_or_else(|| {
+        warnings.push("The profile has no map; assuming The Island".to_string());
+        match server_type {
+            ServerType::ASE => "TheIsland".to_string(),
+            ServerType::ASA => "TheIsland_WP".to_string(),
+        }
+    });
+
+    let admin_password = props.take("AdminPassword");
+    if admin_password.is_none() {
+        warnings.push("No admin password set; a random one will be generated for RCON".to_string());
+    }
+
+    let active_mods: Vec<String> = props
+        .take("ServerModIds")
+        .map(|mods| mods.split(',').map(str::trim).filter(|m| !m.is_empty()).map(String::from).collect())
+        .unwrap_or_default();
+    let motd = props.take("MOTD");
+
+    let mut ini_settings = Vec::new();
+    let mut ini = |file: &str, section: &str, key: &str, value: String| {
+        ini_settings.push(IniSetting {
+            file: file.to_string(),
+            section: section.to_string(),
+            key: key.to_string(),
+            value,
+        });
+    };
+    if !active_mods.is_empty() {
+        ini(GAME_USER_SETTINGS, SERVER_SETTINGS, "ActiveMods", active_mods.join(","));
+    }
+    if let Some(motd) = &motd {
+        // The INI keeps the message on one line, with `\n` for breaks
+        ini(GAME_USER_SETTINGS, "MessageOfTheDay", "Message", motd.replace("\r\n", "\n").replace('\n', "\\n"));
+    }
+    for (property, file, section, key, inverted) in INI_MAPPINGS {
+        if let Some(value) = props.take(property) {
+            ini(file, section, key, ini_value(&value, *inverted));
+        }
+    }
+
+    let mut launch_profile = LaunchProfile::default();
+    for (property, flag) in FLAG_MAPPINGS {
+        if props.take_bool(&[*property]).unwrap_or(false) {
+            launch_profile.flags.push(flag.to_string());
+        }
+    }
+    for (property, flag) in VALUE_FLAG_MAPPINGS {
+        if let Some(value) = props.take(property) {
+            launch_profile.flags.push(format!("{}={}", flag, value));
+        }
+    }
+    for key in OPTION_MAPPINGS {
+        if let Some(value) = props.take(key) {
+            launch_profile.options.push(LaunchOption { key: key.to_string(), value: Some(value) });
+        }
+    }
+    if let Some(args) = props.take("AdditionalArgs") {
+        let leftovers = parse_additional_args(&args, &mut launch_profile);
+        if !leftovers.is_empty() {
+            unmapped.push(UnmappedSetting { name: "AdditionalArgs".to_string(), value: leftovers.join(" ") });
+        }
+    }
+    // Registering sets this too, but the profile saved below replaces that one
+    if let Some(flag) = server_import::mods_flag(&server_type, &active_mods) {
+        launch_profile.flags.push(flag);
+    }
+
+    let settings = ServerSettingsUpdate {
+        battleye_enabled: props.take_bool(&["UseBattlEye", "EnableBattlEye"]),
+        multihome_ip: props.take("ServerIP"),
+        crossplay_enabled: props.take_bool(&["EnableCrossplay", "UseCrossplay"]),
+        auto_restart: props.take_bool(&["AutoRestartIfShutdown"]),
+        auto_update: props.take_bool(&["EnableAutoUpdate"]),
+        launch_profile: (!launch_profile.options.is_empty() || !launch_profile.flags.is_empty()).then_some(launch_profile),
+        ..Default::default()
+    };
+
+    let mut schedules = shutdown_schedules(&mut props, &mut unmapped);
+    if props.take_bool(&["EnableAutoBackup"]).unwrap_or(false) {
+        // ASM keeps the backup interval in its global settings, not the profile
+        warnings.push("Automatic backups were on; they are scheduled hourly here".to_string());
+        schedules.push(ImportedSchedule {
+            task_type: "backup".to_string(),
+            cron_expression: "0 0 * * * * *".to_string(),
+            payload: None,
+        });
+    }
+
+    let install = DetectedInstall {
+        install_path,
+        server_type,
+        build_id,
+        session_name: props.take_any(&["ServerName", "SessionName"]),
+        map_name,
+        ports,
+        max_players: props.take_parsed("MaxPlayers").unwrap_or(DEFAULT_MAX_PLAYERS),
+        server_password: props.take("ServerPassword"),
+        admin_password,
+        motd,
+        active_mods,
+        warnings,
+    };
+
+    let mut leftover = props.unmapped();
+    leftover.append(&mut unmapped);
+
+    Ok(AsmProfileImport {
+        server_id: None,
+        profile_name,
+        install,
+        settings,
+        schedules,
+        ini_settings,
+        unmapped: leftover,
+    })
+}
+
+/// Set `key=value` lines in an INI file, keeping everything else as it is.
+fn set_ini_values(path: &Path, values: &[&IniSetting]) -> Result<()> {
+    let content = fs::read_to_string(path).unwrap_or_default();
+    let mut lines: Vec<String> = content.lines().map(String::from).collect();
+
+    for setting in values {
+        let header = |line: &String| {
+            let line = line.trim();
+            line.starts_with('[') && line.ends_with(']')
+        };
+        let section_start = lines
+            .iter()
+            .position(|l| header(l) && l.trim()[1..l.trim().len() - 1].eq_ignore_ascii_case(&setting.section));
+        let line = format!("{}={}", setting.key, setting.value);
+
+        let Some(start) = section_start else {
+            if lines.last().is_some_and(|l| !l.trim().is_empty()) {
+                lines.push(String::new());
+            }
+            lines.push(format!("[{}]", setting.section));
+            lines.push(line);
+            continue;
+        };
+        let end = lines[start + 1..].iter().position(header).map_or(lines.len(), |i| start + 1 + i);
+        let existing = lines[start + 1..end].iter().position(|l| {
+            l.split_once('=').is_some_and(|(k, _)| k.trim().eq_ignore_ascii_case(&setting.key))
+        });
+        match existing {
+            Some(i) => lines[start + 1 + i] = line,
+            None => {
+                // After the section's last entry, before any blank lines
+                let insert_at = lines[start + 1..end]
+                    .iter()
+                    .rposition(|l| !l.trim().is_empty())
+                    .map_or(start + 1, |i| start + 2 + i);
+                lines.insert(insert_at, line);
+            }
+        }
+    }
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(path, lines.join("\n") + "\n")?;
+    Ok(())
+}
+
+/// Register the server with its settings, mods and schedules and write its
+/// INI values. Nothing is kept if any part fails: the INI files are written
+/// last and put back as they were when that or the commit fails.
+pub fn apply(conn: &Connection, import: &mut AsmProfileImport, name: Option<&str>) -> Result<i64, String> {
+    let tx = conn.unchecked_transaction().map_err(|e| e.to_string())?;
+
+    let name = name.map(str::trim).filter(|n| !n.is_empty()).or(import.profile_name.as_deref());
+    let server_id = server_import::register(&tx, &import.install, name)?;
+    servers::update_settings(&tx, server_id, import.settings.clone())?;
+
+    for schedule in &import.schedules {
+        tx.execute(
+            "INSERT INTO schedules (server_id, task_type, cron_expression, payload) VALUES (?1, ?2, ?3, ?4)",
+            (server_id, &schedule.task_type, &schedule.cron_expression, &schedule.payload),
+        )
+        .map_err(|e| e.to_string())?;
+    }
+
+    let config_dir = platform::config_dir(&import.install.install_path, &import.install.server_type.to_string());
+    let mut originals: Vec<(PathBuf, Option<String>)> = Vec::new();
+    let written = [GAME_USER_SETTINGS, GAME_INI]
+        .into_iter()
+        .try_for_each(|file| {
+            let values: Vec<&IniSetting> = import.ini_settings.iter().filter(|s| s.file == file).collect();
+            if values.is_empty() {
+                return Ok(());
+            }
+            let path = config_dir.join(file);
+            originals.push((path.clone(), fs::read_to_string(&path).ok()));
+            set_ini_values(&path, &values).map_err(|e| format!("Failed to write {}: {:#}", file, e))
+        })
+        .and_then(|()| tx.commit().map_err(|e| e.to_string()));
+
+    if let Err(e) = written {
+        for (path, original) in originals {
+            let _ = match original {
+                Some(content) => fs::write(&path, content),
+                None => fs::remove_file(&path),
+            };
+        }
+        return Err(e);
+    }
+    import.server_id = Some(server_id);
+    Ok(server_id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PROFILE: &str = r#"<?xml version="1.0" encoding="utf-8"?>
+<ServerProfile xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance">
+  <ProfileName>Rock &amp; Stone</ProfileName>
+  <ServerPassword />
+  <MaxPlayers>40</MaxPlayers>
+  <EnablePVP>true</EnablePVP>
+  <DinoSpawnWeightMultipliers>
+    <DinoSpawn><Name>Rex</Name></DinoSpawn>
+    <DinoSpawn><Name>Raptor</Name></DinoSpawn>
+  </DinoSpawnWeightMultipliers>
+  <EnableAutoShutdown1>true</EnableAutoShutdown1>
+  <AutoShutdownTime1>04:30</AutoShutdownTime1>
+  <ShutdownDaysOfTheWeek1>Monday, Friday</ShutdownDaysOfTheWeek1>
+  <RestartAfterShutdown1>true</RestartAfterShutdown1>
+  <EnableAutoShutdown2>true</EnableAutoShutdown2>
+  <AutoShutdownTime2>25:00</AutoShutdownTime2>
+  <UpdateAfterShutdown2>true</UpdateAfterShutdown2>
+  <SomethingNew>kept</SomethingNew>
+</ServerProfile>"#;
+
+    #[test]
+    fn properties_are_handed_out_once_and_the_rest_is_unmapped() {
+        let mut props = Properties::parse(PROFILE).unwrap();
+        assert_eq!(props.take("profilename").as_deref(), Some("Rock & Stone"));
+        assert_eq!(props.take("ProfileName"), None);
+        assert_eq!(props.take("ServerPassword"), None);
+        assert_eq!(props.take_parsed::<i32>("MaxPlayers"), Some(40));
+        assert_eq!(props.take_bool(&["EnablePVP"]), Some(true));
+
+        let mut unmapped = Vec::new();
+        let schedules = shutdown_schedules(&mut props, &mut unmapped);
+        assert_eq!(schedules.len(), 1);
+        assert_eq!(schedules[0].task_type, "restart");
+        assert_eq!(schedules[0].cron_expression, "0 30 4 * * Mon,Fri *");
+        assert_eq!(unmapped[0].name, "EnableAutoShutdown2");
+        assert_eq!(unmapped[0].value, "unreadable shutdown time '25:00'");
+
+        let rest: Vec<(String, String)> = props.unmapped().into_iter().map(|u| (u.name, u.value)).collect();
+        assert_eq!(rest, vec![
+            ("SomethingNew".to_string(), "kept".to_string()),
+            ("DinoSpawnWeightMultipliers".to_string(), "list with 2 entries".to_string()),
+        ]);
+    }
+
+    #[test]
+    fn rejects_other_xml() {
+        assert!(Properties::parse("<Settings><A>1</A></Settings>").is_err());
+        assert!(Properties::parse("not xml").is_err());
+    }
+
+    #[test]
+    fn additional_args_become_options_and_flags() {
+        let mut profile = LaunchProfile::default();
+        let leftovers = parse_additional_args("?AllowCrateSpawnsOnTopOfStructures=True?NoBoss -NoDinos stray", &mut profile);
+        assert_eq!(profile.options, vec![
+            LaunchOption { key: "AllowCrateSpawnsOnTopOfStructures".to_string(), value: Some("True".to_string()) },
+            LaunchOption { key: "NoBoss".to_string(), value: None },
+        ]);
+        assert_eq!(profile.flags, vec!["-NoDinos"]);
+        assert_eq!(leftovers, vec!["stray"]);
+    }
+
+    #[test]
+    fn days_of_the_week() {
+        assert_eq!(cron_days(None), "*");
+        assert_eq!(cron_days(Some("Monday Wednesday")), "Mon,Wed");
+        assert_eq!(cron_days(Some("Sunday Monday Tuesday Wednesday Thursday Friday Saturday")), "*");
+        assert_eq!(cron_days(Some("None")), "*");
+        assert_eq!(ini_value("true", true), "False");
+        assert_eq!(ini_value("2.5", false), "2.5");
+    }
+
+    #[test]
+    fn ini_values_are_merged_into_existing_sections() {
+        let dir = std::env::temp_dir().join(format!("asm-ini-{}", std::process::id()));
+        let path = dir.join(GAME_USER_SETTINGS);
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(&path, "[ServerSettings]\nServerPVE=False\nXPMultiplier=1\n\n[SessionSettings]\nSessionName=x\n").unwrap();
+
+        let setting = |section: &str, key: &str, value: &str| IniSetting {
+            file: GAME_USER_SETTINGS.to_string(),
+            section: section.to_string(),
+            key: key.to_string(),
+            value: value.to_string(),
+        };
+        let values = [
+            setting("serversettings", "serverpve", "True"),
+            setting(SERVER_SETTINGS, "NoTributeDownloads", "True"),
+            setting(GAME_MODE, "bPvEDisableFriendlyFire", "True"),
+        ];
+        set_ini_values(&path, &values.iter().collect::<Vec<_>>()).unwrap();
+
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            "[ServerSettings]\nserverpve=True\nXPMultiplier=1\nNoTributeDownloads=True\n\n[SessionSettings]\nSessionName=x\n\n\
+             [/script/shootergame.shootergamemode]\nbPvEDisableFriendlyFire=True\n"
+        );
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,249 @@
+use crate::models::{ChatKind, ChatMessage};
+use crate::services::rcon_sessions;
+use crate::AppState;
+use regex::Regex;
+use rusqlite::Connection;
+use std::time::{Duration, Instant};
+use tokio::time::sleep;
+
+// In-game chat. `GetChat` hands out what was said since it was last called,
+// so every running server is polled and the lines are stored in
+// `chat_messages` and emitted as `server-chat`. Lines carry no time of their
+// own; `sent_at` is when they were fetched, at most a poll interval late.
+
+const POLL_INTERVAL: Duration = Duration::from_secs(10);
+const PRUNE_INTERVAL: Duration = Duration::from_secs(60 * 60);
+/// Kept when the `chat_retention_days` setting is missing; 0 keeps chat forever.
+const DEFAULT_RETENTION_DAYS: u32 = 30;
+
+/// One chat line, before it is stored.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChatLine {
+    pub kind: ChatKind,
+    pub sender: String,
+    pub character: Option<String>,
+    pub tribe: Option<String>,
+    pub message: String,
+}
+
+impl ChatLine {
+    fn new(kind: ChatKind, sender: &str, message: &str) -> Self {
+        ChatLine {
+            kind,
+            sender: sender.trim().to_string(),
+            character: None,
+            tribe: None,
+            message: message.trim().to_string(),
+        }
+    }
+}
+
+/// Parse a `GetChat` response. Besides player chat (`Name (Character): text`)
+/// it can hold `SERVER:` broadcasts, `AdminCmd:` lines and tribe log entries
+/// (`Tribe Name, ID 123: Day 5, 12:00:00: text`); ARK's empty answer and
+/// blank lines are dropped.
+pub fn parse_chat(response: &str) -> Vec<ChatLine> {
+    let tribe = Regex::new(r"^Tribe (.+?), ID \d+: (.*)$").expect("valid regex");
+    let admin = Regex::new(r"^AdminCmd: (.*?)(?: \(PlayerName: (.*?), ID: [^)]*\))?$").expect("valid regex");
+    let player = Regex::new(r"^(.+?) \((.+?)\): (.*)$").expect("valid regex");
+    let plain = Regex::new(r"^(.+?): (.*)$").expect("valid regex");
+    let rich_text = Regex::new(r"<RichColor[^>]*>|</>").expect("valid regex");
+
+    response
+        .lines()
+        .map(str::trim)
+        .filter(|l| !l.is_empty() && !l.to_lowercase().starts_with("server received, but no response"))
+        .map(|l| {
+            if let Some(caps) = tribe.captures(l) {
+                let mut line = ChatLine::new(ChatKind::Tribe, "", &rich_text.replace_all(&caps[2], ""));
+                line.tribe = Some(caps[1].to_string());
+                line
+            } else if let Some(caps) = admin.captures(l) {
+                let sender = caps.get(2).map_or("Admin", |m| m.as_str());
+                ChatLine::new(ChatKind::Admin, sender, &caps[1])
+            } else if let Some(message) = l.strip_prefix("SERVER:") {
+                ChatLine::new(ChatKind::Server, "SERVER", message)
+            } else if let Some(caps) = player.captures(l) {
+                let mut line = ChatLine::new(ChatKind::Player, &caps[1], &caps[3]);
+                line.character = Some(caps[2].to_string());
+                line
+            } else if let Some(caps) = plain.captures(l) {
+                ChatLine::new(ChatKind::Player, &caps[1], &caps[2])
+            } else {
+                ChatLine::new(ChatKind::Server, "", l)
+            }
+        })
+        .collect()
+}
+
+fn message_from_row(row: &rusqlite::Row) -> rusqlite::Result<ChatMessage> {
+    Ok(ChatMessage {
+        id: row.get(0)?,
+        server_id: row.get(1)?,
+        kind: row.get::<_, String>(2)?.parse().unwrap_or(ChatKind::Player),
+        sender: row.get(3)?,
+        character: row.get(4)?,
+        tribe: row.get(5)?,
+        message: row.get(6)?,
+        sent_at: row.get(7)?,
+    })
+}
+
+fn store(conn: &Connection, server_id: i64, lines: Vec<ChatLine>) -> rusqlite::Result<Vec<ChatMessage>> {
+    let mut stored = Vec::with_capacity(lines.len());
+    for line in lines {
+        conn.execute(
+            "INSERT INTO chat_messages (server_id, kind, sender, character, tribe, message) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            (server_id, line.kind.to_string(), &line.sender, &line.character, &line.tribe, &line.message),
+        )?;
+        stored.push(conn.query_row(
+            "SELECT id, server_id, kind, sender, character, tribe, message, sent_at FROM chat_messages WHERE id = ?1",
+            [conn.last_insert_rowid()],
+            message_from_row,
+        )?);
+    }
+    Ok(stored)
+}
+
+/// Chat newest first, on one server or all. `query` matches the message,
+/// sender, character or tribe; `before_id` pages back from an earlier result.
+pub fn search(
+    conn: &Connection,
+    server_id: Option<i64>,
+    query: Option<&str>,
+    before_id: Option<i64>,
+    limit: usize,
+) -> rusqlite::Result<Vec<ChatMessage>> {
+    let pattern = query
+        .map(str::trim)
+        .filter(|q| !q.is_empty())
+        .map(|q| format!("%{}%", q.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_")));
+    let mut stmt = conn.prepare(
+        "SELECT id, server_id, kind, sender, character, tribe, message, sent_at FROM chat_messages
+         WHERE (?1 IS NULL OR server_id = ?1)
+           AND (?2 IS NULL OR message LIKE ?2 ESCAPE '\\' OR sender LIKE ?2 ESCAPE '\\'
+                OR character LIKE ?2 ESCAPE '\\' OR tribe LIKE ?2 ESCAPE '\\')
+           AND (?3 IS NULL OR id < ?3)
+         ORDER BY id DESC LIMIT ?4",
+    )?;
+    let messages = stmt.query_map((server_id, pattern, before_id, limit as i64), message_from_row)?;
+    messages.collect()
+}
+
+/// Delete chat older than `days` days.
+pub fn prune(conn: &Connection, days: u32) -> rusqlite::Result<usize> {
+    conn.execute(
+        "DELETE FROM chat_messages WHERE sent_at < datetime('now', ?1)",
+        [format!("-{} days", days)],
+    )
+}
+
+async fn poll(state: &AppState) -> Result<(), String> {
+    let running: Vec<i64> = {
+        let db = state.db.lock().map_err(|e| e.to_string())?;
+        let conn = db.get_connection().map_err(|e| e.to_string())?;
+        let mut stmt = conn
+            .prepare("SELECT id FROM servers WHERE status = 'running'")
+            .map_err(|e| e.to_string())?;
+        let running = stmt
+            .query_map([], |row| row.get(0))
+            .and_then(|rows| rows.collect::<rusqlite::Result<Vec<_>>>())
+            .map_err(|e| e.to_string())?;
+        running
+    };
+
+    for server_id in running {
+        let Ok(response) = rcon_sessions::execute(state, server_id, "GetChat").await else { continue };
+        let lines = parse_chat(&response);
+        if lines.is_empty() {
+            continue;
+        }
+        let messages = {
+            let db = state.db.lock().map_err(|e| e.to_string())?;
+            let conn = db.get_connection().map_err(|e| e.to_string())?;
+            store(&conn, server_id, lines).map_err(|e| e.to_string())?
+        };
+        for message in messages {
+            state.events.emit("server-chat", &message);
+        }
+    }
+    Ok(())
+}
+
+fn prune_expired(state: &AppState) -> Result<(), String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let days = db
+        .get_setting("chat_retention_days")
+        .ok()
+        .flatten()
+        .and_then(|v| v.trim().parse::<u32>().ok())
+        .unwrap_or(DEFAULT_RETENTION_DAYS);
+    if days == 0 {
+        return Ok(());
+    }
+    let conn = db.get_connection().map_err(|e| e.to_string())?;
+    prune(&conn, days).map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Poll every running server for chat until the process exits.
+pub fn spawn(state: AppState) {
+    tauri::async_runtime::spawn(async move {
+        let mut last_prune: Option<Instant> = None;
+        loop {
+            sleep(POLL_INTERVAL).await;
+            if let Err(e) = poll(&state).await {
+                eprintln!("Chat poll failed: {}", e);
+            }
+            if last_prune.is_none_or(|at| at.elapsed() >= PRUNE_INTERVAL) {
+                if let Err(e) = prune_expired(&state) {
+                    eprintln!("Failed to prune chat: {}", e);
+                }
+                last_prune = Some(Instant::now());
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn empty_responses() {
+        assert!(parse_chat("Server received, But no response!! \n").is_empty());
+        assert!(parse_chat("\n  \n").is_empty());
+    }
+
+    #[test]
+    fn player_chat_keeps_colons_and_parentheses_in_the_message() {
+        let lines = parse_chat("Rex Hunter (Bob the Builder): meet at (12, 34): bring metal\nAnon: hi");
+        assert_eq!(lines[0], ChatLine {
+            kind: ChatKind::Player,
+            sender: "Rex Hunter".to_string(),
+            character: Some("Bob the Builder".to_string()),
+            tribe: None,
+            message: "meet at (12, 34): bring metal".to_string(),
+        });
+        assert_eq!(lines[1].sender, "Anon");
+        assert_eq!(lines[1].character, None);
+        assert_eq!(lines[1].message, "hi");
+    }
+
+    #[test]
+    fn server_admin_and_tribe_lines() {
+        let lines = parse_chat(concat!(
+            "SERVER: Restarting in 5 minutes\n",
+            "AdminCmd: Broadcast hello (PlayerName: Admin Al, ID: 76561198000000001, Guid: 0002abc)\n",
+            "Tribe Dodo Lovers, ID 1234567890: Day 12, 08:15:30: <RichColor Color=\"1, 0, 0, 1\">Bob was killed!</>\n",
+        ));
+        assert_eq!(lines[0].kind, ChatKind::Server);
+        assert_eq!(lines[0].message, "Restarting in 5 minutes");
+        assert_eq!(lines[1].kind, ChatKind::Admin);
+        assert_eq!(lines[1].sender, "Admin Al");
+        assert_eq!(lines[1].message, "Broadcast hello");
+        assert_eq!(lines[2].kind, ChatKind::Tribe);
+        assert_eq!(lines[2].tribe.as_deref(), Some("Dodo Lovers"));
+        assert_eq!(lines[2].message, "Day 12, 08:15:30: Bob was killed!");
+    }
+}
@@ -0,0 +1,234 @@
+use crate::AppState;
+use chrono::Local;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use tokio::sync::broadcast::error::RecvError;
+
+/// Lines kept in memory per server.
+const HISTORY_LINES: usize = 2000;
+/// A server's console log is rotated once it grows past this.
+const MAX_LOG_SIZE: u64 = 5 * 1024 * 1024;
+/// Rotated logs kept per server, besides the current one.
+const ROTATED_LOGS: usize = 4;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConsoleLine {
+    /// Increases across all servers, so clients can ask for lines after one they have
+    pub seq: u64,
+    pub timestamp: String,
+    /// `stdout`, `stderr` or `log`
+    pub stream: String,
+    pub line: String,
+}
+
+#[derive(Default)]
+struct ServerConsole {
+    lines: VecDeque<ConsoleLine>,
+    /// Current log file, opened on the first line written
+    file: Option<File>,
+    size: u64,
+}
+
+/// Console output of every server, so it survives nobody listening to
+/// `server-console-output` when it was printed. The last lines are kept in
+/// memory; everything is also appended, one JSON line each, to
+/// `console/<server>/console.log`, rotated to `console.log.1` and so on.
+pub struct ConsoleHistory {
+    dir: PathBuf,
+    servers: Mutex<HashMap<i64, ServerConsole>>,
+    next_seq: AtomicU64,
+}
+
+impl ConsoleHistory {
+    /// Load the recent lines of every server logged under `dir`.
+    pub fn open(dir: PathBuf) -> Self {
+        let mut servers = HashMap::new();
+        let mut next_seq = 0;
+
+        for entry in fs::read_dir(&dir).into_iter().flatten().filter_map(|e| e.ok()) {
+            let Some(server_id) = entry.file_name().to_str().and_then(|n| n.parse::<i64>().ok()) else {
+                continue;
+            };
+            let server_dir = entry.path();
+            let mut lines = VecDeque::new();
+            for path in [log_file(&server_dir, 1), log_file(&server_dir, 0)] {
+                for line in read_log(&path) {
+                    if lines.len() >= HISTORY_LINES {
+                        lines.pop_front();
+                    }
+                    lines.push_back(line);
+                }
+            }
+            if let Some(last) = lines.back() {
+                next_seq = next_seq.max(last.seq + 1);
+            }
+            let size = fs::metadata(log_file(&server_dir, 0)).map(|m| m.len()).unwrap_or(0);
+            servers.insert(server_id, ServerConsole { lines, file: None, size });
+        }
+
+        Self {
+            dir,
+            servers: Mutex::new(servers),
+            next_seq: AtomicU64::new(next_seq),
+        }
+    }
+
+    fn server_dir(&self, server_id: i64) -> PathBuf {
+        self.dir.join(server_id.to_string())
+    }
+
+    pub fn push(&self, server_id: i64, stream: &str, line: &str) {
+        let entry = ConsoleLine {
+            seq: self.next_seq.fetch_add(1, Ordering::Relaxed),
+            timestamp: Local::now().to_rfc3339(),
+            stream: stream.to_string(),
+            line: line.to_string(),
+        };
+        let server_dir = self.server_dir(server_id);
+
+        let mut servers = self.servers.lock().unwrap();
+        let console = servers.entry(server_id).or_default();
+        if let Err(e) = append(console, &server_dir, &entry) {
+            // Keep the in-memory history going; the next line tries the file again
+            console.file = None;
+            eprintln!("Failed to write console log for server {}: {}", server_id, e);
+        }
+
+        if console.lines.len() >= HISTORY_LINES {
+            console.lines.pop_front();
+        }
+        console.lines.push_back(entry);
+    }
+
+    /// The last `limit` lines of a server, oldest first.
+    pub fn tail(&self, server_id: i64, limit: usize) -> Vec<ConsoleLine> {
+        self.history(server_id, None, limit)
+    }
+
+    /// Up to `limit` of the newest lines after `since` (a `seq`), oldest first.
+    pub fn history(&self, server_id: i64, since: Option<u64>, limit: usize) -> Vec<ConsoleLine> {
+        let servers = self.servers.lock().unwrap();
+        let Some(console) = servers.get(&server_id) else {
+            return Vec::new();
+        };
+        let newer: Vec<&ConsoleLine> = console
+            .lines
+            .iter()
+            .filter(|l| since.is_none_or(|s| l.seq > s))
+            .collect();
+        newer[newer.len().saturating_sub(limit)..].iter().map(|l| (*l).clone()).collect()
+    }
+
+    /// Lines containing `query`, ignoring case, from the current and rotated
+    /// logs on disk. Returns the newest `limit` matches, oldest first.
+    pub fn search(&self, server_id: i64, query: &str, limit: usize) -> Vec<ConsoleLine> {
+        if limit == 0 {
+            return Vec::new();
+        }
+        let query = query.to_lowercase();
+        let server_dir = self.server_dir(server_id);
+        let mut matches = VecDeque::new();
+
+        for index in (0..=ROTATED_LOGS).rev() {
+            for line in read_log(&log_file(&server_dir, index)) {
+                if !line.line.to_lowercase().contains(&query) {
+                    continue;
+                }
+                if matches.len() >= limit {
+                    matches.pop_front();
+                }
+                matches.push_back(line);
+            }
+        }
+
+        matches.into()
+    }
+}
+
+/// `console.log` for index 0, otherwise the rotated `console.log.<index>`.
+fn log_file(server_dir: &Path, index: usize) -> PathBuf {
+    match index {
+        0 => server_dir.join("console.log"),
+        n => server_dir.join(format!("console.log.{}", n)),
+    }
+}
+
+/// Lines of one log file; unreadable lines are skipped.
+fn read_log(path: &Path) -> Vec<ConsoleLine> {
+    let Ok(file) = File::open(path) else { return Vec::new() };
+    BufReader::new(file)
+        .lines()
+        .map_while(Result::ok)
+        .filter_map(|l| serde_json::from_str(&l).ok())
+        .collect()
+}
+
+fn append(console: &mut ServerConsole, server_dir: &Path, entry: &ConsoleLine) -> std::io::Result<()> {
+    if console.size >= MAX_LOG_SIZE {
+        console.file = None;
+        rotate(server_dir)?;
+        console.size = 0;
+    }
+
+    if console.file.is_none() {
+        fs::create_dir_all(server_dir)?;
+        let file = OpenOptions::new().create(true).append(true).open(log_file(server_dir, 0))?;
+        console.size = file.metadata()?.len();
+        console.file = Some(file);
+    }
+
+    let mut json = serde_json::to_vec(entry)?;
+    json.push(b'\n');
+    if let Some(file) = console.file.as_mut() {
+        file.write_all(&json)?;
+    }
+    console.size += json.len() as u64;
+    Ok(())
+}
+
+/// Shift `console.log` to `console.log.1` and so on, dropping the oldest.
+fn rotate(server_dir: &Path) -> std::io::Result<()> {
+    let _ = fs::remove_file(log_file(server_dir, ROTATED_LOGS));
+    for index in (0..ROTATED_LOGS).rev() {
+        let from = log_file(server_dir, index);
+        if from.exists() {
+            fs::rename(from, log_file(server_dir, index + 1))?;
+        }
+    }
+    Ok(())
+}
+
+/// Record every `server-console-output` event into `state.console`.
+pub fn spawn_recorder(state: &AppState) {
+    let console = state.console.clone();
+    let mut events = state.events.subscribe();
+
+    tauri::async_runtime::spawn(async move {
+        loop {
+            match events.recv().await {
+                Ok(event) if event.name == "server-console-output" => {
+                    let payload = &event.payload;
+                    let (Some(server_id), Some(line)) = (
+                        payload.get("serverId").and_then(|v| v.as_i64()),
+                        payload.get("line").and_then(|v| v.as_str()),
+                    ) else {
+                        continue;
+                    };
+                    let stream = payload.get("type").and_then(|v| v.as_str()).unwrap_or("stdout");
+                    console.push(server_id, stream, line);
+                }
+                Ok(_) => {}
+                Err(RecvError::Lagged(skipped)) => {
+                    eprintln!("Console history fell behind and missed {} events", skipped);
+                }
+                Err(RecvError::Closed) => break,
+            }
+        }
+    });
+}
@@ -0,0 +1,270 @@
+use crate::models::CrashBundle;
+use crate::services::launch::LaunchSpec;
+use crate::AppState;
+use anyhow::{Context, Result};
+use chrono::{Local, TimeZone};
+use regex::Regex;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use walkdir::WalkDir;
+
+/// Console lines saved with each bundle.
+const CONSOLE_LINES: usize = 500;
+/// Log files bigger than this are left out; a runaway log shouldn't fill the disk twice.
+const MAX_LOG_FILE_SIZE: u64 = 64 * 1024 * 1024;
+/// Crash reports from before the server started, minus this slack for
+/// clock skew, belong to earlier crashes.
+const CRASH_REPORT_SLACK: Duration = Duration::from_secs(60);
+
+pub fn bundles_dir(data_dir: &Path) -> PathBuf {
+    data_dir.join("crash_bundles")
+}
+
+/// Collect everything useful about an unexpected exit into
+/// `crash_bundles/<server>/<time>/` and index it:
+///
+/// - `summary.json`: exit code, times, runner and the exact command line
+/// - `console.log`: the last console lines we saw
+/// - `launch_profile.json`: the server's launch profile
+/// - `Logs/`: the server's `ShooterGame/Saved/Logs` files
+/// - `Crashes/`: crash reports and minidumps written since the server started
+///
+/// Bundles are meant to be attached to bug reports, so the server and admin
+/// passwords are scrubbed from every text file. Minidumps are copied as is.
+///
+/// `start_time` is when the crashed process started, in seconds since the
+/// epoch. The caller reads it before the PID is cleared.
+pub fn collect(state: &AppState, server_id: i64, exit_code: Option<i32>, start_time: Option<u64>) -> Result<CrashBundle> {
+    let started_at = start_time.map(|t| UNIX_EPOCH + Duration::from_secs(t));
+    let (spec, name) = {
+        let db = state.db.lock().map_err(|e| anyhow::anyhow!(e.to_string()))?;
+        let conn = db.get_connection().map_err(|e| anyhow::anyhow!(e))?;
+        let spec = LaunchSpec::load(&conn, server_id)?;
+        let name: String = conn.query_row("SELECT name FROM servers WHERE id = ?1", [server_id], |row| row.get(0))?;
+        (spec, name)
+    };
+
+    let crashed_at = Local::now();
+    let bundle_dir = bundles_dir(&state.data_dir)
+        .join(server_id.to_string())
+        .join(crashed_at.format("%Y%m%d_%H%M%S").to_string());
+    fs::create_dir_all(&bundle_dir).context("Failed to create crash bundle directory")?;
+
+    let console = state
+        .console
+        .tail(server_id, CONSOLE_LINES)
+        .into_iter()
+        .map(|l| format!("[{}] [{}] {}", l.timestamp, l.stream, l.line))
+        .collect::<Vec<_>>()
+        .join("\n");
+    let scrubber = Scrubber::new(&spec.admin_password, spec.server_password.as_deref());
+    fs::write(bundle_dir.join("console.log"), scrubber.scrub(&console))?;
+
+    fs::write(
+        bundle_dir.join("launch_profile.json"),
+        scrubber.scrub(&serde_json::to_string_pretty(&spec.profile)?),
+    )?;
+
+    let command_line = spec
+        .preview(&state.data_dir)
+        .map(|p| p.command_line)
+        .unwrap_or_else(|e| format!("<unavailable: {}>", e));
+    let summary = serde_json::json!({
+        "serverId": server_id,
+        "serverName": name,
+        "serverType": spec.server_type,
+        "exitCode": exit_code,
+        "crashedAt": crashed_at.to_rfc3339(),
+        "startedAt": started_at.and_then(local_time),
+        "runner": spec.runner.kind.to_string(),
+        "commandLine": scrubber.scrub(&command_line),
+    });
+    fs::write(bundle_dir.join("summary.json"), serde_json::to_string_pretty(&summary)?)?;
+
+    let saved = spec.install_path.join("ShooterGame").join("Saved");
+    copy_logs(&saved.join("Logs"), &bundle_dir.join("Logs"), &scrubber);
+    copy_crash_reports(&saved.join("Crashes"), &bundle_dir.join("Crashes"), started_at, &scrubber);
+
+    let size = WalkDir::new(&bundle_dir)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter_map(|e| e.metadata().ok())
+        .filter(|m| m.is_file())
+        .map(|m| m.len())
+        .sum::<u64>() as i64;
+
+    let db = state.db.lock().map_err(|e| anyhow::anyhow!(e.to_string()))?;
+    let conn = db.get_connection().map_err(|e| anyhow::anyhow!(e))?;
+    conn.execute(
+        "INSERT INTO crash_bundles (server_id, exit_code, bundle_path, size) VALUES (?1, ?2, ?3, ?4)",
+        (server_id, exit_code, bundle_dir.to_string_lossy().to_string(), size),
+    )?;
+    let id = conn.last_insert_rowid();
+
+    Ok(CrashBundle {
+        id,
+        server_id,
+        exit_code,
+        bundle_path: bundle_dir,
+        size,
+        created_at: crashed_at.format("%Y-%m-%d %H:%M:%S").to_string(),
+    })
+}
+
+/// Replaces passwords with `<redacted>`: the `?…Password=` launch options,
+/// which ARK also writes into its log, and the server's own password values
+/// wherever else they turn up.
+struct Scrubber {
+    options: Regex,
+    secrets: Vec<String>,
+}
+
+impl Scrubber {
+    /// Passwords shorter than this are only scrubbed as launch options, since
+    /// replacing every occurrence would mangle the logs
+    const MIN_SECRET_LEN: usize = 4;
+
+    fn new(admin_password: &str, server_password: Option<&str>) -> Self {
+        let secrets = [Some(admin_password), server_password]
+            .into_iter()
+            .flatten()
+            .filter(|p| p.len() >= Self::MIN_SECRET_LEN)
+            .map(str::to_string)
+            .collect();
+        Scrubber {
+            options: Regex::new(r#"(?i)(\b(?:ServerAdminPassword|ServerPassword|SpectatorPassword)=)[^?\s"]*"#)
+                .expect("valid regex"),
+            secrets,
+        }
+    }
+
+    fn scrub(&self, text: &str) -> String {
+        let mut text = self.options.replace_all(text, "${1}<redacted>").into_owned();
+        for secret in &self.secrets {
+            text = text.replace(secret.as_str(), "<redacted>");
+        }
+        text
+    }
+
+    /// Copy `source`, scrubbed when it is text.
+    fn copy(&self, source: &Path, target: &Path) -> std::io::Result<()> {
+        match fs::read_to_string(source) {
+            Ok(text) => fs::write(target, self.scrub(&text)),
+            Err(_) => fs::copy(source, target).map(|_| ()),
+        }
+    }
+}
+
+fn local_time(time: SystemTime) -> Option<String> {
+    let secs = time.duration_since(UNIX_EPOCH).ok()?.as_secs() as i64;
+    Local.timestamp_opt(secs, 0).single().map(|t| t.to_rfc3339())
+}
+
+/// Copy the files directly in the server's log folder.
+fn copy_logs(source: &Path, target: &Path, scrubber: &Scrubber) {
+    let Ok(entries) = fs::read_dir(source) else { return };
+    for entry in entries.filter_map(|e| e.ok()) {
+        let Ok(metadata) = entry.metadata() else { continue };
+        if !metadata.is_file() || metadata.len() > MAX_LOG_FILE_SIZE {
+            continue;
+        }
+        if fs::create_dir_all(target).is_ok() {
+            let _ = scrubber.copy(&entry.path(), &target.join(entry.file_name()));
+        }
+    }
+}
+
+/// Unreal writes one folder per crash, with the report and a minidump.
+/// Only folders from this run are copied; without a start time, the newest.
+fn copy_crash_reports(source: &Path, target: &Path, started_at: Option<SystemTime>, scrubber: &Scrubber) {
+    let Ok(entries) = fs::read_dir(source) else { return };
+    let mut reports: Vec<(SystemTime, PathBuf)> = entries
+        .filter_map(|e| e.ok())
+        .filter_map(|e| Some((e.metadata().ok()?.modified().ok()?, e.path())))
+        .collect();
+    reports.sort_by_key(|(modified, _)| std::cmp::Reverse(*modified));
+
+    let reports: Vec<PathBuf> = match started_at {
+        Some(started) => {
+            let cutoff = started.checked_sub(CRASH_REPORT_SLACK).unwrap_or(started);
+            reports.into_iter().filter(|(t, _)| *t >= cutoff).map(|(_, p)| p).collect()
+        }
+        None => reports.into_iter().take(1).map(|(_, p)| p).collect(),
+    };
+
+    for report in reports {
+        let Some(name) = report.file_name() else { continue };
+        let _ = copy_recursive(&report, &target.join(name), scrubber);
+    }
+}
+
+fn copy_recursive(source: &Path, target: &Path, scrubber: &Scrubber) -> std::io::Result<()> {
+    if source.is_dir() {
+        fs::create_dir_all(target)?;
+        for entry in fs::read_dir(source)? {
+            let entry = entry?;
+            copy_recursive(&entry.path(), &target.join(entry.file_name()), scrubber)?;
+        }
+    } else {
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        scrubber.copy(source, target)?;
+    }
+    Ok(())
+}
+
+/// Resolve a file inside a bundle, refusing paths that climb out of it.
+pub fn bundle_file(bundle_dir: &Path, relative: &str) -> Result<PathBuf> {
+    let path = bundle_dir.join(relative);
+    let resolved = path.canonicalize().context("File not found in crash bundle")?;
+    if !resolved.starts_with(bundle_dir.canonicalize()?) {
+        return Err(anyhow::anyhow!("Path is outside the crash bundle"));
+    }
+    Ok(resolved)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::process_manager;
+
+    #[test]
+    fn scrubs_password_options_and_values() {
+        let scrubber = Scrubber::new("hunter22", Some("abc"));
+        assert_eq!(
+            scrubber.scrub("TheIsland?listen?ServerPassword=abc?serveradminpassword=hunter22?MaxPlayers=70 -log"),
+            "TheIsland?listen?ServerPassword=<redacted>?serveradminpassword=<redacted>?MaxPlayers=70 -log"
+        );
+        assert_eq!(scrubber.scrub("Admin login with hunter22 from abc"), "Admin login with <redacted> from abc");
+    }
+
+    #[test]
+    fn bundles_keep_the_start_time_once_the_pid_is_cleared() {
+        let dir = std::env::temp_dir().join(format!("asm-crash-{}", std::process::id()));
+        let state = AppState::open(dir.clone()).unwrap();
+        let start_time = {
+            let db = state.db.lock().unwrap();
+            let conn = db.get_connection().unwrap();
+            conn.execute(
+                "INSERT INTO servers (name, server_type, install_path, game_port, query_port, rcon_port,
+                 admin_password, map_name, session_name, pid, pid_start_time)
+                 VALUES ('Test', 'ASE', ?1, 7777, 27015, 27020, 'secret', 'TheIsland', 'Test', 4242, 1700000000)",
+                [dir.join("server").to_string_lossy()],
+            )
+            .unwrap();
+            // What the monitor does once the process is gone
+            let start_time = process_manager::server_pid_start_time(&conn, 1);
+            process_manager::clear_server_pid(&conn, 1).unwrap();
+            start_time
+        };
+
+        let bundle = collect(&state, 1, Some(1), start_time).unwrap();
+        let summary: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(bundle.bundle_path.join("summary.json")).unwrap()).unwrap();
+        let _ = fs::remove_dir_all(&dir);
+        assert_eq!(start_time, Some(1700000000));
+        assert_eq!(summary["startedAt"], local_time(UNIX_EPOCH + Duration::from_secs(1700000000)).unwrap());
+    }
+}
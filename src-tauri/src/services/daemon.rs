@@ -0,0 +1,468 @@
+use crate::commands::console;
+use crate::commands::discord::BotStatus;
+use crate::services::discord_bot;
+use crate::services::events::{Event, EventBus};
+use crate::services::graceful_stop::GracefulStopService;
+use crate::services::{lifecycle, updater};
+use crate::AppState;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncWrite, AsyncWriteExt, BufReader};
+use tokio::sync::broadcast::error::RecvError;
+
+// The daemon owns the server processes, the scheduler, the monitor and the
+// Discord bot, so they keep running without a window. Clients talk to it
+// over a local socket (a named pipe on Windows) with one JSON object per line:
+//
+//   -> {"method": "start_server", "params": {"serverId": 1}}
+//   <- {"result": null}  or  {"error": "..."}
+//
+// After a `subscribe` request the connection turns into a stream of
+// `{"name": ..., "payload": ...}` events.
+
+/// Same identifier as tauri.conf.json, so the GUI and the daemon find the
+/// same database.
+const APP_IDENTIFIER: &str = "com.sanjaysm.ark-server-manager";
+
+#[cfg(windows)]
+const PIPE_NAME: &str = r"\\.\pipe\ark-server-manager";
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Request {
+    method: String,
+    #[serde(default)]
+    params: Value,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Response {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    result: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+impl Response {
+    fn from_result(result: Result<Value, String>) -> Self {
+        match result {
+            Ok(value) => Response { result: Some(value), error: None },
+            Err(e) => Response { result: None, error: Some(e) },
+        }
+    }
+}
+
+/// Where the Tauri app keeps its data on this platform, matching
+/// `app.path().app_data_dir()`.
+pub fn default_data_dir() -> Option<PathBuf> {
+    #[cfg(target_os = "windows")]
+    let base = std::env::var_os("APPDATA").map(PathBuf::from);
+
+    #[cfg(target_os = "macos")]
+    let base = std::env::var_os("HOME").map(|home| PathBuf::from(home).join("Library/Application Support"));
+
+    #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+    let base = std::env::var_os("XDG_DATA_HOME")
+        .map(PathBuf::from)
+        .filter(|p| p.is_absolute())
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".local/share")));
+
+    base.map(|b| b.join(APP_IDENTIFIER))
+}
+
+#[cfg(unix)]
+pub fn socket_path(data_dir: &Path) -> PathBuf {
+    data_dir.join("daemon.sock")
+}
+
+#[cfg(unix)]
+type ClientStream = tokio::net::UnixStream;
+
+#[cfg(windows)]
+type ClientStream = tokio::net::windows::named_pipe::NamedPipeClient;
+
+#[cfg(unix)]
+async fn open_stream(data_dir: &Path) -> std::io::Result<ClientStream> {
+    tokio::net::UnixStream::connect(socket_path(data_dir)).await
+}
+
+#[cfg(windows)]
+async fn open_stream(_data_dir: &Path) -> std::io::Result<ClientStream> {
+    tokio::net::windows::named_pipe::ClientOptions::new().open(PIPE_NAME)
+}
+
+async fn write_line<W: AsyncWrite + Unpin, T: Serialize>(writer: &mut W, value: &T) -> std::io::Result<()> {
+    let mut line = serde_json::to_vec(value)?;
+    line.push(b'\n');
+    writer.write_all(&line).await?;
+    writer.flush().await
+}
+
+/// Connection from the GUI to a running daemon.
+#[derive(Debug, Clone)]
+pub struct DaemonClient {
+    data_dir: PathBuf,
+}
+
+impl DaemonClient {
+    /// Returns a client if a daemon is answering for this data directory.
+    pub async fn connect(data_dir: &Path) -> Option<Self> {
+        let client = DaemonClient { data_dir: data_dir.to_path_buf() };
+        client.call("ping", Value::Null).await.ok()?;
+        Some(client)
+    }
+
+    /// Send one request and wait for its response. Each call gets its own
+    /// connection, so a long update doesn't hold up a start or stop.
+    pub async fn call(&self, method: &str, params: Value) -> Result<Value, String> {
+        let stream = open_stream(&self.data_dir)
+            .await
+            .map_err(|e| format!("Daemon is not reachable: {}", e))?;
+        let (reader, mut writer) = tokio::io::split(stream);
+
+        let request = Request { method: method.to_string(), params };
+        write_line(&mut writer, &request).await.map_err(|e| e.to_string())?;
+
+        let line = BufReader::new(reader)
+            .lines()
+            .next_line()
+            .await
+            .map_err(|e| e.to_string())?
+            .ok_or("Daemon closed the connection")?;
+
+        let response: Response = serde_json::from_str(&line).map_err(|e| e.to_string())?;
+        match response.error {
+            Some(e) => Err(e),
+            None => Ok(response.result.unwrap_or(Value::Null)),
+        }
+    }
+
+    /// Republish the daemon's events on `events` until the connection drops.
+    pub async fn forward_events(&self, events: EventBus) -> Result<(), String> {
+        let stream = open_stream(&self.data_dir)
+            .await
+            .map_err(|e| format!("Daemon is not reachable: {}", e))?;
+        let (reader, mut writer) = tokio::io::split(stream);
+
+        let request = Request { method: "subscribe".to_string(), params: Value::Null };
+        write_line(&mut writer, &request).await.map_err(|e| e.to_string())?;
+
+        let mut lines = BufReader::new(reader).lines();
+        // The first line acknowledges the subscription
+        lines.next_line().await.map_err(|e| e.to_string())?;
+
+        while let Some(line) = lines.next_line().await.map_err(|e| e.to_string())? {
+            if let Ok(event) = serde_json::from_str::<Event>(&line) {
+                events.publish(event);
+            }
+        }
+        Ok(())
+    }
+}
+
+fn server_id(params: &Value) -> Result<i64, String> {
+    params
+        .get("serverId")
+        .and_then(Value::as_i64)
+        .ok_or_else(|| "Missing serverId".to_string())
+}
+
+/// Who asked, for the status history; the GUI says so itself.
+fn cause(params: &Value) -> &str {
+    params.get("cause").and_then(Value::as_str).unwrap_or("daemon request")
+}
+
+async fn dispatch(state: &AppState, method: &str, params: Value) -> Result<Value, String> {
+    match method {
+        "ping" => Ok(Value::String(env!("CARGO_PKG_VERSION").to_string())),
+        "start_server" => lifecycle::start_server(state, server_id(&params)?, cause(&params)).await.map(|_| Value::Null),
+        "stop_server" => lifecycle::stop_server(state, server_id(&params)?, cause(&params)).await.map(|_| Value::Null),
+        "restart_server" => lifecycle::restart_server(state, server_id(&params)?, cause(&params)).await.map(|_| Value::Null),
+        "update_server" => lifecycle::update_server(state, server_id(&params)?, cause(&params)).await.map(|_| Value::Null),
+        "update_server_graceful" => {
+            updater::ServerUpdateService::update_server_graceful(state, server_id(&params)?, cause(&params))
+                .await
+                .map(|_| Value::Null)
+        }
+        "get_console_history" => {
+            let since = params.get("since").and_then(Value::as_u64);
+            let limit = params.get("limit").and_then(Value::as_u64).map(|l| l as usize);
+            let lines = console::history(state, server_id(&params)?, since, limit);
+            serde_json::to_value(lines).map_err(|e| e.to_string())
+        }
+        "search_console_history" => {
+            let query = params
+                .get("query")
+                .and_then(Value::as_str)
+                .ok_or("Missing query")?
+                .to_string();
+            let limit = params.get("limit").and_then(Value::as_u64).map(|l| l as usize);
+            let lines = console::search(state, server_id(&params)?, query, limit).await?;
+            serde_json::to_value(lines).map_err(|e| e.to_string())
+        }
+        "start_discord_bot" => {
+            let token = params
+                .get("token")
+                .and_then(Value::as_str)
+                .ok_or("Missing token")?
+                .to_string();
+            let guild_id = params.get("guildId").and_then(Value::as_u64);
+            start_discord_bot(state, token, guild_id).map(|_| Value::Null)
+        }
+        "stop_discord_bot" => {
+            let mut bot_handle = state.discord_bot.lock().map_err(|e| e.to_string())?;
+            if !bot_handle.is_running {
+                return Err("Discord bot is not running".to_string());
+            }
+            bot_handle.stop();
+            Ok(Value::Null)
+        }
+        "discord_bot_status" => {
+            let bot_handle = state.discord_bot.lock().map_err(|e| e.to_string())?;
+            serde_json::to_value(BotStatus {
+                is_running: bot_handle.is_running,
+                connected: bot_handle.is_running,
+            })
+            .map_err(|e| e.to_string())
+        }
+        _ => Err(format!("Unknown method: {}", method)),
+    }
+}
+
+fn start_discord_bot(state: &AppState, token: String, guild_id: Option<u64>) -> Result<(), String> {
+    {
+        let bot_handle = state.discord_bot.lock().map_err(|e| e.to_string())?;
+        if bot_handle.is_running {
+            return Err("Discord bot is already running".to_string());
+        }
+    }
+
+    let state = state.clone();
+    tauri::async_runtime::spawn(async move {
+        if let Err(e) = discord_bot::start_bot(state, token, guild_id).await {
+            eprintln!("Discord bot error: {}", e);
+        }
+    });
+    Ok(())
+}
+
+async fn handle_client<S>(state: AppState, stream: S) -> std::io::Result<()>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    let (reader, mut writer) = tokio::io::split(stream);
+    let mut lines = BufReader::new(reader).lines();
+
+    while let Some(line) = lines.next_line().await? {
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let request: Request = match serde_json::from_str(&line) {
+            Ok(r) => r,
+            Err(e) => {
+                write_line(&mut writer, &Response::from_result(Err(format!("Invalid request: {}", e)))).await?;
+                continue;
+            }
+        };
+
+        if request.method == "subscribe" {
+            let mut events = state.events.subscribe();
+            write_line(&mut writer, &Response::from_result(Ok(Value::Null))).await?;
+            loop {
+                match events.recv().await {
+                    Ok(event) => write_line(&mut writer, &event).await?,
+                    // A slow client misses events rather than holding up the bus
+                    Err(RecvError::Lagged(_)) => continue,
+                    Err(RecvError::Closed) => return Ok(()),
+                }
+            }
+        }
+
+        let response = Response::from_result(dispatch(&state, &request.method, request.params).await);
+        write_line(&mut writer, &response).await?;
+    }
+
+    Ok(())
+}
+
+#[cfg(unix)]
+type Listener = tokio::net::UnixListener;
+
+#[cfg(windows)]
+type Listener = tokio::net::windows::named_pipe::NamedPipeServer;
+
+/// Claim the socket. Fails if another daemon already has it, before this one
+/// touches any server.
+#[cfg(unix)]
+async fn listen(data_dir: &Path) -> anyhow::Result<Listener> {
+    use std::os::unix::fs::PermissionsExt;
+
+    let path = socket_path(data_dir);
+    if path.exists() {
+        if tokio::net::UnixStream::connect(&path).await.is_ok() {
+            anyhow::bail!("Another daemon is already listening on {}", path.display());
+        }
+        // Left behind by a daemon that didn't shut down cleanly
+        std::fs::remove_file(&path)?;
+    }
+
+    let listener = tokio::net::UnixListener::bind(&path)?;
+    // Anyone who can connect can start and stop servers
+    std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o600))?;
+    println!("Daemon listening on {}", path.display());
+    Ok(listener)
+}
+
+#[cfg(windows)]
+async fn listen(_data_dir: &Path) -> anyhow::Result<Listener> {
+    use tokio::net::windows::named_pipe::ServerOptions;
+
+    let server = ServerOptions::new()
+        .first_pipe_instance(true)
+        .create(PIPE_NAME)
+        .map_err(|e| anyhow::anyhow!("Another daemon may already be running: {}", e))?;
+    println!("Daemon listening on {}", PIPE_NAME);
+    Ok(server)
+}
+
+#[cfg(unix)]
+async fn serve(listener: Listener, state: AppState) -> anyhow::Result<()> {
+    loop {
+        let (stream, _) = listener.accept().await?;
+        let state = state.clone();
+        tauri::async_runtime::spawn(async move {
+            if let Err(e) = handle_client(state, stream).await {
+                eprintln!("Daemon client error: {}", e);
+            }
+        });
+    }
+}
+
+#[cfg(windows)]
+async fn serve(mut server: Listener, state: AppState) -> anyhow::Result<()> {
+    use tokio::net::windows::named_pipe::ServerOptions;
+
+    loop {
+        server.connect().await?;
+        let client = server;
+        // Open the next instance before serving this one so clients never miss the pipe
+        server = ServerOptions::new().create(PIPE_NAME)?;
+
+        let state = state.clone();
+        tauri::async_runtime::spawn(async move {
+            if let Err(e) = handle_client(state, client).await {
+                eprintln!("Daemon client error: {}", e);
+            }
+        });
+    }
+}
+
+async fn shutdown_signal() {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{signal, SignalKind};
+        match signal(SignalKind::terminate()) {
+            Ok(mut term) => {
+                tokio::select! {
+                    _ = tokio::signal::ctrl_c() => {}
+                    _ = term.recv() => {}
+                }
+            }
+            Err(_) => {
+                let _ = tokio::signal::ctrl_c().await;
+            }
+        }
+    }
+
+    #[cfg(not(unix))]
+    {
+        let _ = tokio::signal::ctrl_c().await;
+    }
+}
+
+/// Entry point of the `ark-daemon` binary. Takes an optional
+/// `--data-dir <path>` to use a data directory other than the GUI's, and
+/// `--api <address>` to serve the HTTP API there whatever the settings say.
+pub fn run_daemon() {
+    let mut args = std::env::args().skip(1);
+    let mut data_dir = None;
+    let mut api_bind = None;
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--data-dir" => data_dir = args.next().map(PathBuf::from),
+            "--api" => api_bind = args.next(),
+            "-h" | "--help" => {
+                println!("Usage: ark-daemon [--data-dir <path>] [--api <address:port>]");
+                return;
+            }
+            other => {
+                eprintln!("Unknown argument: {}", other);
+                std::process::exit(2);
+            }
+        }
+    }
+
+    let Some(data_dir) = data_dir.or_else(default_data_dir) else {
+        eprintln!("Could not determine the data directory; pass --data-dir");
+        std::process::exit(2);
+    };
+
+    let runtime = tokio::runtime::Runtime::new().expect("failed to start tokio runtime");
+    // Services spawn through Tauri's runtime; point it at ours
+    tauri::async_runtime::set(runtime.handle().clone());
+
+    runtime.block_on(async move {
+        let state = match AppState::open(data_dir) {
+            Ok(state) => state,
+            Err(e) => {
+                eprintln!("Failed to open data directory: {}", e);
+                std::process::exit(1);
+            }
+        };
+
+        let listener = match listen(&state.data_dir).await {
+            Ok(listener) => listener,
+            Err(e) => {
+                eprintln!("Failed to start daemon: {}", e);
+                std::process::exit(1);
+            }
+        };
+
+        crate::start_core_services(&state, api_bind);
+
+        let bot_settings = state.db.lock().ok().map(|db| {
+            let enabled = db.get_setting("discord_bot_enabled").ok().flatten();
+            let token = db.get_setting("discord_bot_token").ok().flatten();
+            let guild_id = db.get_setting("discord_bot_guild_id").ok().flatten();
+            (enabled, token, guild_id)
+        });
+        if let Some((Some(enabled), Some(token), guild_id)) = bot_settings {
+            if enabled == "true" && !token.is_empty() {
+                let guild_id = guild_id.and_then(|g| g.parse().ok());
+                if let Err(e) = start_discord_bot(&state, token, guild_id) {
+                    eprintln!("Failed to start Discord bot: {}", e);
+                }
+            }
+        }
+
+        tokio::select! {
+            result = serve(listener, state.clone()) => {
+                if let Err(e) = result {
+                    eprintln!("Daemon stopped: {}", e);
+                }
+            }
+            _ = shutdown_signal() => {
+                println!("Shutting down, saving and stopping servers...");
+            }
+        }
+
+        if let Ok(mut bot_handle) = state.discord_bot.lock() {
+            bot_handle.stop();
+        }
+        GracefulStopService::stop_all(&state).await;
+
+        #[cfg(unix)]
+        let _ = std::fs::remove_file(socket_path(&state.data_dir));
+    });
+}
@@ -1,11 +1,18 @@
 use crate::AppState;
+use crate::commands::{backup, console};
+use crate::models::PlayerPlatform;
+use crate::services::{lifecycle, rcon_sessions};
 use serenity::async_trait;
 use serenity::builder::{CreateCommand, CreateCommandOption, CreateEmbed, CreateInteractionResponse, CreateInteractionResponseMessage};
 use serenity::model::application::{CommandOptionType, Interaction, ResolvedOption, ResolvedValue};
 use serenity::model::gateway::Ready;
 use serenity::model::id::GuildId;
 use serenity::prelude::*;
-use tauri::{AppHandle, Manager, Emitter};
+
+/// Console lines fetched for `/servers console`.
+const CONSOLE_LINES: usize = 50;
+/// Embed descriptions are capped at 4096 characters.
+const CONSOLE_MAX_CHARS: usize = 3800;
 
 /// Discord bot handle for managing the bot lifecycle
 pub struct DiscordBotHandle {
@@ -36,7 +43,7 @@ impl DiscordBotHandle {
 
 /// Event handler for the Discord bot
 struct Handler {
-    app_handle: AppHandle,
+    state: AppState,
     guild_id: Option<u64>,
 }
 
@@ -80,6 +87,23 @@ impl EventHandler for Handler {
                             CreateCommandOption::new(CommandOptionType::Integer, "id", "Server ID")
                                 .required(true)
                         )
+                )
+                .add_option(
+                    CreateCommandOption::new(CommandOptionType::SubCommand, "players", "List online players")
+                        .add_sub_option(
+                            CreateCommandOption::new(CommandOptionType::Integer, "id", "Server ID")
+                                .required(true)
+                        )
+                )
+                .add_option(
+                    CreateCommandOption::new(CommandOptionType::SubCommand, "console", "Show recent console output")
+                        .add_sub_option(
+                            CreateCommandOption::new(CommandOptionType::Integer, "id", "Server ID")
+                                .required(true)
+                        )
+                        .add_sub_option(
+                            CreateCommandOption::new(CommandOptionType::String, "search", "Only lines containing this text")
+                        )
                 ),
             // Mod commands
             CreateCommand::new("mods")
@@ -208,6 +232,20 @@ impl Handler {
                             "❌ Server ID is required".to_string()
                         }
                     }
+                    "players" => {
+                        if let Some(id) = self.get_integer_from_options(sub_opts, "id") {
+                            self.get_players(id).await
+                        } else {
+                            "❌ Server ID is required".to_string()
+                        }
+                    }
+                    "console" => {
+                        if let Some(id) = self.get_integer_from_options(sub_opts, "id") {
+                            self.get_console_output(id, self.get_string_from_options(sub_opts, "search")).await
+                        } else {
+                            "❌ Server ID is required".to_string()
+                        }
+                    }
                     _ => "❌ Unknown subcommand".to_string(),
                 }
             }
@@ -313,277 +351,312 @@ impl Handler {
     // ==================== Server Commands ====================
 
     async fn get_servers_list(&self) -> String {
-        if let Some(state) = self.app_handle.try_state::<AppState>() {
-            if let Ok(db) = state.db.lock() {
-                if let Ok(conn) = db.get_connection() {
-                    let mut stmt = match conn.prepare(
-                        "SELECT id, name, server_type, status, map_name FROM servers ORDER BY id"
-                    ) {
-                        Ok(s) => s,
-                        Err(e) => return format!("❌ Database error: {}", e),
-                    };
-
-                    let servers: Vec<String> = stmt
-                        .query_map([], |row| {
-                            let id: i64 = row.get(0)?;
-                            let name: String = row.get(1)?;
-                            let server_type: String = row.get(2)?;
-                            let status: String = row.get(3)?;
-                            let map: String = row.get(4)?;
-                            
-                            let status_emoji = match status.as_str() {
-                                "running" => "🟢",
-                                "stopped" => "🔴",
-                                "starting" => "🟡",
-                                "updating" => "🔄",
-                                _ => "⚪",
-                            };
-                            
-                            Ok(format!("{} **[{}]** {} ({}) - {}", status_emoji, id, name, server_type, map))
-                        })
-                        .ok()
-                        .map(|rows| rows.filter_map(|r| r.ok()).collect())
-                        .unwrap_or_default();
-
-                    if servers.is_empty() {
-                        return "📭 No servers configured".to_string();
-                    }
-
-                    return format!("**📋 Server List**\n\n{}", servers.join("\n"));
+        let state = &self.state;
+        if let Ok(db) = state.db.lock() {
+            if let Ok(conn) = db.get_connection() {
+                let mut stmt = match conn.prepare(
+                    "SELECT id, name, server_type, status, map_name FROM servers ORDER BY id"
+                ) {
+                    Ok(s) => s,
+                    Err(e) => return format!("❌ Database error: {}", e),
+                };
+
+                let servers: Vec<String> = stmt
+                    .query_map([], |row| {
+                        let id: i64 = row.get(0)?;
+                        let name: String = row.get(1)?;
+                        let server_type: String = row.get(2)?;
+                        let status: String = row.get(3)?;
+                        let map: String = row.get(4)?;
+                        
+                        let status_emoji = match status.as_str() {
+                            "running" => "🟢",
+                            "stopped" => "🔴",
+                            "starting" => "🟡",
+                            "updating" => "🔄",
+                            "failed" => "❌",
+                            "crash-looping" => "🔁",
+                            _ => "⚪",
+                        };
+                        
+                        Ok(format!("{} **[{}]** {} ({}) - {}", status_emoji, id, name, server_type, map))
+                    })
+                    .ok()
+                    .map(|rows| rows.filter_map(|r| r.ok()).collect())
+                    .unwrap_or_default();
+
+                if servers.is_empty() {
+                    return "📭 No servers configured".to_string();
                 }
+
+                return format!("**📋 Server List**\n\n{}", servers.join("\n"));
             }
         }
         "❌ Failed to access database".to_string()
     }
 
     async fn start_server(&self, server_id: i64) -> String {
-        if let Some(state) = self.app_handle.try_state::<AppState>() {
-            // Check if server exists and get details
-            let server_info = {
-                if let Ok(db) = state.db.lock() {
-                    if let Ok(conn) = db.get_connection() {
-                        conn.query_row(
-                            "SELECT name, status FROM servers WHERE id = ?1",
-                            [server_id],
-                            |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
-                        ).ok()
-                    } else {
-                        None
-                    }
+        let state = &self.state;
+        // Check if server exists and get details
+        let server_info = {
+            if let Ok(db) = state.db.lock() {
+                if let Ok(conn) = db.get_connection() {
+                    conn.query_row(
+                        "SELECT name, status FROM servers WHERE id = ?1",
+                        [server_id],
+                        |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+                    ).ok()
                 } else {
                     None
                 }
-            };
+            } else {
+                None
+            }
+        };
 
-            match server_info {
-                Some((name, status)) => {
-                    if status == "running" {
-                        return format!("⚠️ Server **{}** is already running", name);
-                    }
-                    
-                    // Use the app_handle's emit to trigger the start
-                    let _ = self.app_handle.emit("discord-server-command", serde_json::json!({
-                        "action": "start",
-                        "server_id": server_id
-                    }));
-                    
-                    format!("🚀 Starting server **{}** (ID: {})...\nUse `/servers status {}` to check progress", name, server_id, server_id)
+        match server_info {
+            Some((name, status)) => {
+                if status == "running" {
+                    return format!("⚠️ Server **{}** is already running", name);
                 }
-                None => format!("❌ Server with ID {} not found", server_id),
+                
+                let state = self.state.clone();
+                tauri::async_runtime::spawn(async move {
+                    if let Err(e) = lifecycle::start_server(&state, server_id, "Discord command").await {
+                        eprintln!("Discord: failed to start server {}: {}", server_id, e);
+                    }
+                });
+                
+                format!("🚀 Starting server **{}** (ID: {})...\nUse `/servers status {}` to check progress", name, server_id, server_id)
             }
-        } else {
-            "❌ Failed to access app state".to_string()
+            None => format!("❌ Server with ID {} not found", server_id),
         }
     }
 
     async fn stop_server(&self, server_id: i64) -> String {
-        if let Some(state) = self.app_handle.try_state::<AppState>() {
-            let server_info = {
-                if let Ok(db) = state.db.lock() {
-                    if let Ok(conn) = db.get_connection() {
-                        conn.query_row(
-                            "SELECT name, status FROM servers WHERE id = ?1",
-                            [server_id],
-                            |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
-                        ).ok()
-                    } else {
-                        None
-                    }
+        let state = &self.state;
+        let server_info = {
+            if let Ok(db) = state.db.lock() {
+                if let Ok(conn) = db.get_connection() {
+                    conn.query_row(
+                        "SELECT name, status FROM servers WHERE id = ?1",
+                        [server_id],
+                        |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+                    ).ok()
                 } else {
                     None
                 }
-            };
+            } else {
+                None
+            }
+        };
 
-            match server_info {
-                Some((name, status)) => {
-                    if status == "stopped" {
-                        return format!("⚠️ Server **{}** is already stopped", name);
-                    }
-                    
-                    // Emit stop command
-                    let _ = self.app_handle.emit("discord-server-command", serde_json::json!({
-                        "action": "stop",
-                        "server_id": server_id
-                    }));
-                    
-                    // Also try to stop via process manager directly
-                    let _ = state.process_manager.stop_server(server_id);
-                    
-                    // Update DB status
-                    if let Ok(db) = state.db.lock() {
-                        if let Ok(conn) = db.get_connection() {
-                            let _ = conn.execute("UPDATE servers SET status = 'stopped' WHERE id = ?1", [server_id]);
-                        }
-                    }
-                    
-                    format!("🛑 Stopped server **{}** (ID: {})", name, server_id)
+        match server_info {
+            Some((name, status)) => {
+                if status == "stopped" {
+                    return format!("⚠️ Server **{}** is already stopped", name);
                 }
-                None => format!("❌ Server with ID {} not found", server_id),
+                
+                // Saving and shutting down can take minutes, longer than
+                // Discord waits for a reply
+                let state = self.state.clone();
+                tauri::async_runtime::spawn(async move {
+                    if let Err(e) = lifecycle::stop_server(&state, server_id, "Discord command").await {
+                        eprintln!("Discord: failed to stop server {}: {}", server_id, e);
+                    }
+                });
+                
+                format!("🛑 Stopping server **{}** (ID: {})...\nThe world is saved before it shuts down.", name, server_id)
             }
-        } else {
-            "❌ Failed to access app state".to_string()
+            None => format!("❌ Server with ID {} not found", server_id),
         }
     }
 
     async fn restart_server(&self, server_id: i64) -> String {
-        if let Some(state) = self.app_handle.try_state::<AppState>() {
-            let server_info = {
-                if let Ok(db) = state.db.lock() {
-                    if let Ok(conn) = db.get_connection() {
-                        conn.query_row(
-                            "SELECT name FROM servers WHERE id = ?1",
-                            [server_id],
-                            |row| row.get::<_, String>(0)
-                        ).ok()
-                    } else {
-                        None
-                    }
+        let state = &self.state;
+        let server_info = {
+            if let Ok(db) = state.db.lock() {
+                if let Ok(conn) = db.get_connection() {
+                    conn.query_row(
+                        "SELECT name FROM servers WHERE id = ?1",
+                        [server_id],
+                        |row| row.get::<_, String>(0)
+                    ).ok()
                 } else {
                     None
                 }
-            };
-
-            match server_info {
-                Some(name) => {
-                    let _ = self.app_handle.emit("discord-server-command", serde_json::json!({
-                        "action": "restart",
-                        "server_id": server_id
-                    }));
-                    
-                    format!("🔄 Restarting server **{}** (ID: {})...", name, server_id)
-                }
-                None => format!("❌ Server with ID {} not found", server_id),
+            } else {
+                None
             }
-        } else {
-            "❌ Failed to access app state".to_string()
+        };
+
+        match server_info {
+            Some(name) => {
+                let state = self.state.clone();
+                tauri::async_runtime::spawn(async move {
+                    if let Err(e) = lifecycle::restart_server(&state, server_id, "Discord command").await {
+                        eprintln!("Discord: failed to restart server {}: {}", server_id, e);
+                    }
+                });
+                
+                format!("🔄 Restarting server **{}** (ID: {})...", name, server_id)
+            }
+            None => format!("❌ Server with ID {} not found", server_id),
         }
     }
 
     async fn get_server_status(&self, server_id: i64) -> String {
-        if let Some(state) = self.app_handle.try_state::<AppState>() {
-            if let Ok(db) = state.db.lock() {
-                if let Ok(conn) = db.get_connection() {
-                    let result = conn.query_row(
-                        "SELECT name, server_type, status, map_name, game_port, max_players, last_started 
-                         FROM servers WHERE id = ?1",
-                        [server_id],
-                        |row| {
-                            Ok((
-                                row.get::<_, String>(0)?,
-                                row.get::<_, String>(1)?,
-                                row.get::<_, String>(2)?,
-                                row.get::<_, String>(3)?,
-                                row.get::<_, u16>(4)?,
-                                row.get::<_, i32>(5)?,
-                                row.get::<_, Option<String>>(6)?,
-                            ))
-                        }
-                    );
-
-                    match result {
-                        Ok((name, server_type, status, map, port, max_players, last_started)) => {
-                            let status_emoji = match status.as_str() {
-                                "running" => "🟢 Running",
-                                "stopped" => "🔴 Stopped",
-                                "starting" => "🟡 Starting",
-                                "updating" => "🔄 Updating",
-                                "crashed" => "💥 Crashed",
-                                _ => "⚪ Unknown",
-                            };
-                            
-                            let last_started_str = last_started.unwrap_or_else(|| "Never".to_string());
-                            
-                            format!(
-                                "**📊 Server Status: {}**\n\n\
-                                 • **Type:** {}\n\
-                                 • **Status:** {}\n\
-                                 • **Map:** {}\n\
-                                 • **Port:** {}\n\
-                                 • **Max Players:** {}\n\
-                                 • **Last Started:** {}",
-                                name, server_type, status_emoji, map, port, max_players, last_started_str
-                            )
-                        }
-                        Err(_) => format!("❌ Server with ID {} not found", server_id),
+        let state = &self.state;
+        if let Ok(db) = state.db.lock() {
+            if let Ok(conn) = db.get_connection() {
+                let result = conn.query_row(
+                    "SELECT name, server_type, status, map_name, game_port, max_players, last_started 
+                     FROM servers WHERE id = ?1",
+                    [server_id],
+                    |row| {
+                        Ok((
+                            row.get::<_, String>(0)?,
+                            row.get::<_, String>(1)?,
+                            row.get::<_, String>(2)?,
+                            row.get::<_, String>(3)?,
+                            row.get::<_, u16>(4)?,
+                            row.get::<_, i32>(5)?,
+                            row.get::<_, Option<String>>(6)?,
+                        ))
                     }
-                } else {
-                    "❌ Failed to access database".to_string()
+                );
+
+                match result {
+                    Ok((name, server_type, status, map, port, max_players, last_started)) => {
+                        let status_emoji = match status.as_str() {
+                            "running" => "🟢 Running",
+                            "stopped" => "🔴 Stopped",
+                            "starting" => "🟡 Starting",
+                            "updating" => "🔄 Updating",
+                            "crashed" => "💥 Crashed",
+                            "failed" => "❌ Failed to start",
+                            "crash-looping" => "🔁 Crash-looping (auto-restart paused)",
+                            _ => "⚪ Unknown",
+                        };
+                        
+                        let last_started_str = last_started.unwrap_or_else(|| "Never".to_string());
+                        
+                        format!(
+                            "**📊 Server Status: {}**\n\n\
+                             • **Type:** {}\n\
+                             • **Status:** {}\n\
+                             • **Map:** {}\n\
+                             • **Port:** {}\n\
+                             • **Max Players:** {}\n\
+                             • **Last Started:** {}",
+                            name, server_type, status_emoji, map, port, max_players, last_started_str
+                        )
+                    }
+                    Err(_) => format!("❌ Server with ID {} not found", server_id),
                 }
             } else {
-                "❌ Failed to lock database".to_string()
+                "❌ Failed to access database".to_string()
             }
         } else {
-            "❌ Failed to access app state".to_string()
+            "❌ Failed to lock database".to_string()
         }
     }
 
-    // ==================== Mod Commands ====================
+    async fn get_players(&self, server_id: i64) -> String {
+        let players = match rcon_sessions::players(&self.state, server_id).await {
+            Ok(players) => players,
+            Err(e) => return format!("❌ Couldn't get players of server {}: {}", server_id, e),
+        };
 
-    async fn get_mods_list(&self, server_id: i64) -> String {
-        if let Some(state) = self.app_handle.try_state::<AppState>() {
-            if let Ok(db) = state.db.lock() {
-                if let Ok(conn) = db.get_connection() {
-                    // First verify server exists
-                    let server_name: Option<String> = conn.query_row(
-                        "SELECT name FROM servers WHERE id = ?1",
-                        [server_id],
-                        |row| row.get(0)
-                    ).ok();
+        if players.is_empty() {
+            return format!("No players online on server {}", server_id);
+        }
 
-                    if server_name.is_none() {
-                        return format!("❌ Server with ID {} not found", server_id);
-                    }
+        let mut response = format!("**👥 {} online on server {}**\n\n", players.len(), server_id);
+        for player in &players {
+            let platform = match player.platform {
+                PlayerPlatform::Steam => "Steam",
+                PlayerPlatform::Eos => "EOS",
+                PlayerPlatform::Unknown => "ID",
+            };
+            response.push_str(&format!("• **{}** ({} `{}`)\n", player.name, platform, player.platform_id));
+        }
+        response
+    }
 
-                    let mut stmt = match conn.prepare(
-                        "SELECT mod_id, mod_name FROM installed_mods WHERE server_id = ?1"
-                    ) {
-                        Ok(s) => s,
-                        Err(_) => return format!("📦 No mods installed on **{}**", server_name.unwrap()),
-                    };
-
-                    let mods: Vec<String> = stmt
-                        .query_map([server_id], |row| {
-                            let mod_id: String = row.get(0)?;
-                            let mod_name: String = row.get(1)?;
-                            Ok(format!("• {} (ID: {})", mod_name, mod_id))
-                        })
-                        .ok()
-                        .map(|rows| rows.filter_map(|r| r.ok()).collect())
-                        .unwrap_or_default();
-
-                    if mods.is_empty() {
-                        return format!("📦 No mods installed on **{}**", server_name.unwrap());
-                    }
+    async fn get_console_output(&self, server_id: i64, search: Option<&str>) -> String {
+        let lines = match search {
+            Some(query) => match console::search(&self.state, server_id, query.to_string(), Some(CONSOLE_LINES)).await {
+                Ok(lines) => lines,
+                Err(e) => return format!("❌ {}", e),
+            },
+            None => console::history(&self.state, server_id, None, Some(CONSOLE_LINES)),
+        };
+
+        if lines.is_empty() {
+            return match search {
+                Some(query) => format!("No console lines matching `{}` for server {}", query, server_id),
+                None => format!("No console output recorded for server {}", server_id),
+            };
+        }
 
-                    format!("**📦 Installed Mods on {}**\n\n{}", server_name.unwrap(), mods.join("\n"))
-                } else {
-                    "❌ Failed to access database".to_string()
+        // Newest lines that fit in an embed, leaving room for the header
+        let mut body = String::new();
+        for line in lines.iter().rev() {
+            let text = line.line.replace("```", "'''");
+            if body.len() + text.len() + 1 > CONSOLE_MAX_CHARS {
+                break;
+            }
+            body.insert_str(0, &format!("{}\n", text));
+        }
+
+        format!("**🖥️ Console of server {}**\n```\n{}```", server_id, body)
+    }
+
+    // ==================== Mod Commands ====================
+
+    async fn get_mods_list(&self, server_id: i64) -> String {
+        let state = &self.state;
+        if let Ok(db) = state.db.lock() {
+            if let Ok(conn) = db.get_connection() {
+                // First verify server exists
+                let server_name: Option<String> = conn.query_row(
+                    "SELECT name FROM servers WHERE id = ?1",
+                    [server_id],
+                    |row| row.get(0)
+                ).ok();
+
+                if server_name.is_none() {
+                    return format!("❌ Server with ID {} not found", server_id);
+                }
+
+                let mut stmt = match conn.prepare(
+                    "SELECT mod_id, mod_name FROM installed_mods WHERE server_id = ?1"
+                ) {
+                    Ok(s) => s,
+                    Err(_) => return format!("📦 No mods installed on **{}**", server_name.unwrap()),
+                };
+
+                let mods: Vec<String> = stmt
+                    .query_map([server_id], |row| {
+                        let mod_id: String = row.get(0)?;
+                        let mod_name: String = row.get(1)?;
+                        Ok(format!("• {} (ID: {})", mod_name, mod_id))
+                    })
+                    .ok()
+                    .map(|rows| rows.filter_map(|r| r.ok()).collect())
+                    .unwrap_or_default();
+
+                if mods.is_empty() {
+                    return format!("📦 No mods installed on **{}**", server_name.unwrap());
                 }
+
+                format!("**📦 Installed Mods on {}**\n\n{}", server_name.unwrap(), mods.join("\n"))
             } else {
-                "❌ Failed to lock database".to_string()
+                "❌ Failed to access database".to_string()
             }
         } else {
-            "❌ Failed to access app state".to_string()
+            "❌ Failed to lock database".to_string()
         }
     }
 
@@ -595,88 +668,83 @@ impl Handler {
     // ==================== Backup Commands ====================
 
     async fn get_backups_list(&self, server_id: i64) -> String {
-        if let Some(state) = self.app_handle.try_state::<AppState>() {
-            if let Ok(db) = state.db.lock() {
-                if let Ok(conn) = db.get_connection() {
-                    // Verify server exists
-                    let server_name: Option<String> = conn.query_row(
-                        "SELECT name FROM servers WHERE id = ?1",
-                        [server_id],
-                        |row| row.get(0)
-                    ).ok();
-
-                    if server_name.is_none() {
-                        return format!("❌ Server with ID {} not found", server_id);
-                    }
-
-                    let mut stmt = match conn.prepare(
-                        "SELECT id, name, created_at, size_bytes FROM backups WHERE server_id = ?1 ORDER BY created_at DESC LIMIT 10"
-                    ) {
-                        Ok(s) => s,
-                        Err(_) => return format!("💾 No backups found for **{}**", server_name.unwrap()),
-                    };
-
-                    let backups: Vec<String> = stmt
-                        .query_map([server_id], |row| {
-                            let id: i64 = row.get(0)?;
-                            let name: String = row.get(1)?;
-                            let created: String = row.get(2)?;
-                            let size: i64 = row.get::<_, i64>(3).unwrap_or(0);
-                            let size_mb = size / (1024 * 1024);
-                            Ok(format!("• **[{}]** {} - {} ({} MB)", id, name, created, size_mb))
-                        })
-                        .ok()
-                        .map(|rows| rows.filter_map(|r| r.ok()).collect())
-                        .unwrap_or_default();
-
-                    if backups.is_empty() {
-                        return format!("💾 No backups found for **{}**", server_name.unwrap());
-                    }
+        let state = &self.state;
+        if let Ok(db) = state.db.lock() {
+            if let Ok(conn) = db.get_connection() {
+                // Verify server exists
+                let server_name: Option<String> = conn.query_row(
+                    "SELECT name FROM servers WHERE id = ?1",
+                    [server_id],
+                    |row| row.get(0)
+                ).ok();
+
+                if server_name.is_none() {
+                    return format!("❌ Server with ID {} not found", server_id);
+                }
 
-                    format!("**💾 Backups for {}** (Last 10)\n\n{}", server_name.unwrap(), backups.join("\n"))
-                } else {
-                    "❌ Failed to access database".to_string()
+                let mut stmt = match conn.prepare(
+                    "SELECT id, name, created_at, size_bytes FROM backups WHERE server_id = ?1 ORDER BY created_at DESC LIMIT 10"
+                ) {
+                    Ok(s) => s,
+                    Err(_) => return format!("💾 No backups found for **{}**", server_name.unwrap()),
+                };
+
+                let backups: Vec<String> = stmt
+                    .query_map([server_id], |row| {
+                        let id: i64 = row.get(0)?;
+                        let name: String = row.get(1)?;
+                        let created: String = row.get(2)?;
+                        let size: i64 = row.get::<_, i64>(3).unwrap_or(0);
+                        let size_mb = size / (1024 * 1024);
+                        Ok(format!("• **[{}]** {} - {} ({} MB)", id, name, created, size_mb))
+                    })
+                    .ok()
+                    .map(|rows| rows.filter_map(|r| r.ok()).collect())
+                    .unwrap_or_default();
+
+                if backups.is_empty() {
+                    return format!("💾 No backups found for **{}**", server_name.unwrap());
                 }
+
+                format!("**💾 Backups for {}** (Last 10)\n\n{}", server_name.unwrap(), backups.join("\n"))
             } else {
-                "❌ Failed to lock database".to_string()
+                "❌ Failed to access database".to_string()
             }
         } else {
-            "❌ Failed to access app state".to_string()
+            "❌ Failed to lock database".to_string()
         }
     }
 
     async fn create_backup(&self, server_id: i64) -> String {
-        if let Some(state) = self.app_handle.try_state::<AppState>() {
-            let server_name: Option<String> = {
-                if let Ok(db) = state.db.lock() {
-                    if let Ok(conn) = db.get_connection() {
-                        conn.query_row(
-                            "SELECT name FROM servers WHERE id = ?1",
-                            [server_id],
-                            |row| row.get(0)
-                        ).ok()
-                    } else {
-                        None
-                    }
+        let state = &self.state;
+        let server_name: Option<String> = {
+            if let Ok(db) = state.db.lock() {
+                if let Ok(conn) = db.get_connection() {
+                    conn.query_row(
+                        "SELECT name FROM servers WHERE id = ?1",
+                        [server_id],
+                        |row| row.get(0)
+                    ).ok()
                 } else {
                     None
                 }
-            };
-
-            match server_name {
-                Some(name) => {
-                    // Emit backup command
-                    let _ = self.app_handle.emit("discord-backup-command", serde_json::json!({
-                        "action": "create",
-                        "server_id": server_id
-                    }));
-                    
-                    format!("💾 Creating backup for server **{}**...\nThis may take a few minutes. Use `/backups list {}` to see when it's complete.", name, server_id)
-                }
-                None => format!("❌ Server with ID {} not found", server_id),
+            } else {
+                None
             }
-        } else {
-            "❌ Failed to access app state".to_string()
+        };
+
+        match server_name {
+            Some(name) => {
+                let state = self.state.clone();
+                tauri::async_runtime::spawn(async move {
+                    if let Err(e) = backup::backup_server(&state, server_id, "manual".to_string(), None).await {
+                        eprintln!("Discord: failed to back up server {}: {}", server_id, e);
+                    }
+                });
+                
+                format!("💾 Creating backup for server **{}**...\nThis may take a few minutes. Use `/backups list {}` to see when it's complete.", name, server_id)
+            }
+            None => format!("❌ Server with ID {} not found", server_id),
         }
     }
 
@@ -687,42 +755,39 @@ impl Handler {
     }
 
     async fn get_system_info(&self) -> String {
-        if let Some(state) = self.app_handle.try_state::<AppState>() {
-            if let Ok(mut sys) = state.sys.lock() {
-                sys.refresh_all();
-                
-                let total_memory = sys.total_memory();
-                let used_memory = sys.used_memory();
-                let memory_percent = (used_memory as f64 / total_memory as f64 * 100.0).round();
-                
-                let cpu_usage: f32 = sys.cpus().iter().map(|c| c.cpu_usage()).sum::<f32>() / sys.cpus().len() as f32;
-                
-                format!(
-                    "**💻 System Information**\n\n\
-                     • **CPU Usage:** {:.1}%\n\
-                     • **Memory:** {} GB / {} GB ({:.0}%)\n\
-                     • **CPU Cores:** {}",
-                    cpu_usage,
-                    used_memory / (1024 * 1024 * 1024),
-                    total_memory / (1024 * 1024 * 1024),
-                    memory_percent,
-                    sys.cpus().len()
-                )
-            } else {
-                "❌ Failed to get system info".to_string()
-            }
+        let state = &self.state;
+        if let Ok(mut sys) = state.sys.lock() {
+            sys.refresh_all();
+            
+            let total_memory = sys.total_memory();
+            let used_memory = sys.used_memory();
+            let memory_percent = (used_memory as f64 / total_memory as f64 * 100.0).round();
+            
+            let cpu_usage: f32 = sys.cpus().iter().map(|c| c.cpu_usage()).sum::<f32>() / sys.cpus().len() as f32;
+            
+            format!(
+                "**💻 System Information**\n\n\
+                 • **CPU Usage:** {:.1}%\n\
+                 • **Memory:** {} GB / {} GB ({:.0}%)\n\
+                 • **CPU Cores:** {}",
+                cpu_usage,
+                used_memory / (1024 * 1024 * 1024),
+                total_memory / (1024 * 1024 * 1024),
+                memory_percent,
+                sys.cpus().len()
+            )
         } else {
-            "❌ Failed to access app state".to_string()
+            "❌ Failed to get system info".to_string()
         }
     }
 }
 
 /// Start the Discord bot
-pub async fn start_bot(app_handle: AppHandle, token: String, guild_id: Option<u64>) -> Result<(), String> {
+pub async fn start_bot(state: AppState, token: String, guild_id: Option<u64>) -> Result<(), String> {
     let intents = GatewayIntents::empty();
     
     let handler = Handler {
-        app_handle: app_handle.clone(),
+        state: state.clone(),
         guild_id,
     };
 
@@ -735,10 +800,8 @@ pub async fn start_bot(app_handle: AppHandle, token: String, guild_id: Option<u6
     let (tx, mut rx) = tokio::sync::oneshot::channel::<()>();
 
     // Update bot handle in app state
-    if let Some(state) = app_handle.try_state::<AppState>() {
-        if let Ok(mut bot_handle) = state.discord_bot.lock() {
-            bot_handle.set_running(tx);
-        }
+    if let Ok(mut bot_handle) = state.discord_bot.lock() {
+        bot_handle.set_running(tx);
     }
 
     // Start the bot with graceful shutdown
@@ -0,0 +1,50 @@
+use serde::{Deserialize, Serialize};
+use tokio::sync::broadcast;
+
+/// Events buffered per subscriber before slow ones start missing events.
+const CAPACITY: usize = 1024;
+
+/// A named event with a JSON payload, the same shape the webview receives
+/// from `emit`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Event {
+    pub name: String,
+    pub payload: serde_json::Value,
+}
+
+/// Fan-out for server events. Services emit here instead of through a Tauri
+/// `AppHandle`, so they run the same inside the GUI and inside the daemon.
+/// The GUI forwards the bus to the webview; the daemon forwards it to
+/// connected clients.
+#[derive(Clone)]
+pub struct EventBus {
+    sender: broadcast::Sender<Event>,
+}
+
+impl EventBus {
+    pub fn new() -> Self {
+        let (sender, _) = broadcast::channel(CAPACITY);
+        Self { sender }
+    }
+
+    pub fn emit<S: Serialize>(&self, name: &str, payload: S) {
+        let payload = serde_json::to_value(payload).unwrap_or(serde_json::Value::Null);
+        // No subscribers is fine; the event is simply dropped
+        let _ = self.sender.send(Event { name: name.to_string(), payload });
+    }
+
+    /// Re-emit an event received from elsewhere (the daemon) on this bus.
+    pub fn publish(&self, event: Event) {
+        let _ = self.sender.send(event);
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<Event> {
+        self.sender.subscribe()
+    }
+}
+
+impl Default for EventBus {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,346 @@
+use crate::db::Database;
+use crate::models::ServerStatus;
+use crate::services::process_manager::{self, ProcessManager};
+use crate::services::{platform, server_status};
+use crate::services::rcon_sessions;
+use crate::AppState;
+use serde::Serialize;
+use std::path::Path;
+use std::time::{Duration, Instant, SystemTime};
+use sysinfo::Signal;
+use tokio::time::{sleep, timeout};
+
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+const RCON_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// How long each step of a graceful stop may take. Read from the settings
+/// table so it can be tuned per install; ASA in particular can take minutes
+/// to write a large world.
+#[derive(Debug, Clone)]
+pub struct StopOptions {
+    /// Broadcast to players before saving; `None` skips the warning
+    pub warning: Option<String>,
+    pub warning_delay: Duration,
+    pub save_timeout: Duration,
+    pub exit_timeout: Duration,
+    pub terminate_timeout: Duration,
+}
+
+impl Default for StopOptions {
+    fn default() -> Self {
+        Self {
+            warning: None,
+            warning_delay: Duration::from_secs(30),
+            save_timeout: Duration::from_secs(60),
+            exit_timeout: Duration::from_secs(120),
+            terminate_timeout: Duration::from_secs(30),
+        }
+    }
+}
+
+impl StopOptions {
+    pub fn from_settings(db: &Database) -> Self {
+        let defaults = Self::default();
+        let seconds = |key: &str, default: Duration| {
+            db.get_setting(key)
+                .ok()
+                .flatten()
+                .and_then(|v| v.trim().parse::<u64>().ok())
+                .map(Duration::from_secs)
+                .unwrap_or(default)
+        };
+
+        Self {
+            warning: db
+                .get_setting("stop_warning_message")
+                .ok()
+                .flatten()
+                .filter(|m| !m.trim().is_empty()),
+            warning_delay: seconds("stop_warning_seconds", defaults.warning_delay),
+            save_timeout: seconds("stop_save_timeout", defaults.save_timeout),
+            exit_timeout: seconds("stop_exit_timeout", defaults.exit_timeout),
+            terminate_timeout: seconds("stop_terminate_timeout", defaults.terminate_timeout),
+        }
+    }
+
+    /// Same timings, but without the player broadcast. Used when the caller
+    /// has already warned players itself (updates) or nobody can hear it
+    /// (frozen servers).
+    pub fn without_warning(mut self) -> Self {
+        self.warning = None;
+        self
+    }
+}
+
+/// Steps of the stop pipeline, reported through `server-stop-phase` events.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum StopPhase {
+    Warning,
+    Saving,
+    Saved,
+    SaveTimedOut,
+    Exiting,
+    Terminating,
+    Killing,
+    Stopped,
+}
+
+/// How the server ended up stopping.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StopOutcome {
+    NotRunning,
+    Exited,
+    Terminated,
+    Killed,
+}
+
+/// The process being stopped: one we spawned, or a PID left over from an
+/// earlier session that we only know from the database.
+enum Target {
+    Managed,
+    /// Still running from an earlier session and not adopted. Identified by
+    /// PID and start time.
+    Orphan { pid: u32, start_time: u64 },
+}
+
+impl Target {
+    fn has_exited(&self, process_manager: &ProcessManager, server_id: i64) -> bool {
+        match self {
+            Target::Managed => process_manager.has_exited(server_id),
+            // A different start time means the PID has been reused
+            Target::Orphan { pid, start_time } => process_manager::process_start_time(*pid) != Some(*start_time),
+        }
+    }
+
+    fn terminate(&self, process_manager: &ProcessManager, server_id: i64) -> bool {
+        match self {
+            Target::Managed => process_manager.terminate_server(server_id),
+            Target::Orphan { pid, .. } => {
+                !self.has_exited(process_manager, server_id) && process_manager::signal_process_tree(*pid, Signal::Term)
+            }
+        }
+    }
+
+    fn kill(&self, process_manager: &ProcessManager, server_id: i64) -> Result<(), String> {
+        match self {
+            Target::Managed => process_manager.kill_server(server_id).map_err(|e| e.to_string()),
+            Target::Orphan { pid, .. } => {
+                if self.has_exited(process_manager, server_id)
+                    || process_manager::signal_process_tree(*pid, Signal::Kill)
+                {
+                    Ok(())
+                } else {
+                    Err(format!("Failed to kill process {}", pid))
+                }
+            }
+        }
+    }
+}
+
+/// Stops servers the way an admin would: warn, save, ask the server to exit,
+/// and only signal or kill it when it doesn't.
+pub struct GracefulStopService;
+
+impl GracefulStopService {
+    /// Run the stop pipeline for one server:
+    /// optional broadcast -> `SaveWorld` -> wait for the save file to change
+    /// -> `DoExit` -> wait -> SIGTERM -> wait -> kill.
+    /// Steps that need RCON are skipped once RCON stops answering. A server
+    /// that was up is `stopping` meanwhile; setting the final status is left
+    /// to the caller.
+    pub async fn stop(state: &AppState, server_id: i64, options: &StopOptions, cause: &str) -> Result<StopOutcome, String> {
+
+        let (install_path, server_type, pid, pid_start_time) = {
+            let db = state.db.lock().map_err(|e| e.to_string())?;
+            let conn = db.get_connection().map_err(|e| e.to_string())?;
+            conn.query_row(
+                "SELECT install_path, server_type, pid, pid_start_time FROM servers WHERE id = ?1",
+                [server_id],
+                |row| Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, Option<i64>>(2)?.map(|p| p as u32),
+                    row.get::<_, Option<i64>>(3)?.map(|t| t as u64),
+                )),
+            ).map_err(|e| e.to_string())?
+        };
+
+        let process_manager = &state.process_manager;
+        // A recorded PID only counts when it is still the server's own
+        // process; it may have been reused since the server went away
+        let orphan = || {
+            let pid = pid.filter(|p| *p != 0)?;
+            let executable = platform::server_executable(Path::new(&install_path), &server_type).ok()?;
+            let start_time = process_manager::verify_server_process(pid, pid_start_time, &executable)?;
+            Some(Target::Orphan { pid, start_time })
+        };
+        let target = if process_manager.request_stop(server_id) {
+            Target::Managed
+        } else if let Some(orphan) = orphan() {
+            orphan
+        } else {
+            return Ok(StopOutcome::NotRunning);
+        };
+
+        {
+            let db = state.db.lock().map_err(|e| e.to_string())?;
+            let conn = db.get_connection().map_err(|e| e.to_string())?;
+            server_status::transition_from(
+                &conn,
+                server_id,
+                &[ServerStatus::Starting, ServerStatus::Running],
+                ServerStatus::Stopping,
+                cause,
+            )?;
+        }
+
+        let emit = |phase: StopPhase, message: &str| {
+            state.events.emit("server-stop-phase", serde_json::json!({
+                "serverId": server_id,
+                "phase": phase,
+                "message": message
+            }));
+        };
+
+        let mut rcon_up = true;
+
+        // 1. Warn players
+        if let Some(message) = &options.warning {
+            emit(StopPhase::Warning, message);
+            rcon_up = rcon(state, server_id, &format!("Broadcast {}", message)).await.is_some();
+            if rcon_up {
+                sleep(options.warning_delay).await;
+            }
+        }
+
+        // 2. Save the world and wait until the save file has actually been written
+        if rcon_up {
+            emit(StopPhase::Saving, "Saving world");
+            let saves = platform::saved_arks_dir(Path::new(&install_path));
+            let before = latest_save_time(&saves);
+
+            rcon_up = rcon(state, server_id, "SaveWorld").await.is_some();
+            if rcon_up {
+                if wait_for_save(&saves, before, options.save_timeout).await {
+                    emit(StopPhase::Saved, "World saved");
+                } else {
+                    emit(StopPhase::SaveTimedOut, "Save file did not change before the timeout");
+                }
+            }
+        }
+
+        // 3. Ask the server to exit on its own
+        if rcon_up {
+            emit(StopPhase::Exiting, "Sent DoExit");
+            // The server often drops the connection before answering DoExit
+            let _ = rcon(state, server_id, "DoExit").await;
+            if wait_for_exit(&target, process_manager, server_id, options.exit_timeout).await {
+                emit(StopPhase::Stopped, "Server exited");
+                return Ok(StopOutcome::Exited);
+            }
+        }
+
+        // 4. Escalate to SIGTERM where the platform has it
+        if target.terminate(process_manager, server_id) {
+            emit(StopPhase::Terminating, "Server did not exit, sent SIGTERM");
+            if wait_for_exit(&target, process_manager, server_id, options.terminate_timeout).await {
+                emit(StopPhase::Stopped, "Server terminated");
+                return Ok(StopOutcome::Terminated);
+            }
+        }
+
+        // 5. Kill it
+        emit(StopPhase::Killing, "Server did not terminate, killing it");
+        target.kill(process_manager, server_id)?;
+        emit(StopPhase::Stopped, "Server killed");
+        Ok(StopOutcome::Killed)
+    }
+
+    /// Gracefully stop every server we spawned, in parallel. Used when the
+    /// manager itself is closing.
+    pub async fn stop_all(state: &AppState) {
+        let options = match state.db.lock() {
+            Ok(db) => StopOptions::from_settings(&db),
+            Err(_) => StopOptions::default(),
+        };
+
+        let tasks: Vec<_> = state
+            .process_manager
+            .running_server_ids()
+            .into_iter()
+            .map(|server_id| {
+                let state = state.clone();
+                let options = options.clone();
+                tauri::async_runtime::spawn(async move {
+                    (server_id, Self::stop(&state, server_id, &options, "manager closing").await)
+                })
+            })
+            .collect();
+
+        for task in tasks {
+            match task.await {
+                Ok((server_id, Ok(_))) => {
+                    if let Ok(db) = state.db.lock() {
+                        if let Ok(conn) = db.get_connection() {
+                            let _ = server_status::transition_from(
+                                &conn,
+                                server_id,
+                                &[ServerStatus::Starting, ServerStatus::Running, ServerStatus::Stopping],
+                                ServerStatus::Stopped,
+                                "manager closing",
+                            );
+                            let _ = process_manager::clear_server_pid(&conn, server_id);
+                        }
+                    }
+                }
+                Ok((server_id, Err(e))) => eprintln!("Failed to stop server {}: {}", server_id, e),
+                Err(e) => eprintln!("Stop task failed: {}", e),
+            }
+        }
+    }
+}
+
+/// Run an RCON command on the server, giving up after `RCON_TIMEOUT`
+/// so a frozen server can't stall the pipeline.
+async fn rcon(state: &AppState, server_id: i64, command: &str) -> Option<String> {
+    match timeout(RCON_TIMEOUT, rcon_sessions::execute(state, server_id, command)).await {
+        Ok(Ok(response)) => Some(response),
+        _ => None,
+    }
+}
+
+/// Newest modification time of any `.ark` save under `dir`.
+fn latest_save_time(dir: &Path) -> Option<SystemTime> {
+    walkdir::WalkDir::new(dir)
+        .max_depth(2)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.path().extension().is_some_and(|ext| ext == "ark"))
+        .filter_map(|e| e.metadata().ok()?.modified().ok())
+        .max()
+}
+
+async fn wait_for_save(dir: &Path, before: Option<SystemTime>, limit: Duration) -> bool {
+    let deadline = Instant::now() + limit;
+    while Instant::now() < deadline {
+        if latest_save_time(dir) > before {
+            return true;
+        }
+        sleep(POLL_INTERVAL).await;
+    }
+    false
+}
+
+async fn wait_for_exit(target: &Target, process_manager: &ProcessManager, server_id: i64, limit: Duration) -> bool {
+    let deadline = Instant::now() + limit;
+    loop {
+        if target.has_exited(process_manager, server_id) {
+            return true;
+        }
+        if Instant::now() >= deadline {
+            return false;
+        }
+        sleep(POLL_INTERVAL).await;
+    }
+}
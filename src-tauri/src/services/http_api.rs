@@ -0,0 +1,578 @@
+use crate::commands::{backup, config, mods, rcon, scheduler, server};
+use crate::models::{ApiToken, Backup, Ban, ModInfo, Schedule, Server, ServerPorts, ServerSettingsUpdate, StatusChange};
+use crate::services::events::Event;
+use crate::services::updater::ServerUpdateService;
+use crate::services::{api_tokens, lifecycle, metrics, moderation, server_status, servers};
+use crate::AppState;
+use axum::extract::{FromRequestParts, Path, Query, State};
+use axum::http::request::Parts;
+use axum::http::{header, StatusCode};
+use axum::response::sse::{Event as SseEvent, KeepAlive, Sse};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{delete, get, post};
+use axum::{Json, Router};
+use futures_util::Stream;
+use rusqlite::Connection;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::convert::Infallible;
+use tokio::sync::broadcast::error::RecvError;
+
+// Optional HTTP/JSON API for managing servers remotely. It runs wherever the
+// servers are owned (the daemon, or the app without one) and calls the same
+// functions as the Tauri commands. Every request needs a bearer token from
+// `api_tokens`, and each route checks one scope. Installs and updates answer
+// 202 at once; their progress arrives on the `/api/events` SSE stream.
+
+/// Where the API listens unless `http_api_bind` says otherwise: this machine only.
+pub const DEFAULT_BIND: &str = "127.0.0.1:8780";
+
+struct ApiError {
+    status: StatusCode,
+    message: String,
+}
+
+impl ApiError {
+    fn new(status: StatusCode, message: impl Into<String>) -> Self {
+        ApiError { status, message: message.into() }
+    }
+
+    fn not_found(what: &str, id: i64) -> Self {
+        ApiError::new(StatusCode::NOT_FOUND, format!("{} {} not found", what, id))
+    }
+}
+
+/// The services report failures as text, nearly always about the request.
+impl From<String> for ApiError {
+    fn from(message: String) -> Self {
+        ApiError::new(StatusCode::BAD_REQUEST, message)
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        (self.status, Json(json!({ "error": self.message }))).into_response()
+    }
+}
+
+type ApiResult<T> = Result<T, ApiError>;
+
+/// The caller's token, from `Authorization: Bearer <secret>`.
+struct Auth(ApiToken);
+
+impl Auth {
+    fn require(&self, scope: &str) -> ApiResult<()> {
+        if self.0.allows(scope) {
+            Ok(())
+        } else {
+            Err(ApiError::new(StatusCode::FORBIDDEN, format!("Token lacks the {} scope", scope)))
+        }
+    }
+
+    /// The server as this token may see it. Its passwords are RCON and join
+    /// credentials, so only tokens that can read the config get them.
+    fn redact(&self, mut server: Server) -> Server {
+        if !self.0.allows("config:read") {
+            server.config.admin_password.clear();
+            server.config.server_password = None;
+        }
+        server
+    }
+
+    /// Status history cause and moderation log actor for what this token does.
+    fn cause(&self) -> String {
+        format!("HTTP API token \"{}\"", self.0.name)
+    }
+}
+
+impl FromRequestParts<AppState> for Auth {
+    type Rejection = ApiError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &AppState) -> Result<Self, Self::Rejection> {
+        let secret = parts
+            .headers
+            .get(header::AUTHORIZATION)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.strip_prefix("Bearer "))
+            .ok_or_else(|| ApiError::new(StatusCode::UNAUTHORIZED, "Missing bearer token"))?;
+        with_db(state, |conn| api_tokens::authenticate(conn, secret.trim()).map_err(|e| e.to_string()))?
+            .map(Auth)
+            .ok_or_else(|| ApiError::new(StatusCode::UNAUTHORIZED, "Invalid or revoked token"))
+    }
+}
+
+fn with_db<T>(state: &AppState, f: impl FnOnce(&Connection) -> Result<T, String>) -> ApiResult<T> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let conn = db.get_connection()?;
+    Ok(f(&conn)?)
+}
+
+fn load_server(state: &AppState, server_id: i64) -> ApiResult<Server> {
+    with_db(state, |conn| servers::load(conn, server_id).map_err(|e| e.to_string()))?
+        .ok_or_else(|| ApiError::not_found("Server", server_id))
+}
+
+async fn list_servers(auth: Auth, State(state): State<AppState>) -> ApiResult<Json<Vec<Server>>> {
+    auth.require("servers:read")?;
+    let list = with_db(&state, |conn| servers::list(conn).map_err(|e| e.to_string()))?;
+    Ok(Json(list.into_iter().map(|server| auth.redact(server)).collect()))
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct InstallRequest {
+    server_type: String,
+    install_path: String,
+    name: String,
+    map_name: String,
+    ports: ServerPorts,
+}
+
+async fn install_server(
+    auth: Auth,
+    State(state): State<AppState>,
+    Json(request): Json<InstallRequest>,
+) -> ApiResult<(StatusCode, Json<Server>)> {
+    auth.require("servers:control")?;
+    let installed = server::install(
+        &state,
+        request.server_type,
+        request.install_path,
+        request.name,
+        request.map_name,
+        request.ports,
+    )?;
+    Ok((StatusCode::ACCEPTED, Json(auth.redact(installed))))
+}
+
+async fn get_server(auth: Auth, State(state): State<AppState>, Path(id): Path<i64>) -> ApiResult<Json<Server>> {
+    auth.require("servers:read")?;
+    load_server(&state, id).map(|server| Json(auth.redact(server)))
+}
+
+async fn update_server_settings(
+    auth: Auth,
+    State(state): State<AppState>,
+    Path(id): Path<i64>,
+    Json(update): Json<ServerSettingsUpdate>,
+) -> ApiResult<Json<Server>> {
+    auth.require("config:write")?;
+    load_server(&state, id)?;
+    with_db(&state, |conn| servers::update_settings(conn, id, update)).map(|server| Json(auth.redact(server)))
+}
+
+#[derive(Deserialize)]
+struct HistoryQuery {
+    limit: Option<usize>,
+}
+
+async fn status_history(
+    auth: Auth,
+    State(state): State<AppState>,
+    Path(id): Path<i64>,
+    Query(query): Query<HistoryQuery>,
+) -> ApiResult<Json<Vec<StatusChange>>> {
+    auth.require("servers:read")?;
+    let limit = query.limit.unwrap_or(100);
+    with_db(&state, |conn| server_status::history(conn, id, limit).map_err(|e| e.to_string())).map(Json)
+}
+
+async fn start_server(auth: Auth, State(state): State<AppState>, Path(id): Path<i64>) -> ApiResult<StatusCode> {
+    auth.require("servers:control")?;
+    load_server(&state, id)?;
+    lifecycle::start_server(&state, id, &auth.cause()).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn stop_server(auth: Auth, State(state): State<AppState>, Path(id): Path<i64>) -> ApiResult<StatusCode> {
+    auth.require("servers:control")?;
+    load_server(&state, id)?;
+    lifecycle::stop_server(&state, id, &auth.cause()).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn restart_server(auth: Auth, State(state): State<AppState>, Path(id): Path<i64>) -> ApiResult<StatusCode> {
+    auth.require("servers:control")?;
+    load_server(&state, id)?;
+    lifecycle::restart_server(&state, id, &auth.cause()).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[derive(Deserialize)]
+struct UpdateQuery {
+    /// Warn players, update and start the server again
+    #[serde(default)]
+    restart: bool,
+}
+
+/// Runs in the background; `server-update-complete` reports the outcome.
+async fn update_server(
+    auth: Auth,
+    State(state): State<AppState>,
+    Path(id): Path<i64>,
+    Query(query): Query<UpdateQuery>,
+) -> ApiResult<(StatusCode, Json<Value>)> {
+    auth.require("servers:control")?;
+    load_server(&state, id)?;
+
+    let cause = auth.cause();
+    let background = state.clone();
+    tauri::async_runtime::spawn(async move {
+        let result = if query.restart {
+            ServerUpdateService::update_server_graceful(&background, id, &cause).await
+        } else {
+            lifecycle::update_server(&background, id, &cause).await
+        };
+        background.events.emit(
+            "server-update-complete",
+            json!({ "serverId": id, "success": result.is_ok(), "error": result.err() }),
+        );
+    });
+
+    Ok((
+        StatusCode::ACCEPTED,
+        Json(json!({ "serverId": id, "events": format!("/api/events?serverId={}", id) })),
+    ))
+}
+
+#[derive(Deserialize)]
+struct RconRequest {
+    command: String,
+}
+
+async fn send_rcon(
+    auth: Auth,
+    State(state): State<AppState>,
+    Path(id): Path<i64>,
+    Json(request): Json<RconRequest>,
+) -> ApiResult<Json<Value>> {
+    auth.require("rcon")?;
+    load_server(&state, id)?;
+    let response = rcon::send_to_server(&state, id, request.command).await?;
+    Ok(Json(json!({ "response": response })))
+}
+
+/// `?reason=` of a kick or unban; neither takes a body.
+#[derive(Deserialize)]
+struct ReasonRequest {
+    reason: Option<String>,
+}
+
+async fn kick_player(
+    auth: Auth,
+    State(state): State<AppState>,
+    Path((id, platform_id)): Path<(i64, String)>,
+    Query(request): Query<ReasonRequest>,
+) -> ApiResult<StatusCode> {
+    auth.require("rcon")?;
+    load_server(&state, id)?;
+    moderation::kick(&state, id, &platform_id, request.reason.as_deref(), &auth.cause()).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[derive(Deserialize)]
+struct BansQuery {
+    include_lifted: Option<bool>,
+}
+
+async fn list_bans(
+    auth: Auth,
+    State(state): State<AppState>,
+    Path(id): Path<i64>,
+    Query(query): Query<BansQuery>,
+) -> ApiResult<Json<Vec<Ban>>> {
+    auth.require("servers:read")?;
+    load_server(&state, id)?;
+    with_db(&state, |conn| moderation::list_bans(conn, id, query.include_lifted.unwrap_or(false))).map(Json)
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BanRequest {
+    platform_id: String,
+    player_name: Option<String>,
+    reason: Option<String>,
+    /// RFC 3339; a permanent ban without it
+    expires_at: Option<String>,
+}
+
+async fn ban_player(
+    auth: Auth,
+    State(state): State<AppState>,
+    Path(id): Path<i64>,
+    Json(request): Json<BanRequest>,
+) -> ApiResult<(StatusCode, Json<Ban>)> {
+    auth.require("rcon")?;
+    load_server(&state, id)?;
+    let ban = moderation::ban(
+        &state,
+        id,
+        &request.platform_id,
+        request.player_name.as_deref(),
+        request.reason.as_deref(),
+        request.expires_at.as_deref(),
+        &auth.cause(),
+    )
+    .await?;
+    Ok((StatusCode::CREATED, Json(ban)))
+}
+
+async fn unban_player(
+    auth: Auth,
+    State(state): State<AppState>,
+    Path((id, platform_id)): Path<(i64, String)>,
+    Query(request): Query<ReasonRequest>,
+) -> ApiResult<StatusCode> {
+    auth.require("rcon")?;
+    load_server(&state, id)?;
+    moderation::unban(&state, id, &platform_id, request.reason.as_deref(), &auth.cause()).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn list_backups(auth: Auth, State(state): State<AppState>, Path(id): Path<i64>) -> ApiResult<Json<Vec<Backup>>> {
+    auth.require("backups:read")?;
+    load_server(&state, id)?;
+    Ok(Json(backup::backups_for(&state, id)?))
+}
+
+#[derive(Deserialize)]
+struct BackupRequest {
+    note: Option<String>,
+}
+
+async fn create_backup(
+    auth: Auth,
+    State(state): State<AppState>,
+    Path(id): Path<i64>,
+    Json(request): Json<BackupRequest>,
+) -> ApiResult<(StatusCode, Json<Backup>)> {
+    auth.require("backups:write")?;
+    load_server(&state, id)?;
+    let created = backup::backup_server(&state, id, "manual".to_string(), request.note).await?;
+    Ok((StatusCode::CREATED, Json(created)))
+}
+
+async fn restore_backup(auth: Auth, State(state): State<AppState>, Path(id): Path<i64>) -> ApiResult<StatusCode> {
+    auth.require("backups:write")?;
+    backup::restore(&state, id)?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn list_schedules(auth: Auth, State(state): State<AppState>, Path(id): Path<i64>) -> ApiResult<Json<Vec<Schedule>>> {
+    auth.require("schedules:read")?;
+    load_server(&state, id)?;
+    Ok(Json(scheduler::schedules_for(&state, id)?))
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ScheduleRequest {
+    task_type: String,
+    cron_expression: String,
+    payload: Option<String>,
+}
+
+async fn create_schedule(
+    auth: Auth,
+    State(state): State<AppState>,
+    Path(id): Path<i64>,
+    Json(request): Json<ScheduleRequest>,
+) -> ApiResult<(StatusCode, Json<Value>)> {
+    auth.require("schedules:write")?;
+    load_server(&state, id)?;
+    let schedule_id = scheduler::add_schedule(
+        &state,
+        id,
+        &request.task_type,
+        &request.cron_expression,
+        request.payload.as_deref(),
+    )?;
+    Ok((StatusCode::CREATED, Json(json!({ "id": schedule_id }))))
+}
+
+#[derive(Deserialize)]
+struct ScheduleUpdate {
+    enabled: bool,
+}
+
+async fn update_schedule(
+    auth: Auth,
+    State(state): State<AppState>,
+    Path(id): Path<i64>,
+    Json(update): Json<ScheduleUpdate>,
+) -> ApiResult<StatusCode> {
+    auth.require("schedules:write")?;
+    scheduler::set_schedule_enabled(&state, id, update.enabled)?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn delete_schedule(auth: Auth, State(state): State<AppState>, Path(id): Path<i64>) -> ApiResult<StatusCode> {
+    auth.require("schedules:write")?;
+    scheduler::remove_schedule(&state, id)?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn list_mods(auth: Auth, State(state): State<AppState>, Path(id): Path<i64>) -> ApiResult<Json<Vec<ModInfo>>> {
+    auth.require("mods:read")?;
+    load_server(&state, id)?;
+    Ok(Json(mods::installed_mods(&state, id)?))
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ActiveModsRequest {
+    mod_ids: Vec<String>,
+}
+
+async fn set_active_mods(
+    auth: Auth,
+    State(state): State<AppState>,
+    Path(id): Path<i64>,
+    Json(request): Json<ActiveModsRequest>,
+) -> ApiResult<StatusCode> {
+    auth.require("mods:write")?;
+    load_server(&state, id)?;
+    mods::set_active_mods(&state, id, &request.mod_ids)?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn remove_mod(
+    auth: Auth,
+    State(state): State<AppState>,
+    Path((id, mod_id)): Path<(i64, String)>,
+) -> ApiResult<StatusCode> {
+    auth.require("mods:write")?;
+    load_server(&state, id)?;
+    mods::remove_mod(&state, id, &mod_id)?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// The INI as plain text; empty if the server hasn't written it yet.
+async fn read_config(
+    auth: Auth,
+    State(state): State<AppState>,
+    Path((id, file)): Path<(i64, String)>,
+) -> ApiResult<String> {
+    auth.require("config:read")?;
+    load_server(&state, id)?;
+    Ok(config::read(&state, id, &file)?)
+}
+
+async fn save_config(
+    auth: Auth,
+    State(state): State<AppState>,
+    Path((id, file)): Path<(i64, String)>,
+    content: String,
+) -> ApiResult<StatusCode> {
+    auth.require("config:write")?;
+    load_server(&state, id)?;
+    config::save(&state, id, &file, &content)?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct EventsQuery {
+    server_id: Option<i64>,
+}
+
+/// Server events use `serverId`, status changes `id`.
+fn event_server_id(event: &Event) -> Option<i64> {
+    event
+        .payload
+        .get("serverId")
+        .or_else(|| event.payload.get("id"))
+        .and_then(Value::as_i64)
+}
+
+/// The same events the app receives, as server-sent events named after them.
+async fn events(
+    auth: Auth,
+    State(state): State<AppState>,
+    Query(query): Query<EventsQuery>,
+) -> ApiResult<Sse<impl Stream<Item = Result<SseEvent, Infallible>>>> {
+    auth.require("servers:read")?;
+    let server_id = query.server_id;
+
+    let stream = futures_util::stream::unfold(state.events.subscribe(), move |mut receiver| async move {
+        loop {
+            match receiver.recv().await {
+                Ok(event) => {
+                    if server_id.is_some_and(|id| event_server_id(&event) != Some(id)) {
+                        continue;
+                    }
+                    let sse = SseEvent::default()
+                        .event(&event.name)
+                        .json_data(&event.payload)
+                        .unwrap_or_default();
+                    return Some((Ok(sse), receiver));
+                }
+                Err(RecvError::Lagged(_)) => continue,
+                Err(RecvError::Closed) => return None,
+            }
+        }
+    });
+
+    Ok(Sse::new(stream).keep_alive(KeepAlive::default()))
+}
+
+/// Prometheus text format, for scrapers configured with a bearer token.
+async fn metrics(auth: Auth, State(state): State<AppState>) -> ApiResult<Response> {
+    auth.require("metrics")?;
+    let body = metrics::render(&state)
+        .await
+        .map_err(|e| ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, e))?;
+    Ok(([(header::CONTENT_TYPE, "text/plain; version=0.0.4")], body).into_response())
+}
+
+fn router(state: AppState) -> Router {
+    Router::new()
+        .route("/api/servers", get(list_servers).post(install_server))
+        .route("/api/servers/{id}", get(get_server).patch(update_server_settings))
+        .route("/api/servers/{id}/status-history", get(status_history))
+        .route("/api/servers/{id}/start", post(start_server))
+        .route("/api/servers/{id}/stop", post(stop_server))
+        .route("/api/servers/{id}/restart", post(restart_server))
+        .route("/api/servers/{id}/update", post(update_server))
+        .route("/api/servers/{id}/rcon", post(send_rcon))
+        .route("/api/servers/{id}/players/{platform_id}/kick", post(kick_player))
+        .route("/api/servers/{id}/bans", get(list_bans).post(ban_player))
+        .route("/api/servers/{id}/bans/{platform_id}", delete(unban_player))
+        .route("/api/servers/{id}/backups", get(list_backups).post(create_backup))
+        .route("/api/backups/{id}/restore", post(restore_backup))
+        .route("/api/servers/{id}/schedules", get(list_schedules).post(create_schedule))
+        .route("/api/schedules/{id}", delete(delete_schedule).patch(update_schedule))
+        .route("/api/servers/{id}/mods", get(list_mods).put(set_active_mods))
+        .route("/api/servers/{id}/mods/{mod_id}", delete(remove_mod))
+        .route("/api/servers/{id}/config/{file}", get(read_config).put(save_config))
+        .route("/api/events", get(events))
+        .route("/metrics", get(metrics))
+        .with_state(state)
+}
+
+/// The address from settings if the API is enabled there.
+pub fn configured_bind(state: &AppState) -> Option<String> {
+    let db = state.db.lock().ok()?;
+    if db.get_setting("http_api_enabled").ok().flatten().as_deref() != Some("true") {
+        return None;
+    }
+    let bind = db.get_setting("http_api_bind").ok().flatten().filter(|b| !b.trim().is_empty());
+    Some(bind.unwrap_or_else(|| DEFAULT_BIND.to_string()))
+}
+
+/// Serve the API on `bind` in the background.
+pub fn spawn(state: AppState, bind: String) {
+    tauri::async_runtime::spawn(async move {
+        let listener = match tokio::net::TcpListener::bind(&bind).await {
+            Ok(listener) => listener,
+            Err(e) => {
+                eprintln!("Failed to start the HTTP API on {}: {}", bind, e);
+                return;
+            }
+        };
+        println!("HTTP API listening on {}", bind);
+        if let Err(e) = axum::serve(listener, router(state)).await {
+            eprintln!("HTTP API stopped: {}", e);
+        }
+    });
+}
@@ -0,0 +1,335 @@
+use crate::models::{LaunchEnvVar, LaunchOption, LaunchPreview, LaunchProfile, RunnerConfig};
+use crate::services::runner::{self, WrappedCommand};
+use crate::services::platform;
+use anyhow::Result;
+use rusqlite::Connection;
+use std::path::{Path, PathBuf};
+
+/// Everything needed to launch one server, as stored in its row.
+#[derive(Debug, Clone)]
+pub struct LaunchSpec {
+    pub server_id: i64,
+    pub server_type: String,
+    pub install_path: PathBuf,
+    pub map_name: String,
+    pub session_name: String,
+    pub game_port: u16,
+    pub query_port: u16,
+    pub rcon_port: u16,
+    pub max_players: i32,
+    pub server_password: Option<String>,
+    pub admin_password: String,
+    pub battleye_enabled: bool,
+    pub multihome_ip: Option<String>,
+    pub crossplay_enabled: bool,
+    pub runner: RunnerConfig,
+    pub profile: LaunchProfile,
+}
+
+impl LaunchSpec {
+    pub fn load(conn: &Connection, server_id: i64) -> rusqlite::Result<Self> {
+        let runner = runner::load_runner_config(conn, server_id)?;
+
+        conn.query_row(
+            "SELECT server_type, install_path, map_name, session_name, game_port, query_port, rcon_port,
+             max_players, server_password, admin_password, battleye_enabled, multihome_ip, crossplay_enabled,
+             launch_profile FROM servers WHERE id = ?1",
+            [server_id],
+            |row| Ok(LaunchSpec {
+                server_id,
+                server_type: row.get(0)?,
+                install_path: PathBuf::from(row.get::<_, String>(1)?),
+                map_name: row.get(2)?,
+                session_name: row.get(3)?,
+                game_port: row.get(4)?,
+                query_port: row.get(5)?,
+                rcon_port: row.get(6)?,
+                max_players: row.get(7)?,
+                server_password: row.get(8)?,
+                admin_password: row.get(9)?,
+                battleye_enabled: row.get::<_, bool>(10).unwrap_or(false),
+                multihome_ip: row.get(11)?,
+                crossplay_enabled: row.get::<_, bool>(12).unwrap_or(false),
+                runner,
+                profile: parse_profile(row.get(13)?),
+            }),
+        )
+    }
+
+    /// `?`-options derived from the server's own settings, in launch order.
+    fn builtin_options(&self) -> Vec<LaunchOption> {
+        let option = |key: &str, value: String| LaunchOption { key: key.to_string(), value: Some(value) };
+
+        let mut options = vec![
+            LaunchOption { key: "listen".to_string(), value: None },
+            option("SessionName", self.session_name.clone()),
+            option("Port", self.game_port.to_string()),
+            option("QueryPort", self.query_port.to_string()),
+            option("RCONEnabled", "True".to_string()),
+            option("RCONPort", self.rcon_port.to_string()),
+            option("MaxPlayers", self.max_players.to_string()),
+        ];
+
+        if let Some(password) = self.server_password.as_deref().filter(|p| !p.is_empty()) {
+            options.push(option("ServerPassword", password.to_string()));
+        }
+        options.push(option("ServerAdminPassword", self.admin_password.clone()));
+
+        if let Some(ip) = self.multihome_ip.as_deref().filter(|ip| !ip.is_empty()) {
+            options.push(option("MultiHome", ip.to_string()));
+        }
+
+        options
+    }
+
+    /// Dash flags derived from the server's own settings.
+    fn builtin_flags(&self) -> Vec<String> {
+        let mut flags = vec!["-log".to_string()];
+
+        if !self.battleye_enabled {
+            flags.push("-NoBattlEye".to_string());
+        }
+
+        // ASA has crossplay on by default; ASE needs it asked for
+        if self.crossplay_enabled && self.server_type == "ASE" {
+            flags.push("-crossplay".to_string());
+        }
+
+        flags
+    }
+
+    /// Server arguments: one `Map?listen?Key=Value...` token followed by the flags.
+    pub fn args(&self) -> Vec<String> {
+        let mut map_arg = self.map_name.clone();
+        for option in merge_options(self.builtin_options(), &self.profile.options) {
+            map_arg.push('?');
+            map_arg.push_str(&option.key);
+            if let Some(value) = option.value {
+                map_arg.push('=');
+                map_arg.push_str(&value);
+            }
+        }
+
+        let mut args = vec![map_arg];
+        args.extend(merge_flags(self.builtin_flags(), &self.profile.flags));
+        args
+    }
+
+    /// Compat prefix for Wine/Proton runners.
+    pub fn prefix(&self, app_data_dir: &Path) -> PathBuf {
+        runner::resolve_prefix(&self.runner, app_data_dir, self.server_id)
+    }
+
+    pub fn working_dir(&self) -> PathBuf {
+        platform::binaries_dir(&self.install_path, &self.server_type)
+    }
+
+    /// The command to spawn: server executable and arguments, wrapped in the
+    /// runner, with the profile's environment on top of the runner's.
+    pub fn build(&self, app_data_dir: &Path) -> Result<WrappedCommand> {
+        let executable = platform::server_executable(&self.install_path, &self.server_type)?;
+
+        let mut wrapped = runner::wrap(
+            &self.runner,
+            &executable,
+            self.args(),
+            &self.prefix(app_data_dir),
+            &app_data_dir.join("steamcmd"),
+        )?;
+
+        for var in &self.profile.env {
+            wrapped.env.retain(|(name, _)| name != &var.name);
+            wrapped.env.push((var.name.clone(), var.value.clone()));
+        }
+
+        Ok(wrapped)
+    }
+
+    pub fn preview(&self, app_data_dir: &Path) -> Result<LaunchPreview> {
+        let wrapped = self.build(app_data_dir)?;
+        let program = wrapped.program.to_string_lossy().to_string();
+
+        let command_line = std::iter::once(program.as_str())
+            .chain(wrapped.args.iter().map(String::as_str))
+            .map(quote_arg)
+            .collect::<Vec<_>>()
+            .join(" ");
+
+        Ok(LaunchPreview {
+            program,
+            args: wrapped.args,
+            env: wrapped
+                .env
+                .into_iter()
+                .map(|(name, value)| LaunchEnvVar { name, value })
+                .collect(),
+            working_dir: self.working_dir().to_string_lossy().to_string(),
+            command_line,
+        })
+    }
+}
+
+/// Read a stored profile; a missing or unreadable one is treated as empty.
+pub fn parse_profile(json: Option<String>) -> LaunchProfile {
+    json.and_then(|j| serde_json::from_str(&j).ok())
+        .unwrap_or_default()
+}
+
+/// Check a profile before it is saved, so bad entries can't corrupt the
+/// map token or the environment.
+pub fn validate_profile(profile: &LaunchProfile) -> Result<(), String> {
+    for option in &profile.options {
+        let key = option.key.trim();
+        if key.is_empty() || key.contains(['?', '=', ' ', '"']) {
+            return Err(format!("Invalid launch option name: '{}'", option.key));
+        }
+        if option.value.as_deref().is_some_and(|v| v.contains('?')) {
+            return Err(format!("Value of launch option {} can't contain '?'", key));
+        }
+    }
+
+    for flag in &profile.flags {
+        let name = flag.trim().trim_start_matches('-');
+        if name.is_empty() || flag.trim().contains(char::is_whitespace) {
+            return Err(format!("Invalid launch flag: '{}'", flag));
+        }
+    }
+
+    for var in &profile.env {
+        if var.name.is_empty() || var.name.contains(['=', '\0']) || var.name.contains(char::is_whitespace) {
+            return Err(format!("Invalid environment variable name: '{}'", var.name));
+        }
+    }
+
+    Ok(())
+}
+
+/// Profile options replace built-in ones with the same key (ARK keys are
+/// case-insensitive) where they stand; new keys go at the end.
+fn merge_options(mut options: Vec<LaunchOption>, overrides: &[LaunchOption]) -> Vec<LaunchOption> {
+    for entry in overrides {
+        let entry = LaunchOption {
+            key: entry.key.trim().to_string(),
+            value: entry.value.clone(),
+        };
+        match options.iter_mut().find(|o| o.key.eq_ignore_ascii_case(&entry.key)) {
+            Some(existing) => *existing = entry,
+            None => options.push(entry),
+        }
+    }
+    options
+}
+
+/// Same for flags, matched on the name before any `=`.
+fn merge_flags(mut flags: Vec<String>, overrides: &[String]) -> Vec<String> {
+    fn name(flag: &str) -> String {
+        flag.trim_start_matches('-')
+            .split('=')
+            .next()
+            .unwrap_or_default()
+            .to_ascii_lowercase()
+    }
+
+    for flag in overrides {
+        let flag = flag.trim();
+        let flag = if flag.starts_with('-') { flag.to_string() } else { format!("-{}", flag) };
+        match flags.iter_mut().find(|f| name(f) == name(&flag)) {
+            Some(existing) => *existing = flag,
+            None => flags.push(flag),
+        }
+    }
+    flags
+}
+
+fn quote_arg(arg: &str) -> String {
+    if arg.is_empty() || arg.contains(char::is_whitespace) || arg.contains('"') {
+        format!("\"{}\"", arg.replace('"', "\\\""))
+    } else {
+        arg.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spec(profile: LaunchProfile) -> LaunchSpec {
+        LaunchSpec {
+            server_id: 1,
+            server_type: "ASE".to_string(),
+            install_path: PathBuf::from("/srv/ark"),
+            map_name: "TheIsland".to_string(),
+            session_name: "My Server".to_string(),
+            game_port: 7777,
+            query_port: 27015,
+            rcon_port: 27020,
+            max_players: 70,
+            server_password: None,
+            admin_password: "secret".to_string(),
+            battleye_enabled: false,
+            multihome_ip: None,
+            crossplay_enabled: false,
+            runner: RunnerConfig::default(),
+            profile,
+        }
+    }
+
+    fn option(key: &str, value: Option<&str>) -> LaunchOption {
+        LaunchOption { key: key.to_string(), value: value.map(str::to_string) }
+    }
+
+    #[test]
+    fn profile_options_replace_builtins_in_place() {
+        let spec = spec(LaunchProfile {
+            options: vec![option("maxplayers", Some("20")), option("AllowFlyerCarryPvE", Some("True")), option("NoTributeDownloads", None)],
+            ..Default::default()
+        });
+        assert_eq!(
+            spec.args()[0],
+            "TheIsland?listen?SessionName=My Server?Port=7777?QueryPort=27015?RCONEnabled=True?RCONPort=27020\
+             ?maxplayers=20?ServerAdminPassword=secret?AllowFlyerCarryPvE=True?NoTributeDownloads"
+        );
+    }
+
+    #[test]
+    fn duplicate_flags_collapse_case_insensitively() {
+        let spec = spec(LaunchProfile {
+            flags: vec!["-LOG".to_string(), "clusterid=a".to_string(), "-ClusterID=b".to_string()],
+            ..Default::default()
+        });
+        assert_eq!(spec.args()[1..], ["-LOG", "-NoBattlEye", "-ClusterID=b"]);
+    }
+
+    #[test]
+    fn preview_quotes_arguments_with_spaces() {
+        let preview = spec(LaunchProfile::default()).preview(Path::new("/data")).unwrap();
+        assert_eq!(preview.args, spec(LaunchProfile::default()).args());
+        assert!(preview.command_line.ends_with(
+            " \"TheIsland?listen?SessionName=My Server?Port=7777?QueryPort=27015?RCONEnabled=True?RCONPort=27020\
+             ?MaxPlayers=70?ServerAdminPassword=secret\" -log -NoBattlEye"
+        ));
+    }
+
+    #[test]
+    fn rejects_entries_that_would_break_the_command() {
+        let options = |o: LaunchOption| LaunchProfile { options: vec![o], ..Default::default() };
+        assert!(validate_profile(&options(option("", Some("x")))).is_err());
+        assert!(validate_profile(&options(option("Key?Other", None))).is_err());
+        assert!(validate_profile(&options(option("Key=1", None))).is_err());
+        assert!(validate_profile(&options(option("Key", Some("1?Other=2")))).is_err());
+        assert!(validate_profile(&options(option("Key", Some("1")))).is_ok());
+
+        let flags = |f: &str| LaunchProfile { flags: vec![f.to_string()], ..Default::default() };
+        assert!(validate_profile(&flags("--")).is_err());
+        assert!(validate_profile(&flags("-a -b")).is_err());
+        assert!(validate_profile(&flags("-ForceAllowCaveFlyers")).is_ok());
+
+        let env = |name: &str| LaunchProfile {
+            env: vec![LaunchEnvVar { name: name.to_string(), value: "1".to_string() }],
+            ..Default::default()
+        };
+        assert!(validate_profile(&env("A=B")).is_err());
+        assert!(validate_profile(&env("A B")).is_err());
+        assert!(validate_profile(&env("WINEDEBUG")).is_ok());
+    }
+}
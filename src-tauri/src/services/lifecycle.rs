@@ -0,0 +1,276 @@
+use crate::models::{ServerPorts, ServerStatus, WebhookEvent};
+use crate::services::graceful_stop::{GracefulStopService, StopOptions, StopOutcome};
+use crate::services::launch::LaunchSpec;
+use crate::services::notifications::NotificationService;
+use crate::services::port_allocator::{self, CheckScope};
+use crate::services::{process_manager, readiness, server_clone, server_status, webhooks};
+use crate::services::steamcmd::SteamCmdService;
+use crate::AppState;
+use std::path::PathBuf;
+
+// Server lifecycle operations shared by the Tauri commands, the scheduler,
+// the monitor, the Discord bot and the daemon. Everything here works from
+// `AppState` alone so it runs the same with or without a window. `cause`
+// says who asked, for the server's status history.
+
+pub async fn start_server(state: &AppState, server_id: i64, cause: &str) -> Result<(), String> {
+    // Claimed before anything else, so a second start fails here instead of
+    // spawning another process; put back if the server doesn't get spawned
+    let previous = {
+        let db = state.db.lock().map_err(|e| e.to_string())?;
+        let conn = db.get_connection().map_err(|e| e.to_string())?;
+        server_status::claim(&conn, server_id, ServerStatus::Starting, cause)?
+    };
+
+    let pid = match spawn_server(state, server_id) {
+        Ok(pid) => pid,
+        Err(e) => {
+            let db = state.db.lock().map_err(|e| e.to_string())?;
+            let conn = db.get_connection().map_err(|e| e.to_string())?;
+            server_status::transition_from(
+                &conn,
+                server_id,
+                &[ServerStatus::Starting],
+                previous,
+                &format!("start failed: {}", e),
+            )?;
+            return Err(e);
+        }
+    };
+
+    // Loading takes minutes; the server stays `starting` until its readiness probe passes
+    {
+        let db = state.db.lock().map_err(|e| e.to_string())?;
+        let conn = db.get_connection().map_err(|e| e.to_string())?;
+        process_manager::record_server_pid(&conn, server_id, pid).map_err(|e| e.to_string())?;
+    }
+    state.events.emit("server-status-changed", serde_json::json!({
+        "id": server_id,
+        "status": "starting"
+    }));
+    readiness::spawn(state.clone(), server_id, pid, false);
+
+    Ok(())
+}
+
+/// Check the ports and spawn the server process of a server claimed as `Starting`.
+fn spawn_server(state: &AppState, server_id: i64) -> Result<u32, String> {
+    // Get server details from database
+    let spec = {
+        let db = state.db.lock().map_err(|e| e.to_string())?;
+        let conn = db.get_connection().map_err(|e| e.to_string())?;
+        let spec = LaunchSpec::load(&conn, server_id).map_err(|e| e.to_string())?;
+
+        // Refuse to start into ports a running server or another program holds
+        let ports = ServerPorts { game_port: spec.game_port, query_port: spec.query_port, rcon_port: spec.rcon_port };
+        let multihome_ip = spec.multihome_ip.as_deref();
+        let conflicts = port_allocator::find_conflicts(&conn, Some(server_id), &ports, multihome_ip, CheckScope::ActiveServers)
+            .map_err(|e| e.to_string())?;
+        if !conflicts.is_empty() {
+            return Err(port_allocator::describe(&conn, &conflicts, multihome_ip));
+        }
+        spec
+    };
+
+    // SYNC: Write settings to GameUserSettings.ini before starting
+    if let Err(e) = sync_settings_to_ini(
+        &spec.install_path, 
+        &spec.session_name, 
+        &spec.map_name, // Map name usually goes to start args but sometimes INI
+        spec.server_password.as_deref(), 
+        &spec.admin_password, 
+        spec.max_players
+    ) {
+        println!("Warning: Failed to sync INI settings: {}", e);
+        // We continue anyway because command line args will override, but logging it is good
+    }
+
+    state.process_manager
+        .start_server(&state.events, &state.data_dir, &spec)
+        .map_err(|e| e.to_string())
+}
+
+fn sync_settings_to_ini(
+    install_path: &PathBuf,
+    session_name: &str,
+    _map_name: &str,
+    server_password: Option<&str>,
+    admin_password: &str,
+    max_players: i32,
+) -> Result<(), String> {
+    /*
+    let config_path = platform::config_dir(install_path, server_type).join("GameUserSettings.ini");
+    
+    // Create parent dirs if missing
+    if let Some(parent) = config_path.parent() {
+        let _ = std::fs::create_dir_all(parent);
+    }
+
+    // Load existing or create new
+    let mut conf = if config_path.exists() {
+        ini::Ini::load_from_file(&config_path).map_err(|e| e.to_string())?
+    } else {
+        ini::Ini::new()
+    };
+
+    // Update [ServerSettings]
+    conf.with_section(Some("ServerSettings"))
+        .set("SessionName", session_name)
+        .set("ServerAdminPassword", admin_password)
+        .set("MaxPlayers", max_players.to_string());
+    
+    if let Some(pwd) = server_password {
+        conf.with_section(Some("ServerSettings"))
+            .set("ServerPassword", pwd);
+    }
+
+    // Write back
+    conf.write_to_file(&config_path).map_err(|e| e.to_string())?;
+    */
+    println!("TODO: INI Sync temporarily disabled due to dependency issue");
+    Ok(())
+}
+
+pub async fn stop_server(state: &AppState, server_id: i64, cause: &str) -> Result<(), String> {
+    let options = {
+        let db = state.db.lock().map_err(|e| e.to_string())?;
+        StopOptions::from_settings(&db)
+    };
+
+    let outcome = GracefulStopService::stop(state, server_id, &options, cause).await?;
+    if outcome == StopOutcome::Killed {
+        println!("Server {} did not shut down cleanly and was killed", server_id);
+    }
+    mark_stopped(state, server_id, cause)?;
+    webhooks::fire(state, WebhookEvent::Stop, server_id, serde_json::json!({
+        "cause": cause,
+        "killed": outcome == StopOutcome::Killed
+    }));
+    
+    let _ = NotificationService::send_notification(state, "Server Stopped", &format!("Server {} has been stopped.", server_id)).await;
+
+    Ok(())
+}
+
+pub async fn restart_server(state: &AppState, server_id: i64, cause: &str) -> Result<(), String> {
+    let options = {
+        let db = state.db.lock().map_err(|e| e.to_string())?;
+        StopOptions::from_settings(&db)
+    };
+
+    GracefulStopService::stop(state, server_id, &options, cause).await?;
+    mark_stopped(state, server_id, cause)?;
+
+    start_server(state, server_id, cause).await?;
+    
+    let _ = NotificationService::send_notification(state, "Server Restarted", &format!("Server {} has been restarted.", server_id)).await;
+
+    Ok(())
+}
+
+/// Stop the server if needed and update its files through SteamCMD. The
+/// server is left stopped.
+pub async fn update_server(state: &AppState, server_id: i64, cause: &str) -> Result<(), String> {
+    // 1. Get server details
+    let (server_type, install_path, current_status) = {
+        let db = state.db.lock().map_err(|e| e.to_string())?;
+        let conn = db.get_connection().map_err(|e| e.to_string())?;
+        server_clone::ensure_own_install(&conn, server_id)?;
+        let mut stmt = conn.prepare("SELECT server_type, install_path FROM servers WHERE id = ?1").map_err(|e| e.to_string())?;
+        
+        let (server_type, install_path) = stmt.query_row([server_id], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+            ))
+        }).map_err(|e| e.to_string())?;
+        (server_type, install_path, server_status::current(&conn, server_id)?)
+    };
+
+    // 2. Stop server if running
+    if matches!(current_status, ServerStatus::Running | ServerStatus::Starting) {
+        let options = {
+            let db = state.db.lock().map_err(|e| e.to_string())?;
+            StopOptions::from_settings(&db)
+        };
+        GracefulStopService::stop(state, server_id, &options, cause).await?;
+        mark_stopped(state, server_id, cause)?;
+    }
+
+    // 3. Claim `updating`; of two updates racing here only one runs SteamCMD
+    let previous = {
+        let db = state.db.lock().map_err(|e| e.to_string())?;
+        let conn = db.get_connection().map_err(|e| e.to_string())?;
+        server_status::claim(&conn, server_id, ServerStatus::Updating, cause)?
+    };
+
+    // 4. Run SteamCMD Update. The service spawns SteamCMD and waits for it,
+    // so keep that off the async workers.
+    let service = SteamCmdService::new(state);
+    let path = PathBuf::from(&install_path);
+    
+    let result = match tauri::async_runtime::spawn_blocking(move || {
+        service.install_server(&server_type, &path)
+    }).await {
+        Ok(result) => result,
+        Err(e) => {
+            release_update(state, server_id, previous, &e.to_string())?;
+            return Err(e.to_string());
+        }
+    };
+
+    // 5. Handle Result
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let conn = db.get_connection().map_err(|e| e.to_string())?;
+    
+    match result {
+        Ok(_) => {
+            server_status::transition(&conn, server_id, ServerStatus::Stopped, "update finished")?;
+            drop(conn);
+            drop(db);
+            webhooks::fire(state, WebhookEvent::Update, server_id, serde_json::json!({ "success": true }));
+            Ok(())
+        }
+        Err(e) => {
+            server_status::transition(&conn, server_id, ServerStatus::Stopped, &format!("update failed: {}", e))?;
+            drop(conn);
+            drop(db);
+            webhooks::fire(state, WebhookEvent::Update, server_id, serde_json::json!({
+                "success": false,
+                "error": e.to_string()
+            }));
+            Err(e.to_string())
+        }
+    }
+}
+
+/// Put back the status an update claimed when SteamCMD never got to run.
+pub fn release_update(state: &AppState, server_id: i64, previous: ServerStatus, error: &str) -> Result<(), String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let conn = db.get_connection().map_err(|e| e.to_string())?;
+    server_status::transition_from(
+        &conn,
+        server_id,
+        &[ServerStatus::Updating],
+        previous,
+        &format!("update failed: {}", error),
+    )?;
+    Ok(())
+}
+
+/// After a stop: `stopped`, unless the monitor already recorded how the
+/// process ended or the server wasn't running at all.
+pub fn mark_stopped(state: &AppState, server_id: i64, cause: &str) -> Result<(), String> {
+    state.rcon.close(server_id);
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let conn = db.get_connection().map_err(|e| e.to_string())?;
+    server_status::transition_from(
+        &conn,
+        server_id,
+        &[ServerStatus::Starting, ServerStatus::Running, ServerStatus::Stopping, ServerStatus::Failed],
+        ServerStatus::Stopped,
+        cause,
+    )?;
+    process_manager::clear_server_pid(&conn, server_id).map_err(|e| e.to_string())?;
+    Ok(())
+}
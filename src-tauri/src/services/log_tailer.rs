@@ -0,0 +1,71 @@
+use std::fs::File;
+use std::io::{BufRead, BufReader, Seek, SeekFrom};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use crate::services::events::EventBus;
+
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Follows a server log file and forwards new lines as `server-console-output`
+/// events, for servers whose stdout we can't read (Wine/Proton wrappers).
+pub struct LogTailer;
+
+impl LogTailer {
+    /// Tail `path` until `stop` is set. Only lines written after the call are
+    /// forwarded; a truncated or rotated file is followed from its start.
+    pub fn spawn(events: EventBus, server_id: i64, path: PathBuf, stop: Arc<AtomicBool>) {
+        tauri::async_runtime::spawn_blocking(move || {
+            let mut position = std::fs::metadata(&path).map(|m| m.len()).unwrap_or(0);
+            let mut pending = String::new();
+
+            while !stop.load(Ordering::Relaxed) {
+                std::thread::sleep(POLL_INTERVAL);
+
+                let len = match std::fs::metadata(&path) {
+                    Ok(m) => m.len(),
+                    Err(_) => continue, // Not created yet, or mid-rotation
+                };
+
+                if len < position {
+                    // ARK moves the old log aside on boot and starts a fresh file
+                    position = 0;
+                    pending.clear();
+                }
+                if len == position {
+                    continue;
+                }
+
+                let Ok(mut file) = File::open(&path) else { continue };
+                if file.seek(SeekFrom::Start(position)).is_err() {
+                    continue;
+                }
+
+                let mut reader = BufReader::new(file);
+                let mut buf = Vec::new();
+                loop {
+                    buf.clear();
+                    match reader.read_until(b'\n', &mut buf) {
+                        Ok(0) | Err(_) => break,
+                        Ok(n) => {
+                            position += n as u64;
+                            pending.push_str(&String::from_utf8_lossy(&buf));
+                            // Hold back a partial line until the rest is written
+                            if !pending.ends_with('\n') {
+                                continue;
+                            }
+                            let line = pending.trim_end_matches(['\r', '\n']).to_string();
+                            pending.clear();
+                            events.emit("server-console-output", serde_json::json!({
+                                "serverId": server_id,
+                                "line": line,
+                                "type": "log"
+                            }));
+                        }
+                    }
+                }
+            }
+        });
+    }
+}
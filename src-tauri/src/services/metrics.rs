@@ -0,0 +1,322 @@
+use crate::models::{Server, ServerStatus};
+use crate::services::rcon_client::RconClient;
+use crate::services::rcon_sessions::RconTarget;
+use crate::services::{server_import, servers};
+use crate::AppState;
+use rusqlite::Connection;
+use std::collections::HashSet;
+use std::fmt::Write;
+use std::time::Duration;
+use sysinfo::{Pid, ProcessesToUpdate, System};
+
+// Prometheus text exposition of every server and the host, for `/metrics`.
+// Everything is gathered when scraped; CPU usage is averaged over the time
+// since the previous scrape.
+
+/// How long a scrape waits for a server to answer `ListPlayers`.
+const RCON_TIMEOUT: Duration = Duration::from_secs(3);
+
+const STATUSES: [ServerStatus; 10] = [
+    ServerStatus::Stopped,
+    ServerStatus::Installing,
+    ServerStatus::Starting,
+    ServerStatus::Running,
+    ServerStatus::Stopping,
+    ServerStatus::Crashed,
+    ServerStatus::Updating,
+    ServerStatus::Failed,
+    ServerStatus::CrashLooping,
+    ServerStatus::Maintenance,
+];
+
+#[derive(Default)]
+struct ProcessStats {
+    cpu_percent: f64,
+    rss_bytes: u64,
+    uptime_seconds: u64,
+}
+
+struct ServerMetrics {
+    server: Server,
+    players: Option<usize>,
+    process: Option<ProcessStats>,
+    /// Start time recorded with the server's PID
+    pid_start_time: Option<u64>,
+    starts: i64,
+    crashes: i64,
+    /// Age in seconds and size in bytes of the newest backup
+    backup: Option<(i64, i64)>,
+    build_id: Option<u64>,
+}
+
+impl ServerMetrics {
+    fn labels(&self) -> String {
+        format!("server_id=\"{}\",server=\"{}\"", self.server.id, escape(&self.server.name))
+    }
+}
+
+fn escape(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
+
+/// One metric family: its HELP and TYPE lines followed by the samples.
+fn family(out: &mut String, name: &str, kind: &str, help: &str, samples: impl IntoIterator<Item = (String, f64)>) {
+    let _ = writeln!(out, "# HELP {} {}", name, help);
+    let _ = writeln!(out, "# TYPE {} {}", name, kind);
+    for (labels, value) in samples {
+        if labels.is_empty() {
+            let _ = writeln!(out, "{} {}", name, value);
+        } else {
+            let _ = writeln!(out, "{}{{{}}} {}", name, labels, value);
+        }
+    }
+}
+
+/// CPU, RSS and uptime of the server's process and everything it spawned,
+/// which under Wine or Proton is where the server itself runs.
+fn process_stats(sys: &System, root: u32, start_time: Option<u64>) -> Option<ProcessStats> {
+    let root = sys.process(Pid::from_u32(root))?;
+    // The PID may since have been reused by something else
+    if start_time.is_some_and(|start_time| root.start_time() != start_time) {
+        return None;
+    }
+    let mut stats = ProcessStats { uptime_seconds: root.run_time(), ..Default::default() };
+
+    let mut seen = HashSet::new();
+    let mut frontier = vec![root.pid()];
+    while let Some(pid) = frontier.pop() {
+        if !seen.insert(pid) {
+            continue;
+        }
+        if let Some(process) = sys.process(pid) {
+            stats.cpu_percent += process.cpu_usage() as f64;
+            stats.rss_bytes += process.memory();
+        }
+        frontier.extend(
+            sys.processes()
+                .iter()
+                .filter(|(_, p)| p.parent() == Some(pid))
+                .map(|(child, _)| *child),
+        );
+    }
+    Some(stats)
+}
+
+fn history_counts(conn: &Connection, server_id: i64) -> rusqlite::Result<(i64, i64)> {
+    conn.query_row(
+        "SELECT COUNT(*) FILTER (WHERE to_status = 'starting'), COUNT(*) FILTER (WHERE to_status = 'crashed')
+         FROM server_status_history WHERE server_id = ?1",
+        [server_id],
+        |row| Ok((row.get(0)?, row.get(1)?)),
+    )
+}
+
+fn latest_backup(conn: &Connection, server_id: i64) -> Option<(i64, i64)> {
+    let (created_at, size): (String, i64) = conn
+        .query_row(
+            "SELECT created_at, size FROM backups WHERE server_id = ?1 ORDER BY created_at DESC LIMIT 1",
+            [server_id],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .ok()?;
+    let created_at = chrono::DateTime::parse_from_rfc3339(&created_at).ok()?;
+    Some(((chrono::Utc::now() - created_at.to_utc()).num_seconds(), size))
+}
+
+async fn player_count(state: &AppState, server: &Server) -> Option<usize> {
+    let target = RconTarget::for_server(server);
+    let request = state.rcon.execute(server.id, &target, "ListPlayers");
+    let response = tokio::time::timeout(RCON_TIMEOUT, request).await.ok()?.ok()?;
+    Some(RconClient::parse_players(&response).len())
+}
+
+/// The whole exposition, one family after another.
+pub async fn render(state: &AppState) -> Result<String, String> {
+    let mut collected = {
+        let db = state.db.lock().map_err(|e| e.to_string())?;
+        let conn = db.get_connection()?;
+        servers::list(&conn)
+            .map_err(|e| e.to_string())?
+            .into_iter()
+            .map(|server| {
+                let (starts, crashes) = history_counts(&conn, server.id).unwrap_or_default();
+                ServerMetrics {
+                    backup: latest_backup(&conn, server.id),
+                    build_id: server_import::detect_server_type(&server.install_path)
+                        .and_then(|(_, build_id)| build_id?.parse().ok()),
+                    players: None,
+                    process: None,
+                    pid_start_time: conn
+                        .query_row("SELECT pid_start_time FROM servers WHERE id = ?1", [server.id], |row| row.get(0))
+                        .unwrap_or(None),
+                    starts,
+                    crashes,
+                    server,
+                }
+            })
+            .collect::<Vec<_>>()
+    };
+
+    let players = futures_util::future::join_all(collected.iter().map(|m| async {
+        match m.server.status {
+            ServerStatus::Running => player_count(state, &m.server).await,
+            _ => None,
+        }
+    }))
+    .await;
+    for (metrics, players) in collected.iter_mut().zip(players) {
+        metrics.players = players;
+    }
+
+    let mut out = String::new();
+    {
+        let mut sys = state.sys.lock().map_err(|e| e.to_string())?;
+        sys.refresh_processes(ProcessesToUpdate::All, true);
+        sys.refresh_cpu_usage();
+        sys.refresh_memory();
+
+        for metrics in collected.iter_mut() {
+            let live = matches!(
+                metrics.server.status,
+                ServerStatus::Starting | ServerStatus::Running | ServerStatus::Stopping
+            );
+            metrics.process = metrics
+                .server
+                .pid
+                .filter(|_| live)
+                .and_then(|pid| process_stats(&sys, pid, metrics.pid_start_time));
+        }
+
+        family(&mut out, "ark_host_cpu_usage_percent", "gauge", "CPU usage of the host across all cores.", [
+            (String::new(), sys.global_cpu_usage() as f64),
+        ]);
+        family(&mut out, "ark_host_memory_total_bytes", "gauge", "Physical memory of the host.", [
+            (String::new(), sys.total_memory() as f64),
+        ]);
+        family(&mut out, "ark_host_memory_used_bytes", "gauge", "Physical memory in use on the host.", [
+            (String::new(), sys.used_memory() as f64),
+        ]);
+        family(&mut out, "ark_host_swap_total_bytes", "gauge", "Swap space of the host.", [
+            (String::new(), sys.total_swap() as f64),
+        ]);
+        family(&mut out, "ark_host_swap_used_bytes", "gauge", "Swap space in use on the host.", [
+            (String::new(), sys.used_swap() as f64),
+        ]);
+    }
+    let load = System::load_average();
+    family(&mut out, "ark_host_load_average", "gauge", "Host load average over 1, 5 and 15 minutes; 0 on Windows.", [
+        ("period=\"1m\"".to_string(), load.one),
+        ("period=\"5m\"".to_string(), load.five),
+        ("period=\"15m\"".to_string(), load.fifteen),
+    ]);
+    family(&mut out, "ark_host_uptime_seconds", "gauge", "Time since the host booted.", [
+        (String::new(), System::uptime() as f64),
+    ]);
+
+    family(
+        &mut out,
+        "ark_server_info",
+        "gauge",
+        "Constant 1, labelled with the server's game and map.",
+        collected.iter().map(|m| {
+            let labels = format!(
+                "{},server_type=\"{}\",map=\"{}\"",
+                m.labels(),
+                m.server.server_type.to_string(),
+                escape(&m.server.config.map_name)
+            );
+            (labels, 1.0)
+        }),
+    );
+    family(
+        &mut out,
+        "ark_server_status",
+        "gauge",
+        "1 for the status the server is in, 0 for the others.",
+        collected.iter().flat_map(|m| {
+            STATUSES.iter().map(move |status| {
+                let labels = format!("{},status=\"{}\"", m.labels(), status.to_string());
+                (labels, if m.server.status == *status { 1.0 } else { 0.0 })
+            })
+        }),
+    );
+    family(
+        &mut out,
+        "ark_server_up",
+        "gauge",
+        "1 while the server is running and ready for players.",
+        collected.iter().map(|m| (m.labels(), if m.server.status == ServerStatus::Running { 1.0 } else { 0.0 })),
+    );
+    family(
+        &mut out,
+        "ark_server_uptime_seconds",
+        "gauge",
+        "Time since the server's process started.",
+        collected.iter().filter_map(|m| Some((m.labels(), m.process.as_ref()?.uptime_seconds as f64))),
+    );
+    family(
+        &mut out,
+        "ark_server_players",
+        "gauge",
+        "Players online according to RCON ListPlayers.",
+        collected.iter().filter_map(|m| Some((m.labels(), m.players? as f64))),
+    );
+    family(
+        &mut out,
+        "ark_server_max_players",
+        "gauge",
+        "Player slots the server is configured with.",
+        collected.iter().map(|m| (m.labels(), m.server.config.max_players as f64)),
+    );
+    family(
+        &mut out,
+        "ark_server_cpu_usage_percent",
+        "gauge",
+        "CPU usage of the server's processes, where 100 is one full core.",
+        collected.iter().filter_map(|m| Some((m.labels(), m.process.as_ref()?.cpu_percent))),
+    );
+    family(
+        &mut out,
+        "ark_server_memory_rss_bytes",
+        "gauge",
+        "Resident memory of the server's processes.",
+        collected.iter().filter_map(|m| Some((m.labels(), m.process.as_ref()?.rss_bytes as f64))),
+    );
+    family(
+        &mut out,
+        "ark_server_starts_total",
+        "counter",
+        "Times the server was started, restarts included.",
+        collected.iter().map(|m| (m.labels(), m.starts as f64)),
+    );
+    family(
+        &mut out,
+        "ark_server_crashes_total",
+        "counter",
+        "Times the server crashed.",
+        collected.iter().map(|m| (m.labels(), m.crashes as f64)),
+    );
+    family(
+        &mut out,
+        "ark_server_last_backup_age_seconds",
+        "gauge",
+        "Time since the newest backup was taken.",
+        collected.iter().filter_map(|m| Some((m.labels(), m.backup?.0 as f64))),
+    );
+    family(
+        &mut out,
+        "ark_server_last_backup_size_bytes",
+        "gauge",
+        "Size of the newest backup.",
+        collected.iter().filter_map(|m| Some((m.labels(), m.backup?.1 as f64))),
+    );
+    family(
+        &mut out,
+        "ark_server_build_id",
+        "gauge",
+        "Steam build ID of the installed server files.",
+        collected.iter().filter_map(|m| Some((m.labels(), m.build_id? as f64))),
+    );
+
+    Ok(out)
+}
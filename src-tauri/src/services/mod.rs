@@ -1,15 +1,42 @@
 pub mod api_key_manager;
+pub mod api_tokens;
 pub mod app_updater;
+pub mod arkctl;
+pub mod asm_profile;
+pub mod chat;
+pub mod console;
+pub mod crash_bundle;
+pub mod daemon;
 pub mod discord_bot;
+pub mod events;
 pub mod file_manager;
+pub mod graceful_stop;
 pub mod health_checker;
+pub mod http_api;
+pub mod launch;
+pub mod lifecycle;
+pub mod metrics;
+pub mod moderation;
+pub mod log_tailer;
 pub mod mod_compatibility;
 pub mod mod_scraper;
 pub mod notifications;
 pub mod performance_tracker;
+pub mod platform;
+pub mod player_sessions;
+pub mod port_allocator;
 pub mod process_manager;
 pub mod rcon_client;
+pub mod rcon_sessions;
+pub mod readiness;
+pub mod restart_policy;
+pub mod runner;
 pub mod scheduler;
+pub mod server_clone;
+pub mod server_import;
 pub mod server_monitor;
+pub mod server_status;
+pub mod servers;
 pub mod steamcmd;
 pub mod updater;
+pub mod webhooks;
@@ -0,0 +1,500 @@
+use crate::models::{Ban, ModerationAction, ModerationKind};
+use crate::services::{platform, rcon_sessions};
+use crate::AppState;
+use chrono::{DateTime, Utc};
+use rusqlite::{Connection, OptionalExtension};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+// Kicks, bans and unbans. A ban is kept in `bans`, sent over RCON when the
+// server is running and written to its `BanList.txt`, so it holds across
+// restarts and while the server is down. Temporary bans are lifted by the
+// scheduler once they expire. Every action goes to `moderation_log` with
+// who made it, including the ones that failed.
+//
+// A clone sharing its game files with another server reads that server's
+// `BanList.txt`. Such a group keeps its bans on the server owning the
+// install: the clones show its list and can't ban or unban themselves.
+
+/// Actor recorded for bans lifted because they expired.
+pub const SCHEDULER: &str = "scheduler";
+
+/// Platform IDs are Steam IDs or EOS IDs, both plain alphanumeric; anything
+/// else would let a caller append to the RCON command.
+fn validate_platform_id(platform_id: &str) -> Result<&str, String> {
+    let platform_id = platform_id.trim();
+    if platform_id.is_empty() || !platform_id.chars().all(|c| c.is_ascii_alphanumeric()) {
+        return Err(format!("Invalid player ID: {:?}", platform_id));
+    }
+    Ok(platform_id)
+}
+
+fn non_empty(value: Option<&str>) -> Option<String> {
+    value.map(str::trim).filter(|v| !v.is_empty()).map(str::to_string)
+}
+
+/// RFC 3339 expiry to the UTC `YYYY-MM-DD HH:MM:SS` SQLite compares against.
+fn parse_expiry(expires_at: &str) -> Result<String, String> {
+    let expires_at = DateTime::parse_from_rfc3339(expires_at.trim())
+        .map_err(|e| format!("Invalid ban expiry {:?}: {}", expires_at, e))?
+        .with_timezone(&Utc);
+    if expires_at <= Utc::now() {
+        return Err("Ban expiry must be in the future".to_string());
+    }
+    Ok(expires_at.format("%Y-%m-%d %H:%M:%S").to_string())
+}
+
+/// The server whose install holds this server's `BanList.txt`.
+fn ban_list_owner(conn: &Connection, server_id: i64) -> Result<i64, String> {
+    conn.query_row(
+        "SELECT COALESCE(shared_install_of, id) FROM servers WHERE id = ?1",
+        [server_id],
+        |row| row.get(0),
+    )
+    .optional()
+    .map_err(|e| e.to_string())?
+    .ok_or_else(|| format!("Server {} not found", server_id))
+}
+
+fn ensure_owns_ban_list(conn: &Connection, server_id: i64) -> Result<(), String> {
+    let owner = ban_list_owner(conn, server_id)?;
+    if owner != server_id {
+        return Err(format!(
+            "Server {} shares its game files and ban list with server {}; ban and unban players there",
+            server_id, owner
+        ));
+    }
+    Ok(())
+}
+
+fn ban_list_path(conn: &Connection, server_id: i64) -> Result<PathBuf, String> {
+    let (install_path, server_type): (String, String) = conn
+        .query_row(
+            "SELECT install_path, server_type FROM servers WHERE id = ?1",
+            [server_id],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .optional()
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("Server {} not found", server_id))?;
+    Ok(platform::ban_list_file(Path::new(&install_path), &server_type))
+}
+
+/// The ID on a `BanList.txt` line; some tools add a name or note after it.
+fn ban_list_id(line: &str) -> Option<&str> {
+    let line = line.trim();
+    if line.is_empty() || line.starts_with('#') {
+        return None;
+    }
+    line.split(|c: char| c == ',' || c.is_whitespace()).next()
+}
+
+fn read_ban_list(path: &Path) -> Vec<String> {
+    fs::read_to_string(path)
+        .map(|content| content.lines().filter_map(ban_list_id).map(str::to_string).collect())
+        .unwrap_or_default()
+}
+
+/// Add or remove `platform_id`, keeping every other line as it was.
+fn update_ban_list(path: &Path, platform_id: &str, banned: bool) -> Result<(), String> {
+    let content = fs::read_to_string(path).unwrap_or_default();
+    let mut lines: Vec<&str> = content
+        .lines()
+        .filter(|l| banned || ban_list_id(l) != Some(platform_id))
+        .collect();
+    if banned && !lines.iter().any(|l| ban_list_id(l) == Some(platform_id)) {
+        lines.push(platform_id);
+    }
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    let mut content = lines.join("\n");
+    if !content.is_empty() {
+        content.push('\n');
+    }
+    fs::write(path, content).map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+}
+
+/// Last name the player was seen under on any server.
+fn known_name(conn: &Connection, platform_id: &str) -> Option<String> {
+    conn.query_row(
+        "SELECT name FROM player_sessions WHERE platform_id = ?1 ORDER BY joined_at DESC LIMIT 1",
+        [platform_id],
+        |row| row.get(0),
+    )
+    .optional()
+    .ok()
+    .flatten()
+}
+
+/// Running servers that read the ban list of `owner`, including itself.
+fn running_sharers(conn: &Connection, owner: i64) -> Result<Vec<i64>, String> {
+    let mut stmt = conn
+        .prepare("SELECT id FROM servers WHERE (id = ?1 OR shared_install_of = ?1) AND status = 'running'")
+        .map_err(|e| e.to_string())?;
+    let ids = stmt
+        .query_map([owner], |row| row.get(0))
+        .and_then(|rows| rows.collect::<rusqlite::Result<Vec<_>>>())
+        .map_err(|e| e.to_string())?;
+    Ok(ids)
+}
+
+/// What is written to `moderation_log` for one action.
+struct Entry<'a> {
+    server_id: i64,
+    action: ModerationKind,
+    platform_id: &'a str,
+    player_name: Option<&'a str>,
+    reason: Option<&'a str>,
+    actor: &'a str,
+}
+
+fn log(conn: &Connection, entry: &Entry, error: Option<&str>) -> Result<(), String> {
+    conn.execute(
+        "INSERT INTO moderation_log (server_id, action, platform_id, player_name, reason, actor, error)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+        (
+            entry.server_id,
+            entry.action.to_string(),
+            entry.platform_id,
+            entry.player_name,
+            entry.reason,
+            entry.actor,
+            error,
+        ),
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Run `command` on the running servers. A stopped server reads
+/// `BanList.txt` when it starts, so there is nothing to send it.
+async fn apply(state: &AppState, running: &[i64], command: String) -> Option<String> {
+    let mut errors = Vec::new();
+    for &server_id in running {
+        if let Err(e) = rcon_sessions::execute(state, server_id, &command).await {
+            errors.push(format!("server {}: {}", server_id, e));
+        }
+    }
+    (!errors.is_empty()).then(|| errors.join("; "))
+}
+
+/// Kick a player off a running server.
+pub async fn kick(
+    state: &AppState,
+    server_id: i64,
+    platform_id: &str,
+    reason: Option<&str>,
+    actor: &str,
+) -> Result<(), String> {
+    let platform_id = validate_platform_id(platform_id)?;
+    let reason = non_empty(reason);
+    let result = rcon_sessions::execute(state, server_id, &format!("KickPlayer {}", platform_id)).await;
+
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let conn = db.get_connection().map_err(|e| e.to_string())?;
+    let name = known_name(&conn, platform_id);
+    let error = result.as_ref().err().map(String::as_str);
+    let entry = Entry {
+        server_id,
+        action: ModerationKind::Kick,
+        platform_id,
+        player_name: name.as_deref(),
+        reason: reason.as_deref(),
+        actor,
+    };
+    log(&conn, &entry, error)?;
+    result.map(|_| ())
+}
+
+/// Ban a player, for good or until `expires_at` (RFC 3339). Banning someone
+/// already banned replaces the reason and expiry of their ban.
+pub async fn ban(
+    state: &AppState,
+    server_id: i64,
+    platform_id: &str,
+    player_name: Option<&str>,
+    reason: Option<&str>,
+    expires_at: Option<&str>,
+    actor: &str,
+) -> Result<Ban, String> {
+    let platform_id = validate_platform_id(platform_id)?;
+    let reason = non_empty(reason);
+    let expires_at = non_empty(expires_at).map(|e| parse_expiry(&e)).transpose()?;
+
+    let (ban_id, running) = {
+        let db = state.db.lock().map_err(|e| e.to_string())?;
+        let conn = db.get_connection().map_err(|e| e.to_string())?;
+        ensure_owns_ban_list(&conn, server_id)?;
+        let path = ban_list_path(&conn, server_id)?;
+        let name = non_empty(player_name).or_else(|| known_name(&conn, platform_id));
+
+        let existing: Option<i64> = conn
+            .query_row(
+                "SELECT id FROM bans WHERE server_id = ?1 AND platform_id = ?2 AND lifted_at IS NULL",
+                (server_id, platform_id),
+                |row| row.get(0),
+            )
+            .optional()
+            .map_err(|e| e.to_string())?;
+        let ban_id = match existing {
+            Some(id) => {
+                conn.execute(
+                    "UPDATE bans SET player_name = COALESCE(?1, player_name), reason = ?2, banned_by = ?3, expires_at = ?4
+                     WHERE id = ?5",
+                    (&name, &reason, actor, &expires_at, id),
+                )
+                .map_err(|e| e.to_string())?;
+                id
+            }
+            None => {
+                conn.execute(
+                    "INSERT INTO bans (server_id, platform_id, player_name, reason, banned_by, expires_at)
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                    (server_id, platform_id, &name, &reason, actor, &expires_at),
+                )
+                .map_err(|e| e.to_string())?;
+                conn.last_insert_rowid()
+            }
+        };
+        update_ban_list(&path, platform_id, true)?;
+        (ban_id, running_sharers(&conn, server_id)?)
+    };
+
+    let error = apply(state, &running, format!("BanPlayer {}", platform_id)).await;
+
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let conn = db.get_connection().map_err(|e| e.to_string())?;
+    let ban = conn
+        .query_row(&format!("{} WHERE id = ?1", SELECT_BANS), [ban_id], ban_from_row)
+        .map_err(|e| e.to_string())?;
+    let entry = Entry {
+        server_id,
+        action: ModerationKind::Ban,
+        platform_id,
+        player_name: ban.player_name.as_deref(),
+        reason: reason.as_deref(),
+        actor,
+    };
+    log(&conn, &entry, error.as_deref())?;
+    match error {
+        Some(e) => Err(format!("Ban saved, but it could not be applied over RCON: {}", e)),
+        None => Ok(ban),
+    }
+}
+
+/// Lift a player's ban, whether it was made here or is only in `BanList.txt`.
+pub async fn unban(
+    state: &AppState,
+    server_id: i64,
+    platform_id: &str,
+    reason: Option<&str>,
+    actor: &str,
+) -> Result<(), String> {
+    let platform_id = validate_platform_id(platform_id)?;
+    let reason = non_empty(reason);
+
+    let (name, running) = {
+        let db = state.db.lock().map_err(|e| e.to_string())?;
+        let conn = db.get_connection().map_err(|e| e.to_string())?;
+        ensure_owns_ban_list(&conn, server_id)?;
+        let path = ban_list_path(&conn, server_id)?;
+        let name: Option<String> = conn
+            .query_row(
+                "SELECT player_name FROM bans WHERE server_id = ?1 AND platform_id = ?2 AND lifted_at IS NULL",
+                (server_id, platform_id),
+                |row| row.get(0),
+            )
+            .optional()
+            .map_err(|e| e.to_string())?
+            .flatten()
+            .or_else(|| known_name(&conn, platform_id));
+        let lifted = conn
+            .execute(
+                "UPDATE bans SET lifted_at = CURRENT_TIMESTAMP, lifted_by = ?1
+                 WHERE server_id = ?2 AND platform_id = ?3 AND lifted_at IS NULL",
+                (actor, server_id, platform_id),
+            )
+            .map_err(|e| e.to_string())?;
+        if lifted == 0 && !read_ban_list(&path).iter().any(|id| id == platform_id) {
+            return Err(format!("{} is not banned on this server", platform_id));
+        }
+        update_ban_list(&path, platform_id, false)?;
+        (name, running_sharers(&conn, server_id)?)
+    };
+
+    let error = apply(state, &running, format!("UnbanPlayer {}", platform_id)).await;
+
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let conn = db.get_connection().map_err(|e| e.to_string())?;
+    let entry = Entry {
+        server_id,
+        action: ModerationKind::Unban,
+        platform_id,
+        player_name: name.as_deref(),
+        reason: reason.as_deref(),
+        actor,
+    };
+    log(&conn, &entry, error.as_deref())?;
+    match error {
+        Some(e) => Err(format!("Ban lifted, but it could not be lifted over RCON: {}", e)),
+        None => Ok(()),
+    }
+}
+
+const SELECT_BANS: &str = "SELECT id, server_id, platform_id, player_name, reason, banned_by, created_at, expires_at, lifted_at, lifted_by FROM bans";
+
+fn ban_from_row(row: &rusqlite::Row) -> rusqlite::Result<Ban> {
+    Ok(Ban {
+        id: row.get(0)?,
+        server_id: row.get(1)?,
+        platform_id: row.get(2)?,
+        player_name: row.get(3)?,
+        reason: row.get(4)?,
+        banned_by: row.get(5)?,
+        created_at: row.get(6)?,
+        expires_at: row.get(7)?,
+        lifted_at: row.get(8)?,
+        lifted_by: row.get(9)?,
+    })
+}
+
+/// Bans on the server, newest first, followed by IDs that are only in its
+/// `BanList.txt`. Lifted bans are left out unless `include_lifted`. A clone
+/// sharing another server's game files gets that server's list.
+pub fn list_bans(conn: &Connection, server_id: i64, include_lifted: bool) -> Result<Vec<Ban>, String> {
+    let server_id = ban_list_owner(conn, server_id)?;
+    let path = ban_list_path(conn, server_id)?;
+    let mut stmt = conn
+        .prepare(&format!(
+            "{} WHERE server_id = ?1 AND (?2 OR lifted_at IS NULL) ORDER BY id DESC",
+            SELECT_BANS
+        ))
+        .map_err(|e| e.to_string())?;
+    let mut bans = stmt
+        .query_map((server_id, include_lifted), ban_from_row)
+        .and_then(|rows| rows.collect::<rusqlite::Result<Vec<_>>>())
+        .map_err(|e| e.to_string())?;
+
+    for platform_id in read_ban_list(&path) {
+        let known = bans.iter().any(|b| b.platform_id == platform_id && b.lifted_at.is_none());
+        if !known {
+            bans.push(Ban {
+                id: None,
+                server_id,
+                player_name: known_name(conn, &platform_id),
+                platform_id,
+                reason: None,
+                banned_by: None,
+                created_at: None,
+                expires_at: None,
+                lifted_at: None,
+                lifted_by: None,
+            });
+        }
+    }
+    Ok(bans)
+}
+
+/// The audit log, newest first, on one server or all.
+pub fn history(conn: &Connection, server_id: Option<i64>, limit: usize) -> rusqlite::Result<Vec<ModerationAction>> {
+    let mut stmt = conn.prepare(
+        "SELECT id, server_id, action, platform_id, player_name, reason, actor, error, created_at FROM moderation_log
+         WHERE (?1 IS NULL OR server_id = ?1) ORDER BY id DESC LIMIT ?2",
+    )?;
+    let actions = stmt.query_map((server_id, limit as i64), |row| {
+        Ok(ModerationAction {
+            id: row.get(0)?,
+            server_id: row.get(1)?,
+            action: row.get::<_, String>(2)?.parse().unwrap_or(ModerationKind::Kick),
+            platform_id: row.get(3)?,
+            player_name: row.get(4)?,
+            reason: row.get(5)?,
+            actor: row.get(6)?,
+            error: row.get(7)?,
+            created_at: row.get(8)?,
+        })
+    })?;
+    actions.collect()
+}
+
+/// Lift every temporary ban that has run out. Called by the scheduler.
+pub async fn lift_expired(state: &AppState) -> Result<(), String> {
+    let expired: Vec<(i64, String)> = {
+        let db = state.db.lock().map_err(|e| e.to_string())?;
+        let conn = db.get_connection().map_err(|e| e.to_string())?;
+        let mut stmt = conn
+            .prepare(
+                "SELECT server_id, platform_id FROM bans
+                 WHERE lifted_at IS NULL AND expires_at IS NOT NULL AND expires_at <= CURRENT_TIMESTAMP",
+            )
+            .map_err(|e| e.to_string())?;
+        let expired = stmt
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+            .and_then(|rows| rows.collect::<rusqlite::Result<Vec<_>>>())
+            .map_err(|e| e.to_string())?;
+        expired
+    };
+
+    for (server_id, platform_id) in expired {
+        // The ban is already lifted in the database and file when only RCON
+        // fails, so it is not retried
+        if let Err(e) = unban(state, server_id, &platform_id, Some("Ban expired"), SCHEDULER).await {
+            eprintln!("Failed to lift expired ban of {} on server {}: {}", platform_id, server_id, e);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ban_list_edits_keep_other_lines() {
+        let dir = std::env::temp_dir().join(format!("asm-banlist-{}", std::process::id()));
+        let path = dir.join("BanList.txt");
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(&path, "# banned by hand\n76561198000000001\n0002abcdef0123456789abcdef012345, griefer\n").unwrap();
+
+        update_ban_list(&path, "76561198000000002", true).unwrap();
+        update_ban_list(&path, "76561198000000002", true).unwrap();
+        assert_eq!(
+            read_ban_list(&path),
+            vec!["76561198000000001", "0002abcdef0123456789abcdef012345", "76561198000000002"]
+        );
+
+        update_ban_list(&path, "0002abcdef0123456789abcdef012345", false).unwrap();
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            "# banned by hand\n76561198000000001\n76561198000000002\n"
+        );
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn shared_clones_use_the_ban_list_of_their_source() {
+        let conn = crate::db::test_connection();
+        conn.execute(
+            "INSERT INTO servers (name, server_type, install_path, game_port, query_port, rcon_port,
+             admin_password, map_name, session_name, shared_install_of)
+             VALUES ('Clone', 'ASE', '/srv/ark-clone', 7787, 27025, 27030, 'secret', 'TheIsland', 'Clone', 1)",
+            [],
+        )
+        .unwrap();
+        conn.execute("INSERT INTO bans (server_id, platform_id) VALUES (1, '76561198000000001')", []).unwrap();
+
+        assert!(ensure_owns_ban_list(&conn, 1).is_ok());
+        assert!(ensure_owns_ban_list(&conn, 2).is_err());
+        let bans = list_bans(&conn, 2, false).unwrap();
+        assert_eq!(bans.len(), 1);
+        assert_eq!((bans[0].server_id, bans[0].platform_id.as_str()), (1, "76561198000000001"));
+    }
+
+    #[test]
+    fn rejects_ids_that_would_extend_the_command() {
+        assert_eq!(validate_platform_id(" 76561198000000001 "), Ok("76561198000000001"));
+        assert!(validate_platform_id("123; DestroyAll").is_err());
+        assert!(validate_platform_id("").is_err());
+    }
+}
@@ -0,0 +1,128 @@
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use anyhow::Result;
+
+/// Host platform the manager was compiled for, as far as ARK is concerned.
+/// ARK ships `Win64` server binaries for both games, and a native `Linux`
+/// binary for ASE only.
+pub fn is_linux_host() -> bool {
+    cfg!(target_os = "linux")
+}
+
+/// Whether this server type has a server binary that runs natively on the host.
+pub fn has_native_binary(server_type: &str) -> bool {
+    !is_linux_host() || server_type == "ASE"
+}
+
+/// Folder name under `ShooterGame/Binaries` used by the server we launch.
+pub fn binaries_platform(server_type: &str) -> &'static str {
+    if is_linux_host() && server_type == "ASE" {
+        "Linux"
+    } else {
+        "Win64"
+    }
+}
+
+/// Folder name under `ShooterGame/Saved/Config` that the server reads its INIs from.
+/// The Windows ASA build keeps using `WindowsServer` even when hosted on Linux.
+pub fn config_platform(server_type: &str) -> &'static str {
+    if is_linux_host() && server_type == "ASE" {
+        "LinuxServer"
+    } else {
+        "WindowsServer"
+    }
+}
+
+pub fn binaries_dir(install_path: &Path, server_type: &str) -> PathBuf {
+    install_path
+        .join("ShooterGame")
+        .join("Binaries")
+        .join(binaries_platform(server_type))
+}
+
+pub fn config_dir(install_path: &Path, server_type: &str) -> PathBuf {
+    install_path
+        .join("ShooterGame")
+        .join("Saved")
+        .join("Config")
+        .join(config_platform(server_type))
+}
+
+/// Log file the server writes next to its saves; used when stdout isn't available.
+pub fn server_log_file(install_path: &Path) -> PathBuf {
+    install_path
+        .join("ShooterGame")
+        .join("Saved")
+        .join("Logs")
+        .join("ShooterGame.log")
+}
+
+/// Platform IDs the server refuses to let in, one per line.
+/// Both games read it from next to the server executable.
+pub fn ban_list_file(install_path: &Path, server_type: &str) -> PathBuf {
+    binaries_dir(install_path, server_type).join("BanList.txt")
+}
+
+/// Folder holding the world saves. ASA nests each map in its own subfolder.
+pub fn saved_arks_dir(install_path: &Path) -> PathBuf {
+    install_path
+        .join("ShooterGame")
+        .join("Saved")
+        .join("SavedArks")
+}
+
+/// Path of the dedicated server executable for this server type on this host.
+pub fn server_executable(install_path: &Path, server_type: &str) -> Result<PathBuf> {
+    let file_name = match (server_type, binaries_platform(server_type)) {
+        ("ASE", "Linux") => "ShooterGameServer",
+        ("ASE", _) => "ShooterGameServer.exe",
+        ("ASA", _) => "ArkAscendedServer.exe",
+        _ => return Err(anyhow::anyhow!("Invalid server type")),
+    };
+
+    Ok(binaries_dir(install_path, server_type).join(file_name))
+}
+
+/// File name of the SteamCMD entry point inside the SteamCMD directory.
+pub fn steamcmd_executable_name() -> &'static str {
+    if cfg!(windows) {
+        "steamcmd.exe"
+    } else {
+        "steamcmd.sh"
+    }
+}
+
+pub fn steamcmd_download_url() -> &'static str {
+    if cfg!(windows) {
+        "https://steamcdn-a.akamaihd.net/client/installer/steamcmd.zip"
+    } else {
+        "https://steamcdn-a.akamaihd.net/client/installer/steamcmd_linux.tar.gz"
+    }
+}
+
+/// Keep spawned console programs from popping up a window on Windows.
+/// This is a no-op everywhere else.
+pub fn hide_console_window(command: &mut Command) -> &mut Command {
+    #[cfg(windows)]
+    {
+        use std::os::windows::process::CommandExt;
+        const CREATE_NO_WINDOW: u32 = 0x08000000;
+        command.creation_flags(CREATE_NO_WINDOW);
+    }
+
+    command
+}
+
+/// Link a directory into another place. On Windows this needs Developer Mode
+/// or an elevated manager, like any symlink there.
+pub fn symlink_dir(original: &Path, link: &Path) -> std::io::Result<()> {
+    #[cfg(unix)]
+    {
+        std::os::unix::fs::symlink(original, link)
+    }
+
+    #[cfg(windows)]
+    {
+        std::os::windows::fs::symlink_dir(original, link)
+    }
+}
@@ -0,0 +1,275 @@
+use crate::models::{DailyPeak, Player, PlayerSession, PlayerStats, WebhookEvent};
+use crate::services::{rcon_sessions, webhooks};
+use crate::AppState;
+use chrono::{Duration as ChronoDuration, NaiveDate, NaiveDateTime, Utc};
+use rusqlite::Connection;
+use serde_json::json;
+use std::collections::{BTreeMap, HashSet};
+use std::time::Duration;
+use tokio::time::sleep;
+
+// Who played where and when. A poller diffs each running server's player
+// list against the sessions still open in `player_sessions`, opening and
+// closing them and emitting `player-joined` / `player-left`. Sessions are
+// keyed by platform ID; times are UTC in SQLite's `CURRENT_TIMESTAMP` form.
+
+const POLL_INTERVAL: Duration = Duration::from_secs(30);
+const TIME_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+fn session_from_row(row: &rusqlite::Row) -> rusqlite::Result<PlayerSession> {
+    Ok(PlayerSession {
+        id: row.get(0)?,
+        server_id: row.get(1)?,
+        platform_id: row.get(2)?,
+        platform: row.get::<_, String>(3)?.parse().unwrap_or(crate::models::PlayerPlatform::Unknown),
+        name: row.get(4)?,
+        joined_at: row.get(5)?,
+        left_at: row.get(6)?,
+    })
+}
+
+/// Players online right now according to the last poll, on one server or all.
+pub fn online(conn: &Connection, server_id: Option<i64>) -> rusqlite::Result<Vec<PlayerSession>> {
+    let mut stmt = conn.prepare(
+        "SELECT id, server_id, platform_id, platform, name, joined_at, left_at FROM player_sessions
+         WHERE left_at IS NULL AND (?1 IS NULL OR server_id = ?1) ORDER BY server_id, joined_at",
+    )?;
+    let sessions = stmt.query_map([server_id], session_from_row)?;
+    sessions.collect()
+}
+
+/// A player's sessions on any server, newest first.
+pub fn history(conn: &Connection, platform_id: &str, limit: usize) -> rusqlite::Result<Vec<PlayerSession>> {
+    let mut stmt = conn.prepare(
+        "SELECT id, server_id, platform_id, platform, name, joined_at, left_at FROM player_sessions
+         WHERE platform_id = ?1 ORDER BY id DESC LIMIT ?2",
+    )?;
+    let sessions = stmt.query_map((platform_id, limit as i64), session_from_row)?;
+    sessions.collect()
+}
+
+/// Playtime and last seen of every player, on one server or all, most
+/// playtime first.
+pub fn stats(conn: &Connection, server_id: Option<i64>) -> rusqlite::Result<Vec<PlayerStats>> {
+    let mut stmt = conn.prepare(
+        "SELECT platform_id, platform,
+                (SELECT name FROM player_sessions l WHERE l.platform_id = p.platform_id ORDER BY l.id DESC LIMIT 1),
+                COUNT(*),
+                CAST(SUM(julianday(COALESCE(left_at, CURRENT_TIMESTAMP)) - julianday(joined_at)) * 86400 AS INTEGER),
+                MAX(COALESCE(left_at, CURRENT_TIMESTAMP)),
+                MAX(left_at IS NULL)
+         FROM player_sessions p
+         WHERE ?1 IS NULL OR server_id = ?1
+         GROUP BY platform_id
+         ORDER BY 5 DESC",
+    )?;
+    let stats = stmt.query_map([server_id], |row| {
+        Ok(PlayerStats {
+            platform_id: row.get(0)?,
+            platform: row.get::<_, String>(1)?.parse().unwrap_or(crate::models::PlayerPlatform::Unknown),
+            name: row.get(2)?,
+            sessions: row.get(3)?,
+            playtime_seconds: row.get(4)?,
+            last_seen: row.get(5)?,
+            online: row.get(6)?,
+        })
+    })?;
+    stats.collect()
+}
+
+/// Most players online at once on each of the last `days` UTC days, oldest
+/// first. Without a server, players are counted across all of them.
+pub fn daily_peaks(conn: &Connection, server_id: Option<i64>, days: u32) -> rusqlite::Result<Vec<DailyPeak>> {
+    let today = Utc::now().date_naive();
+    let first = today - ChronoDuration::days(days.max(1) as i64 - 1);
+    peaks_between(conn, server_id, first, today)
+}
+
+fn peaks_between(conn: &Connection, server_id: Option<i64>, first: NaiveDate, last: NaiveDate) -> rusqlite::Result<Vec<DailyPeak>> {
+    let start = first.and_hms_opt(0, 0, 0).expect("midnight exists");
+    let mut stmt = conn.prepare(
+        "SELECT joined_at, left_at FROM player_sessions
+         WHERE (?1 IS NULL OR server_id = ?1) AND (left_at IS NULL OR left_at >= ?2)",
+    )?;
+    let sessions = stmt
+        .query_map((server_id, start.format(TIME_FORMAT).to_string()), |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, Option<String>>(1)?))
+        })?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+
+    let parse = |s: &str| NaiveDateTime::parse_from_str(s, TIME_FORMAT).ok();
+    // Leaves sort before joins at the same second, so a reconnect isn't two players
+    let mut changes: Vec<(NaiveDateTime, i64)> = Vec::new();
+    for (joined_at, left_at) in &sessions {
+        let Some(joined_at) = parse(joined_at) else { continue };
+        changes.push((joined_at.max(start), 1));
+        if let Some(left_at) = left_at.as_deref().and_then(parse) {
+            changes.push((left_at, -1));
+        }
+    }
+    changes.sort();
+
+    let mut peaks: BTreeMap<NaiveDate, i64> = first.iter_days().take_while(|d| *d <= last).map(|d| (d, 0)).collect();
+    let mut online = 0;
+    let mut day = first;
+    for (time, change) in changes {
+        // Days without changes still had whoever stayed on
+        while day < time.date() && day < last {
+            day = day.succ_opt().expect("date in range");
+            peaks.insert(day, online);
+        }
+        online += change;
+        if let Some(peak) = peaks.get_mut(&time.date()) {
+            *peak = (*peak).max(online);
+        }
+    }
+    while day < last {
+        day = day.succ_opt().expect("date in range");
+        peaks.insert(day, online);
+    }
+
+    Ok(peaks
+        .into_iter()
+        .map(|(date, peak)| DailyPeak { date: date.to_string(), peak: peak.max(0) as u32 })
+        .collect())
+}
+
+/// Open sessions for players who appeared and close those of players who
+/// are gone. Returns who joined and the sessions that ended.
+fn record(conn: &Connection, server_id: i64, players: &[Player]) -> rusqlite::Result<(Vec<Player>, Vec<PlayerSession>)> {
+    let open = online(conn, Some(server_id))?;
+    let open_ids: HashSet<&str> = open.iter().map(|s| s.platform_id.as_str()).collect();
+    let current_ids: HashSet<&str> = players.iter().map(|p| p.platform_id.as_str()).collect();
+
+    let left: Vec<PlayerSession> = open.iter().filter(|s| !current_ids.contains(s.platform_id.as_str())).cloned().collect();
+    let mut seen = HashSet::new();
+    let joined: Vec<Player> = players
+        .iter()
+        .filter(|p| !open_ids.contains(p.platform_id.as_str()) && seen.insert(p.platform_id.as_str()))
+        .cloned()
+        .collect();
+
+    for session in &left {
+        conn.execute("UPDATE player_sessions SET left_at = CURRENT_TIMESTAMP WHERE id = ?1", [session.id])?;
+    }
+    for player in &joined {
+        conn.execute(
+            "INSERT INTO player_sessions (server_id, platform_id, platform, name) VALUES (?1, ?2, ?3, ?4)",
+            (server_id, &player.platform_id, player.platform.to_string(), &player.name),
+        )?;
+    }
+    Ok((joined, left))
+}
+
+fn emit_changes(state: &AppState, server_id: i64, joined: &[Player], left: &[PlayerSession]) {
+    for player in joined {
+        let data = json!({
+            "platformId": player.platform_id,
+            "name": player.name,
+            "platform": player.platform,
+        });
+        state.events.emit("player-joined", json!({
+            "serverId": server_id,
+            "platformId": player.platform_id,
+            "name": player.name,
+            "platform": player.platform,
+        }));
+        webhooks::fire(state, WebhookEvent::PlayerJoined, server_id, data);
+    }
+    for session in left {
+        state.events.emit("player-left", json!({
+            "serverId": server_id,
+            "platformId": session.platform_id,
+            "name": session.name,
+            "platform": session.platform,
+            "joinedAt": session.joined_at,
+        }));
+    }
+}
+
+async fn poll(state: &AppState) -> Result<(), String> {
+    let running: Vec<i64> = {
+        let db = state.db.lock().map_err(|e| e.to_string())?;
+        let conn = db.get_connection().map_err(|e| e.to_string())?;
+
+        // Whoever was on a server that is no longer running has left it
+        let mut stmt = conn
+            .prepare(
+                "SELECT DISTINCT server_id FROM player_sessions WHERE left_at IS NULL
+                 AND server_id NOT IN (SELECT id FROM servers WHERE status = 'running')",
+            )
+            .map_err(|e| e.to_string())?;
+        let stopped = stmt
+            .query_map([], |row| row.get::<_, i64>(0))
+            .and_then(|rows| rows.collect::<rusqlite::Result<Vec<_>>>())
+            .map_err(|e| e.to_string())?;
+        for server_id in stopped {
+            let (_, left) = record(&conn, server_id, &[]).map_err(|e| e.to_string())?;
+            emit_changes(state, server_id, &[], &left);
+        }
+
+        let mut stmt = conn
+            .prepare("SELECT id FROM servers WHERE status = 'running'")
+            .map_err(|e| e.to_string())?;
+        let running = stmt
+            .query_map([], |row| row.get(0))
+            .and_then(|rows| rows.collect::<rusqlite::Result<Vec<_>>>())
+            .map_err(|e| e.to_string())?;
+        running
+    };
+
+    for server_id in running {
+        // An unanswered poll says nothing about who left
+        let Ok(players) = rcon_sessions::players(state, server_id).await else { continue };
+        let (joined, left) = {
+            let db = state.db.lock().map_err(|e| e.to_string())?;
+            let conn = db.get_connection().map_err(|e| e.to_string())?;
+            record(&conn, server_id, &players).map_err(|e| e.to_string())?
+        };
+        emit_changes(state, server_id, &joined, &left);
+    }
+    Ok(())
+}
+
+/// Poll every running server for its players until the process exits.
+pub fn spawn(state: AppState) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            sleep(POLL_INTERVAL).await;
+            if let Err(e) = poll(&state).await {
+                eprintln!("Player session poll failed: {}", e);
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::test_connection;
+
+    fn session(conn: &Connection, id: &str, joined_at: &str, left_at: Option<&str>) {
+        conn.execute(
+            "INSERT INTO player_sessions (server_id, platform_id, platform, name, joined_at, left_at)
+             VALUES (1, ?1, 'steam', ?1, ?2, ?3)",
+            (id, joined_at, left_at),
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn daily_peaks_carry_players_across_midnight() {
+        let conn = test_connection();
+        // Online since before the range until the second day
+        session(&conn, "a", "2026-03-01 20:00:00", Some("2026-03-03 01:00:00"));
+        session(&conn, "b", "2026-03-02 10:00:00", Some("2026-03-02 11:00:00"));
+        // Reconnects in the same second as leaving
+        session(&conn, "c", "2026-03-02 10:30:00", Some("2026-03-02 12:00:00"));
+        session(&conn, "c", "2026-03-02 12:00:00", None);
+
+        let day = |d: &str| NaiveDate::parse_from_str(d, "%Y-%m-%d").unwrap();
+        let peaks = peaks_between(&conn, None, day("2026-03-02"), day("2026-03-05")).unwrap();
+        let peaks: Vec<_> = peaks.iter().map(|p| (p.date.as_str(), p.peak)).collect();
+        assert_eq!(peaks, [("2026-03-02", 3), ("2026-03-03", 2), ("2026-03-04", 1), ("2026-03-05", 1)]);
+    }
+}
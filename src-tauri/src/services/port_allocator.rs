@@ -0,0 +1,248 @@
+use crate::models::{PortConflict, PortProtocol, ServerPorts};
+use rusqlite::Connection;
+use std::net::{TcpListener, UdpSocket};
+
+/// First port block handed out; the defaults of a fresh ARK install.
+const BASE_GAME_PORT: u16 = 7777;
+const BASE_QUERY_PORT: u16 = 27015;
+const BASE_RCON_PORT: u16 = 32330;
+/// Blocks tried before giving up on finding free ones.
+const MAX_BLOCKS: u16 = 200;
+
+/// Which other servers count when looking for clashes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CheckScope {
+    /// Every server in the database, whether or not it is running
+    AllServers,
+    /// Only servers that are up or on their way up
+    ActiveServers,
+}
+
+struct PortUse {
+    protocol: PortProtocol,
+    port: u16,
+    purpose: &'static str,
+}
+
+/// Everything a server binds: the game port, the raw UDP port right after
+/// it, the Steam query port and RCON.
+fn port_uses(ports: &ServerPorts) -> Vec<PortUse> {
+    let mut uses = vec![PortUse { protocol: PortProtocol::Udp, port: ports.game_port, purpose: "game" }];
+    if let Some(raw) = ports.game_port.checked_add(1) {
+        uses.push(PortUse { protocol: PortProtocol::Udp, port: raw, purpose: "raw UDP" });
+    }
+    uses.push(PortUse { protocol: PortProtocol::Udp, port: ports.query_port, purpose: "query" });
+    uses.push(PortUse { protocol: PortProtocol::Tcp, port: ports.rcon_port, purpose: "RCON" });
+    uses
+}
+
+/// Two binds collide when either listens on all interfaces or both use the same address.
+fn same_interface(a: Option<&str>, b: Option<&str>) -> bool {
+    match (a.filter(|ip| !ip.is_empty()), b.filter(|ip| !ip.is_empty())) {
+        (Some(a), Some(b)) => a == b,
+        _ => true,
+    }
+}
+
+/// Try to bind the port the way the server will, and report why it can't.
+fn bind_error(protocol: PortProtocol, ip: Option<&str>, port: u16) -> Option<std::io::Error> {
+    let ip = ip.filter(|ip| !ip.is_empty()).unwrap_or("0.0.0.0");
+    let result = match protocol {
+        PortProtocol::Udp => UdpSocket::bind((ip, port)).map(drop),
+        PortProtocol::Tcp => TcpListener::bind((ip, port)).map(drop),
+    };
+    result.err()
+}
+
+/// Ports `ports` would clash on: within the server itself, with other servers
+/// in `scope` (except `server_id`), and with anything already bound on
+/// `multihome_ip`.
+pub fn find_conflicts(
+    conn: &Connection,
+    server_id: Option<i64>,
+    ports: &ServerPorts,
+    multihome_ip: Option<&str>,
+    scope: CheckScope,
+) -> rusqlite::Result<Vec<PortConflict>> {
+    let uses = port_uses(ports);
+    let mut conflicts = Vec::new();
+
+    for (i, u) in uses.iter().enumerate() {
+        if let Some(other) = uses[..i].iter().find(|o| o.protocol == u.protocol && o.port == u.port) {
+            conflicts.push(PortConflict {
+                port: u.port,
+                protocol: u.protocol,
+                purpose: u.purpose.to_string(),
+                conflicting_server_id: None,
+                conflicting_server_name: None,
+                message: format!("{} {} is both the {} and the {} port", u.protocol, u.port, other.purpose, u.purpose),
+            });
+        }
+    }
+
+    let mut stmt = conn.prepare(
+        "SELECT id, name, game_port, query_port, rcon_port, multihome_ip, status FROM servers
+         WHERE ?1 IS NULL OR id != ?1",
+    )?;
+    let others = stmt
+        .query_map([server_id], |row| {
+            Ok((
+                row.get::<_, i64>(0)?,
+                row.get::<_, String>(1)?,
+                ServerPorts { game_port: row.get(2)?, query_port: row.get(3)?, rcon_port: row.get(4)? },
+                row.get::<_, Option<String>>(5)?,
+                row.get::<_, String>(6)?,
+            ))
+        })?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+
+    for (other_id, other_name, other_ports, other_ip, status) in others {
+        if scope == CheckScope::ActiveServers && !matches!(status.as_str(), "starting" | "running") {
+            continue;
+        }
+        if !same_interface(multihome_ip, other_ip.as_deref()) {
+            continue;
+        }
+        let taken = port_uses(&other_ports);
+        for u in &uses {
+            if taken.iter().any(|t| t.protocol == u.protocol && t.port == u.port) {
+                conflicts.push(PortConflict {
+                    port: u.port,
+                    protocol: u.protocol,
+                    purpose: u.purpose.to_string(),
+                    conflicting_server_id: Some(other_id),
+                    conflicting_server_name: Some(other_name.clone()),
+                    message: format!(
+                        "{} {} ({}) is also used by server \"{}\" (ID {})",
+                        u.protocol, u.port, u.purpose, other_name, other_id
+                    ),
+                });
+            }
+        }
+    }
+
+    for u in &uses {
+        // A clash already reported against another server says more than the bind error
+        if conflicts.iter().any(|c| c.protocol == u.protocol && c.port == u.port) {
+            continue;
+        }
+        if let Some(e) = bind_error(u.protocol, multihome_ip, u.port) {
+            conflicts.push(PortConflict {
+                port: u.port,
+                protocol: u.protocol,
+                purpose: u.purpose.to_string(),
+                conflicting_server_id: None,
+                conflicting_server_name: None,
+                message: format!("{} {} ({}) can't be bound on this machine: {}", u.protocol, u.port, u.purpose, e),
+            });
+        }
+    }
+
+    Ok(conflicts)
+}
+
+/// Up to `count` port blocks that clash with no server in the database and
+/// are free on `multihome_ip`. Block `n` is game `7777 + 2n`, query
+/// `27015 + n` and RCON `32330 + n`.
+pub fn suggest_ports(conn: &Connection, multihome_ip: Option<&str>, count: usize) -> rusqlite::Result<Vec<ServerPorts>> {
+    let mut free = Vec::new();
+    for n in 0..MAX_BLOCKS {
+        if free.len() >= count {
+            break;
+        }
+        let ports = ServerPorts {
+            game_port: BASE_GAME_PORT + 2 * n,
+            query_port: BASE_QUERY_PORT + n,
+            rcon_port: BASE_RCON_PORT + n,
+        };
+        if find_conflicts(conn, None, &ports, multihome_ip, CheckScope::AllServers)?.is_empty() {
+            free.push(ports);
+        }
+    }
+    Ok(free)
+}
+
+/// One error for a list of conflicts, with a free block to use instead.
+pub fn describe(conn: &Connection, conflicts: &[PortConflict], multihome_ip: Option<&str>) -> String {
+    let mut message = format!(
+        "Port conflict: {}.",
+        conflicts.iter().map(|c| c.message.as_str()).collect::<Vec<_>>().join("; ")
+    );
+    if let Some(ports) = suggest_ports(conn, multihome_ip, 1).ok().and_then(|p| p.into_iter().next()) {
+        message.push_str(&format!(
+            " Free ports: game {}, query {}, RCON {}.",
+            ports.game_port, ports.query_port, ports.rcon_port
+        ));
+    }
+    message
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::test_connection;
+
+    fn add_server(conn: &Connection, game_port: u16, query_port: u16, rcon_port: u16) -> i64 {
+        conn.execute(
+            "INSERT INTO servers (name, server_type, install_path, game_port, query_port, rcon_port,
+             admin_password, map_name, session_name)
+             VALUES ('Other', 'ASE', '/srv/other', ?1, ?2, ?3, 'secret', 'TheIsland', 'Other')",
+            (game_port, query_port, rcon_port),
+        )
+        .unwrap();
+        conn.last_insert_rowid()
+    }
+
+    /// Clashes with other servers as (purpose, server); what happens to be
+    /// bound on the machine running the tests is left out.
+    fn clashes(conn: &Connection, game_port: u16, query_port: u16, rcon_port: u16, ip: Option<&str>) -> Vec<(String, i64)> {
+        let ports = ServerPorts { game_port, query_port, rcon_port };
+        find_conflicts(conn, None, &ports, ip, CheckScope::AllServers)
+            .unwrap()
+            .into_iter()
+            .filter_map(|c| Some((c.purpose, c.conflicting_server_id?)))
+            .collect()
+    }
+
+    #[test]
+    fn clashes_with_the_ports_of_other_servers() {
+        // Server 1 uses game 7777 (and raw 7778), query 27015 and RCON 27020
+        let conn = test_connection();
+        assert_eq!(
+            clashes(&conn, 7777, 27115, 27120, None),
+            vec![("game".to_string(), 1), ("raw UDP".to_string(), 1)]
+        );
+        assert_eq!(clashes(&conn, 7877, 27015, 27120, None), vec![("query".to_string(), 1)]);
+        assert_eq!(clashes(&conn, 7877, 27115, 27020, None), vec![("RCON".to_string(), 1)]);
+        assert!(clashes(&conn, 7877, 27115, 27120, None).is_empty());
+    }
+
+    #[test]
+    fn the_raw_udp_port_after_the_game_port_is_taken_too() {
+        let conn = test_connection();
+        assert_eq!(clashes(&conn, 7776, 27115, 27120, None), vec![("raw UDP".to_string(), 1)]);
+        assert_eq!(clashes(&conn, 7778, 27115, 27120, None), vec![("game".to_string(), 1)]);
+    }
+
+    #[test]
+    fn multihome_addresses_keep_servers_apart() {
+        let conn = test_connection();
+        conn.execute("UPDATE servers SET multihome_ip = '10.0.0.1' WHERE id = 1", []).unwrap();
+        assert!(clashes(&conn, 7777, 27015, 27020, Some("10.0.0.2")).is_empty());
+        assert_eq!(clashes(&conn, 7777, 27115, 27120, Some("10.0.0.1")).len(), 2);
+        // Without an address the server listens on every interface
+        assert_eq!(clashes(&conn, 7777, 27115, 27120, None).len(), 2);
+    }
+
+    #[test]
+    fn suggestions_skip_taken_blocks() {
+        let conn = test_connection();
+        add_server(&conn, 7779, 27016, 32331);
+        let suggested = suggest_ports(&conn, Some("127.0.0.1"), 2).unwrap();
+        assert_eq!(suggested.len(), 2);
+        for ports in suggested {
+            assert!(ports.game_port >= 7781, "block {:?} is taken", ports);
+            assert!(clashes(&conn, ports.game_port, ports.query_port, ports.rcon_port, Some("127.0.0.1")).is_empty());
+        }
+    }
+}
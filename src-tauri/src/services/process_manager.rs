@@ -1,12 +1,99 @@
-use tauri::{AppHandle, Emitter};
+use crate::services::events::EventBus;
 use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::collections::HashMap;
 use std::process::{Child, Command};
-use std::path::PathBuf;
 use anyhow::{Context, Result};
+use rusqlite::Connection;
+use std::path::Path;
+use sysinfo::{Pid, ProcessRefreshKind, ProcessesToUpdate, Signal, System, UpdateKind};
+use crate::models::RunnerKind;
+use crate::services::launch::LaunchSpec;
+use crate::services::log_tailer::LogTailer;
+use crate::services::{platform, runner};
+
+/// The OS process behind a managed server.
+enum ProcessHandle {
+    /// Spawned by us this session: the server itself, or its Wine/Proton wrapper
+    Child(Child),
+    /// Still running from an earlier session of the manager. We only have its
+    /// PID, so there is no exit status and no stdout.
+    Adopted { pid: u32, start_time: u64 },
+}
+
+impl ProcessHandle {
+    fn id(&self) -> u32 {
+        match self {
+            ProcessHandle::Child(child) => child.id(),
+            ProcessHandle::Adopted { pid, .. } => *pid,
+        }
+    }
+
+    /// Like `Child::try_wait`: `Some(exit_code)` once the process is gone.
+    fn try_wait(&mut self) -> std::io::Result<Option<Option<i32>>> {
+        match self {
+            ProcessHandle::Child(child) => Ok(child.try_wait()?.map(|status| status.code())),
+            ProcessHandle::Adopted { pid, start_time } => {
+                // A different start time means the PID has been reused
+                if process_start_time(*pid) == Some(*start_time) {
+                    Ok(None)
+                } else {
+                    Ok(Some(None))
+                }
+            }
+        }
+    }
+
+    fn kill(&mut self) -> Result<()> {
+        match self {
+            ProcessHandle::Child(child) => {
+                child.kill().context("Failed to kill server process")?;
+                child.wait().context("Failed to wait for server process")?;
+            }
+            ProcessHandle::Adopted { pid, .. } => {
+                if !signal_process_tree(*pid, Signal::Kill) && is_pid_alive(*pid) {
+                    return Err(anyhow::anyhow!("Failed to kill server process {}", pid));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A server process we manage, plus whatever helpers live as long as it does.
+struct ManagedProcess {
+    handle: ProcessHandle,
+    /// Stops the log-file tailer that stands in for stdout under a wrapper
+    /// or for an adopted process
+    tail_stop: Option<Arc<AtomicBool>>,
+    /// Set once a stop has been asked for, so the exit isn't taken for a crash
+    stop_requested: bool,
+}
+
+/// A managed server process that has exited since the last check.
+pub struct ProcessExit {
+    pub server_id: i64,
+    pub exit_code: Option<i32>,
+    /// The exit followed a stop request rather than happening on its own
+    pub requested: bool,
+}
+
+impl Drop for ManagedProcess {
+    fn drop(&mut self) {
+        if let Some(stop) = &self.tail_stop {
+            stop.store(true, Ordering::Relaxed);
+        }
+    }
+}
+
+/// PID of the server's tracked process, if it is still running.
+fn live_pid(processes: &mut HashMap<i64, ManagedProcess>, server_id: i64) -> Option<u32> {
+    let process = processes.get_mut(&server_id)?;
+    matches!(process.handle.try_wait(), Ok(None)).then(|| process.handle.id())
+}
 
 pub struct ProcessManager {
-    processes: Arc<Mutex<HashMap<i64, Child>>>,
+    processes: Arc<Mutex<HashMap<i64, ManagedProcess>>>,
 }
 
 impl ProcessManager {
@@ -16,123 +103,47 @@ impl ProcessManager {
         }
     }
 
-    /// Start ARK server
-    pub fn start_server(
-        &self,
-        app_handle: &AppHandle, // ADDED
-        server_id: i64,
-        server_type: &str,
-        install_path: &PathBuf,
-        map_name: &str,
-        session_name: &str,
-        game_port: u16,
-        query_port: u16,
-        rcon_port: u16,
-        max_players: i32,
-        server_password: Option<&str>,
-        admin_password: &str,
-        use_battleye: bool,
-        multihome_ip: Option<String>,
-        crossplay_enabled: bool,
-    ) -> Result<u32> {
-        let executable = match server_type {
-            "ASE" => install_path
-                .join("ShooterGame")
-                .join("Binaries")
-                .join("Win64")
-                .join("ShooterGameServer.exe"),
-            "ASA" => install_path
-                .join("ShooterGame")
-                .join("Binaries")
-                .join("Win64")
-                .join("ArkAscendedServer.exe"),
-            _ => return Err(anyhow::anyhow!("Invalid server type")),
-        };
+    /// Start ARK server. Fails when the server already has a live process.
+    pub fn start_server(&self, events: &EventBus, app_data_dir: &Path, spec: &LaunchSpec) -> Result<u32> {
+        let server_id = spec.server_id;
+        if let Some(pid) = live_pid(&mut self.processes.lock().unwrap(), server_id) {
+            return Err(anyhow::anyhow!("Server {} is already running (PID {})", server_id, pid));
+        }
 
-        if !executable.exists() {
+        if spec.runner.kind == RunnerKind::Direct && !platform::has_native_binary(&spec.server_type) {
             return Err(anyhow::anyhow!(
-                "Server executable not found at {:?}",
-                executable
+                "{} has no native server binary for this platform. Configure a Wine or Proton runner for this server.",
+                spec.server_type
             ));
         }
 
-        // Build launch arguments
-        let mut args = vec![
-            map_name.to_string(),
-            format!("listen"),
-            format!("?SessionName={}", session_name),
-            format!("?Port={}", game_port),
-            format!("?QueryPort={}", query_port),
-            format!("?RCONPort={}", rcon_port),
-            format!("?MaxPlayers={}", max_players),
-            format!("?ServerAdminPassword={}", admin_password),
-        ];
-
-        if let Some(password) = server_password {
-            args.push(format!("?ServerPassword={}", password));
-        }
-
-        if let Some(ip) = multihome_ip {
-            if !ip.is_empty() {
-                args.push(format!("?MultiHome={}", ip));
-            }
-        }
+        let executable = platform::server_executable(&spec.install_path, &spec.server_type)?;
 
-        args.push("-log".to_string());
-        if !use_battleye {
-            args.push("-NoBattlEye".to_string());
-        }
-
-        if crossplay_enabled {
-            match server_type {
-                "ASE" => args.push("-crossplay".to_string()),
-                "ASA" => {
-                    // ASA usually has crossplay on by default, but this enforces it for Win10/Xbox
-                    // Or specific flags. Common one:
-                    // args.push("-WinLiveMaxPlayers=70".to_string()); // Example
-                    // Actually, simple -crossplay works effectively as a marker for some wrappers,
-                    // but for raw server: -ServerPlatform=ALL is often implied.
-                    // We'll add a generic launch arg if needed, but for now we'll stick to
-                    // -crossplay if ASE, and for ASA perhaps nothing special is needed OR
-                    // we add -WinLiveMaxPlayers if requested.
-                    // Let's rely on user knowledge via "ConfigEditor" args for advanced,
-                    // but for this toggle we'll add the standard recommended flag.
-                    // ASE: -crossplay. ASA: -WinLiveMaxPlayers=MaxPlayers?
-                    // Let's disable BattleEye restriction for Crossplay if needed?
-                    // Actually, Microsoft Store version requires no BattlEye often.
-                    if !use_battleye {
-                        // Crossplay on ASA without BattlEye is easier.
-                    }
-                    // For now, let's just add the arg if it's ASE.
-                    // For ASA, it's mostly default.
-                }
-                _ => {}
-            }
-            if server_type == "ASE" {
-                // args.push("-crossplay".to_string());
-                // Wait, I can't push conditionally inside the match due to ownership?
-                // No, standard Vec push is fine.
-            }
+        if !executable.exists() {
+            return Err(anyhow::anyhow!(
+                "Server executable not found at {:?}",
+                executable
+            ));
         }
 
-        // Refined Crossplay Logic
-        if crossplay_enabled {
-            if server_type == "ASE" {
-                args.push("-crossplay".to_string());
-            }
-            // For ASA, crossplay is default. We might add specific overrides later.
-        }
+        runner::prepare_prefix(&spec.runner, &spec.prefix(app_data_dir))?;
 
-        use std::os::windows::process::CommandExt;
+        let wrapped = spec.build(app_data_dir)?;
+        // Under Wine/Proton the server's own output goes to its log file, which
+        // is tailed below; the wrapper's pipes would only repeat it or add noise
+        let is_wrapped = spec.runner.kind != RunnerKind::Direct;
+        let output = || if is_wrapped { std::process::Stdio::null() } else { std::process::Stdio::piped() };
 
-        // ...
+        let mut command = Command::new(&wrapped.program);
+        command
+            .args(&wrapped.args)
+            .envs(wrapped.env)
+            .current_dir(spec.working_dir())
+            .stdout(output())
+            .stderr(output());
+        platform::hide_console_window(&mut command);
 
-        const CREATE_NO_WINDOW: u32 = 0x08000000;
-        let mut child = Command::new(&executable)
-            .args(&args)
-            .creation_flags(CREATE_NO_WINDOW)
-            .stdout(std::process::Stdio::piped())
-            .stderr(std::process::Stdio::piped())
+        let mut child = command
             .spawn()
             .context("Failed to start server process")?;
 
@@ -143,13 +154,13 @@ impl ProcessManager {
         {
              // Capture stdout
              if let Some(stdout) = child.stdout.take() {
-                let app_handle = app_handle.clone();
+                let events = events.clone();
                 tauri::async_runtime::spawn_blocking(move || {
                     use std::io::{BufRead, BufReader};
                     let reader = BufReader::new(stdout);
                     for line in reader.lines() {
                         if let Ok(l) = line {
-                            let _ = app_handle.emit("server-console-output", serde_json::json!({
+                            events.emit("server-console-output", serde_json::json!({
                                 "serverId": server_id,
                                 "line": l,
                                 "type": "stdout"
@@ -161,13 +172,13 @@ impl ProcessManager {
              
              // Capture stderr
              if let Some(stderr) = child.stderr.take() {
-                let app_handle = app_handle.clone();
+                let events = events.clone();
                 tauri::async_runtime::spawn_blocking(move || {
                     use std::io::{BufRead, BufReader};
                     let reader = BufReader::new(stderr);
                     for line in reader.lines() {
                         if let Ok(l) = line {
-                             let _ = app_handle.emit("server-console-output", serde_json::json!({
+                             events.emit("server-console-output", serde_json::json!({
                                 "serverId": server_id,
                                 "line": l,
                                 "type": "stderr"
@@ -178,31 +189,119 @@ impl ProcessManager {
              }
         }
 
+        // The only console source for wrapped runs
+        let tail_stop = if is_wrapped {
+            let stop = Arc::new(AtomicBool::new(false));
+            LogTailer::spawn(
+                events.clone(),
+                server_id,
+                platform::server_log_file(&spec.install_path),
+                stop.clone(),
+            );
+            Some(stop)
+        } else {
+            None
+        };
+
         let mut processes = self.processes.lock().unwrap();
-        processes.insert(server_id, child);
+        // Started by someone else while this one was spawning
+        if let Some(pid) = live_pid(&mut processes, server_id) {
+            let _ = child.kill();
+            let _ = child.wait();
+            if let Some(stop) = tail_stop {
+                stop.store(true, Ordering::Relaxed);
+            }
+            return Err(anyhow::anyhow!("Server {} is already running (PID {})", server_id, pid));
+        }
+        processes.insert(server_id, ManagedProcess {
+            handle: ProcessHandle::Child(child),
+            tail_stop,
+            stop_requested: false,
+        });
 
         Ok(child_id)
     }
 
-    /// Stop ARK server
-    pub fn stop_server(&self, server_id: i64) -> Result<()> {
+    /// Take over a server that is still running from an earlier session, as
+    /// found by `verify_server_process`. Its log file is tailed in place of
+    /// the stdout pipe we no longer have.
+    pub fn adopt_server(&self, events: &EventBus, spec: &LaunchSpec, pid: u32, start_time: u64) {
+        let stop = Arc::new(AtomicBool::new(false));
+        LogTailer::spawn(
+            events.clone(),
+            spec.server_id,
+            platform::server_log_file(&spec.install_path),
+            stop.clone(),
+        );
+
+        let mut processes = self.processes.lock().unwrap();
+        processes.insert(spec.server_id, ManagedProcess {
+            handle: ProcessHandle::Adopted { pid, start_time },
+            tail_stop: Some(stop),
+            stop_requested: false,
+        });
+    }
+
+    /// Kill an ARK server outright, without giving it a chance to save.
+    /// Normal stops go through `GracefulStopService`, which only ends up here
+    /// when the server ignores everything else.
+    pub fn kill_server(&self, server_id: i64) -> Result<()> {
         let mut processes = self.processes.lock().unwrap();
 
-        if let Some(mut child) = processes.remove(&server_id) {
-            child.kill().context("Failed to kill server process")?;
-            child.wait().context("Failed to wait for server process")?;
-            Ok(())
+        if let Some(mut process) = processes.remove(&server_id) {
+            // Wrappers leave the real server (and wineserver) as descendants
+            signal_descendants(process.handle.id(), Signal::Kill);
+            process.handle.kill()
         } else {
             Err(anyhow::anyhow!("Server process not found"))
         }
     }
 
+    /// Mark the server's next exit as requested. Returns false when the
+    /// server isn't tracked by this manager (spawned or adopted).
+    pub fn request_stop(&self, server_id: i64) -> bool {
+        let mut processes = self.processes.lock().unwrap();
+
+        match processes.get_mut(&server_id) {
+            Some(process) => {
+                process.stop_requested = true;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Whether a managed server has exited. Unlike `is_running` the entry is
+    /// kept, so `check_dead_processes` still reports the exit.
+    pub fn has_exited(&self, server_id: i64) -> bool {
+        let mut processes = self.processes.lock().unwrap();
+
+        match processes.get_mut(&server_id) {
+            Some(process) => !matches!(process.handle.try_wait(), Ok(None)),
+            None => true,
+        }
+    }
+
+    /// Ask a managed server to terminate (SIGTERM). Returns false when the
+    /// signal isn't available on this platform or the server isn't ours.
+    pub fn terminate_server(&self, server_id: i64) -> bool {
+        let pid = {
+            let processes = self.processes.lock().unwrap();
+            match processes.get(&server_id) {
+                Some(process) => process.handle.id(),
+                None => return false,
+            }
+        };
+
+        signal_process_tree(pid, Signal::Term)
+    }
+
     /// Check if server is running
     pub fn is_running(&self, server_id: i64) -> bool {
         let mut processes = self.processes.lock().unwrap();
 
-        if let Some(child) = processes.get_mut(&server_id) {
-            match child.try_wait() {
+        if let Some(process) = processes.get_mut(&server_id) {
+            match process.handle.try_wait() {
                 Ok(Some(_)) => {
                     // Process has exited
                     processes.remove(&server_id);
@@ -216,55 +315,20 @@ impl ProcessManager {
         }
     }
 
-    /// Restart server
-    pub fn restart_server(
-        &self,
-        app_handle: &AppHandle, // ADDED
-        server_id: i64,
-        server_type: &str,
-        install_path: &PathBuf,
-        map_name: &str,
-        session_name: &str,
-        game_port: u16,
-        query_port: u16,
-        rcon_port: u16,
-        max_players: i32,
-        server_password: Option<&str>,
-        admin_password: &str,
-        use_battleye: bool,
-        multihome_ip: Option<String>,
-        crossplay_enabled: bool,
-    ) -> Result<u32> {
-        // Stop if running
-        if self.is_running(server_id) {
-            self.stop_server(server_id)?;
-        }
+    /// PID of the server's process (or its wrapper), if we hold one.
+    pub fn pid(&self, server_id: i64) -> Option<u32> {
+        let processes = self.processes.lock().unwrap();
+        processes.get(&server_id).map(|process| process.handle.id())
+    }
 
-        // Wait a moment for cleanup
-        std::thread::sleep(std::time::Duration::from_secs(2));
-
-        // Start again
-        self.start_server(
-            app_handle, // PASS IT
-            server_id,
-            server_type,
-            install_path,
-            map_name,
-            session_name,
-            game_port,
-            query_port,
-            rcon_port,
-            max_players,
-            server_password,
-            admin_password,
-            use_battleye,
-            multihome_ip,
-            crossplay_enabled,
-        )
+    /// IDs of every server we currently hold a process for.
+    pub fn running_server_ids(&self) -> Vec<i64> {
+        let processes = self.processes.lock().unwrap();
+        processes.keys().cloned().collect()
     }
 
-    /// Check for dead processes and remove them, returning their IDs and exit status
-    pub fn check_dead_processes(&self) -> Vec<(i64, Option<i32>)> {
+    /// Check for dead processes and remove them, returning how each one exited
+    pub fn check_dead_processes(&self) -> Vec<ProcessExit> {
         let mut processes = self.processes.lock().unwrap();
         let mut dead_servers = Vec::new();
 
@@ -272,23 +336,23 @@ impl ProcessManager {
         let ids: Vec<i64> = processes.keys().cloned().collect();
 
         for id in ids {
-            if let Some(child) = processes.get_mut(&id) {
-                match child.try_wait() {
-                    Ok(Some(status)) => {
-                        dead_servers.push((id, status.code()));
-                    }
-                    Ok(None) => {} // Running
-                    Err(_) => {
-                        // Error waiting? Assume dead?
-                        dead_servers.push((id, None));
-                    }
-                }
+            if let Some(process) = processes.get_mut(&id) {
+                let exit_code = match process.handle.try_wait() {
+                    Ok(Some(code)) => code,
+                    Ok(None) => continue, // Running
+                    Err(_) => None, // Error waiting? Assume dead
+                };
+                dead_servers.push(ProcessExit {
+                    server_id: id,
+                    exit_code,
+                    requested: process.stop_requested,
+                });
             }
         }
 
         // Remove dead ones
-        for (id, _) in &dead_servers {
-            processes.remove(id);
+        for exit in &dead_servers {
+            processes.remove(&exit.server_id);
         }
 
         dead_servers
@@ -301,6 +365,123 @@ impl ProcessManager {
     }
 }
 
+/// Send `signal` to every process descended from `root_pid`, deepest first.
+fn signal_descendants(root_pid: u32, signal: Signal) {
+    let mut sys = System::new();
+    sys.refresh_processes(ProcessesToUpdate::All, true);
+
+    let mut descendants = Vec::new();
+    let mut frontier = vec![Pid::from_u32(root_pid)];
+    while let Some(parent) = frontier.pop() {
+        for (pid, process) in sys.processes() {
+            if process.parent() == Some(parent) {
+                descendants.push(*pid);
+                frontier.push(*pid);
+            }
+        }
+    }
+
+    for pid in descendants.iter().rev() {
+        if let Some(process) = sys.process(*pid) {
+            let _ = process.kill_with(signal);
+        }
+    }
+}
+
+/// Send `signal` to a process and its descendants. Returns false when the
+/// process is gone or the signal isn't supported on this platform
+/// (Windows only knows `Kill`).
+pub fn signal_process_tree(pid: u32, signal: Signal) -> bool {
+    if !sysinfo::SUPPORTED_SIGNALS.contains(&signal) || !is_pid_alive(pid) {
+        return false;
+    }
+
+    signal_descendants(pid, signal);
+
+    let mut sys = System::new();
+    sys.refresh_processes(ProcessesToUpdate::Some(&[Pid::from_u32(pid)]), true);
+    sys.process(Pid::from_u32(pid))
+        .and_then(|process| process.kill_with(signal))
+        .unwrap_or(false)
+}
+
+/// When the process started, in seconds since the epoch.
+pub fn process_start_time(pid: u32) -> Option<u64> {
+    let mut sys = System::new();
+    sys.refresh_processes(ProcessesToUpdate::Some(&[Pid::from_u32(pid)]), true);
+    sys.process(Pid::from_u32(pid)).map(|process| process.start_time())
+}
+
+/// Check that `pid` is still the server we launched and not an unrelated
+/// process that reused the PID: it must have the recorded start time (when
+/// one was recorded) and be running `executable`, either directly or as an
+/// argument of a Wine/Proton wrapper. Returns the process start time.
+pub fn verify_server_process(pid: u32, start_time: Option<u64>, executable: &Path) -> Option<u64> {
+    let mut sys = System::new();
+    sys.refresh_processes_specifics(
+        ProcessesToUpdate::Some(&[Pid::from_u32(pid)]),
+        true,
+        ProcessRefreshKind::new().with_exe(UpdateKind::Always).with_cmd(UpdateKind::Always),
+    );
+    let process = sys.process(Pid::from_u32(pid))?;
+
+    if start_time.is_some_and(|expected| expected != process.start_time()) {
+        return None;
+    }
+
+    let expected = normalize_path(executable);
+    let runs_executable = process.exe().is_some_and(|exe| normalize_path(exe) == expected)
+        || process
+            .cmd()
+            .iter()
+            .any(|arg| normalize_path(Path::new(arg)) == expected);
+
+    runs_executable.then(|| process.start_time())
+}
+
+/// Compare paths the way the OS would, also for Windows paths seen through Wine.
+fn normalize_path(path: &Path) -> String {
+    let path = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+    let path = path.to_string_lossy().replace('\\', "/");
+    if cfg!(windows) {
+        path.to_lowercase()
+    } else {
+        path
+    }
+}
+
+/// Store the PID of a freshly started server along with its start time, so
+/// it can be recognised again after the manager restarts.
+pub fn record_server_pid(conn: &Connection, server_id: i64, pid: u32) -> rusqlite::Result<()> {
+    conn.execute(
+        "UPDATE servers SET pid = ?1, pid_start_time = ?2 WHERE id = ?3",
+        (pid, process_start_time(pid).map(|t| t as i64), server_id),
+    )?;
+    Ok(())
+}
+
+/// Start time recorded with the server's PID, while it has one.
+pub fn server_pid_start_time(conn: &Connection, server_id: i64) -> Option<u64> {
+    conn.query_row("SELECT pid_start_time FROM servers WHERE id = ?1", [server_id], |row| row.get::<_, Option<i64>>(0))
+        .ok()
+        .flatten()
+        .map(|t| t as u64)
+}
+
+/// Forget the server's process once it has stopped or crashed, so the PID
+/// is never mistaken for the server after the OS hands it out again.
+pub fn clear_server_pid(conn: &Connection, server_id: i64) -> rusqlite::Result<()> {
+    conn.execute("UPDATE servers SET pid = NULL, pid_start_time = NULL WHERE id = ?1", [server_id])?;
+    Ok(())
+}
+
+/// Whether a process with this PID still exists.
+pub fn is_pid_alive(pid: u32) -> bool {
+    let mut sys = System::new();
+    sys.refresh_processes(ProcessesToUpdate::Some(&[Pid::from_u32(pid)]), true);
+    sys.process(Pid::from_u32(pid)).is_some()
+}
+
 impl Default for ProcessManager {
     fn default() -> Self {
         Self::new()
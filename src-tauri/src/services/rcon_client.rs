@@ -1,5 +1,6 @@
+use crate::models::{Player, PlayerPlatform};
 use rcon::Connection;
-use std::time::Duration;
+use regex::Regex;
 // use std::sync::Arc;
 // use tokio::sync::Mutex;
 
@@ -25,20 +26,86 @@ impl RconClient {
     }
 
     // Helper to get list of players
-    // ARK returns "No Players Connected" or a list like "Name, SteamID \n ..."
-    pub async fn get_players(ip: &str, port: u16, password: &str) -> Result<Vec<String>, String> {
+    pub async fn get_players(ip: &str, port: u16, password: &str) -> Result<Vec<Player>, String> {
         let response = Self::execute(ip, port, password, "ListPlayers").await?;
-        
-        if response.trim() == "No Players Connected" {
-            return Ok(vec![]);
+        Ok(Self::parse_players(&response))
+    }
+
+    /// Parse a `ListPlayers` response. ARK answers "No Players Connected"
+    /// (in a few spellings) or one `<index>. <name>, <id>` line per player;
+    /// names may contain commas, the ID never does. Anything else is skipped.
+    pub fn parse_players(response: &str) -> Vec<Player> {
+        let line = Regex::new(r"^(\d+)\.\s*(.*?)\s*,\s*([0-9A-Za-z]+)$").expect("valid regex");
+
+        response
+            .lines()
+            .map(str::trim)
+            .filter(|l| !l.is_empty() && !l.to_lowercase().starts_with("no players connected"))
+            .filter_map(|l| {
+                let caps = line.captures(l)?;
+                let platform_id = caps[3].to_string();
+                Some(Player {
+                    index: caps[1].parse().ok()?,
+                    name: caps[2].to_string(),
+                    platform: Self::platform_of(&platform_id),
+                    platform_id,
+                })
+            })
+            .collect()
+    }
+
+    fn platform_of(id: &str) -> PlayerPlatform {
+        if id.len() == 17 && id.starts_with("7656") && id.bytes().all(|b| b.is_ascii_digit()) {
+            PlayerPlatform::Steam
+        } else if id.len() == 32 && id.bytes().all(|b| b.is_ascii_hexdigit()) {
+            PlayerPlatform::Eos
+        } else {
+            PlayerPlatform::Unknown
         }
+    }
+}
 
-        // Parse line by line
-        let players = response.lines()
-            .map(|s| s.trim().to_string())
-            .filter(|s| !s.is_empty())
-            .collect();
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn empty_server_variants() {
+        for response in ["No Players Connected", "No Players Connected \n", "\nNo Players Connected\n\n", "no players connected."] {
+            assert!(RconClient::parse_players(response).is_empty(), "{:?}", response);
+        }
+    }
+
+    #[test]
+    fn steam_and_eos_players() {
+        let players = RconClient::parse_players(
+            "\n0. Rex Hunter, 76561198012345678\n1. Dodo, 0002a1b2c3d4e5f60718293a4b5c6d7e \n",
+        );
+        assert_eq!(players.len(), 2);
+        assert_eq!(players[0], Player {
+            index: 0,
+            name: "Rex Hunter".to_string(),
+            platform_id: "76561198012345678".to_string(),
+            platform: PlayerPlatform::Steam,
+        });
+        assert_eq!(players[1].index, 1);
+        assert_eq!(players[1].name, "Dodo");
+        assert_eq!(players[1].platform, PlayerPlatform::Eos);
+    }
+
+    #[test]
+    fn names_with_commas_and_odd_characters() {
+        let players = RconClient::parse_players("3. Smith, John, Jr., 76561198000000001\n4. ,,, 76561198000000002\n12. 名前 🦖, abc");
+        assert_eq!(players[0].name, "Smith, John, Jr.");
+        assert_eq!(players[0].platform_id, "76561198000000001");
+        assert_eq!(players[1].name, ",,");
+        assert_eq!(players[2].index, 12);
+        assert_eq!(players[2].name, "名前 🦖");
+        assert_eq!(players[2].platform, PlayerPlatform::Unknown);
+    }
 
-        Ok(players)
+    #[test]
+    fn unrelated_lines_are_skipped() {
+        assert!(RconClient::parse_players("Server received, But no response!! ").is_empty());
     }
 }
@@ -0,0 +1,180 @@
+use crate::models::{Player, Server};
+use crate::services::rcon_client::RconClient;
+use crate::AppState;
+use rcon::Connection as RconConnection;
+use rusqlite::{Connection, OptionalExtension};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::time::timeout;
+
+// One authenticated RCON connection per server, shared by everything that
+// talks to it. Commands queue behind each other on the session instead of
+// each opening and authenticating its own connection. A connection that
+// errors or times out is dropped and opened again for the next command,
+// with a backoff while the server keeps refusing.
+
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+const COMMAND_TIMEOUT: Duration = Duration::from_secs(10);
+/// Longest wait between connection attempts; the readiness probe polls
+/// every 5s, so this bounds how late a server is seen to come up.
+const MAX_BACKOFF: Duration = Duration::from_secs(10);
+
+/// Where a server's RCON listens.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RconTarget {
+    pub host: String,
+    pub port: u16,
+    pub password: String,
+}
+
+impl RconTarget {
+    /// `rcon_host` when set, else the MultiHome address the server is bound
+    /// to, else this machine.
+    pub fn new(rcon_host: Option<&str>, multihome_ip: Option<&str>, port: u16, password: &str) -> Self {
+        let host = rcon_host
+            .or(multihome_ip)
+            .map(str::trim)
+            .filter(|h| !h.is_empty())
+            .unwrap_or("127.0.0.1");
+        RconTarget { host: host.to_string(), port, password: password.to_string() }
+    }
+
+    pub fn for_server(server: &Server) -> Self {
+        let config = &server.config;
+        Self::new(
+            config.rcon_host.as_deref(),
+            config.multihome_ip.as_deref(),
+            server.ports.rcon_port,
+            &config.admin_password,
+        )
+    }
+
+    pub fn load(conn: &Connection, server_id: i64) -> Result<Self, String> {
+        conn.query_row(
+            "SELECT rcon_host, multihome_ip, rcon_port, admin_password FROM servers WHERE id = ?1",
+            [server_id],
+            |row| {
+                let rcon_host: Option<String> = row.get(0)?;
+                let multihome_ip: Option<String> = row.get(1)?;
+                Ok(Self::new(rcon_host.as_deref(), multihome_ip.as_deref(), row.get(2)?, &row.get::<_, String>(3)?))
+            },
+        )
+        .optional()
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("Server {} not found", server_id))
+    }
+}
+
+#[derive(Default)]
+struct Session {
+    target: Option<RconTarget>,
+    connection: Option<RconConnection>,
+    failures: u32,
+    retry_at: Option<Instant>,
+    last_error: String,
+}
+
+impl Session {
+    async fn connect(&mut self, target: &RconTarget) -> Result<RconConnection, String> {
+        let now = Instant::now();
+        if let Some(retry_at) = self.retry_at.filter(|at| *at > now) {
+            return Err(format!(
+                "{}; retrying in {}s",
+                self.last_error,
+                (retry_at - now).as_secs().max(1)
+            ));
+        }
+
+        let address = (target.host.as_str(), target.port);
+        let result = match timeout(CONNECT_TIMEOUT, RconConnection::builder().connect(address, &target.password)).await {
+            Ok(Ok(connection)) => Ok(connection),
+            Ok(Err(e)) => Err(format!("Failed to connect to RCON: {}", e)),
+            Err(_) => Err(format!("Timed out connecting to RCON at {}:{}", target.host, target.port)),
+        };
+
+        match &result {
+            Ok(_) => {
+                self.failures = 0;
+                self.retry_at = None;
+            }
+            Err(e) => {
+                self.failures += 1;
+                let backoff = Duration::from_secs(1 << (self.failures - 1).min(4)).min(MAX_BACKOFF);
+                self.retry_at = Some(now + backoff);
+                self.last_error = e.clone();
+            }
+        }
+        result
+    }
+}
+
+/// The RCON sessions of all servers, keyed by server ID.
+#[derive(Default)]
+pub struct RconSessions {
+    sessions: Mutex<HashMap<i64, Arc<tokio::sync::Mutex<Session>>>>,
+}
+
+impl RconSessions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn session(&self, server_id: i64) -> Arc<tokio::sync::Mutex<Session>> {
+        self.sessions.lock().unwrap().entry(server_id).or_default().clone()
+    }
+
+    /// Run `command` on the server's session, connecting first if needed.
+    /// Waits for commands already queued on it.
+    pub async fn execute(&self, server_id: i64, target: &RconTarget, command: &str) -> Result<String, String> {
+        let session = self.session(server_id);
+        let mut session = session.lock().await;
+        if session.target.as_ref() != Some(target) {
+            *session = Session { target: Some(target.clone()), ..Default::default() };
+        }
+
+        // Taken out while in use, so a command cut off midway never leaves
+        // half a response behind for the next one
+        let (mut connection, reused) = match session.connection.take() {
+            Some(connection) => (connection, true),
+            None => (session.connect(target).await?, false),
+        };
+
+        let mut result = timeout(COMMAND_TIMEOUT, connection.cmd(command)).await;
+        // The server may have restarted or dropped an idle connection
+        if reused && matches!(result, Ok(Err(_))) {
+            connection = session.connect(target).await?;
+            result = timeout(COMMAND_TIMEOUT, connection.cmd(command)).await;
+        }
+
+        match result {
+            Ok(Ok(response)) => {
+                session.connection = Some(connection);
+                Ok(response)
+            }
+            Ok(Err(e)) => Err(format!("Failed to execute RCON command: {}", e)),
+            Err(_) => Err("RCON command timed out".to_string()),
+        }
+    }
+
+    /// Drop the server's connection, e.g. once it has stopped.
+    pub fn close(&self, server_id: i64) {
+        self.sessions.lock().unwrap().remove(&server_id);
+    }
+}
+
+/// Run `command` on the server over its shared session.
+pub async fn execute(state: &AppState, server_id: i64, command: &str) -> Result<String, String> {
+    let target = {
+        let db = state.db.lock().map_err(|e| e.to_string())?;
+        let conn = db.get_connection().map_err(|e| e.to_string())?;
+        RconTarget::load(&conn, server_id)?
+    };
+    state.rcon.execute(server_id, &target, command).await
+}
+
+/// Players online on the server.
+pub async fn players(state: &AppState, server_id: i64) -> Result<Vec<Player>, String> {
+    let response = execute(state, server_id, "ListPlayers").await?;
+    Ok(RconClient::parse_players(&response))
+}
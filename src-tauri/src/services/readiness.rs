@@ -0,0 +1,252 @@
+use crate::models::{ReadinessConfig, ReadinessProbe, ServerStatus, WebhookEvent};
+use crate::services::graceful_stop::{GracefulStopService, StopOptions};
+use crate::services::launch::LaunchSpec;
+use crate::services::notifications::NotificationService;
+use crate::services::{platform, server_status, webhooks};
+use crate::services::rcon_sessions;
+use crate::AppState;
+use rusqlite::Connection;
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+use tokio::net::UdpSocket;
+use tokio::time::{sleep, timeout};
+
+const PROBE_INTERVAL: Duration = Duration::from_secs(5);
+const PROBE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Steam A2S_INFO request. Any well-formed reply, even a challenge, means
+/// the server is answering queries.
+const A2S_INFO: &[u8] = b"\xFF\xFF\xFF\xFFTSource Engine Query\0";
+
+/// Read a stored readiness config; a missing or unreadable one is the default.
+pub fn load_config(conn: &Connection, server_id: i64) -> rusqlite::Result<ReadinessConfig> {
+    let json: Option<String> = conn.query_row(
+        "SELECT readiness_probe FROM servers WHERE id = ?1",
+        [server_id],
+        |row| row.get(0),
+    )?;
+
+    Ok(json
+        .and_then(|j| serde_json::from_str(&j).ok())
+        .unwrap_or_default())
+}
+
+pub fn validate_config(config: &ReadinessConfig) -> Result<(), String> {
+    if let ReadinessProbe::LogLine { pattern } = &config.probe {
+        if pattern.trim().is_empty() {
+            return Err("Log line pattern can't be empty".to_string());
+        }
+    }
+    if config.startup_timeout_secs < 30 {
+        return Err("Startup timeout must be at least 30 seconds".to_string());
+    }
+    Ok(())
+}
+
+/// Follows the server log for a pattern, reading only complete lines.
+struct LogScanner {
+    path: PathBuf,
+    position: u64,
+    pattern: String,
+}
+
+impl LogScanner {
+    fn new(path: PathBuf, pattern: &str, from_start: bool) -> Self {
+        let position = if from_start {
+            0
+        } else {
+            std::fs::metadata(&path).map(|m| m.len()).unwrap_or(0)
+        };
+        Self { path, position, pattern: pattern.to_lowercase() }
+    }
+
+    /// Whether a line written since the last call contains the pattern.
+    fn matched(&mut self) -> bool {
+        let Ok(len) = std::fs::metadata(&self.path).map(|m| m.len()) else {
+            return false;
+        };
+        if len < self.position {
+            // ARK moves the previous log aside on boot and starts a fresh file
+            self.position = 0;
+        }
+        if len == self.position {
+            return false;
+        }
+
+        let Ok(mut file) = File::open(&self.path) else { return false };
+        if file.seek(SeekFrom::Start(self.position)).is_err() {
+            return false;
+        }
+        let mut buf = Vec::new();
+        if file.take(len - self.position).read_to_end(&mut buf).is_err() {
+            return false;
+        }
+
+        // Leave a partial last line for the next call
+        let Some(complete) = buf.iter().rposition(|b| *b == b'\n').map(|i| i + 1) else {
+            return false;
+        };
+        self.position += complete as u64;
+
+        String::from_utf8_lossy(&buf[..complete])
+            .to_lowercase()
+            .contains(&self.pattern)
+    }
+}
+
+/// A readiness probe bound to one server.
+enum Probe {
+    Log(LogScanner),
+    Query { host: String, port: u16 },
+    Rcon { server_id: i64 },
+}
+
+impl Probe {
+    fn new(config: &ReadinessConfig, spec: &LaunchSpec, server_id: i64, resume: bool) -> Self {
+        match &config.probe {
+            ReadinessProbe::LogLine { pattern } => Probe::Log(LogScanner::new(
+                platform::server_log_file(&spec.install_path),
+                pattern,
+                resume,
+            )),
+            ReadinessProbe::QueryPort => Probe::Query {
+                // With MultiHome the server only listens on that address
+                host: spec
+                    .multihome_ip
+                    .clone()
+                    .filter(|ip| !ip.is_empty())
+                    .unwrap_or_else(|| "127.0.0.1".to_string()),
+                port: spec.query_port,
+            },
+            ReadinessProbe::Rcon => Probe::Rcon { server_id },
+        }
+    }
+
+    async fn passed(&mut self, state: &AppState) -> bool {
+        match self {
+            Probe::Log(scanner) => scanner.matched(),
+            Probe::Query { host, port } => query_port_answers(host, *port).await,
+            Probe::Rcon { server_id } => matches!(
+                timeout(PROBE_TIMEOUT, rcon_sessions::execute(state, *server_id, "ListPlayers")).await,
+                Ok(Ok(_))
+            ),
+        }
+    }
+}
+
+async fn query_port_answers(host: &str, port: u16) -> bool {
+    let Ok(socket) = UdpSocket::bind("0.0.0.0:0").await else { return false };
+    if socket.send_to(A2S_INFO, (host, port)).await.is_err() {
+        return false;
+    }
+
+    let mut buf = [0u8; 1400];
+    matches!(
+        timeout(PROBE_TIMEOUT, socket.recv(&mut buf)).await,
+        Ok(Ok(n)) if n >= 5 && buf[..4] == [0xFF; 4]
+    )
+}
+
+/// Watch a freshly started server until its probe passes, then mark it
+/// `running` and emit `server-ready`. If the startup timeout passes first it
+/// is marked `failed` and stopped. `resume` is for servers adopted while
+/// still loading: their whole current log is scanned.
+pub fn spawn(state: AppState, server_id: i64, pid: u32, resume: bool) {
+    tauri::async_runtime::spawn(async move {
+        if let Err(e) = watch(&state, server_id, pid, resume).await {
+            eprintln!("Readiness check for server {} failed: {}", server_id, e);
+        }
+    });
+}
+
+async fn watch(state: &AppState, server_id: i64, pid: u32, resume: bool) -> Result<(), String> {
+    let (spec, config) = {
+        let db = state.db.lock().map_err(|e| e.to_string())?;
+        let conn = db.get_connection().map_err(|e| e.to_string())?;
+        (
+            LaunchSpec::load(&conn, server_id).map_err(|e| e.to_string())?,
+            load_config(&conn, server_id).map_err(|e| e.to_string())?,
+        )
+    };
+
+    let started = Instant::now();
+    let deadline = started + Duration::from_secs(config.startup_timeout_secs);
+    let mut probe = Probe::new(&config, &spec, server_id, resume);
+
+    loop {
+        sleep(PROBE_INTERVAL).await;
+
+        // Stopped, crashed or restarted meanwhile; whoever did that owns the status now
+        let process_manager = &state.process_manager;
+        if process_manager.pid(server_id) != Some(pid) || process_manager.has_exited(server_id) {
+            return Ok(());
+        }
+        if !is_starting(state, server_id)? {
+            return Ok(());
+        }
+
+        if probe.passed(state).await {
+            if finish_starting(state, server_id, ServerStatus::Running, "readiness probe passed")? {
+                let seconds = started.elapsed().as_secs();
+                println!("Server {} is ready after {}s", server_id, seconds);
+                state.events.emit("server-ready", serde_json::json!({
+                    "serverId": server_id,
+                    "secondsToReady": seconds
+                }));
+                webhooks::fire(state, WebhookEvent::Start, server_id, serde_json::json!({
+                    "secondsToReady": seconds
+                }));
+                state.events.emit("server-status-changed", serde_json::json!({
+                    "id": server_id,
+                    "status": "running"
+                }));
+            }
+            return Ok(());
+        }
+
+        if Instant::now() >= deadline {
+            break;
+        }
+    }
+
+    let cause = format!("not ready within {}s", config.startup_timeout_secs);
+    if !finish_starting(state, server_id, ServerStatus::Failed, &cause)? {
+        return Ok(());
+    }
+    let message = format!(
+        "Server {} did not become ready within {}s and was stopped.",
+        spec.session_name, config.startup_timeout_secs
+    );
+    println!("{}", message);
+    state.events.emit("server-status-changed", serde_json::json!({
+        "id": server_id,
+        "status": "failed",
+        "reason": message
+    }));
+
+    // Players can't be on a server that never finished loading
+    let options = {
+        let db = state.db.lock().map_err(|e| e.to_string())?;
+        StopOptions::from_settings(&db).without_warning()
+    };
+    GracefulStopService::stop(state, server_id, &options, &cause).await?;
+
+    let _ = NotificationService::send_notification(state, "Server Failed to Start", &message).await;
+    Ok(())
+}
+
+fn is_starting(state: &AppState, server_id: i64) -> Result<bool, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let conn = db.get_connection().map_err(|e| e.to_string())?;
+    Ok(server_status::current(&conn, server_id)? == ServerStatus::Starting)
+}
+
+/// Move the server from `starting` to `status`. Returns false when it had
+/// already left `starting`, e.g. because it was stopped meanwhile.
+fn finish_starting(state: &AppState, server_id: i64, status: ServerStatus, cause: &str) -> Result<bool, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let conn = db.get_connection().map_err(|e| e.to_string())?;
+    server_status::transition_from(&conn, server_id, &[ServerStatus::Starting], status, cause)
+}
@@ -0,0 +1,171 @@
+use crate::models::RestartPolicy;
+use rusqlite::Connection;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::time::{Duration, Instant};
+
+/// Read a stored restart policy; a missing or unreadable one is the default.
+pub fn load_policy(conn: &Connection, server_id: i64) -> rusqlite::Result<RestartPolicy> {
+    let json: Option<String> = conn.query_row(
+        "SELECT restart_policy FROM servers WHERE id = ?1",
+        [server_id],
+        |row| row.get(0),
+    )?;
+
+    Ok(json
+        .and_then(|j| serde_json::from_str(&j).ok())
+        .unwrap_or_default())
+}
+
+pub fn validate_policy(policy: &RestartPolicy) -> Result<(), String> {
+    if policy.max_restarts == 0 {
+        return Err("Allow at least one restart, or turn auto-restart off".to_string());
+    }
+    if policy.window_secs == 0 {
+        return Err("Restart window can't be zero".to_string());
+    }
+    if policy.max_backoff_secs < policy.initial_backoff_secs {
+        return Err("Maximum backoff can't be shorter than the initial backoff".to_string());
+    }
+    Ok(())
+}
+
+/// What to do about a crash.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RestartDecision {
+    /// Restart once this much time has passed
+    After(Duration),
+    /// Too many restarts in the window; stop trying
+    GiveUp,
+}
+
+/// Automatic restarts per server, kept by the monitor between ticks.
+#[derive(Default)]
+pub struct RestartTracker {
+    /// When each recent automatic restart was scheduled
+    history: HashMap<i64, VecDeque<Instant>>,
+    /// Restarts waiting out their backoff
+    pending: HashMap<i64, Instant>,
+    /// Servers marked `crash-looping`
+    given_up: HashSet<i64>,
+}
+
+impl RestartTracker {
+    /// Record a crash and decide whether, and when, to restart.
+    pub fn on_crash(&mut self, server_id: i64, policy: &RestartPolicy) -> RestartDecision {
+        let now = Instant::now();
+        let window = Duration::from_secs(policy.window_secs);
+        let history = self.history.entry(server_id).or_default();
+        while history.front().is_some_and(|t| now.duration_since(*t) > window) {
+            history.pop_front();
+        }
+
+        if history.len() >= policy.max_restarts as usize {
+            self.pending.remove(&server_id);
+            self.given_up.insert(server_id);
+            return RestartDecision::GiveUp;
+        }
+
+        let delay = backoff(policy, history.len());
+        history.push_back(now);
+        self.pending.insert(server_id, now + delay);
+        RestartDecision::After(delay)
+    }
+
+    /// Servers whose backoff has run out, removed from the pending list.
+    pub fn take_due(&mut self) -> Vec<i64> {
+        let now = Instant::now();
+        let due: Vec<i64> = self
+            .pending
+            .iter()
+            .filter(|(_, at)| **at <= now)
+            .map(|(id, _)| *id)
+            .collect();
+        for id in &due {
+            self.pending.remove(id);
+        }
+        due
+    }
+
+    pub fn given_up(&self) -> Vec<i64> {
+        self.given_up.iter().copied().collect()
+    }
+
+    /// Forget a server's crashes, e.g. once an operator has reset it.
+    pub fn reset(&mut self, server_id: i64) {
+        self.history.remove(&server_id);
+        self.pending.remove(&server_id);
+        self.given_up.remove(&server_id);
+    }
+}
+
+/// Wait before the restart that follows `previous` restarts in the window.
+fn backoff(policy: &RestartPolicy, previous: usize) -> Duration {
+    let factor = 1u64 << previous.min(16);
+    Duration::from_secs(
+        policy
+            .initial_backoff_secs
+            .saturating_mul(factor)
+            .min(policy.max_backoff_secs),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy(max_restarts: u32) -> RestartPolicy {
+        RestartPolicy { max_restarts, window_secs: 600, initial_backoff_secs: 10, max_backoff_secs: 60 }
+    }
+
+    #[test]
+    fn gives_up_once_max_restarts_are_used() {
+        let mut tracker = RestartTracker::default();
+        let policy = policy(3);
+        for _ in 0..3 {
+            assert!(matches!(tracker.on_crash(1, &policy), RestartDecision::After(_)));
+        }
+        assert_eq!(tracker.on_crash(1, &policy), RestartDecision::GiveUp);
+        assert_eq!(tracker.given_up(), vec![1]);
+        // Other servers have their own count
+        assert!(matches!(tracker.on_crash(2, &policy), RestartDecision::After(_)));
+
+        tracker.reset(1);
+        assert!(tracker.given_up().is_empty());
+        assert!(matches!(tracker.on_crash(1, &policy), RestartDecision::After(_)));
+    }
+
+    #[test]
+    fn crashes_outside_the_window_are_forgotten() {
+        let mut tracker = RestartTracker::default();
+        let policy = policy(2);
+        let long_ago = Instant::now() - Duration::from_secs(policy.window_secs + 1);
+        tracker.history.insert(1, VecDeque::from([long_ago, long_ago]));
+
+        // Both earlier restarts have aged out, so this one waits the initial backoff
+        assert_eq!(tracker.on_crash(1, &policy), RestartDecision::After(Duration::from_secs(10)));
+        assert_eq!(tracker.history[&1].len(), 1);
+    }
+
+    #[test]
+    fn backoff_doubles_up_to_the_maximum() {
+        let policy = policy(10);
+        let delays: Vec<u64> = (0..6).map(|n| backoff(&policy, n).as_secs()).collect();
+        assert_eq!(delays, vec![10, 20, 40, 60, 60, 60]);
+        assert_eq!(backoff(&policy, 100), Duration::from_secs(60));
+
+        let mut tracker = RestartTracker::default();
+        let delays: Vec<RestartDecision> = (0..5).map(|_| tracker.on_crash(1, &policy)).collect();
+        assert_eq!(delays.last(), Some(&RestartDecision::After(Duration::from_secs(60))));
+    }
+
+    #[test]
+    fn restarts_come_due_after_their_backoff() {
+        let mut tracker = RestartTracker::default();
+        let policy = RestartPolicy { initial_backoff_secs: 0, ..policy(3) };
+        tracker.on_crash(1, &policy);
+        tracker.pending.insert(2, Instant::now() + Duration::from_secs(60));
+
+        assert_eq!(tracker.take_due(), vec![1]);
+        assert!(tracker.take_due().is_empty());
+    }
+}
@@ -0,0 +1,146 @@
+use crate::models::{RunnerConfig, RunnerKind};
+use anyhow::{Context, Result};
+use rusqlite::Connection;
+use std::path::{Path, PathBuf};
+
+/// The program, arguments and extra environment that actually get spawned
+/// once the server executable has been wrapped by its runner.
+pub struct WrappedCommand {
+    pub program: PathBuf,
+    pub args: Vec<String>,
+    pub env: Vec<(String, String)>,
+}
+
+/// Read the runner settings stored for a server.
+pub fn load_runner_config(conn: &Connection, server_id: i64) -> rusqlite::Result<RunnerConfig> {
+    conn.query_row(
+        "SELECT runner, runner_path, compat_prefix FROM servers WHERE id = ?1",
+        [server_id],
+        |row| {
+            Ok(RunnerConfig {
+                kind: row
+                    .get::<_, Option<String>>(0)?
+                    .and_then(|k| k.parse().ok())
+                    .unwrap_or_default(),
+                path: row.get(1)?,
+                prefix: row.get(2)?,
+            })
+        },
+    )
+}
+
+/// Compat prefix used when the server doesn't configure one explicitly.
+pub fn default_prefix(app_data_dir: &Path, server_id: i64) -> PathBuf {
+    app_data_dir.join("compat").join(format!("server_{}", server_id))
+}
+
+pub fn resolve_prefix(runner: &RunnerConfig, app_data_dir: &Path, server_id: i64) -> PathBuf {
+    match &runner.prefix {
+        Some(prefix) if !prefix.trim().is_empty() => PathBuf::from(prefix),
+        _ => default_prefix(app_data_dir, server_id),
+    }
+}
+
+/// Make sure the compat prefix exists before Wine/Proton is pointed at it.
+/// Wine populates an empty WINEPREFIX on first run, Proton does the same
+/// for `<STEAM_COMPAT_DATA_PATH>/pfx`.
+pub fn prepare_prefix(runner: &RunnerConfig, prefix: &Path) -> Result<()> {
+    if runner.kind == RunnerKind::Direct {
+        return Ok(());
+    }
+
+    std::fs::create_dir_all(prefix)
+        .with_context(|| format!("Failed to create compat prefix at {:?}", prefix))?;
+    Ok(())
+}
+
+/// Check the runner settings before they are saved or used.
+pub fn validate(runner: &RunnerConfig) -> Result<(), String> {
+    match runner.kind {
+        RunnerKind::Direct => Ok(()),
+        RunnerKind::Wine => match runner.path.as_deref().map(str::trim) {
+            Some(path) if !path.is_empty() && path.contains(std::path::MAIN_SEPARATOR) => {
+                if Path::new(path).exists() {
+                    Ok(())
+                } else {
+                    Err(format!("Wine binary not found at {}", path))
+                }
+            }
+            // Bare names such as "wine" or "wine64" are resolved from PATH
+            _ => Ok(()),
+        },
+        RunnerKind::Proton => {
+            let path = runner
+                .path
+                .as_deref()
+                .map(str::trim)
+                .filter(|p| !p.is_empty())
+                .ok_or("Proton runner needs the path of a Proton install")?;
+            if Path::new(path).join("proton").exists() {
+                Ok(())
+            } else {
+                Err(format!("No `proton` script found in {}", path))
+            }
+        }
+    }
+}
+
+/// Wrap the server executable in the configured runner. The ARK arguments
+/// are passed through untouched, so the same ?-options work either way.
+pub fn wrap(
+    runner: &RunnerConfig,
+    executable: &Path,
+    args: Vec<String>,
+    prefix: &Path,
+    steam_client_dir: &Path,
+) -> Result<WrappedCommand> {
+    let exe = executable.to_string_lossy().to_string();
+
+    match runner.kind {
+        RunnerKind::Direct => Ok(WrappedCommand {
+            program: executable.to_path_buf(),
+            args,
+            env: Vec::new(),
+        }),
+        RunnerKind::Wine => {
+            let wine = runner
+                .path
+                .as_deref()
+                .map(str::trim)
+                .filter(|p| !p.is_empty())
+                .unwrap_or("wine");
+
+            let mut wrapped_args = vec![exe];
+            wrapped_args.extend(args);
+
+            Ok(WrappedCommand {
+                program: PathBuf::from(wine),
+                args: wrapped_args,
+                env: vec![
+                    ("WINEPREFIX".to_string(), prefix.to_string_lossy().to_string()),
+                    ("WINEDEBUG".to_string(), "-all".to_string()),
+                ],
+            })
+        }
+        RunnerKind::Proton => {
+            validate(runner).map_err(anyhow::Error::msg)?;
+            let proton_dir = PathBuf::from(runner.path.as_deref().unwrap_or_default().trim());
+
+            let mut wrapped_args = vec!["run".to_string(), exe];
+            wrapped_args.extend(args);
+
+            Ok(WrappedCommand {
+                program: proton_dir.join("proton"),
+                args: wrapped_args,
+                env: vec![
+                    ("STEAM_COMPAT_DATA_PATH".to_string(), prefix.to_string_lossy().to_string()),
+                    (
+                        "STEAM_COMPAT_CLIENT_INSTALL_PATH".to_string(),
+                        steam_client_dir.to_string_lossy().to_string(),
+                    ),
+                    ("WINEDEBUG".to_string(), "-all".to_string()),
+                ],
+            })
+        }
+    }
+}
@@ -1,22 +1,22 @@
-use crate::commands::{server, backup, rcon};
+use crate::commands::{backup, rcon};
+use crate::services::{lifecycle, moderation};
 use crate::AppState;
-use tauri::Manager;
 use std::time::Duration;
 use cron::Schedule as CronSchedule;
 use std::str::FromStr;
 use chrono::Local;
 
 pub struct SchedulerService {
-    app_handle: tauri::AppHandle,
+    state: AppState,
 }
 
 impl SchedulerService {
-    pub fn new(app_handle: tauri::AppHandle) -> Self {
-        SchedulerService { app_handle }
+    pub fn new(state: AppState) -> Self {
+        SchedulerService { state }
     }
 
     pub fn start(&self) {
-        let app_handle = self.app_handle.clone();
+        let state = self.state.clone();
         
         tauri::async_runtime::spawn(async move {
             println!("Scheduler Service started.");
@@ -25,16 +25,17 @@ impl SchedulerService {
                 // We align to the minute boundary roughly
                 tokio::time::sleep(Duration::from_secs(60)).await;
                 
-                if let Err(e) = Self::check_and_run_tasks(&app_handle).await {
+                if let Err(e) = Self::check_and_run_tasks(&state).await {
                      eprintln!("Scheduler Error: {}", e);
                 }
+                if let Err(e) = moderation::lift_expired(&state).await {
+                    eprintln!("Failed to lift expired bans: {}", e);
+                }
             }
         });
     }
 
-    async fn check_and_run_tasks(app_handle: &tauri::AppHandle) -> Result<(), String> {
-        let state = app_handle.state::<AppState>();
-        
+    async fn check_and_run_tasks(state: &AppState) -> Result<(), String> {
         // 1. Fetch active schedules
         let schedules = {
             let db = state.db.lock().map_err(|e| e.to_string())?;
@@ -69,20 +70,21 @@ impl SchedulerService {
                         // It's due now (or very close)
                         
                         println!("Executing scheduled task: {} for server {}", task_type, server_id);
+                        let cause = format!("schedule {}", id);
                         
                         match task_type.as_str() {
                             "restart" => {
-                                let _ = server::restart_server(app_handle.clone(), state.clone(), server_id).await;
+                                let _ = lifecycle::restart_server(state, server_id, &cause).await;
                             },
                             "backup" => {
-                                let _ = backup::create_backup(state.clone(), server_id, "auto".to_string(), None).await;
+                                let _ = backup::backup_server(state, server_id, "auto".to_string(), None).await;
                             },
                             "update" => {
-                                let _ = server::update_server(app_handle.clone(), state.clone(), server_id).await;
+                                let _ = lifecycle::update_server(state, server_id, &cause).await;
                             },
                             "broadcast" => {
                                 if let Some(msg) = &payload {
-                                    let _ = rcon::send_rcon_command(state.clone(), server_id, format!("Broadcast {}", msg)).await;
+                                    let _ = rcon::send_to_server(state, server_id, format!("Broadcast {}", msg)).await;
                                 }
                             },
                             _ => {}
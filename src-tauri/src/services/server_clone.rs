@@ -0,0 +1,260 @@
+use crate::models::{CloneInstallMode, CloneServerOptions, ServerPorts, ServerStatus};
+use crate::services::port_allocator::{self, CheckScope};
+use crate::services::{platform, server_status, servers};
+use anyhow::{Context, Result};
+use rusqlite::{Connection, OptionalExtension};
+use std::fs;
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+/// Everything needed to copy files for a clone once its row exists.
+#[derive(Debug, Clone)]
+pub struct ClonePlan {
+    pub server_id: i64,
+    pub server_type: String,
+    pub source_install: PathBuf,
+    pub target_install: PathBuf,
+    pub install_mode: CloneInstallMode,
+    pub copy_saves: bool,
+    /// Backup folder to take saves and configs from
+    pub backup: Option<PathBuf>,
+}
+
+/// Check the request and create the clone's row, marked `installing` until
+/// its files are in place.
+pub fn prepare(conn: &Connection, source_id: i64, new_name: &str, options: &CloneServerOptions) -> Result<ClonePlan, String> {
+    let source = servers::load(conn, source_id)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("Server {} not found", source_id))?;
+
+    let new_name = new_name.trim();
+    if new_name.is_empty() {
+        return Err("Clone name can't be empty".to_string());
+    }
+    let name_taken: bool = conn
+        .query_row("SELECT EXISTS(SELECT 1 FROM servers WHERE name = ?1)", [new_name], |row| row.get(0))
+        .map_err(|e| e.to_string())?;
+    if name_taken {
+        return Err(format!("A server named \"{}\" already exists", new_name));
+    }
+
+    let target_install = match options.install_path.as_deref().map(str::trim).filter(|p| !p.is_empty()) {
+        Some(path) => PathBuf::from(path),
+        None => source
+            .install_path
+            .parent()
+            .unwrap_or(&source.install_path)
+            .join(new_name.replace(|c: char| !c.is_alphanumeric() && c != '-', "_")),
+    };
+    if target_install == source.install_path {
+        return Err("The clone needs its own install path".to_string());
+    }
+    if fs::read_dir(&target_install).is_ok_and(|mut entries| entries.next().is_some()) {
+        return Err(format!("{} already exists and is not empty", target_install.display()));
+    }
+
+    let backup = match options.from_backup_id {
+        Some(backup_id) => {
+            let (path, backup_server): (String, i64) = conn
+                .query_row("SELECT file_path, server_id FROM backups WHERE id = ?1", [backup_id], |row| {
+                    Ok((row.get(0)?, row.get(1)?))
+                })
+                .optional()
+                .map_err(|e| e.to_string())?
+                .ok_or_else(|| format!("Backup {} not found", backup_id))?;
+            if backup_server != source_id {
+                return Err(format!("Backup {} belongs to another server", backup_id));
+            }
+            let path = PathBuf::from(path);
+            if !path.exists() {
+                return Err("Backup folder not found".to_string());
+            }
+            Some(path)
+        }
+        None => None,
+    };
+
+    let multihome_ip = source.config.multihome_ip.as_deref();
+    let ports = match &options.ports {
+        Some(ports) => {
+            let conflicts = port_allocator::find_conflicts(conn, None, ports, multihome_ip, CheckScope::AllServers)
+                .map_err(|e| e.to_string())?;
+            if !conflicts.is_empty() {
+                return Err(port_allocator::describe(conn, &conflicts, multihome_ip));
+            }
+            ports.clone()
+        }
+        None => port_allocator::suggest_ports(conn, multihome_ip, 1)
+            .map_err(|e| e.to_string())?
+            .into_iter()
+            .next()
+            .ok_or("No free ports left for the clone")?,
+    };
+
+    let server_id = insert(conn, source_id, new_name, &target_install, &ports, options).map_err(|e| e.to_string())?;
+
+    Ok(ClonePlan {
+        server_id,
+        server_type: source.server_type.to_string(),
+        source_install: source.install_path,
+        target_install,
+        install_mode: options.install_mode,
+        copy_saves: options.copy_saves,
+        backup,
+    })
+}
+
+fn insert(
+    conn: &Connection,
+    source_id: i64,
+    name: &str,
+    install_path: &Path,
+    ports: &ServerPorts,
+    options: &CloneServerOptions,
+) -> rusqlite::Result<i64> {
+    conn.execute(
+        "INSERT INTO servers (name, server_type, install_path, status, game_port, query_port, rcon_port,
+         max_players, server_password, admin_password, map_name, session_name, motd, battleye_enabled,
+         multihome_ip, crossplay_enabled, auto_restart, auto_update, runner, runner_path, compat_prefix,
+         launch_profile, readiness_probe, restart_policy, shared_install_of)
+         SELECT ?1, server_type, ?2, 'installing', ?3, ?4, ?5,
+         max_players, server_password, admin_password, map_name, ?1, motd, battleye_enabled,
+         multihome_ip, crossplay_enabled, auto_restart, CASE WHEN ?6 THEN 0 ELSE auto_update END,
+         runner, runner_path, compat_prefix,
+         CASE WHEN ?7 THEN launch_profile END, readiness_probe, restart_policy,
+         CASE WHEN ?6 THEN COALESCE(shared_install_of, id) END
+         FROM servers WHERE id = ?8",
+        rusqlite::params![
+            name,
+            install_path.to_string_lossy().to_string(),
+            ports.game_port,
+            ports.query_port,
+            ports.rcon_port,
+            // A shared install is updated through its source
+            options.install_mode == CloneInstallMode::Shared,
+            options.copy_launch_profile,
+            source_id,
+        ],
+    )?;
+    let server_id = conn.last_insert_rowid();
+    server_status::record_created(conn, server_id, ServerStatus::Installing, &format!("cloned from server {}", source_id))?;
+
+    if options.copy_mods {
+        conn.execute(
+            "INSERT INTO mods (server_id, mod_id, name, version, author, description, workshop_url,
+             server_type, enabled, load_order)
+             SELECT ?1, mod_id, name, version, author, description, workshop_url, server_type, enabled, load_order
+             FROM mods WHERE server_id = ?2",
+            (server_id, source_id),
+        )?;
+    }
+
+    Ok(server_id)
+}
+
+/// Lay out the clone's install: game files copied or linked, configs and
+/// optionally saves copied from the source or the chosen backup.
+pub fn copy_files(plan: &ClonePlan) -> Result<()> {
+    let source = &plan.source_install;
+    let target = &plan.target_install;
+    let saved = Path::new("ShooterGame").join("Saved");
+    fs::create_dir_all(target).context("Failed to create the clone's install folder")?;
+
+    match plan.install_mode {
+        CloneInstallMode::Copy => {
+            for entry in WalkDir::new(source)
+                .min_depth(1)
+                .into_iter()
+                .filter_entry(|e| e.path().strip_prefix(source).map_or(true, |p| p != saved))
+            {
+                let entry = entry?;
+                let destination = target.join(entry.path().strip_prefix(source)?);
+                if entry.file_type().is_dir() {
+                    fs::create_dir_all(&destination)?;
+                } else {
+                    fs::copy(entry.path(), &destination)
+                        .with_context(|| format!("Failed to copy {}", entry.path().display()))?;
+                }
+            }
+        }
+        CloneInstallMode::Shared => {
+            for entry in fs::read_dir(source)? {
+                let entry = entry?;
+                if entry.file_name() == "ShooterGame" {
+                    let game_dir = target.join("ShooterGame");
+                    fs::create_dir_all(&game_dir)?;
+                    for inner in fs::read_dir(entry.path())? {
+                        let inner = inner?;
+                        if inner.file_name() != "Saved" {
+                            link(&inner.path(), &game_dir.join(inner.file_name()))?;
+                        }
+                    }
+                } else {
+                    link(&entry.path(), &target.join(entry.file_name()))?;
+                }
+            }
+        }
+    }
+
+    let config_source = match &plan.backup {
+        Some(backup) if backup.join("Config").exists() => backup.join("Config"),
+        _ => platform::config_dir(source, &plan.server_type),
+    };
+    if config_source.exists() {
+        copy_dir(&config_source, &platform::config_dir(target, &plan.server_type))
+            .context("Failed to copy the config INIs")?;
+    }
+
+    let saves_source = match &plan.backup {
+        Some(backup) => Some(backup.join("SavedArks")),
+        None if plan.copy_saves => Some(platform::saved_arks_dir(source)),
+        None => None,
+    };
+    if let Some(saves) = saves_source.filter(|s| s.exists()) {
+        copy_dir(&saves, &platform::saved_arks_dir(target)).context("Failed to copy the saves")?;
+    }
+
+    Ok(())
+}
+
+/// Refuse to update a clone that shares its game files: SteamCMD would write
+/// through the links into the source's install, maybe while it is running.
+pub fn ensure_own_install(conn: &Connection, server_id: i64) -> Result<(), String> {
+    let source: Option<i64> = conn
+        .query_row("SELECT shared_install_of FROM servers WHERE id = ?1", [server_id], |row| row.get(0))
+        .map_err(|e| e.to_string())?;
+    match source {
+        Some(source) => Err(format!(
+            "Server {} shares its game files with server {}; update server {} instead",
+            server_id, source, source
+        )),
+        None => Ok(()),
+    }
+}
+
+/// Directories are linked, files hard-linked where the volume allows and copied otherwise.
+fn link(original: &Path, link: &Path) -> Result<()> {
+    if original.is_dir() {
+        platform::symlink_dir(original, link)
+            .with_context(|| format!("Failed to link {}", original.display()))?;
+    } else if fs::hard_link(original, link).is_err() {
+        fs::copy(original, link)?;
+    }
+    Ok(())
+}
+
+fn copy_dir(source: &Path, target: &Path) -> Result<()> {
+    for entry in WalkDir::new(source).min_depth(1) {
+        let entry = entry?;
+        let destination = target.join(entry.path().strip_prefix(source)?);
+        if entry.file_type().is_dir() {
+            fs::create_dir_all(&destination)?;
+        } else {
+            if let Some(parent) = destination.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::copy(entry.path(), &destination)?;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,296 @@
+use crate::models::{DetectedInstall, LaunchProfile, ServerPorts, ServerStatus, ServerType};
+use crate::services::{platform, server_status};
+use crate::services::port_allocator::{self, CheckScope};
+use anyhow::{anyhow, Result};
+use rand::RngCore;
+use rusqlite::Connection;
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::time::SystemTime;
+use walkdir::WalkDir;
+
+const ASE_APP_ID: &str = "376030";
+const ASA_APP_ID: &str = "2430930";
+
+/// ARK's own defaults, used for anything the INI leaves out.
+pub const DEFAULT_GAME_PORT: u16 = 7777;
+pub const DEFAULT_QUERY_PORT: u16 = 27015;
+pub const DEFAULT_RCON_PORT: u16 = 27020;
+pub const DEFAULT_MAX_PLAYERS: i32 = 70;
+
+/// Values of an INI file keyed by lowercase `(section, key)`; the first
+/// occurrence of a key wins.
+pub struct IniValues(HashMap<(String, String), String>);
+
+impl IniValues {
+    pub fn parse(content: &str) -> Self {
+        let mut values = HashMap::new();
+        let mut section = String::new();
+
+        for line in content.lines() {
+            let line = line.trim();
+            if line.starts_with('[') && line.ends_with(']') {
+                section = line[1..line.len() - 1].trim().to_lowercase();
+                continue;
+            }
+            if line.starts_with(';') || line.starts_with('#') {
+                continue;
+            }
+            if let Some((key, value)) = line.split_once('=') {
+                values
+                    .entry((section.clone(), key.trim().to_lowercase()))
+                    .or_insert_with(|| value.trim().to_string());
+            }
+        }
+
+        Self(values)
+    }
+
+    pub fn load(path: &Path) -> Option<Self> {
+        fs::read_to_string(path).ok().map(|c| Self::parse(&c))
+    }
+
+    /// A non-empty value.
+    pub fn get(&self, section: &str, key: &str) -> Option<&str> {
+        self.0
+            .get(&(section.to_lowercase(), key.to_lowercase()))
+            .map(String::as_str)
+            .filter(|v| !v.is_empty())
+    }
+
+    pub fn get_parsed<T: std::str::FromStr>(&self, section: &str, key: &str) -> Option<T> {
+        self.get(section, key).and_then(|v| v.parse().ok())
+    }
+}
+
+/// ASE or ASA, from the Steam appmanifest or else the server binaries.
+pub fn detect_server_type(install_path: &Path) -> Option<(ServerType, Option<String>)> {
+    let steamapps = install_path.join("steamapps");
+    let manifest = [(ASA_APP_ID, ServerType::ASA), (ASE_APP_ID, ServerType::ASE)]
+        .into_iter()
+        .find_map(|(app_id, server_type)| {
+            let content = fs::read_to_string(steamapps.join(format!("appmanifest_{}.acf", app_id))).ok()?;
+            Some((server_type, content))
+        });
+    if let Some((server_type, content)) = manifest {
+        let build_id = regex::Regex::new(r#""buildid"\s+"(\d+)""#)
+            .ok()
+            .and_then(|re| re.captures(&content))
+            .map(|c| c[1].to_string());
+        return Some((server_type, build_id));
+    }
+
+    let binaries = install_path.join("ShooterGame").join("Binaries");
+    if binaries.join("Win64").join("ArkAscendedServer.exe").exists() {
+        return Some((ServerType::ASA, None));
+    }
+    if binaries.join("Win64").join("ShooterGameServer.exe").exists()
+        || binaries.join("Linux").join("ShooterGameServer").exists()
+    {
+        return Some((ServerType::ASE, None));
+    }
+    None
+}
+
+/// The map of the newest world save. ASA keeps each map in its own folder,
+/// and both games leave dated copies such as `TheIsland_01.02.2024_10.00.00.ark`.
+fn detect_map(install_path: &Path) -> Option<String> {
+    let dated = regex::Regex::new(r"_\d{2}\.\d{2}\.\d{4}_\d{2}\.\d{2}\.\d{2}$").ok()?;
+
+    WalkDir::new(platform::saved_arks_dir(install_path))
+        .max_depth(2)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_file() && e.path().extension().is_some_and(|x| x == "ark"))
+        .filter_map(|e| {
+            let modified = e.metadata().ok()?.modified().ok()?;
+            Some((modified, e.path().file_stem()?.to_string_lossy().to_string()))
+        })
+        .max_by_key(|(modified, _): &(SystemTime, String)| *modified)
+        .map(|(_, stem)| dated.replace(&stem, "").to_string())
+}
+
+/// Read what an existing install is configured with. Nothing is written.
+pub fn detect(install_path: &Path) -> Result<DetectedInstall> {
+    if !install_path.is_dir() {
+        return Err(anyhow!("{} is not a folder", install_path.display()));
+    }
+    let (server_type, build_id) = detect_server_type(install_path)
+        .ok_or_else(|| anyhow!("No ARK server found in {}: no appmanifest or server binary", install_path.display()))?;
+    let type_name = server_type.to_string();
+    let mut warnings = Vec::new();
+
+    // The INIs may have been written by a server on the other platform
+    let config_root = install_path.join("ShooterGame").join("Saved").join("Config");
+    let ini = [platform::config_platform(&type_name), "WindowsServer", "LinuxServer"]
+        .iter()
+        .find_map(|platform| IniValues::load(&config_root.join(platform).join("GameUserSettings.ini")))
+        .unwrap_or_else(|| {
+            warnings.push("No GameUserSettings.ini found; using ARK's default settings".to_string());
+            IniValues::parse("")
+        });
+
+    let ports = ServerPorts {
+        game_port: ini.get_parsed("SessionSettings", "Port").unwrap_or(DEFAULT_GAME_PORT),
+        query_port: ini.get_parsed("SessionSettings", "QueryPort").unwrap_or(DEFAULT_QUERY_PORT),
+        rcon_port: ini.get_parsed("ServerSettings", "RCONPort").unwrap_or(DEFAULT_RCON_PORT),
+    };
+
+    let max_players = ini
+        .get_parsed("/Script/Engine.GameSession", "MaxPlayers")
+        .or_else(|| ini.get_parsed("ServerSettings", "MaxPlayers"))
+        .unwrap_or(DEFAULT_MAX_PLAYERS);
+
+    let admin_password = ini.get("ServerSettings", "ServerAdminPassword").map(String::from);
+    if admin_password.is_none() {
+        warnings.push("No admin password set; a random one will be generated for RCON".to_string());
+    }
+
+    let map_name = match detect_map(install_path) {
+        Some(map) => map,
+        None => {
+            warnings.push("No world save found; assuming The Island".to_string());
+            match server_type {
+                ServerType::ASE => "TheIsland".to_string(),
+                ServerType::ASA => "TheIsland_WP".to_string(),
+            }
+        }
+    };
+
+    let active_mods = ini
+        .get("ServerSettings", "ActiveMods")
+        .map(|mods| mods.split(',').map(str::trim).filter(|m| !m.is_empty()).map(String::from).collect())
+        .unwrap_or_default();
+
+    Ok(DetectedInstall {
+        install_path: install_path.to_path_buf(),
+        server_type,
+        build_id,
+        session_name: ini.get("SessionSettings", "SessionName").map(String::from),
+        map_name,
+        ports,
+        max_players,
+        server_password: ini.get("ServerSettings", "ServerPassword").map(String::from),
+        admin_password,
+        motd: ini.get("MessageOfTheDay", "Message").map(String::from),
+        active_mods,
+        warnings,
+    })
+}
+
+/// 24 random hex characters.
+fn generate_password() -> String {
+    let mut bytes = [0u8; 12];
+    rand::rngs::OsRng.fill_bytes(&mut bytes);
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// The launch flag that loads `mods` on ASA, which ignores `ActiveMods` in
+/// the INI. ASE reads the INI, so it needs none.
+pub fn mods_flag(server_type: &ServerType, mods: &[String]) -> Option<String> {
+    (matches!(server_type, ServerType::ASA) && !mods.is_empty()).then(|| format!("-mods={}", mods.join(",")))
+}
+
+/// Add mods found in an existing install to the server's mod list, in load order.
+pub fn record_mods(conn: &Connection, server_id: i64, server_type: &ServerType, mods: &[String]) -> rusqlite::Result<()> {
+    for (load_order, mod_id) in mods.iter().enumerate() {
+        let workshop_url = matches!(server_type, ServerType::ASE)
+            .then(|| format!("https://steamcommunity.com/sharedfiles/filedetails/?id={}", mod_id));
+        conn.execute(
+            "INSERT OR IGNORE INTO mods (server_id, mod_id, name, workshop_url, server_type, load_order)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            (server_id, mod_id, format!("Mod {}", mod_id), workshop_url, server_type.to_string(), load_order as i64),
+        )?;
+    }
+    Ok(())
+}
+
+/// `name`, or `name (2)`, `name (3)`... whichever is free.
+fn unique_name(conn: &Connection, name: &str) -> rusqlite::Result<String> {
+    let mut candidate = name.to_string();
+    let mut n = 2;
+    loop {
+        let taken: bool = conn.query_row(
+            "SELECT EXISTS(SELECT 1 FROM servers WHERE name = ?1)",
+            [&candidate],
+            |row| row.get(0),
+        )?;
+        if !taken {
+            return Ok(candidate);
+        }
+        candidate = format!("{} ({})", name, n);
+        n += 1;
+    }
+}
+
+/// Add a detected install to the `servers` table as a stopped server, with
+/// the mods its INI lists. Refuses installs that are already registered or whose ports another
+/// server uses.
+pub fn register(conn: &Connection, detected: &DetectedInstall, name: Option<&str>) -> Result<i64, String> {
+    let install_path = detected.install_path.to_string_lossy().to_string();
+    let existing: Option<String> = conn
+        .query_row("SELECT name FROM servers WHERE install_path = ?1", [&install_path], |row| row.get(0))
+        .ok();
+    if let Some(existing) = existing {
+        return Err(format!("This install is already registered as \"{}\"", existing));
+    }
+
+    let multihome_ip = None;
+    // The install may be running right now, so only clashes with other servers count
+    let conflicts: Vec<_> = port_allocator::find_conflicts(conn, None, &detected.ports, multihome_ip, CheckScope::AllServers)
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .filter(|c| c.conflicting_server_id.is_some())
+        .collect();
+    if !conflicts.is_empty() {
+        return Err(port_allocator::describe(conn, &conflicts, multihome_ip));
+    }
+
+    let folder_name = detected
+        .install_path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| "Imported Server".to_string());
+    let name = name
+        .map(str::trim)
+        .filter(|n| !n.is_empty())
+        .map(String::from)
+        .or_else(|| detected.session_name.clone())
+        .unwrap_or(folder_name);
+    let name = unique_name(conn, &name).map_err(|e| e.to_string())?;
+    let session_name = detected.session_name.clone().unwrap_or_else(|| name.clone());
+    let admin_password = detected.admin_password.clone().unwrap_or_else(generate_password);
+    let launch_profile = mods_flag(&detected.server_type, &detected.active_mods)
+        .map(|flag| serde_json::to_string(&LaunchProfile { flags: vec![flag], ..Default::default() }))
+        .transpose()
+        .map_err(|e| e.to_string())?;
+
+    conn.execute(
+        "INSERT INTO servers (name, server_type, install_path, status, game_port, query_port, rcon_port,
+         max_players, server_password, admin_password, map_name, session_name, motd, launch_profile)
+         VALUES (?1, ?2, ?3, 'stopped', ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13)",
+        rusqlite::params![
+            name,
+            detected.server_type.to_string(),
+            install_path,
+            detected.ports.game_port,
+            detected.ports.query_port,
+            detected.ports.rcon_port,
+            detected.max_players,
+            detected.server_password,
+            admin_password,
+            detected.map_name,
+            session_name,
+            detected.motd,
+            launch_profile,
+        ],
+    )
+    .map_err(|e| e.to_string())?;
+
+    let server_id = conn.last_insert_rowid();
+    record_mods(conn, server_id, &detected.server_type, &detected.active_mods).map_err(|e| e.to_string())?;
+    server_status::record_created(conn, server_id, ServerStatus::Stopped, "imported existing install")
+        .map_err(|e| e.to_string())?;
+    Ok(server_id)
+}
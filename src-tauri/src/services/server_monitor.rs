@@ -1,256 +1,328 @@
-use tauri::{AppHandle, Manager, Emitter};
 use std::time::Duration;
 use tokio::time::{sleep, timeout};
 use crate::AppState;
 use std::collections::HashMap;
-use std::path::PathBuf;
-use crate::services::rcon_client::RconClient;
+use crate::services::rcon_sessions;
+use crate::services::launch::LaunchSpec;
+use crate::services::lifecycle;
+use crate::models::{ServerStatus, WebhookEvent};
+use crate::services::{crash_bundle, platform, process_manager, readiness, server_status, webhooks};
+use crate::services::restart_policy::{self, RestartDecision, RestartTracker};
 
 pub struct ServerMonitorService;
 
 impl ServerMonitorService {
-    pub fn init(app_handle: AppHandle) {
-        // 1. PID Reconciliation on Start
-        if let Some(state) = app_handle.try_state::<AppState>() {
-            let running_servers: Result<Vec<(i64, u32)>, _> = {
-                let db = state.db.lock().map_err(|e| e.to_string());
-                if let Ok(db_guard) = db {
-                    if let Ok(conn) = db_guard.get_connection() {
-                         let mut stmt = conn.prepare("SELECT id, pid FROM servers WHERE status = 'running'").unwrap();
-                         let rows = stmt.query_map([], |row| {
-                             let pid_i64: Option<i64> = row.get(1)?;
-                             Ok((row.get(0)?, pid_i64.map(|p| p as u32).unwrap_or(0)))
-                         }).unwrap();
-                         rows.collect()
-                    } else { Ok(vec![]) }
-                } else { Ok(vec![]) }
+    pub fn init(state: AppState) {
+        // 1. PID Reconciliation on Start: servers that kept running while the
+        // manager was closed are adopted again, everything else is marked stopped
+        let running_servers: Vec<(i64, Option<u32>, Option<u64>, String)> = {
+            let db = state.db.lock().map_err(|e| e.to_string());
+            if let Ok(db_guard) = db {
+                if let Ok(conn) = db_guard.get_connection() {
+                     let mut stmt = conn.prepare("SELECT id, pid, pid_start_time, status FROM servers WHERE status IN ('running', 'starting')").unwrap();
+                     let rows = stmt.query_map([], |row| {
+                         let pid_i64: Option<i64> = row.get(1)?;
+                         let start_i64: Option<i64> = row.get(2)?;
+                         Ok((row.get(0)?, pid_i64.map(|p| p as u32), start_i64.map(|t| t as u64), row.get(3)?))
+                     }).unwrap();
+                     rows.filter_map(Result::ok).collect()
+                } else { vec![] }
+            } else { vec![] }
+        };
+
+        for (id, pid, start_time, status) in running_servers {
+            let spec = {
+                let db = state.db.lock().unwrap();
+                db.get_connection().ok().and_then(|conn| LaunchSpec::load(&conn, id).ok())
             };
 
-            if let Ok(servers) = running_servers {
-                use sysinfo::{System, Pid};
-                let mut sys = System::new_all();
-                sys.refresh_all(); // Refresh processes
-
-                for (id, pid) in servers {
-                    let mut should_stop = false;
-                    if pid == 0 {
-                        should_stop = true;
-                    } else {
-                        let s_pid = Pid::from(pid as usize);
-                        if sys.process(s_pid).is_none() {
-                            println!("Reconciliation: Server {} (PID {}) is dead. Marking stopped.", id, pid);
-                            should_stop = true;
-                        } else {
-                            println!("Reconciliation: Server {} (PID {}) is alive.", id, pid);
+            let adopted = match (pid.filter(|p| *p != 0), spec) {
+                (Some(pid), Some(spec)) => {
+                    let verified = platform::server_executable(&spec.install_path, &spec.server_type)
+                        .ok()
+                        .and_then(|exe| process_manager::verify_server_process(pid, start_time, &exe));
+
+                    match verified {
+                        Some(actual_start) => {
+                            println!("Reconciliation: Server {} (PID {}) is alive. Adopting it.", id, pid);
+                            state.process_manager.adopt_server(&state.events, &spec, pid, actual_start);
+                            // Still loading when the manager went away: keep waiting for it
+                            if status == "starting" {
+                                readiness::spawn(state.clone(), id, pid, true);
+                            }
+                            true
+                        }
+                        None => {
+                            println!("Reconciliation: Server {} (PID {}) is gone or PID was reused. Marking stopped.", id, pid);
+                            false
                         }
                     }
+                }
+                _ => false,
+            };
 
-                    if should_stop {
-                        let db = state.db.lock().unwrap();
-                        let conn_res = db.get_connection();
-                        if let Ok(conn) = conn_res {
-                            let _ = conn.execute("UPDATE servers SET status = 'stopped' WHERE id = ?1", [id]);
-                        }
+            let db = state.db.lock().unwrap();
+            if let Ok(conn) = db.get_connection() {
+                if adopted {
+                    // Older rows have no start time yet
+                    if let Some(pid) = pid {
+                        let _ = process_manager::record_server_pid(&conn, id, pid);
                     }
+                } else {
+                    let _ = server_status::transition_from(
+                        &conn,
+                        id,
+                        &[ServerStatus::Starting, ServerStatus::Running],
+                        ServerStatus::Stopped,
+                        "process gone while the manager was closed",
+                    );
+                    let _ = process_manager::clear_server_pid(&conn, id);
                 }
-            }
+            };
         }
 
         tauri::async_runtime::spawn(async move {
             let mut failure_counts: HashMap<i64, u32> = HashMap::new();
+            let mut restarts = RestartTracker::default();
             let mut tick_iters = 0;
 
             loop {
                 sleep(Duration::from_secs(5)).await;
                 tick_iters += 1;
 
-                if let Some(state) = app_handle.try_state::<AppState>() {
-                    // 1. Check Dead Processes (Crash Detection)
-                    let dead_processes = state.process_manager.check_dead_processes(); // ... existing logic ...
-
-                    if !dead_processes.is_empty() {
-                        let mut notifications = Vec::new();
-
-                        { // Scope for DB Lock
-                            let db_lock = state.db.lock();
-                            if let Ok(db) = db_lock {
-                                if let Ok(conn) = db.get_connection() {
-                                    for (server_id, exit_code) in dead_processes {
-                                        let mut status = if exit_code == Some(0) { "stopped" } else { "crashed" };
-                                        
-                                        // Check for Auto Restart if crashed
-                                        let mut auto_restart = false;
-                                        if status == "crashed" {
-                                            if let Ok(enabled) = conn.query_row(
-                                                "SELECT auto_restart FROM servers WHERE id = ?1",
-                                                [server_id],
-                                                |row| row.get::<_, Option<bool>>(0),
-                                            ) {
-                                                auto_restart = enabled.unwrap_or(false);
-                                            }
+                // 1. Check Dead Processes (Crash Detection)
+                let dead_processes = state.process_manager.check_dead_processes(); // ... existing logic ...
+
+                if !dead_processes.is_empty() {
+                    let mut notifications = Vec::new();
+                    let mut crashes = Vec::new();
+
+                    { // Scope for DB Lock
+                        let db_lock = state.db.lock();
+                        if let Ok(db) = db_lock {
+                            if let Ok(conn) = db.get_connection() {
+                                for exit in dead_processes {
+                                    let (server_id, exit_code) = (exit.server_id, exit.exit_code);
+
+                                    // Only a server that was up can stop or crash. Anything else,
+                                    // such as `failed` after a start timed out, keeps its status.
+                                    let current = server_status::current(&conn, server_id).ok();
+                                    if !matches!(current, Some(ServerStatus::Starting | ServerStatus::Running | ServerStatus::Stopping)) {
+                                        continue;
+                                    }
+
+                                    // A stop we asked for is never a crash, whatever the exit code
+                                    let mut status = if exit.requested || exit_code == Some(0) { ServerStatus::Stopped } else { ServerStatus::Crashed };
+                                    let code = exit_code.map(|c| c.to_string()).unwrap_or_else(|| "none".to_string());
+                                    let mut cause = if exit.requested {
+                                        "process exited after a stop request".to_string()
+                                    } else {
+                                        format!("process exited with code {}", code)
+                                    };
+                                    
+                                    // Check for Auto Restart if crashed
+                                    let mut auto_restart = false;
+                                    if status == ServerStatus::Crashed {
+                                        if let Ok(enabled) = conn.query_row(
+                                            "SELECT auto_restart FROM servers WHERE id = ?1",
+                                            [server_id],
+                                            |row| row.get::<_, Option<bool>>(0),
+                                        ) {
+                                            auto_restart = enabled.unwrap_or(false);
                                         }
+                                    }
 
-                                        if auto_restart {
-                                            println!("Server {} crashed. Auto-restarting...", server_id);
-                                            // Fetch details
-                                            let details: Result<(String, String, String, String, u16, u16, u16, i32, Option<String>, String, bool, Option<String>, bool), _> = conn.query_row(
-                                                "SELECT server_type, install_path, map_name, session_name, game_port, query_port, rcon_port,
-                                                 max_players, server_password, admin_password, battleye_enabled, multihome_ip, crossplay_enabled 
-                                                 FROM servers WHERE id = ?1",
-                                                [server_id],
-                                                |row| Ok((
-                                                    row.get::<_, String>(0)?,
-                                                    row.get::<_, String>(1)?,
-                                                    row.get::<_, String>(2)?,
-                                                    row.get::<_, String>(3)?,
-                                                    row.get::<_, u16>(4)?,
-                                                    row.get::<_, u16>(5)?,
-                                                    row.get::<_, u16>(6)?,
-                                                    row.get::<_, i32>(7)?,
-                                                    row.get::<_, Option<String>>(8)?,
-                                                    row.get::<_, String>(9)?,
-                                                    row.get::<_, bool>(10).unwrap_or(false),
-                                                    row.get::<_, Option<String>>(11)?,
-                                                    row.get::<_, bool>(12).unwrap_or(false),
-                                                ))
-                                            );
-
-                                            if let Ok((server_type, install_path, map_name, session_name, game_port, query_port, rcon_port, max_players, server_password, admin_password, use_battleye, multihome_ip, crossplay_enabled)) = details {
-                                                match state.process_manager.start_server(
-                                                    &app_handle,
-                                                    server_id,
-                                                    &server_type,
-                                                    &PathBuf::from(install_path),
-                                                    &map_name,
-                                                    &session_name,
-                                                    game_port,
-                                                    query_port,
-                                                    rcon_port,
-                                                    max_players,
-                                                    server_password.as_deref(),
-                                                    &admin_password,
-                                                    use_battleye,
-                                                    multihome_ip,
-                                                    crossplay_enabled,
-                                                ) {
-                                                    Ok(_) => {
-                                                        status = "running";
-                                                        println!("Auto-restart successful.");
-                                                        notifications.push((
-                                                            "Server Restarted".to_string(),
-                                                            format!("Server {} crashed and was auto-restarted.", session_name)
-                                                        ));
-                                                    }
-                                                    Err(e) => {
-                                                        eprintln!("Failed to auto-restart server: {}", e);
-                                                    }
-                                                }
+                                    let mut restart_in = None;
+                                    if auto_restart {
+                                        let policy = restart_policy::load_policy(&conn, server_id).unwrap_or_default();
+                                        match restarts.on_crash(server_id, &policy) {
+                                            RestartDecision::After(delay) => {
+                                                println!("Server {} crashed. Restarting in {}s...", server_id, delay.as_secs());
+                                                restart_in = Some(delay.as_secs());
+                                            }
+                                            RestartDecision::GiveUp => {
+                                                status = ServerStatus::CrashLooping;
+                                                cause = format!("crashed {} times within {}s", policy.max_restarts + 1, policy.window_secs);
+                                                println!("Server {} crashed {} times within {}s. Not restarting it again.", server_id, policy.max_restarts + 1, policy.window_secs);
+                                                notifications.push((
+                                                    "Server Crash-Looping".to_string(),
+                                                    format!(
+                                                        "Server {} keeps crashing ({} restarts within {} minutes). Automatic restarts are paused until it is reset.",
+                                                        server_id, policy.max_restarts, policy.window_secs / 60
+                                                    )
+                                                ));
                                             }
                                         }
+                                    }
 
-                                        // Update DB Status
-                                        let _ = conn.execute(
-                                            "UPDATE servers SET status = ?1 WHERE id = ?2",
-                                            (status, server_id),
-                                        );
-
-                                        // Emit event
-                                        let _ = app_handle.emit("server-status-changed",  serde_json::json!({
-                                            "id": server_id,
-                                            "status": status,
-                                            "exit_code": exit_code
-                                        }));
-                                        
-                                        if status == "crashed" {
-                                            println!("Detected server {} exit (code {:?}). Updated status to {}", server_id, exit_code, status);
-                                            notifications.push((
-                                                "Server Crashed".to_string(),
-                                                format!("Server {} has crashed!", server_id)
-                                            ));
-                                        }
+                                    // Update DB Status
+                                    if let Err(e) = server_status::transition(&conn, server_id, status, &cause) {
+                                        eprintln!("Failed to record exit of server {}: {}", server_id, e);
+                                    }
+                                    // The crash bundle still needs to know when this run started
+                                    let started_at = process_manager::server_pid_start_time(&conn, server_id);
+                                    let _ = process_manager::clear_server_pid(&conn, server_id);
+
+                                    // Emit event
+                                    state.events.emit("server-status-changed", serde_json::json!({
+                                        "id": server_id,
+                                        "status": status.to_string(),
+                                        "exit_code": exit_code,
+                                        "restartInSecs": restart_in
+                                    }));
+                                    
+                                    if status != ServerStatus::Stopped {
+                                        println!("Detected server {} exit (code {:?}). Updated status to {}", server_id, exit_code, status.to_string());
+                                        crashes.push((server_id, exit_code, started_at));
                                     }
                                 }
                             }
-                        } // Lock dropped
+                        }
+                    } // Lock dropped
+
+                    // Save logs and dumps before a restart overwrites them
+                    let mut crash_notifications = Vec::new();
+                    for (server_id, exit_code, started_at) in crashes {
+                        let core = state.clone();
+                        let bundle = tauri::async_runtime::spawn_blocking(move || {
+                            crash_bundle::collect(&core, server_id, exit_code, started_at)
+                        }).await;
+
+                        let code = exit_code.map(|c| c.to_string()).unwrap_or_else(|| "none".to_string());
+                        let mut bundle_id = None;
+                        let message = match bundle {
+                            Ok(Ok(bundle)) => {
+                                state.events.emit("crash-bundle-created", &bundle);
+                                bundle_id = Some(bundle.id);
+                                format!("Server {} has crashed (exit code {}). Logs saved as crash bundle #{}.", server_id, code, bundle.id)
+                            }
+                            Ok(Err(e)) => {
+                                eprintln!("Failed to collect crash bundle for server {}: {}", server_id, e);
+                                format!("Server {} has crashed (exit code {}).", server_id, code)
+                            }
+                            Err(e) => {
+                                eprintln!("Crash bundle task failed: {}", e);
+                                format!("Server {} has crashed (exit code {}).", server_id, code)
+                            }
+                        };
+                        webhooks::fire(&state, WebhookEvent::Crash, server_id, serde_json::json!({
+                            "exitCode": exit_code,
+                            "crashBundleId": bundle_id,
+                            "message": message
+                        }));
+                        crash_notifications.push(("Server Crashed".to_string(), message));
+                    }
+
+                    // Send Notifications, crashes ahead of any crash-loop notice
+                    for (title, msg) in crash_notifications.into_iter().chain(notifications) {
+                         let _ = crate::services::notifications::NotificationService::send_notification(&state, &title, &msg).await;
+                    }
+                }
 
-                        // Send Notifications
-                        for (title, msg) in notifications {
-                             let _ = crate::services::notifications::NotificationService::send_notification(&state, &title, &msg).await;
+                // 2. Restarts whose backoff has run out
+                for server_id in restarts.take_due() {
+                    // Started, stopped or deleted by someone else while we waited
+                    if current_status(&state, server_id).as_deref() != Some("crashed") {
+                        continue;
+                    }
+
+                    match lifecycle::start_server(&state, server_id, "automatic restart after a crash").await {
+                        Ok(()) => {
+                            println!("Auto-restart of server {} successful.", server_id);
+                            let _ = crate::services::notifications::NotificationService::send_notification(
+                                &state,
+                                "Server Restarted",
+                                &format!("Server {} crashed and was auto-restarted.", server_id)
+                            ).await;
+                        }
+                        Err(e) => {
+                            // A start that fails counts against the policy like a crash
+                            eprintln!("Failed to auto-restart server {}: {}", server_id, e);
+                            let policy = {
+                                let db = state.db.lock().unwrap();
+                                db.get_connection().ok().and_then(|conn| restart_policy::load_policy(&conn, server_id).ok())
+                            }.unwrap_or_default();
+                            if restarts.on_crash(server_id, &policy) == RestartDecision::GiveUp {
+                                mark_crash_looping(&state, server_id).await;
+                            }
                         }
                     }
+                }
+
+                // 3. An operator reset a crash-looping server (or started it by hand)
+                for server_id in restarts.given_up() {
+                    if current_status(&state, server_id).as_deref() != Some("crash-looping") {
+                        restarts.reset(server_id);
+                    }
+                }
 
-                    // 2. Watchdog / Freeze Detection (Every 60s)
-                    if tick_iters % 12 == 0 {
-                        let mut restart_list = Vec::new();
-
-                        let running_servers_op: Option<Vec<(i64, u16, String, bool)>> = {
-                            let db_lock = state.db.lock();
-                            if let Ok(db) = db_lock {
-                                if let Ok(conn) = db.get_connection() {
-                                    if let Ok(mut stmt) = conn.prepare("SELECT id, rcon_port, admin_password, auto_restart FROM servers WHERE status = 'running'") {
-                                        stmt.query_map([], |row| {
-                                            Ok((
-                                                row.get(0)?,
-                                                row.get::<_, u16>(1)?,
-                                                row.get::<_, String>(2)?,
-                                                row.get::<_, Option<bool>>(3)?.unwrap_or(false),
-                                            ))
-                                        }).ok().map(|rows| rows.filter_map(Result::ok).collect())
-                                    } else { None }
+                // 4. Watchdog / Freeze Detection (Every 60s)
+                if tick_iters % 12 == 0 {
+                    let mut restart_list = Vec::new();
+
+                    let running_servers_op: Option<Vec<(i64, bool)>> = {
+                        let db_lock = state.db.lock();
+                        if let Ok(db) = db_lock {
+                            if let Ok(conn) = db.get_connection() {
+                                if let Ok(mut stmt) = conn.prepare("SELECT id, auto_restart FROM servers WHERE status = 'running'") {
+                                    stmt.query_map([], |row| {
+                                        Ok((
+                                            row.get(0)?,
+                                            row.get::<_, Option<bool>>(1)?.unwrap_or(false),
+                                        ))
+                                    }).ok().map(|rows| rows.filter_map(Result::ok).collect())
                                 } else { None }
                             } else { None }
-                        };
+                        } else { None }
+                    };
 
-                        if let Some(servers) = running_servers_op {
-                             for (id, port, pass, auto_restart) in servers {
-                                // ... loop logic ...
-                                                if !auto_restart {
-                                                    continue; 
-                                                }
+                    if let Some(servers) = running_servers_op {
+                         for (id, auto_restart) in servers {
+                            // ... loop logic ...
+                                            if !auto_restart {
+                                                continue; 
+                                            }
+
+                                            // Perform Check
+                                            let is_healthy = match timeout(Duration::from_secs(10), rcon_sessions::execute(&state, id, "ListPlayers")).await {
+                                                Ok(Ok(_)) => true,
+                                                _ => false,
+                                            };
 
-                                                // Perform Check
-                                                let is_healthy = match timeout(Duration::from_secs(10), RconClient::execute("127.0.0.1", port, &pass, "ListPlayers")).await {
-                                                    Ok(Ok(_)) => true,
-                                                    _ => false,
-                                                };
+                                            if is_healthy {
+                                                failure_counts.remove(&id);
+                                            } else {
+                                                let count = failure_counts.entry(id).or_insert(0);
+                                                *count += 1;
+                                                println!("Server {} failed health check. Count: {}", id, count);
 
-                                                if is_healthy {
+                                                if *count >= 3 && auto_restart {
+                                                    restart_list.push(id);
                                                     failure_counts.remove(&id);
-                                                } else {
-                                                    let count = failure_counts.entry(id).or_insert(0);
-                                                    *count += 1;
-                                                    println!("Server {} failed health check. Count: {}", id, count);
-
-                                                    if *count >= 3 && auto_restart {
-                                                        restart_list.push(id);
-                                                        failure_counts.remove(&id);
-                                                    }
                                                 }
-                             }
-                        }
+                                            }
+                         }
+                    }
 
-                        // Handle Restarts
-                        for id in restart_list {
-                             println!("Watchdog: Server {} frozen. Restarting...", id);
-                             // To restart, we need to Stop then Start.
-                             // We can reuse the logic.
-                             // Call restart_server logic? 
-                             // Using process_manager.stop_server(id) then the Monitor Loop will pick up the 'Dead Process' next tick!
-                             // BUT it might not be 'Dead' immediately if kill takes time?
-                             // process_manager.stop_server sends Kill.
-                             // And sets exit code?
-                             let _ = state.process_manager.stop_server(id);
-                             
-                             // The NEXT loop iteration (5s later) will see it as DEAD.
-                             // And trigger the "Crash Auto-Recovery" logic which restarts it!
-                             // Perfect synergy.
-                             
+                    // Handle Restarts, each in its own task: a restart waits out the
+                    // stop warning and exit timeouts, which would hold up crash
+                    // detection for every other server
+                    for id in restart_list {
+                         println!("Watchdog: Server {} frozen. Restarting...", id);
+                         let state = state.clone();
+                         tauri::async_runtime::spawn(async move {
                              let _ = crate::services::notifications::NotificationService::send_notification(
-                                &state, 
-                                "Watchdog Triggered", 
+                                &state,
+                                "Watchdog Triggered",
                                 &format!("Server {} was frozen and is being restarted.", id)
                              ).await;
-                        }
+
+                             // Goes through the graceful stop, which escalates to a kill
+                             // when the frozen server doesn't answer RCON
+                             if let Err(e) = lifecycle::restart_server(&state, id, "watchdog: server stopped answering RCON").await {
+                                 eprintln!("Watchdog failed to restart server {}: {}", id, e);
+                             }
+                         });
                     }
                 }
             }
@@ -258,3 +330,25 @@ impl ServerMonitorService {
     }
 }
 
+fn current_status(state: &AppState, server_id: i64) -> Option<String> {
+    let db = state.db.lock().ok()?;
+    let conn = db.get_connection().ok()?;
+    conn.query_row("SELECT status FROM servers WHERE id = ?1", [server_id], |row| row.get(0)).ok()
+}
+
+async fn mark_crash_looping(state: &AppState, server_id: i64) {
+    if let Ok(db) = state.db.lock() {
+        if let Ok(conn) = db.get_connection() {
+            let _ = server_status::transition(&conn, server_id, ServerStatus::CrashLooping, "automatic restart failed");
+        }
+    }
+    state.events.emit("server-status-changed", serde_json::json!({
+        "id": server_id,
+        "status": "crash-looping"
+    }));
+    let _ = crate::services::notifications::NotificationService::send_notification(
+        state,
+        "Server Crash-Looping",
+        &format!("Server {} could not be restarted. Automatic restarts are paused until it is reset.", server_id)
+    ).await;
+}
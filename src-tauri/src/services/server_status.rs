@@ -0,0 +1,234 @@
+use crate::models::{ServerStatus, StatusChange};
+use rusqlite::{Connection, OptionalExtension};
+
+// The one place `servers.status` is changed. Every move is checked against
+// the transition table and written to `server_status_history` with its cause.
+
+/// Whether a server may go from `from` to `to`.
+pub fn can_transition(from: ServerStatus, to: ServerStatus) -> bool {
+    use ServerStatus::*;
+
+    match from {
+        Stopped => matches!(to, Installing | Starting | Updating | Maintenance),
+        Installing => matches!(to, Stopped | Crashed),
+        Starting => matches!(to, Running | Stopping | Stopped | Crashed | Failed | CrashLooping),
+        Running => matches!(to, Stopping | Stopped | Crashed | CrashLooping),
+        Stopping => matches!(to, Stopped | Crashed | CrashLooping),
+        Updating => matches!(to, Stopped | Crashed),
+        // A crashed server can be restarted by the monitor or handed to an operator
+        Crashed => matches!(to, Starting | Stopped | Installing | Updating | CrashLooping | Maintenance),
+        // The process of a failed start may still be shutting down
+        Failed => matches!(to, Starting | Stopping | Stopped | Crashed | Updating | Maintenance),
+        // Only an operator gets a server out of a crash loop, by hand
+        CrashLooping => matches!(to, Starting | Stopped | Updating | Maintenance),
+        Maintenance => matches!(to, Stopped),
+    }
+}
+
+pub fn current(conn: &Connection, server_id: i64) -> Result<ServerStatus, String> {
+    let status: String = conn
+        .query_row("SELECT status FROM servers WHERE id = ?1", [server_id], |row| row.get(0))
+        .optional()
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("Server {} not found", server_id))?;
+    status.parse()
+}
+
+/// Check that the server could move to `to` right now, without moving it.
+/// A server already in `to` can't move there again.
+pub fn ensure_can(conn: &Connection, server_id: i64, to: ServerStatus) -> Result<ServerStatus, String> {
+    let from = current(conn, server_id)?;
+    if !can_transition(from, to) {
+        return Err(illegal(server_id, from, to));
+    }
+    Ok(from)
+}
+
+fn illegal(server_id: i64, from: ServerStatus, to: ServerStatus) -> String {
+    format!(
+        "Server {} is {} and can't become {}",
+        server_id,
+        from.to_string(),
+        to.to_string()
+    )
+}
+
+/// Move the server to `to` and record why. Returns the status it had; moving
+/// to the status it already has is a no-op and records nothing.
+pub fn transition(conn: &Connection, server_id: i64, to: ServerStatus, cause: &str) -> Result<ServerStatus, String> {
+    let from = current(conn, server_id)?;
+    if from == to {
+        return Ok(from);
+    }
+    claim(conn, server_id, to, cause)
+}
+
+/// Like `transition`, but fails when the server is already in `to`. Of two
+/// callers racing to start a server, only one gets `Starting`.
+pub fn claim(conn: &Connection, server_id: i64, to: ServerStatus, cause: &str) -> Result<ServerStatus, String> {
+    let from = ensure_can(conn, server_id, to)?;
+
+    // The daemon and the GUI share the database; only move from what we checked
+    let changed = conn
+        .execute(
+            "UPDATE servers SET status = ?1 WHERE id = ?2 AND status = ?3",
+            (to.to_string(), server_id, from.to_string()),
+        )
+        .map_err(|e| e.to_string())?;
+    if changed == 0 {
+        return claim(conn, server_id, to, cause);
+    }
+
+    record(conn, server_id, Some(from), to, cause).map_err(|e| e.to_string())?;
+    Ok(from)
+}
+
+/// Like `transition`, but only while the server is in one of `from`; for
+/// watchers that must not overwrite whatever someone else did meanwhile.
+/// Returns whether the server moved.
+pub fn transition_from(
+    conn: &Connection,
+    server_id: i64,
+    from: &[ServerStatus],
+    to: ServerStatus,
+    cause: &str,
+) -> Result<bool, String> {
+    let current = current(conn, server_id)?;
+    if current == to || !from.contains(&current) {
+        return Ok(false);
+    }
+    transition(conn, server_id, to, cause)?;
+    Ok(true)
+}
+
+/// Record the status a new server row was inserted with.
+pub fn record_created(conn: &Connection, server_id: i64, status: ServerStatus, cause: &str) -> rusqlite::Result<()> {
+    record(conn, server_id, None, status, cause)
+}
+
+fn record(
+    conn: &Connection,
+    server_id: i64,
+    from: Option<ServerStatus>,
+    to: ServerStatus,
+    cause: &str,
+) -> rusqlite::Result<()> {
+    conn.execute(
+        "INSERT INTO server_status_history (server_id, from_status, to_status, cause) VALUES (?1, ?2, ?3, ?4)",
+        (server_id, from.map(|s| s.to_string()), to.to_string(), cause),
+    )?;
+    Ok(())
+}
+
+/// The server's most recent status changes, newest first.
+pub fn history(conn: &Connection, server_id: i64, limit: usize) -> rusqlite::Result<Vec<StatusChange>> {
+    let mut stmt = conn.prepare(
+        "SELECT id, server_id, from_status, to_status, cause, created_at FROM server_status_history
+         WHERE server_id = ?1 ORDER BY id DESC LIMIT ?2",
+    )?;
+    let changes = stmt.query_map((server_id, limit as i64), |row| {
+        Ok(StatusChange {
+            id: row.get(0)?,
+            server_id: row.get(1)?,
+            from_status: row.get::<_, Option<String>>(2)?.and_then(|s| s.parse().ok()),
+            to_status: row.get::<_, String>(3)?.parse().unwrap_or(ServerStatus::Stopped),
+            cause: row.get(4)?,
+            created_at: row.get(5)?,
+        })
+    })?;
+    changes.collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::test_connection;
+    use ServerStatus::*;
+
+    const ALL: [ServerStatus; 10] = [
+        Stopped, Installing, Starting, Running, Stopping, Crashed, Updating, Failed, CrashLooping, Maintenance,
+    ];
+
+    #[test]
+    fn status_strings_round_trip() {
+        for status in ALL {
+            assert_eq!(status.to_string().parse::<ServerStatus>(), Ok(status));
+        }
+        assert!("paused".parse::<ServerStatus>().is_err());
+    }
+
+    #[test]
+    fn schema_allows_every_status() {
+        let conn = test_connection();
+        for status in ALL {
+            conn.execute("UPDATE servers SET status = ?1", [status.to_string()]).unwrap();
+        }
+    }
+
+    #[test]
+    fn busy_servers_cant_be_started() {
+        for from in [Starting, Running, Stopping, Updating, Installing, Maintenance] {
+            assert!(!can_transition(from, Starting), "{:?} -> Starting", from);
+        }
+        for from in [Stopped, Crashed, Failed, CrashLooping] {
+            assert!(can_transition(from, Starting), "{:?} -> Starting", from);
+        }
+    }
+
+    #[test]
+    fn running_server_must_stop_before_updating() {
+        assert!(!can_transition(Running, Updating));
+        assert!(can_transition(Running, Stopping));
+        assert!(can_transition(Stopping, Stopped));
+        assert!(can_transition(Stopped, Updating));
+    }
+
+    #[test]
+    fn maintenance_is_left_through_stopped() {
+        for to in ALL {
+            assert_eq!(can_transition(Maintenance, to), to == Stopped, "Maintenance -> {:?}", to);
+        }
+    }
+
+    #[test]
+    fn transitions_are_recorded() {
+        let conn = test_connection();
+        assert_eq!(transition(&conn, 1, Starting, "started from the GUI"), Ok(Stopped));
+        assert_eq!(transition(&conn, 1, Running, "readiness probe passed"), Ok(Starting));
+        // Already running: nothing to record
+        assert_eq!(transition(&conn, 1, Running, "again"), Ok(Running));
+
+        let history = history(&conn, 1, 10).unwrap();
+        assert_eq!(history.len(), 2);
+        assert_eq!(history[0].from_status, Some(Starting));
+        assert_eq!(history[0].to_status, Running);
+        assert_eq!(history[0].cause, "readiness probe passed");
+        assert_eq!(history[1].from_status, Some(Stopped));
+    }
+
+    #[test]
+    fn illegal_transitions_are_rejected() {
+        let conn = test_connection();
+        transition(&conn, 1, Updating, "update").unwrap();
+        assert!(transition(&conn, 1, Starting, "start").is_err());
+        assert_eq!(current(&conn, 1), Ok(Updating));
+        assert_eq!(history(&conn, 1, 10).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn a_start_can_only_be_claimed_once() {
+        let conn = test_connection();
+        assert_eq!(claim(&conn, 1, Starting, "start"), Ok(Stopped));
+        assert!(claim(&conn, 1, Starting, "second start").is_err());
+        assert!(ensure_can(&conn, 1, Starting).is_err());
+        assert_eq!(history(&conn, 1, 10).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn transition_from_leaves_other_statuses_alone() {
+        let conn = test_connection();
+        transition(&conn, 1, Updating, "update").unwrap();
+        assert_eq!(transition_from(&conn, 1, &[Starting, Running], Stopped, "process exited"), Ok(false));
+        assert_eq!(current(&conn, 1), Ok(Updating));
+    }
+}
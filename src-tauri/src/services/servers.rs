@@ -0,0 +1,361 @@
+use crate::models::{RunnerConfig, Server, ServerConfig, ServerPorts, ServerSettingsUpdate, ServerStatus, ServerType};
+use crate::services::port_allocator::{self, CheckScope};
+use crate::services::{launch, runner};
+use rusqlite::{Connection, OptionalExtension};
+use std::net::IpAddr;
+use std::path::PathBuf;
+
+// Reading whole `Server` rows and editing their settings, for the commands
+// and anything else that needs a server as the UI sees it.
+
+const SERVER_COLUMNS: &str = "id, name, server_type, install_path, status, game_port, query_port, rcon_port,
+    max_players, server_password, admin_password, map_name, session_name, motd,
+    created_at, last_started, battleye_enabled, multihome_ip, crossplay_enabled, auto_restart, auto_update, pid,
+    runner, runner_path, compat_prefix, launch_profile, rcon_host, shared_install_of";
+
+fn server_from_row(row: &rusqlite::Row) -> rusqlite::Result<Server> {
+    Ok(Server {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        server_type: row.get::<_, String>(2)?.parse().unwrap_or(ServerType::ASE),
+        install_path: PathBuf::from(row.get::<_, String>(3)?),
+        status: row.get::<_, String>(4)?.parse().map_err(|e: String| {
+            rusqlite::Error::FromSqlConversionFailure(4, rusqlite::types::Type::Text, e.into())
+        })?,
+        ports: ServerPorts {
+            game_port: row.get(5)?,
+            query_port: row.get(6)?,
+            rcon_port: row.get(7)?,
+        },
+        config: ServerConfig {
+            max_players: row.get(8)?,
+            server_password: row.get(9)?,
+            admin_password: row.get(10)?,
+            map_name: row.get(11)?,
+            session_name: row.get(12)?,
+            motd: row.get(13)?,
+            battleye_enabled: row.get::<_, bool>(16).unwrap_or(false),
+            multihome_ip: row.get(17).unwrap_or(None),
+            rcon_host: row.get(26)?,
+            crossplay_enabled: row.get::<_, bool>(18).unwrap_or(false),
+            auto_restart: row.get(19).unwrap_or(Some(false)),
+            auto_update: row.get(20).unwrap_or(Some(false)),
+            runner: RunnerConfig {
+                kind: row.get::<_, Option<String>>(22)?
+                    .and_then(|k| k.parse().ok())
+                    .unwrap_or_default(),
+                path: row.get(23)?,
+                prefix: row.get(24)?,
+            },
+            launch_profile: launch::parse_profile(row.get(25)?),
+        },
+        created_at: row.get(14)?,
+        last_started: row.get(15)?,
+        pid: row.get(21).unwrap_or(None),
+        shared_install_of: row.get(27)?,
+    })
+}
+
+pub fn list(conn: &Connection) -> rusqlite::Result<Vec<Server>> {
+    let mut stmt = conn.prepare(&format!("SELECT {} FROM servers ORDER BY id", SERVER_COLUMNS))?;
+    let servers = stmt.query_map([], server_from_row)?;
+    servers.collect()
+}
+
+pub fn load(conn: &Connection, server_id: i64) -> rusqlite::Result<Option<Server>> {
+    conn.query_row(
+        &format!("SELECT {} FROM servers WHERE id = ?1", SERVER_COLUMNS),
+        [server_id],
+        server_from_row,
+    )
+    .optional()
+}
+
+/// Whether the server has, or is about to have, a live process.
+pub fn is_active(status: &ServerStatus) -> bool {
+    matches!(
+        status,
+        ServerStatus::Installing | ServerStatus::Starting | ServerStatus::Running | ServerStatus::Stopping | ServerStatus::Updating
+    )
+}
+
+/// Trimmed text, with empty meaning unset.
+fn non_empty(value: String) -> Option<String> {
+    let value = value.trim().to_string();
+    (!value.is_empty()).then_some(value)
+}
+
+fn apply(current: &Server, update: ServerSettingsUpdate) -> Server {
+    let mut server = current.clone();
+    let config = &mut server.config;
+
+    if let Some(name) = update.name {
+        server.name = name.trim().to_string();
+    }
+    if let Some(path) = update.install_path {
+        server.install_path = PathBuf::from(path.trim());
+    }
+    if let Some(ports) = update.ports {
+        server.ports = ports;
+    }
+    if let Some(max_players) = update.max_players {
+        config.max_players = max_players;
+    }
+    if let Some(password) = update.server_password {
+        config.server_password = non_empty(password);
+    }
+    if let Some(password) = update.admin_password {
+        config.admin_password = password.trim().to_string();
+    }
+    if let Some(map_name) = update.map_name {
+        config.map_name = map_name.trim().to_string();
+    }
+    if let Some(session_name) = update.session_name {
+        config.session_name = session_name.trim().to_string();
+    }
+    if let Some(motd) = update.motd {
+        config.motd = non_empty(motd);
+    }
+    if let Some(enabled) = update.battleye_enabled {
+        config.battleye_enabled = enabled;
+    }
+    if let Some(ip) = update.multihome_ip {
+        config.multihome_ip = non_empty(ip);
+    }
+    if let Some(host) = update.rcon_host {
+        config.rcon_host = non_empty(host);
+    }
+    if let Some(enabled) = update.crossplay_enabled {
+        config.crossplay_enabled = enabled;
+    }
+    if update.auto_restart.is_some() {
+        config.auto_restart = update.auto_restart;
+    }
+    if update.auto_update.is_some() {
+        config.auto_update = update.auto_update;
+    }
+    if let Some(runner) = update.runner {
+        config.runner = runner;
+    }
+    if let Some(profile) = update.launch_profile {
+        config.launch_profile = profile;
+    }
+
+    server
+}
+
+/// Values that end up in the `Map?Key=Value` launch token can't contain `?`,
+/// and ARK cuts passwords and map names at the first space.
+fn validate_token(label: &str, value: &str, allow_spaces: bool) -> Result<(), String> {
+    if value.contains('?') || value.contains('"') {
+        return Err(format!("{} can't contain '?' or '\"'", label));
+    }
+    if !allow_spaces && value.contains(char::is_whitespace) {
+        return Err(format!("{} can't contain spaces", label));
+    }
+    Ok(())
+}
+
+pub fn validate(server: &Server) -> Result<(), String> {
+    let config = &server.config;
+
+    if server.name.is_empty() {
+        return Err("Server name can't be empty".to_string());
+    }
+    if server.install_path.as_os_str().is_empty() {
+        return Err("Install path can't be empty".to_string());
+    }
+    if config.session_name.is_empty() {
+        return Err("Session name can't be empty".to_string());
+    }
+    validate_token("Session name", &config.session_name, true)?;
+    if config.map_name.is_empty() {
+        return Err("Map name can't be empty".to_string());
+    }
+    validate_token("Map name", &config.map_name, false)?;
+    if config.admin_password.is_empty() {
+        return Err("Admin password can't be empty".to_string());
+    }
+    validate_token("Admin password", &config.admin_password, false)?;
+    if let Some(password) = &config.server_password {
+        validate_token("Server password", password, false)?;
+    }
+    if !(1..=255).contains(&config.max_players) {
+        return Err("Max players must be between 1 and 255".to_string());
+    }
+
+    let ports = &server.ports;
+    if ports.game_port == 0 || ports.query_port == 0 || ports.rcon_port == 0 {
+        return Err("Ports can't be 0".to_string());
+    }
+    if ports.game_port == u16::MAX {
+        return Err("Game port must leave room for the raw UDP port after it".to_string());
+    }
+
+    if let Some(ip) = &config.multihome_ip {
+        ip.parse::<IpAddr>()
+            .map_err(|_| format!("Invalid MultiHome IP address: {}", ip))?;
+    }
+
+    if let Some(host) = &config.rcon_host {
+        // A port goes in the RCON port, not here
+        if host.contains(char::is_whitespace) || (host.contains(':') && host.parse::<IpAddr>().is_err()) {
+            return Err(format!("Invalid RCON host: {}", host));
+        }
+    }
+
+    runner::validate(&config.runner)?;
+    launch::validate_profile(&config.launch_profile)?;
+    Ok(())
+}
+
+/// Settings the running process, RCON or the monitor depend on.
+fn unsafe_changes(current: &Server, updated: &Server) -> Vec<&'static str> {
+    let mut changes = Vec::new();
+    if updated.ports != current.ports {
+        changes.push("ports");
+    }
+    if updated.install_path != current.install_path {
+        changes.push("install path");
+    }
+    if updated.config.multihome_ip != current.config.multihome_ip {
+        changes.push("MultiHome IP");
+    }
+    // Stops, the watchdog and readiness probes reach RCON through it
+    if updated.config.rcon_host != current.config.rcon_host {
+        changes.push("RCON host");
+    }
+    // Graceful stops log in over RCON with it
+    if updated.config.admin_password != current.config.admin_password {
+        changes.push("admin password");
+    }
+    if updated.config.runner != current.config.runner {
+        changes.push("runner");
+    }
+    changes
+}
+
+/// Validate and save `update`. Changes that would cut the manager off from a
+/// running server are refused until it is stopped; the rest apply on the
+/// next start.
+pub fn update_settings(conn: &Connection, server_id: i64, update: ServerSettingsUpdate) -> Result<Server, String> {
+    let current = load(conn, server_id)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("Server {} not found", server_id))?;
+
+    let updated = apply(&current, update);
+    validate(&updated)?;
+
+    if is_active(&current.status) {
+        let changes = unsafe_changes(&current, &updated);
+        if !changes.is_empty() {
+            return Err(format!("Stop the server before changing its {}", changes.join(", ")));
+        }
+    }
+
+    if updated.ports != current.ports || updated.config.multihome_ip != current.config.multihome_ip {
+        let multihome_ip = updated.config.multihome_ip.as_deref();
+        let conflicts = port_allocator::find_conflicts(conn, Some(server_id), &updated.ports, multihome_ip, CheckScope::AllServers)
+            .map_err(|e| e.to_string())?;
+        if !conflicts.is_empty() {
+            return Err(port_allocator::describe(conn, &conflicts, multihome_ip));
+        }
+    }
+
+    let config = &updated.config;
+    let profile = serde_json::to_string(&config.launch_profile).map_err(|e| e.to_string())?;
+    conn.execute(
+        "UPDATE servers SET name = ?1, install_path = ?2, game_port = ?3, query_port = ?4, rcon_port = ?5,
+         max_players = ?6, server_password = ?7, admin_password = ?8, map_name = ?9, session_name = ?10,
+         motd = ?11, battleye_enabled = ?12, multihome_ip = ?13, crossplay_enabled = ?14,
+         auto_restart = ?15, auto_update = ?16, runner = ?17, runner_path = ?18, compat_prefix = ?19,
+         launch_profile = ?20, rcon_host = ?21
+         WHERE id = ?22",
+        rusqlite::params![
+            updated.name,
+            updated.install_path.to_string_lossy().to_string(),
+            updated.ports.game_port,
+            updated.ports.query_port,
+            updated.ports.rcon_port,
+            config.max_players,
+            config.server_password,
+            config.admin_password,
+            config.map_name,
+            config.session_name,
+            config.motd,
+            config.battleye_enabled,
+            config.multihome_ip,
+            config.crossplay_enabled,
+            config.auto_restart.unwrap_or(false),
+            config.auto_update.unwrap_or(false),
+            config.runner.kind.to_string(),
+            config.runner.path,
+            config.runner.prefix,
+            profile,
+            config.rcon_host,
+            server_id,
+        ],
+    )
+    .map_err(|e| e.to_string())?;
+
+    Ok(updated)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::test_connection;
+
+    fn ports(game_port: u16, query_port: u16, rcon_port: u16) -> ServerPorts {
+        ServerPorts { game_port, query_port, rcon_port }
+    }
+
+    #[test]
+    fn launch_tokens_reject_question_marks_and_spaces() {
+        let conn = test_connection();
+        for update in [
+            ServerSettingsUpdate { map_name: Some("The Island".to_string()), ..Default::default() },
+            ServerSettingsUpdate { map_name: Some("TheIsland?Port=1".to_string()), ..Default::default() },
+            ServerSettingsUpdate { admin_password: Some("pass word".to_string()), ..Default::default() },
+            ServerSettingsUpdate { admin_password: Some("pass?word".to_string()), ..Default::default() },
+        ] {
+            assert!(update_settings(&conn, 1, update).is_err());
+        }
+
+        let server = load(&conn, 1).unwrap().unwrap();
+        assert_eq!((server.config.map_name.as_str(), server.config.admin_password.as_str()), ("TheIsland", "secret"));
+    }
+
+    #[test]
+    fn running_servers_keep_their_ports_but_take_other_changes() {
+        let conn = test_connection();
+        conn.execute("UPDATE servers SET status = 'running' WHERE id = 1", []).unwrap();
+
+        let moved = ServerSettingsUpdate { ports: Some(ports(7877, 27115, 27120)), ..Default::default() };
+        let error = update_settings(&conn, 1, moved).unwrap_err();
+        assert!(error.contains("ports"), "{}", error);
+
+        let motd = ServerSettingsUpdate { motd: Some("Welcome".to_string()), ..Default::default() };
+        update_settings(&conn, 1, motd).unwrap();
+        let server = load(&conn, 1).unwrap().unwrap();
+        assert_eq!(server.config.motd.as_deref(), Some("Welcome"));
+        assert_eq!(server.ports, ports(7777, 27015, 27020));
+    }
+
+    #[test]
+    fn ports_of_another_server_are_refused() {
+        let conn = test_connection();
+        conn.execute(
+            "INSERT INTO servers (name, server_type, install_path, game_port, query_port, rcon_port,
+             admin_password, map_name, session_name)
+             VALUES ('Other', 'ASE', '/srv/other', 7877, 27115, 27120, 'secret', 'TheIsland', 'Other')",
+            [],
+        )
+        .unwrap();
+
+        let update = ServerSettingsUpdate { ports: Some(ports(7877, 27015, 27020)), ..Default::default() };
+        let error = update_settings(&conn, 1, update).unwrap_err();
+        assert!(error.contains("server \"Other\""), "{}", error);
+        assert_eq!(load(&conn, 1).unwrap().unwrap().ports, ports(7777, 27015, 27020));
+    }
+}
@@ -1,25 +1,29 @@
 use std::path::PathBuf;
 use std::io::Cursor;
-use tauri::{AppHandle, Manager, Emitter};
+use crate::services::events::EventBus;
+use crate::AppState;
 use anyhow::{Result, Context};
-use std::os::windows::process::CommandExt; // For create_no_window
+use crate::services::platform;
 
 pub struct SteamCmdService {
-    app_handle: AppHandle,
+    events: EventBus,
+    data_dir: PathBuf,
 }
 
 impl SteamCmdService {
-    pub fn new(app_handle: AppHandle) -> Self {
-        Self { app_handle }
+    pub fn new(state: &AppState) -> Self {
+        Self {
+            events: state.events.clone(),
+            data_dir: state.data_dir.clone(),
+        }
     }
 
     pub fn get_steamcmd_dir(&self) -> Result<PathBuf> {
-        let app_dir = self.app_handle.path().app_data_dir()?;
-        Ok(app_dir.join("steamcmd"))
+        Ok(self.data_dir.join("steamcmd"))
     }
 
     pub fn get_steamcmd_exe(&self) -> Result<PathBuf> {
-        Ok(self.get_steamcmd_dir()?.join("steamcmd.exe"))
+        Ok(self.get_steamcmd_dir()?.join(platform::steamcmd_executable_name()))
     }
 
     pub fn check_installation(&self) -> bool {
@@ -36,7 +40,7 @@ impl SteamCmdService {
         }
 
         println!("Downloading SteamCMD...");
-        let response = reqwest::get("https://steamcdn-a.akamaihd.net/client/installer/steamcmd.zip")
+        let response = reqwest::get(platform::steamcmd_download_url())
             .await
             .context("Failed to download SteamCMD")?;
 
@@ -45,8 +49,16 @@ impl SteamCmdService {
         println!("Extracting SteamCMD...");
         let target_dir = install_dir.clone();
         tokio::task::spawn_blocking(move || -> Result<()> {
-            let mut archive = zip::ZipArchive::new(Cursor::new(bytes))?;
-            archive.extract(&target_dir)?;
+            if cfg!(windows) {
+                let mut archive = zip::ZipArchive::new(Cursor::new(bytes))?;
+                archive.extract(&target_dir)?;
+            } else {
+                // Linux ships a tarball with steamcmd.sh and the linux32 runtime
+                let decoder = flate2::read::GzDecoder::new(Cursor::new(bytes));
+                let mut archive = tar::Archive::new(decoder);
+                archive.set_preserve_permissions(true);
+                archive.unpack(&target_dir)?;
+            }
             Ok(())
         }).await??;
 
@@ -70,24 +82,30 @@ impl SteamCmdService {
 
         println!("Starting SteamCMD update for {} (AppID: {}) to {:?}", server_type, app_id, install_path);
         
-        let _ = self.app_handle.emit("install-progress", format!("Starting installation for {}...", server_type));
-
-        let mut child = std::process::Command::new(steamcmd_exe)
-            .args(&[
-                "+login", "anonymous",
-                "+force_install_dir", path_str,
-                "+app_update", app_id, "validate",
-                "+quit"
-            ])
+        self.events.emit("install-progress", format!("Starting installation for {}...", server_type));
+
+        let mut args = platform_override_args(server_type);
+        args.extend([
+            "+login", "anonymous",
+            "+force_install_dir", path_str,
+            "+app_update", app_id, "validate",
+            "+quit"
+        ]);
+
+        let mut command = std::process::Command::new(steamcmd_exe);
+        command
+            .args(&args)
             .stdout(std::process::Stdio::piped())
-            .stderr(std::process::Stdio::piped()) // Capture stderr too
-            .creation_flags(0x08000000) // CREATE_NO_WINDOW
+            .stderr(std::process::Stdio::piped()); // Capture stderr too
+        platform::hide_console_window(&mut command);
+
+        let mut child = command
             .spawn()
             .context("Failed to spawn SteamCMD process")?;
 
         // Handle stdout streaming
         let stdout = child.stdout.take().unwrap();
-        let app_handle = self.app_handle.clone();
+        let events = self.events.clone();
         
         // We can't use async reading easily here because we are in a blocking function (presumably wrapped in spawn_blocking)
         // So we use BufReader
@@ -98,7 +116,7 @@ impl SteamCmdService {
         for line in reader.lines() {
             if let Ok(l) = line {
                 println!("[SteamCMD] {}", l);
-                let _ = app_handle.emit("install-output", l);
+                events.emit("install-output", l);
             }
         }
 
@@ -106,10 +124,10 @@ impl SteamCmdService {
 
         if status.success() {
             println!("SteamCMD update completed successfully.");
-            let _ = self.app_handle.emit("install-progress", "Installation completed successfully.");
+            self.events.emit("install-progress", "Installation completed successfully.");
             Ok(())
         } else {
-             let _ = self.app_handle.emit("install-progress", "Installation failed.");
+             self.events.emit("install-progress", "Installation failed.");
              Err(anyhow::anyhow!("SteamCMD finished with error code: {:?}", status.code()))
         }
     }
@@ -162,10 +180,11 @@ impl SteamCmdService {
         println!("Starting SteamCMD mod download for {} mods on {}...", mod_ids.len(), server_type);
         
         // Construct arguments
-        let mut args = vec![
+        let mut args = platform_override_args(server_type);
+        args.extend([
             "+login", "anonymous",
             "+force_install_dir", path_str,
-        ];
+        ]);
 
         // Add workshop download commands
         for mod_id in &mod_ids {
@@ -179,16 +198,19 @@ impl SteamCmdService {
 
         args.push("+quit");
 
-        let mut child = std::process::Command::new(steamcmd_exe)
+        let mut command = std::process::Command::new(steamcmd_exe);
+        command
             .args(&args)
             .stdout(std::process::Stdio::piped())
-            .stderr(std::process::Stdio::piped())
-            .creation_flags(0x08000000) // CREATE_NO_WINDOW
+            .stderr(std::process::Stdio::piped());
+        platform::hide_console_window(&mut command);
+
+        let mut child = command
             .spawn()
             .context("Failed to spawn SteamCMD process for mods")?;
 
         let stdout = child.stdout.take().unwrap();
-        let app_handle = self.app_handle.clone();
+        let events = self.events.clone();
         
         use std::io::{BufRead, BufReader};
         let reader = BufReader::new(stdout);
@@ -209,7 +231,7 @@ impl SteamCmdService {
                 if let Some(caps) = re_downloading_item.captures(&l) {
                     if let Some(m_id) = caps.get(1) {
                         current_mod_id = m_id.as_str().to_string();
-                        let _ = app_handle.emit("mod-download-start", &current_mod_id);
+                        events.emit("mod-download-start", &current_mod_id);
                     }
                 }
 
@@ -218,7 +240,7 @@ impl SteamCmdService {
                     if let Some(progress) = caps.get(1) {
                          if let Ok(p_val) = progress.as_str().parse::<f64>() {
                              if !current_mod_id.is_empty() {
-                                 let _ = app_handle.emit("mod-download-progress", serde_json::json!({
+                                 events.emit("mod-download-progress", serde_json::json!({
                                      "modId": current_mod_id,
                                      "progress": p_val
                                  }));
@@ -293,10 +315,20 @@ impl SteamCmdService {
                  }
              }
 
-             let _ = self.app_handle.emit("install-progress", "Installation and file setup completed.");
+             self.events.emit("install-progress", "Installation and file setup completed.");
              Ok(())
         } else {
              Err(anyhow::anyhow!("SteamCMD finished with error code: {:?}", status.code()))
         }
     }
 }
+
+/// ASA only publishes a Windows depot, so a Linux SteamCMD has to be told to
+/// fetch it anyway; the server then runs through Wine/Proton.
+fn platform_override_args(server_type: &str) -> Vec<&'static str> {
+    if platform::has_native_binary(server_type) {
+        Vec::new()
+    } else {
+        vec!["+@sSteamCmdForcePlatformType", "windows"]
+    }
+}
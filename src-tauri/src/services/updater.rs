@@ -1,41 +1,35 @@
-use tauri::State;
 use crate::AppState;
 use std::time::Duration;
 use tokio::time::sleep;
-use crate::services::rcon_client::RconClient;
+use crate::services::rcon_sessions;
 use crate::services::steamcmd::SteamCmdService;
+use crate::services::{lifecycle, server_clone, server_status, webhooks};
+use crate::models::{ServerStatus, WebhookEvent};
+use crate::services::graceful_stop::{GracefulStopService, StopOptions};
 use std::path::PathBuf;
 
 pub struct ServerUpdateService;
 
 impl ServerUpdateService {
-    pub async fn update_server_graceful(
-        app_handle: tauri::AppHandle,
-        state: State<'_, AppState>,
-        server_id: i64,
-    ) -> Result<(), String> {
+    pub async fn update_server_graceful(state: &AppState, server_id: i64, cause: &str) -> Result<(), String> {
         // 1. Get server details
-        let (server_type, install_path, rcon_port, admin_password, session_name) = {
+        let (server_type, install_path, session_name) = {
             let db = state.db.lock().map_err(|e| e.to_string())?;
             let conn = db.get_connection().map_err(|e| e.to_string())?;
+            server_clone::ensure_own_install(&conn, server_id)?;
             conn.query_row(
-                "SELECT server_type, install_path, rcon_port, admin_password, session_name FROM servers WHERE id = ?1",
+                "SELECT server_type, install_path, session_name FROM servers WHERE id = ?1",
                 [server_id],
                 |row| Ok((
                     row.get::<_, String>(0)?,
                     row.get::<_, String>(1)?,
-                    row.get::<_, u16>(2)?,
-                    row.get::<_, String>(3)?,
-                    row.get::<_, String>(4)?,
+                    row.get::<_, String>(2)?,
                 )),
             ).map_err(|e| e.to_string())?
         };
 
         // 2. Notify Players (Graceful Shutdown)
-        // using localhost for RCON
-        let ip = "127.0.0.1";
-        
-        let _ = RconClient::execute(ip, rcon_port, &admin_password, "Broadcast Server updating in 15 minutes!").await;
+        let _ = rcon_sessions::execute(state, server_id, "Broadcast Server updating in 15 minutes!").await;
         // sleep(Duration::from_secs(300)).await; // 5 min passed (Total 10m left) - shortened for MVP?
         // Let's do a shorter sequence for MVP demo or real usage? 
         // Real usage: 15m -> 10m -> 5m -> 1m.
@@ -43,95 +37,60 @@ impl ServerUpdateService {
         // Blocking here is okay if running in async task.
         
         // Let's do a quick 3-step: Update Imminent! -> 30s -> Saving... -> Shutdown.
-        let _ = RconClient::execute(ip, rcon_port, &admin_password, "Broadcast Server updating in 2 minutes!").await;
+        let _ = rcon_sessions::execute(state, server_id, "Broadcast Server updating in 2 minutes!").await;
         sleep(Duration::from_secs(60)).await;
         
-        let _ = RconClient::execute(ip, rcon_port, &admin_password, "Broadcast Server updating in 60 seconds! Please log off.").await;
+        let _ = rcon_sessions::execute(state, server_id, "Broadcast Server updating in 60 seconds! Please log off.").await;
         sleep(Duration::from_secs(50)).await;
         
-        let _ = RconClient::execute(ip, rcon_port, &admin_password, "Broadcast Server shutting down for update NOW!").await;
-        let _ = RconClient::execute(ip, rcon_port, &admin_password, "SaveWorld").await;
-        sleep(Duration::from_secs(10)).await;
+        let _ = rcon_sessions::execute(state, server_id, "Broadcast Server shutting down for update NOW!").await;
 
-        // 3. Stop Server
-        // We call the existing stop_server logic but we need to do it via AppState directly or command logic.
-        // state.process_manager is accessible.
-        state.process_manager.stop_server(server_id).map_err(|e| e.to_string())?;
+        // 3. Stop Server (saves the world and waits for it before exiting)
+        let options = {
+            let db = state.db.lock().map_err(|e| e.to_string())?;
+            StopOptions::from_settings(&db).without_warning()
+        };
+        GracefulStopService::stop(state, server_id, &options, cause).await?;
+        lifecycle::mark_stopped(state, server_id, cause)?;
 
-        // Update DB status to Updating
-         {
+        // Claim `updating`; a concurrent update gets an error here instead
+        // of running SteamCMD into the same install
+        let previous = {
             let db = state.db.lock().map_err(|e| e.to_string())?;
             let conn = db.get_connection().map_err(|e| e.to_string())?;
-            conn.execute("UPDATE servers SET status = 'updating' WHERE id = ?1", [server_id])
-                .map_err(|e| e.to_string())?;
-         }
+            server_status::claim(&conn, server_id, ServerStatus::Updating, cause)?
+        };
 
         // 4. Perform Update
-        let steamcmd = SteamCmdService::new(app_handle.clone());
+        let steamcmd = SteamCmdService::new(state);
         let path = PathBuf::from(&install_path);
         
         let path_clone = path.clone();
         let type_clone = server_type.clone();
 
         // This is blocking, so run in blocking thread if not already
-        match tauri::async_runtime::spawn_blocking(move || {
+        let result = match tauri::async_runtime::spawn_blocking(move || {
             steamcmd.install_server(&type_clone, &path_clone)
-        }).await.map_err(|e| e.to_string())? {
+        }).await {
+            Ok(result) => result,
+            Err(e) => {
+                lifecycle::release_update(state, server_id, previous, &e.to_string())?;
+                return Err(e.to_string());
+            }
+        };
+        match result {
             Ok(_) => {
                 // 5. Restart Server
-                // We need to call start_server. We don't have all args here easily without querying again.
-                // We can call `crate::commands::server::start_server` if we exposed it? 
-                // Or reuse process_manager.restart_server logic.
-                // But ProcessManager logic needs all args.
-                // Let's query again.
-                
-                let (game_port, query_port, max_players, server_password, battleye, multihome, crossplay, map_name) = {
-                     let db = state.db.lock().map_err(|e| e.to_string())?;
-                     let conn = db.get_connection().map_err(|e| e.to_string())?;
-                     conn.query_row(
-                        "SELECT game_port, query_port, max_players, server_password, battleye_enabled, multihome_ip, crossplay_enabled, map_name FROM servers WHERE id = ?1",
-                        [server_id],
-                        |row| Ok((
-                            row.get::<_, u16>(0)?, 
-                            row.get::<_, u16>(1)?, 
-                            row.get::<_, i32>(2)?, 
-                            row.get::<_, Option<String>>(3)?, 
-                            row.get::<_, bool>(4).unwrap_or(false), 
-                            row.get::<_, Option<String>>(5)?, 
-                            row.get::<_, bool>(6).unwrap_or(false), 
-                            row.get::<_, String>(7)?
-                        ))
-                     ).map_err(|e| e.to_string())?
-                };
-
-                state.process_manager.start_server(
-                    &app_handle,
-                    server_id,
-                    &server_type,
-                    &path,
-                    &map_name,
-                    &session_name,
-                    game_port,
-                    query_port,
-                    rcon_port,
-                    max_players,
-                    server_password.as_deref(),
-                    &admin_password,
-                    battleye,
-                    multihome,
-                    crossplay
-                ).map_err(|e| e.to_string())?;
-
-                // Update status
                 {
                     let db = state.db.lock().map_err(|e| e.to_string())?;
                     let conn = db.get_connection().map_err(|e| e.to_string())?;
-                    conn.execute("UPDATE servers SET status = 'running' WHERE id = ?1", [server_id])
-                        .map_err(|e| e.to_string())?;
+                    server_status::transition(&conn, server_id, ServerStatus::Stopped, "update finished")?;
                 }
-                
+                webhooks::fire(state, WebhookEvent::Update, server_id, serde_json::json!({ "success": true }));
+                lifecycle::start_server(state, server_id, cause).await?;
+
                 let _ = crate::services::notifications::NotificationService::send_notification(
-                    &state, 
+                    state, 
                     "Update Complete", 
                     &format!("Server {} has been updated and restarted.", session_name)
                 ).await;
@@ -141,9 +100,12 @@ impl ServerUpdateService {
                  {
                     let db = state.db.lock().map_err(|e| e.to_string())?;
                     let conn = db.get_connection().map_err(|e| e.to_string())?;
-                    conn.execute("UPDATE servers SET status = 'crashed' WHERE id = ?1", [server_id])
-                        .map_err(|e| e.to_string())?;
+                    server_status::transition(&conn, server_id, ServerStatus::Crashed, &format!("update failed: {}", e))?;
                  }
+                 webhooks::fire(state, WebhookEvent::Update, server_id, serde_json::json!({
+                     "success": false,
+                     "error": e.to_string()
+                 }));
                  return Err(format!("Update failed: {}", e));
             }
         }
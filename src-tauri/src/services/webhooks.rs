@@ -0,0 +1,355 @@
+use crate::models::{Webhook, WebhookDelivery, WebhookEvent};
+use crate::AppState;
+use hmac::{Hmac, Mac};
+use rand::RngCore;
+use rusqlite::{Connection, OptionalExtension};
+use serde_json::{json, Value};
+use sha2::Sha256;
+use std::time::Duration;
+
+// Outbound webhooks for lifecycle events, next to the Discord notification.
+// Each target picks its events and may shape the body with a template; the
+// body is signed with the target's secret as
+// `X-ASM-Signature: sha256=<hex HMAC-SHA256 of the body>`. Failed deliveries
+// are retried in the background while the process runs, and every attempt
+// is logged in `webhook_deliveries`.
+
+/// Waits before each retry; a delivery gets one attempt more than this has entries.
+const RETRY_DELAYS: [Duration; 4] = [
+    Duration::from_secs(10),
+    Duration::from_secs(60),
+    Duration::from_secs(5 * 60),
+    Duration::from_secs(30 * 60),
+];
+
+/// Attempts kept in the log per webhook.
+const LOG_LIMIT: i64 = 200;
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+fn webhook_from_row(row: &rusqlite::Row) -> rusqlite::Result<Webhook> {
+    let events: String = row.get(3)?;
+    Ok(Webhook {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        url: row.get(2)?,
+        events: events.split_whitespace().filter_map(|e| e.parse().ok()).collect(),
+        template: row.get(4)?,
+        secret: row.get(5)?,
+        enabled: row.get(6)?,
+    })
+}
+
+const WEBHOOK_COLUMNS: &str = "id, name, url, events, template, secret, enabled";
+
+pub fn list(conn: &Connection) -> rusqlite::Result<Vec<Webhook>> {
+    let mut stmt = conn.prepare(&format!("SELECT {} FROM webhooks ORDER BY id", WEBHOOK_COLUMNS))?;
+    let webhooks = stmt.query_map([], webhook_from_row)?;
+    webhooks.collect()
+}
+
+pub fn load(conn: &Connection, id: i64) -> rusqlite::Result<Option<Webhook>> {
+    conn.query_row(&format!("SELECT {} FROM webhooks WHERE id = ?1", WEBHOOK_COLUMNS), [id], webhook_from_row)
+        .optional()
+}
+
+/// The body sent when a webhook has no template.
+fn default_payload(event: &str, server_id: Option<i64>, server_name: Option<&str>, data: Value) -> Value {
+    json!({
+        "event": event,
+        "serverId": server_id,
+        "serverName": server_name,
+        "timestamp": chrono::Utc::now().to_rfc3339(),
+        "data": data,
+    })
+}
+
+/// Fill `{{name}}` placeholders from the default payload: `{{event}}`,
+/// `{{serverName}}`, `{{data.exitCode}}` and so on, or `{{payload}}` for all
+/// of it. Strings go in JSON-escaped without their quotes, so they can sit
+/// inside the template's own strings; anything missing becomes `null`.
+pub fn render(template: &str, payload: &Value) -> Result<String, String> {
+    let placeholder = regex::Regex::new(r"\{\{\s*([\w.]+)\s*\}\}").map_err(|e| e.to_string())?;
+    let body = placeholder.replace_all(template, |caps: &regex::Captures| {
+        let path = &caps[1];
+        if path == "payload" {
+            return payload.to_string();
+        }
+        let value = path.split('.').try_fold(payload, |value, key| value.get(key));
+        match value {
+            Some(Value::String(s)) => {
+                let quoted = Value::String(s.clone()).to_string();
+                quoted[1..quoted.len() - 1].to_string()
+            }
+            Some(value) => value.to_string(),
+            None => "null".to_string(),
+        }
+    });
+
+    serde_json::from_str::<Value>(&body).map_err(|e| format!("Template does not produce valid JSON: {}", e))?;
+    Ok(body.into_owned())
+}
+
+fn generate_secret() -> String {
+    let mut bytes = [0u8; 24];
+    rand::rngs::OsRng.fill_bytes(&mut bytes);
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Create the webhook, or update it when it has an id.
+pub fn save(conn: &Connection, mut webhook: Webhook) -> Result<Webhook, String> {
+    webhook.name = webhook.name.trim().to_string();
+    webhook.url = webhook.url.trim().to_string();
+    if webhook.name.is_empty() {
+        return Err("Webhook name can't be empty".to_string());
+    }
+    if !webhook.url.starts_with("http://") && !webhook.url.starts_with("https://") {
+        return Err("Webhook URL must start with http:// or https://".to_string());
+    }
+    if webhook.events.is_empty() {
+        return Err("Pick at least one event for the webhook".to_string());
+    }
+    webhook.template = webhook.template.filter(|t| !t.trim().is_empty());
+    if let Some(template) = &webhook.template {
+        let sample = default_payload("crash", Some(1), Some("Example"), json!({ "exitCode": 1 }));
+        render(template, &sample)?;
+    }
+    if webhook.secret.trim().is_empty() {
+        webhook.secret = generate_secret();
+    }
+
+    let events = webhook.events.iter().map(|e| e.to_string()).collect::<Vec<_>>().join(" ");
+    match webhook.id {
+        Some(id) => {
+            let updated = conn
+                .execute(
+                    "UPDATE webhooks SET name = ?1, url = ?2, events = ?3, template = ?4, secret = ?5, enabled = ?6
+                     WHERE id = ?7",
+                    (&webhook.name, &webhook.url, &events, &webhook.template, &webhook.secret, webhook.enabled, id),
+                )
+                .map_err(|e| e.to_string())?;
+            if updated == 0 {
+                return Err(format!("Webhook {} not found", id));
+            }
+        }
+        None => {
+            conn.execute(
+                "INSERT INTO webhooks (name, url, events, template, secret, enabled) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                (&webhook.name, &webhook.url, &events, &webhook.template, &webhook.secret, webhook.enabled),
+            )
+            .map_err(|e| e.to_string())?;
+            webhook.id = Some(conn.last_insert_rowid());
+        }
+    }
+    Ok(webhook)
+}
+
+pub fn delete(conn: &Connection, id: i64) -> Result<(), String> {
+    let deleted = conn.execute("DELETE FROM webhooks WHERE id = ?1", [id]).map_err(|e| e.to_string())?;
+    if deleted == 0 {
+        return Err(format!("Webhook {} not found", id));
+    }
+    Ok(())
+}
+
+/// The webhook's most recent delivery attempts, newest first.
+pub fn deliveries(conn: &Connection, webhook_id: i64, limit: usize) -> rusqlite::Result<Vec<WebhookDelivery>> {
+    let mut stmt = conn.prepare(
+        "SELECT id, webhook_id, event, attempt, status_code, error, delivered, created_at FROM webhook_deliveries
+         WHERE webhook_id = ?1 ORDER BY id DESC LIMIT ?2",
+    )?;
+    let deliveries = stmt.query_map((webhook_id, limit as i64), |row| {
+        Ok(WebhookDelivery {
+            id: row.get(0)?,
+            webhook_id: row.get(1)?,
+            event: row.get(2)?,
+            attempt: row.get(3)?,
+            status_code: row.get(4)?,
+            error: row.get(5)?,
+            delivered: row.get(6)?,
+            created_at: row.get(7)?,
+        })
+    })?;
+    deliveries.collect()
+}
+
+fn log_attempt(
+    state: &AppState,
+    webhook_id: i64,
+    event: &str,
+    attempt: usize,
+    status_code: Option<u16>,
+    error: Option<&str>,
+) -> Result<(), String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let conn = db.get_connection()?;
+    conn.execute(
+        "INSERT INTO webhook_deliveries (webhook_id, event, attempt, status_code, error, delivered)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        (webhook_id, event, attempt as i64, status_code, error, error.is_none()),
+    )
+    .map_err(|e| e.to_string())?;
+    conn.execute(
+        "DELETE FROM webhook_deliveries WHERE webhook_id = ?1 AND id NOT IN
+         (SELECT id FROM webhook_deliveries WHERE webhook_id = ?1 ORDER BY id DESC LIMIT ?2)",
+        (webhook_id, LOG_LIMIT),
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+fn sign(secret: &str, body: &str) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC takes keys of any length");
+    mac.update(body.as_bytes());
+    let digest: String = mac.finalize().into_bytes().iter().map(|b| format!("{:02x}", b)).collect();
+    format!("sha256={}", digest)
+}
+
+/// POST `payload` to the webhook, retrying timeouts, rate limits and server
+/// errors when `retry` is set. Returns the last attempt's error.
+async fn deliver(state: &AppState, webhook: &Webhook, event: &str, payload: &Value, retry: bool) -> Result<(), String> {
+    let webhook_id = webhook.id.unwrap_or_default();
+    let body = match &webhook.template {
+        Some(template) => render(template, payload),
+        None => Ok(payload.to_string()),
+    };
+    let body = match body {
+        Ok(body) => body,
+        Err(e) => {
+            let _ = log_attempt(state, webhook_id, event, 1, None, Some(&e));
+            return Err(e);
+        }
+    };
+    let signature = sign(&webhook.secret, &body);
+    let client = reqwest::Client::builder()
+        .timeout(REQUEST_TIMEOUT)
+        .build()
+        .map_err(|e| e.to_string())?;
+
+    let attempts = if retry { RETRY_DELAYS.len() + 1 } else { 1 };
+    let mut last_error = String::new();
+    for attempt in 1..=attempts {
+        let response = client
+            .post(&webhook.url)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .header("X-ASM-Event", event)
+            .header("X-ASM-Signature", &signature)
+            .body(body.clone())
+            .send()
+            .await;
+
+        let (status_code, error, retryable) = match response {
+            Ok(r) if r.status().is_success() => (Some(r.status().as_u16()), None, false),
+            Ok(r) => {
+                let code = r.status().as_u16();
+                (Some(code), Some(format!("HTTP {}", r.status())), code >= 500 || code == 408 || code == 429)
+            }
+            Err(e) => (None, Some(e.to_string()), true),
+        };
+        if let Err(e) = log_attempt(state, webhook_id, event, attempt, status_code, error.as_deref()) {
+            eprintln!("Failed to log webhook delivery: {}", e);
+        }
+
+        match error {
+            None => return Ok(()),
+            Some(e) => last_error = e,
+        }
+        if !retryable {
+            break;
+        }
+        if let Some(delay) = RETRY_DELAYS.get(attempt - 1).filter(|_| attempt < attempts) {
+            tokio::time::sleep(*delay).await;
+        }
+    }
+
+    Err(last_error)
+}
+
+/// Send `event` for a server to every enabled webhook that wants it. Returns
+/// at once; deliveries and their retries run in the background.
+pub fn fire(state: &AppState, event: WebhookEvent, server_id: i64, data: Value) {
+    let (targets, server_name) = {
+        let Ok(db) = state.db.lock() else { return };
+        let Ok(conn) = db.get_connection() else { return };
+        let targets: Vec<Webhook> = list(&conn)
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|w| w.enabled && w.events.contains(&event))
+            .collect();
+        if targets.is_empty() {
+            return;
+        }
+        let server_name: Option<String> = conn
+            .query_row("SELECT name FROM servers WHERE id = ?1", [server_id], |row| row.get(0))
+            .ok();
+        (targets, server_name)
+    };
+
+    let event = event.to_string();
+    let payload = default_payload(&event, Some(server_id), server_name.as_deref(), data);
+    for webhook in targets {
+        let state = state.clone();
+        let event = event.clone();
+        let payload = payload.clone();
+        tauri::async_runtime::spawn(async move {
+            if let Err(e) = deliver(&state, &webhook, &event, &payload, true).await {
+                eprintln!("Webhook \"{}\" failed for {}: {}", webhook.name, event, e);
+            }
+        });
+    }
+}
+
+/// Send a `test` event to the webhook once, without retries.
+pub async fn test(state: &AppState, webhook_id: i64) -> Result<(), String> {
+    let webhook = {
+        let db = state.db.lock().map_err(|e| e.to_string())?;
+        let conn = db.get_connection()?;
+        load(&conn, webhook_id)
+            .map_err(|e| e.to_string())?
+            .ok_or_else(|| format!("Webhook {} not found", webhook_id))?
+    };
+    let payload = default_payload("test", None, None, json!({ "message": "Test delivery from ARK Server Manager" }));
+    deliver(state, &webhook, "test", &payload, false).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn template_placeholders_are_escaped_and_nested() {
+        let payload = default_payload("crash", Some(3), Some("The \"Island\""), json!({ "exitCode": 137 }));
+        let body = render(
+            r#"{"text": "{{serverName}} crashed with {{data.exitCode}}", "code": {{data.exitCode}}, "missing": {{data.nope}}}"#,
+            &payload,
+        )
+        .unwrap();
+        let body: Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(body["text"], "The \"Island\" crashed with 137");
+        assert_eq!(body["code"], 137);
+        assert_eq!(body["missing"], Value::Null);
+    }
+
+    #[test]
+    fn whole_payload_can_be_embedded() {
+        let payload = default_payload("stop", Some(1), None, json!({}));
+        let body = render(r#"{"source": "asm", "payload": {{payload}}}"#, &payload).unwrap();
+        let body: Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(body["payload"]["event"], "stop");
+    }
+
+    #[test]
+    fn templates_must_produce_json() {
+        let payload = default_payload("stop", Some(1), None, json!({}));
+        assert!(render(r#"{"text": {{event}}}"#, &payload).is_err());
+    }
+
+    #[test]
+    fn signature_is_hmac_sha256_hex() {
+        // RFC 4231 test case 2
+        assert_eq!(
+            sign("Jefe", "what do ya want for nothing?"),
+            "sha256=5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+    }
+}
@@ -2,7 +2,7 @@ import { useRef, useEffect, useState } from 'react';
 import { X, Folder } from 'lucide-react';
 import { useServerStore } from '../../stores/serverStore';
 import { useUIStore } from '../../stores/uiStore';
-import { installServer, InstallServerParams } from '../../utils/tauri';
+import { installServer, suggestServerPorts, InstallServerParams } from '../../utils/tauri';
 import { cn } from '../../utils/helpers';
 import toast from 'react-hot-toast';
 import { listen } from '@tauri-apps/api/event';
@@ -74,6 +74,17 @@ export default function InstallServerDialog({ onClose }: Props) {
         rconPort: 32330,
     });
 
+    // Start from ports no other server uses
+    useEffect(() => {
+        suggestServerPorts(1)
+            .then(([ports]) => {
+                if (ports) {
+                    setFormData(prev => ({ ...prev, ...ports }));
+                }
+            })
+            .catch((error) => console.error('Failed to suggest ports:', error));
+    }, []);
+
     const handleInstall = async () => {
         setIsInstalling(true);
         setStep(3); // Move to install view (renumbered)
@@ -110,6 +110,15 @@ export default function Dashboard() {
         if (status === 'crashed') {
           toast.error(`Server ID ${id} process stopped unexpectedly!`);
         }
+        if (status === 'crash-looping') {
+          toast.error(`Server ID ${id} keeps crashing. Auto-restart is paused until it is reset.`);
+        }
+        if (status === 'failed') {
+          toast.error(`Server ID ${id} did not finish starting and was stopped.`);
+        }
+        if (status === 'running') {
+          toast.success(`Server ID ${id} is ready for players.`);
+        }
       });
     }
     setupListener();
@@ -410,7 +419,9 @@ export default function Dashboard() {
                       server.status === 'stopped' && 'bg-slate-500',
                       server.status === 'crashed' && 'bg-red-500 shadow-[0_0_10px_rgba(239,68,68,0.5)]',
                       server.status === 'starting' && 'bg-yellow-500 animate-pulse',
-                      server.status === 'updating' && 'bg-blue-500 animate-pulse'
+                      server.status === 'updating' && 'bg-blue-500 animate-pulse',
+                      server.status === 'failed' && 'bg-red-500',
+                      server.status === 'crash-looping' && 'bg-orange-500'
                     )} />
                     {server.status === 'running' && (
                       <div className="absolute inset-0 bg-green-500 rounded-full animate-ping opacity-20"></div>
@@ -437,7 +448,9 @@ export default function Dashboard() {
                     server.status === 'stopped' && 'bg-slate-500/10 text-slate-400 border-slate-500/20',
                     server.status === 'crashed' && 'bg-red-500/10 text-red-400 border-red-500/20',
                     server.status === 'starting' && 'bg-yellow-500/10 text-yellow-400 border-yellow-500/20',
-                    server.status === 'updating' && 'bg-blue-500/10 text-blue-400 border-blue-500/20'
+                    server.status === 'updating' && 'bg-blue-500/10 text-blue-400 border-blue-500/20',
+                    server.status === 'failed' && 'bg-red-500/10 text-red-400 border-red-500/20',
+                    server.status === 'crash-looping' && 'bg-orange-500/10 text-orange-400 border-orange-500/20'
                   )}>
                     {server.status.charAt(0).toUpperCase() + server.status.slice(1)}
                   </span>
@@ -5,6 +5,7 @@ import { listen } from '@tauri-apps/api/event';
 import { useServerStore } from '../stores/serverStore';
 import { useUIStore } from '../stores/uiStore';
 import { invoke } from '@tauri-apps/api/core';
+import { getConsoleHistory } from '../utils/tauri';
 
 interface LogEntry {
     time: string;
@@ -16,7 +17,18 @@ interface LogEntry {
 interface LogEvent {
     serverId: number;
     line: string;
-    type: 'stdout' | 'stderr';
+    type: 'stdout' | 'stderr' | 'log';
+}
+
+function levelOf(line: string, type: string): LogEntry['level'] {
+    const lowerLine = line.toLowerCase();
+    if (type === 'stderr' || lowerLine.includes('error') || lowerLine.includes('failed') || lowerLine.includes('exception')) {
+        return 'error';
+    }
+    if (lowerLine.includes('warning') || lowerLine.includes('warn')) {
+        return 'warning';
+    }
+    return 'info';
 }
 
 export default function LogsConsole() {
@@ -43,6 +55,29 @@ export default function LogsConsole() {
         logsEndRef.current?.scrollIntoView({ behavior: 'smooth' });
     }, [logs, filter]);
 
+    // Load what the server printed while this page was closed
+    useEffect(() => {
+        if (!selectedServerId) return;
+        let cancelled = false;
+
+        getConsoleHistory(selectedServerId, undefined, 1000)
+            .then((history) => {
+                if (cancelled) return;
+                const entries: LogEntry[] = history.map((l) => ({
+                    time: new Date(l.timestamp).toLocaleTimeString('en-US', { hour12: false }),
+                    level: levelOf(l.line, l.stream),
+                    message: l.line,
+                    serverId: selectedServerId
+                }));
+                setLogs((prev) => [...prev.filter((log) => log.serverId !== selectedServerId), ...entries]);
+            })
+            .catch((error) => console.error('Failed to load console history:', error));
+
+        return () => {
+            cancelled = true;
+        };
+    }, [selectedServerId]);
+
     // Listen for server logs
     useEffect(() => {
         const unlisten = listen<LogEvent>('server-console-output', (event) => {
@@ -50,19 +85,10 @@ export default function LogsConsole() {
             const now = new Date();
             const timeStr = now.toLocaleTimeString('en-US', { hour12: false });
 
-            let level: 'info' | 'warning' | 'error' = 'info';
-            const lowerLine = line.toLowerCase();
-
-            if (type === 'stderr' || lowerLine.includes('error') || lowerLine.includes('failed') || lowerLine.includes('exception')) {
-                level = 'error';
-            } else if (lowerLine.includes('warning') || lowerLine.includes('warn')) {
-                level = 'warning';
-            }
-
             setLogs((prev) => {
                 const newLog = {
                     time: timeStr,
-                    level,
+                    level: levelOf(line, type),
                     message: line,
                     serverId
                 };
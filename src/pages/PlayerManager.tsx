@@ -1,7 +1,8 @@
 import { useState, useEffect, useRef } from 'react';
 import { Users, Terminal, RefreshCw, Send, ShieldAlert, LogOut, Ban, Map as MapIcon, Skull, Lock, Clock, Globe, FileText, CloudDownload } from 'lucide-react';
 import { cn } from '../utils/helpers';
-import { getOnlinePlayers, sendRconCommand, readFileContent, saveFileContent } from '../utils/tauri';
+import { getOnlinePlayers, sendRconCommand, kickPlayer, banPlayer, unbanPlayer, listBans } from '../utils/tauri';
+import { Player, Ban as BanEntry } from '../types';
 import toast from 'react-hot-toast';
 import { useServerStore } from '../stores/serverStore';
 
@@ -18,7 +19,7 @@ type Tab = 'players' | 'maps' | 'tribes' | 'dinos' | 'security' | 'automation' |
 export default function PlayerManager() {
     const { servers } = useServerStore();
     const [selectedServerId, setSelectedServerId] = useState<number | null>(null);
-    const [players, setPlayers] = useState<string[]>([]);
+    const [players, setPlayers] = useState<Player[]>([]);
     const [isLoading, setIsLoading] = useState(false);
     const [activeTab, setActiveTab] = useState<Tab>('players');
 
@@ -31,7 +32,7 @@ export default function PlayerManager() {
 
     // Ban Manager State
     const [showBanManager, setShowBanManager] = useState(false);
-    const [bannedPlayers, setBannedPlayers] = useState<string[]>([]);
+    const [bannedPlayers, setBannedPlayers] = useState<BanEntry[]>([]);
     const [isLoadingBans, setIsLoadingBans] = useState(false);
 
     // Auto-scroll console
@@ -89,50 +90,54 @@ export default function PlayerManager() {
 
     const handleKick = async (steamId: string) => {
         if (!selectedServerId) return;
-        if (!confirm(`Kick player ${steamId}?`)) return;
+        const reason = prompt(`Kick player ${steamId}? Reason (optional):`);
+        if (reason === null) return;
 
         try {
-            // ARK RCON: KickPlayer <SteamID>
-            const res = await sendRconCommand(selectedServerId, `KickPlayer ${steamId}`);
-            toast.success(`Kick command sent: ${res}`);
+            await kickPlayer(selectedServerId, steamId, reason || undefined);
+            toast.success(`Kicked ${steamId}`);
             setTimeout(fetchPlayers, 2000);
         } catch (e) {
             toast.error(`Failed to kick: ${e}`);
         }
     };
 
-
-
-    const handleBan = async (steamId: string) => {
+    const handleBan = async (steamId: string, name?: string) => {
         if (!selectedServerId) return;
-        if (!confirm(`Ban player ${steamId}?`)) return;
+        const reason = prompt(`Ban player ${name ?? steamId}? Reason (optional):`);
+        if (reason === null) return;
+        const hours = prompt('Ban for how many hours? Leave empty for a permanent ban.');
+        if (hours === null) return;
+
+        let expiresAt: string | undefined;
+        if (hours.trim()) {
+            const h = Number(hours);
+            if (!(h > 0)) {
+                toast.error('Ban length must be a positive number of hours');
+                return;
+            }
+            expiresAt = new Date(Date.now() + h * 3600_000).toISOString();
+        }
 
         try {
-            // ARK RCON: BanPlayer <SteamID>
-            const res = await sendRconCommand(selectedServerId, `BanPlayer ${steamId}`);
-            toast.success(`Ban command sent: ${res}`);
+            await banPlayer(selectedServerId, steamId, name, reason || undefined, expiresAt);
+            toast.success(expiresAt ? `Banned ${name ?? steamId} for ${hours.trim()}h` : `Banned ${name ?? steamId}`);
             setTimeout(fetchPlayers, 2000);
-            if (showBanManager) fetchBans(); // Refresh if open
         } catch (e) {
             toast.error(`Failed to ban: ${e}`);
+        } finally {
+            if (showBanManager) fetchBans(); // Refresh if open
         }
     };
 
     const fetchBans = async () => {
         if (!selectedServerId) return;
-        const server = servers.find(s => s.id === selectedServerId);
-        if (!server) return;
 
         setIsLoadingBans(true);
         try {
-            // Helper to handle both slashes
-            const path = `${server.installPath}/ShooterGame/Saved/BanList.txt`.replace(/\\/g, '/');
-            const content = await readFileContent(path);
-            const ids = content.split('\n').map(l => l.trim()).filter(l => l && !l.startsWith('#'));
-            setBannedPlayers(ids);
+            setBannedPlayers(await listBans(selectedServerId));
         } catch (error) {
-            console.error('Failed to read ban list:', error);
-            // Don't toast error on common 'file not found' for new servers
+            console.error('Failed to load bans:', error);
             setBannedPlayers([]);
         } finally {
             setIsLoadingBans(false);
@@ -141,28 +146,15 @@ export default function PlayerManager() {
 
     const handleUnban = async (steamId: string) => {
         if (!selectedServerId) return;
-        const server = servers.find(s => s.id === selectedServerId);
-        if (!server) return;
-
         if (!confirm(`Unban player ${steamId}?`)) return;
 
         try {
-            const path = `${server.installPath}/ShooterGame/Saved/BanList.txt`.replace(/\\/g, '/');
-            const newContent = bannedPlayers.filter(id => id !== steamId).join('\n');
-            await saveFileContent(path, newContent);
-
-            // Also try RCON Unban if available/needed or just reload bans
-            try {
-                // Some servers support UnbanPlayer
-                await sendRconCommand(selectedServerId, `UnbanPlayer ${steamId}`);
-            } catch {
-                // Ignore if unban command fail, file edit is primary
-            }
-
-            toast.success('Player unbanned (removed from BanList.txt)');
-            fetchBans();
+            await unbanPlayer(selectedServerId, steamId);
+            toast.success(`Unbanned ${steamId}`);
         } catch (error) {
             toast.error(`Failed to unban: ${error}`);
+        } finally {
+            fetchBans();
         }
     };
 
@@ -267,13 +259,11 @@ export default function PlayerManager() {
                                         </div>
                                     ) : (
                                         <div className="space-y-3">
-                                            {players.map((line, i) => {
-                                                const parts = line.split(',');
-                                                const name = parts[0] || line;
-                                                const id = parts[1] || 'Unknown ID';
+                                            {players.map((player) => {
+                                                const { name, platformId: id } = player;
 
                                                 return (
-                                                    <div key={i} className="bg-slate-800/50 rounded-xl p-4 flex items-center justify-between group hover:bg-slate-800 transition-colors border border-transparent hover:border-sky-500/20">
+                                                    <div key={player.index} className="bg-slate-800/50 rounded-xl p-4 flex items-center justify-between group hover:bg-slate-800 transition-colors border border-transparent hover:border-sky-500/20">
                                                         <div className="flex items-center space-x-4">
                                                             <div className="w-10 h-10 rounded-full bg-gradient-to-br from-sky-500 to-violet-500 flex items-center justify-center text-white font-bold text-lg">
                                                                 {name.charAt(0).toUpperCase()}
@@ -286,14 +276,14 @@ export default function PlayerManager() {
 
                                                         <div className="flex space-x-2 opacity-0 group-hover:opacity-100 transition-opacity">
                                                             <button
-                                                                onClick={() => handleKick(id.trim())}
+                                                                onClick={() => handleKick(id)}
                                                                 className="p-2 bg-amber-500/10 text-amber-500 hover:bg-amber-500 hover:text-white rounded-lg transition-colors border border-amber-500/20"
                                                                 title="Kick Player"
                                                             >
                                                                 <LogOut className="w-4 h-4" />
                                                             </button>
                                                             <button
-                                                                onClick={() => handleBan(id.trim())}
+                                                                onClick={() => handleBan(id, name)}
                                                                 className="p-2 bg-red-500/10 text-red-500 hover:bg-red-500 hover:text-white rounded-lg transition-colors border border-red-500/20"
                                                                 title="Ban Player"
                                                             >
@@ -380,13 +370,27 @@ export default function PlayerManager() {
                             <div className="space-y-4">
                                 <div className="max-h-[300px] overflow-y-auto space-y-2 pr-2">
                                     {bannedPlayers.length === 0 ? (
-                                        <p className="text-slate-500 text-center py-4">No banned players found in BanList.txt</p>
+                                        <p className="text-slate-500 text-center py-4">No banned players</p>
                                     ) : (
-                                        bannedPlayers.map((id, i) => (
-                                            <div key={i} className="flex items-center justify-between bg-slate-800/50 p-3 rounded-lg border border-slate-700/50">
-                                                <span className="text-slate-300 font-mono text-sm">{id}</span>
+                                        bannedPlayers.map((ban) => (
+                                            <div key={ban.id ?? ban.platformId} className="flex items-center justify-between bg-slate-800/50 p-3 rounded-lg border border-slate-700/50">
+                                                <div className="min-w-0">
+                                                    <div className="text-slate-300 text-sm">
+                                                        {ban.playerName && <span className="font-bold mr-2">{ban.playerName}</span>}
+                                                        <span className="font-mono">{ban.platformId}</span>
+                                                    </div>
+                                                    <div className="text-xs text-slate-500 truncate">
+                                                        {ban.id === undefined
+                                                            ? 'Only in BanList.txt'
+                                                            : [
+                                                                ban.reason,
+                                                                ban.expiresAt ? `until ${new Date(ban.expiresAt.replace(' ', 'T') + 'Z').toLocaleString()}` : 'permanent',
+                                                                ban.bannedBy && `by ${ban.bannedBy}`,
+                                                            ].filter(Boolean).join(' · ')}
+                                                    </div>
+                                                </div>
                                                 <button
-                                                    onClick={() => handleUnban(id)}
+                                                    onClick={() => handleUnban(ban.platformId)}
                                                     className="text-red-400 hover:text-red-300 hover:bg-red-900/20 p-2 rounded transition-colors text-xs font-bold"
                                                 >
                                                     UNBAN
@@ -415,7 +419,7 @@ export default function PlayerManager() {
                                         </button>
                                     </div>
                                     <p className="text-xs text-slate-500 mt-2">
-                                        Modifying: ShooterGame/Saved/BanList.txt
+                                        Bans are applied over RCON and kept in ShooterGame/Saved/BanList.txt
                                     </p>
                                 </div>
                             </div>
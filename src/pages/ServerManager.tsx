@@ -33,8 +33,7 @@ export default function ServerManager() {
         try {
             updateServerStatus(serverId, 'starting');
             await startServer(serverId);
-            updateServerStatus(serverId, 'running');
-            toast.success('Server started successfully');
+            toast.success('Server is starting');
         } catch (error) {
             updateServerStatus(serverId, 'stopped');
             toast.error(`Failed to start server: ${error}`);
@@ -55,8 +54,7 @@ export default function ServerManager() {
         try {
             updateServerStatus(serverId, 'starting');
             await restartServer(serverId);
-            updateServerStatus(serverId, 'running');
-            toast.success('Server restarted successfully');
+            toast.success('Server is restarting');
         } catch (error) {
             toast.error(`Failed to restart server: ${error}`);
         }
@@ -181,7 +179,9 @@ export default function ServerManager() {
                                             server.status === 'stopped' && 'bg-slate-500',
                                             server.status === 'crashed' && 'bg-red-500 shadow-[0_0_15px_rgba(239,68,68,0.5)]',
                                             server.status === 'starting' && 'bg-yellow-500 animate-pulse',
-                                            server.status === 'updating' && 'bg-blue-500 animate-pulse'
+                                            server.status === 'updating' && 'bg-blue-500 animate-pulse',
+                                            server.status === 'failed' && 'bg-red-500',
+                                            server.status === 'crash-looping' && 'bg-orange-500'
                                         )} />
                                         {server.status === 'running' && (
                                             <div className="absolute inset-0 bg-green-500 rounded-full animate-ping opacity-20"></div>
@@ -199,7 +199,9 @@ export default function ServerManager() {
                                                 server.status === 'stopped' && 'bg-slate-500/10 text-slate-400 border-slate-500/20',
                                                 server.status === 'crashed' && 'bg-red-500/10 text-red-400 border-red-500/20',
                                                 server.status === 'starting' && 'bg-yellow-500/10 text-yellow-400 border-yellow-500/20',
-                                                server.status === 'updating' && 'bg-blue-500/10 text-blue-400 border-blue-500/20'
+                                                server.status === 'updating' && 'bg-blue-500/10 text-blue-400 border-blue-500/20',
+                                                server.status === 'failed' && 'bg-red-500/10 text-red-400 border-red-500/20',
+                                                server.status === 'crash-looping' && 'bg-orange-500/10 text-orange-400 border-orange-500/20'
                                             )}>
                                                 {server.status.toUpperCase()}
                                             </span>
@@ -225,7 +227,7 @@ export default function ServerManager() {
                                 {/* Actions */}
                                 <div className="flex items-center gap-3">
                                     {/* Show Resume button for stuck installations */}
-                                    {server.status === 'updating' ? (
+                                    {server.status === 'updating' || server.status === 'installing' || server.status === 'stopping' ? (
                                         <button
                                             onClick={() => handleResetStuck()}
                                             className="flex items-center gap-2 px-3 py-2 bg-amber-500/10 hover:bg-amber-500/20 text-amber-400 border border-amber-500/20 rounded-lg transition-all"
@@ -234,7 +236,7 @@ export default function ServerManager() {
                                             <RefreshCcw className="w-4 h-4" />
                                             <span className="text-sm font-medium">Reset Status</span>
                                         </button>
-                                    ) : server.status === 'stopped' || server.status === 'crashed' ? (
+                                    ) : server.status === 'stopped' || server.status === 'crashed' || server.status === 'failed' || server.status === 'crash-looping' ? (
                                         <button
                                             onClick={() => handleStartServer(server.id)}
                                             className="p-2.5 bg-green-500/10 hover:bg-green-500/20 text-green-400 border border-green-500/20 rounded-lg transition-all hover:scale-105 active:scale-95"
@@ -2,7 +2,28 @@
 
 export type ServerType = 'ASE' | 'ASA';
 
-export type ServerStatus = 'stopped' | 'starting' | 'running' | 'crashed' | 'updating';
+export type ServerStatus =
+    | 'stopped'
+    | 'installing'
+    | 'starting'
+    | 'running'
+    | 'stopping'
+    | 'crashed'
+    | 'updating'
+    | 'failed'
+    | 'crash-looping'
+    | 'maintenance';
+
+/** One entry of a server's status history */
+export interface StatusChange {
+    id: number;
+    serverId: number;
+    /** Missing for the status a server was created with */
+    fromStatus?: ServerStatus;
+    toStatus: ServerStatus;
+    cause: string;
+    createdAt: string;
+}
 
 export interface Server {
     id: number;
@@ -15,6 +36,8 @@ export interface Server {
     createdAt: string;
     lastStarted?: string;
     pid?: number;
+    /** Set on clones sharing their game files with this server, which is the one to update */
+    sharedInstallOf?: number;
 }
 
 export interface ServerPorts {
@@ -23,6 +46,18 @@ export interface ServerPorts {
     rconPort: number;
 }
 
+/** A port a server needs that is already taken */
+export interface PortConflict {
+    port: number;
+    protocol: 'udp' | 'tcp';
+    /** `game`, `raw UDP`, `query` or `RCON` */
+    purpose: string;
+    /** Set when the clash is with another server */
+    conflictingServerId?: number;
+    conflictingServerName?: string;
+    message: string;
+}
+
 export interface ServerConfig {
     maxPlayers: number;
     serverPassword?: string;
@@ -32,9 +67,165 @@ export interface ServerConfig {
     motd?: string;
     battleyeEnabled?: boolean;
     multihomeIp?: string;
+    /** Host RCON is reached on; defaults to the MultiHome IP, then localhost */
+    rconHost?: string;
     crossplayEnabled?: boolean;
     autoRestart?: boolean;
     autoUpdate?: boolean;
+    runner?: RunnerConfig;
+    launchProfile?: LaunchProfile;
+}
+
+/** Fields to change on a server; omitted ones keep their value, an empty string clears optional text */
+export interface ServerSettingsUpdate extends Partial<Omit<ServerConfig, 'runner' | 'launchProfile'>> {
+    name?: string;
+    installPath?: string;
+    ports?: ServerPorts;
+    runner?: RunnerConfig;
+    launchProfile?: LaunchProfile;
+}
+
+/** Settings read from an existing install by `detectServerInstall` */
+export interface DetectedInstall {
+    installPath: string;
+    serverType: ServerType;
+    buildId?: string;
+    sessionName?: string;
+    mapName: string;
+    ports: ServerPorts;
+    maxPlayers: number;
+    serverPassword?: string;
+    /** Missing when the INI has none; a random one is generated on import */
+    adminPassword?: string;
+    motd?: string;
+    activeMods: string[];
+    /** Things that were guessed rather than read */
+    warnings: string[];
+}
+
+export interface ImportedSchedule {
+    taskType: string;
+    cronExpression: string;
+    payload?: string;
+}
+
+export interface IniSetting {
+    /** `GameUserSettings.ini` or `Game.ini` */
+    file: string;
+    section: string;
+    key: string;
+    value: string;
+}
+
+export interface UnmappedSetting {
+    name: string;
+    value: string;
+}
+
+/** What a classic Ark Server Manager `.profile` maps to, and what it doesn't */
+export interface AsmProfileImport {
+    /** Set once the profile has been imported */
+    serverId?: number;
+    profileName?: string;
+    install: DetectedInstall;
+    settings: ServerSettingsUpdate;
+    schedules: ImportedSchedule[];
+    iniSettings: IniSetting[];
+    unmapped: UnmappedSetting[];
+}
+
+/** `copy` duplicates the game files; `shared` links them, so updating either server updates both */
+export type CloneInstallMode = 'copy' | 'shared';
+
+export interface CloneServerOptions {
+    /** Next to the source, named after the clone, when omitted */
+    installPath?: string;
+    installMode?: CloneInstallMode;
+    copySaves?: boolean;
+    copyMods?: boolean;
+    copyLaunchProfile?: boolean;
+    /** Start from this backup's saves and configs instead of the live server */
+    fromBackupId?: number;
+    /** Free ports are picked when omitted */
+    ports?: ServerPorts;
+}
+
+export type RunnerKind = 'direct' | 'wine' | 'proton';
+
+export interface RunnerConfig {
+    kind: RunnerKind;
+    path?: string;
+    prefix?: string;
+}
+
+export interface LaunchOption {
+    key: string;
+    /** Omit for a bare `?Key` */
+    value?: string;
+}
+
+export interface LaunchEnvVar {
+    name: string;
+    value: string;
+}
+
+export interface LaunchProfile {
+    options: LaunchOption[];
+    flags: string[];
+    env: LaunchEnvVar[];
+}
+
+export interface LaunchPreview {
+    program: string;
+    args: string[];
+    env: LaunchEnvVar[];
+    workingDir: string;
+    commandLine: string;
+}
+
+/** How a server is judged ready; until then it stays `starting` */
+export type ReadinessProbe =
+    | { kind: 'logLine'; pattern: string }
+    | { kind: 'queryPort' }
+    | { kind: 'rcon' };
+
+export interface ReadinessConfig {
+    probe: ReadinessProbe;
+    /** After this long without passing the probe the server is marked `failed` and stopped */
+    startupTimeoutSecs: number;
+}
+
+/** Limits on automatic restarts after crashes */
+export interface RestartPolicy {
+    /** Restarts allowed within `windowSecs` before the server is marked `crash-looping` */
+    maxRestarts: number;
+    windowSecs: number;
+    /** Wait before the first restart; doubles for each further one in the window */
+    initialBackoffSecs: number;
+    maxBackoffSecs: number;
+}
+
+/** Payload of the `server-ready` event */
+export interface ServerReadyEvent {
+    serverId: number;
+    secondsToReady: number;
+}
+
+export type StopPhase =
+    | 'warning'
+    | 'saving'
+    | 'saved'
+    | 'save-timed-out'
+    | 'exiting'
+    | 'terminating'
+    | 'killing'
+    | 'stopped';
+
+/** Payload of the `server-stop-phase` event */
+export interface StopPhaseEvent {
+    serverId: number;
+    phase: StopPhase;
+    message: string;
 }
 
 export interface SystemInfo {
@@ -83,6 +274,38 @@ export interface Backup {
     note?: string;
 }
 
+/** Logs, dumps and context collected when a server exits unexpectedly */
+export interface CrashBundle {
+    id: number;
+    serverId: number;
+    exitCode?: number;
+    bundlePath: string;
+    size: number;
+    createdAt: string;
+}
+
+export interface CrashBundleFile {
+    /** Relative to the bundle, e.g. `console.log` or `Logs/ShooterGame.log` */
+    path: string;
+    size: number;
+}
+
+export interface CrashBundleDetail {
+    bundle: CrashBundle;
+    /** Contents of `summary.json`: exit code, times, runner and command line */
+    summary: Record<string, unknown>;
+    files: CrashBundleFile[];
+}
+
+/** One line of server output, kept in memory and in the on-disk console log */
+export interface ConsoleLine {
+    /** Increases across all servers; pass the last one as `since` to get newer lines */
+    seq: number;
+    timestamp: string;
+    stream: 'stdout' | 'stderr' | 'log';
+    line: string;
+}
+
 export interface Cluster {
     id: number;
     name: string;
@@ -121,3 +344,145 @@ export interface Schedule {
     enabled: boolean;
     lastRun?: string;
 }
+
+/** Scopes an HTTP API token can be given; `*` grants all of them */
+export type ApiScope =
+    | '*'
+    | 'servers:read'
+    | 'servers:control'
+    | 'rcon'
+    | 'backups:read'
+    | 'backups:write'
+    | 'schedules:read'
+    | 'schedules:write'
+    | 'mods:read'
+    | 'mods:write'
+    | 'config:read'
+    | 'config:write'
+    | 'metrics';
+
+/** An HTTP API token; only a hash of its secret is stored */
+export interface ApiToken {
+    id: number;
+    name: string;
+    scopes: ApiScope[];
+    createdAt: string;
+    lastUsedAt?: string;
+}
+
+/** A freshly created token; `secret` is shown this once */
+export interface NewApiToken {
+    token: ApiToken;
+    secret: string;
+}
+
+/** Steam64 IDs on ASE, Epic Online Services IDs on ASA */
+export type PlayerPlatform = 'steam' | 'eos' | 'unknown';
+
+/** A player online on a server, from `ListPlayers` */
+export interface Player {
+    index: number;
+    name: string;
+    platformId: string;
+    platform: PlayerPlatform;
+}
+
+/** One stretch of a player being on a server; times are UTC */
+export interface PlayerSession {
+    id: number;
+    serverId: number;
+    platformId: string;
+    platform: PlayerPlatform;
+    name: string;
+    joinedAt: string;
+    /** Missing while the player is online */
+    leftAt?: string;
+}
+
+/** Totals for one player over their recorded sessions */
+export interface PlayerStats {
+    platformId: string;
+    platform: PlayerPlatform;
+    name: string;
+    sessions: number;
+    playtimeSeconds: number;
+    lastSeen: string;
+    online: boolean;
+}
+
+/** Most players online at once during one UTC day */
+export interface DailyPeak {
+    date: string;
+    peak: number;
+}
+
+export type ChatKind = 'player' | 'server' | 'admin' | 'tribe';
+
+/** A chat line captured with GetChat; `sentAt` is UTC, when it was fetched */
+export interface ChatMessage {
+    id: number;
+    serverId: number;
+    kind: ChatKind;
+    sender: string;
+    character?: string;
+    tribe?: string;
+    message: string;
+    sentAt: string;
+}
+
+/** A ban on one server; bans only found in BanList.txt have no `id` or details. Times are UTC */
+export interface Ban {
+    id?: number;
+    serverId: number;
+    platformId: string;
+    playerName?: string;
+    reason?: string;
+    bannedBy?: string;
+    createdAt?: string;
+    /** Unset for permanent bans */
+    expiresAt?: string;
+    liftedAt?: string;
+    liftedBy?: string;
+}
+
+export type ModerationKind = 'kick' | 'ban' | 'unban';
+
+/** An entry in the moderation audit log; `error` is set when the action failed on the server */
+export interface ModerationAction {
+    id: number;
+    serverId: number;
+    action: ModerationKind;
+    platformId: string;
+    playerName?: string;
+    reason?: string;
+    actor: string;
+    error?: string;
+    createdAt: string;
+}
+
+/** Lifecycle events a webhook can subscribe to */
+export type WebhookEvent = 'crash' | 'start' | 'stop' | 'update' | 'backup-failed' | 'player-joined';
+
+/** An outbound webhook; `template` is a JSON body with `{{placeholders}}` */
+export interface Webhook {
+    id?: number;
+    name: string;
+    url: string;
+    events: WebhookEvent[];
+    template?: string;
+    /** HMAC key for `X-ASM-Signature`; generated when left empty */
+    secret: string;
+    enabled: boolean;
+}
+
+/** One attempt at delivering a webhook */
+export interface WebhookDelivery {
+    id: number;
+    webhookId: number;
+    event: string;
+    attempt: number;
+    statusCode?: number;
+    error?: string;
+    delivered: boolean;
+    createdAt: string;
+}
@@ -8,6 +8,37 @@ import type {
     Cluster,
     ServerType,
     Schedule,
+    RunnerKind,
+    LaunchProfile,
+    LaunchPreview,
+    ReadinessConfig,
+    RestartPolicy,
+    CrashBundle,
+    CrashBundleDetail,
+    ConsoleLine,
+    ServerPorts,
+    PortConflict,
+    ServerSettingsUpdate,
+    CloneServerOptions,
+    DetectedInstall,
+    AsmProfileImport,
+    StatusChange,
+    ApiScope,
+    ApiToken,
+    NewApiToken,
+    Webhook,
+    WebhookEvent,
+    WebhookDelivery,
+    Player,
+    PlayerPlatform,
+    PlayerSession,
+    PlayerStats,
+    DailyPeak,
+    ChatKind,
+    ChatMessage,
+    Ban,
+    ModerationKind,
+    ModerationAction,
 } from '../types';
 
 export type {
@@ -18,6 +49,37 @@ export type {
     Cluster,
     ServerType,
     Schedule,
+    RunnerKind,
+    LaunchProfile,
+    LaunchPreview,
+    ReadinessConfig,
+    RestartPolicy,
+    CrashBundle,
+    CrashBundleDetail,
+    ConsoleLine,
+    ServerPorts,
+    PortConflict,
+    ServerSettingsUpdate,
+    CloneServerOptions,
+    DetectedInstall,
+    AsmProfileImport,
+    StatusChange,
+    ApiScope,
+    ApiToken,
+    NewApiToken,
+    Webhook,
+    WebhookEvent,
+    WebhookDelivery,
+    Player,
+    PlayerPlatform,
+    PlayerSession,
+    PlayerStats,
+    DailyPeak,
+    ChatKind,
+    ChatMessage,
+    Ban,
+    ModerationKind,
+    ModerationAction,
 };
 
 // ============================================================================
@@ -52,6 +114,11 @@ export async function getServerById(serverId: number): Promise<Server | null> {
     return await invoke('get_server_by_id', { serverId });
 }
 
+/** Ports, install path, MultiHome IP, admin password and runner only change while stopped */
+export async function updateServerSettings(serverId: number, update: ServerSettingsUpdate): Promise<Server> {
+    return await invoke('update_server_settings', { serverId, update });
+}
+
 export interface InstallServerParams {
     serverType: ServerType;
     installPath: string;
@@ -74,6 +141,31 @@ export async function installServer(params: InstallServerParams): Promise<Server
     });
 }
 
+/** What `importServer` would register for an install folder; nothing is written */
+export async function detectServerInstall(path: string): Promise<DetectedInstall> {
+    return await invoke('detect_server_install', { path });
+}
+
+/** Register an install that is already on disk; `name` defaults to its session name */
+export async function importServer(path: string, name?: string): Promise<Server> {
+    return await invoke('import_server', { path, name });
+}
+
+/** Read a classic Ark Server Manager `.profile` and report what importing it would do; nothing is written */
+export async function previewAsmProfile(path: string, installPath?: string): Promise<AsmProfileImport> {
+    return await invoke('preview_asm_profile', { path, installPath });
+}
+
+/** Import a classic Ark Server Manager `.profile`; `installPath` overrides the profile's install directory */
+export async function importAsmProfile(path: string, installPath?: string, name?: string): Promise<AsmProfileImport> {
+    return await invoke('import_asm_profile', { path, installPath, name });
+}
+
+/** Returns the clone right away; `server-clone-complete` fires once its files are copied */
+export async function cloneServer(sourceId: number, newName: string, options: CloneServerOptions = {}): Promise<Server> {
+    return await invoke('clone_server', { sourceId, newName, options });
+}
+
 export async function startServer(serverId: number): Promise<void> {
     return await invoke('start_server', { serverId });
 }
@@ -181,6 +273,46 @@ export async function viewBackupContent(backupPath: string): Promise<BackupFileI
     return await invoke('view_backup_content', { backupPath });
 }
 
+// ============================================================================
+// Crash Bundle Commands
+// ============================================================================
+
+/** Newest first; all servers when `serverId` is omitted */
+export async function getCrashBundles(serverId?: number): Promise<CrashBundle[]> {
+    return await invoke('get_crash_bundles', { serverId });
+}
+
+export async function getCrashBundle(bundleId: number): Promise<CrashBundleDetail> {
+    return await invoke('get_crash_bundle', { bundleId });
+}
+
+export async function readCrashBundleFile(bundleId: number, path: string): Promise<string> {
+    return await invoke('read_crash_bundle_file', { bundleId, path });
+}
+
+/** Zip the bundle to `destination` */
+export async function exportCrashBundle(bundleId: number, destination: string): Promise<void> {
+    return await invoke('export_crash_bundle', { bundleId, destination });
+}
+
+export async function deleteCrashBundle(bundleId: number): Promise<void> {
+    return await invoke('delete_crash_bundle', { bundleId });
+}
+
+// ============================================================================
+// Console Commands
+// ============================================================================
+
+/** Recent output, oldest first; only lines after `since` when given */
+export async function getConsoleHistory(serverId: number, since?: number, limit?: number): Promise<ConsoleLine[]> {
+    return await invoke('get_console_history', { serverId, since, limit });
+}
+
+/** Lines containing `query` from the whole on-disk console log */
+export async function searchConsoleHistory(serverId: number, query: string, limit?: number): Promise<ConsoleLine[]> {
+    return await invoke('search_console_history', { serverId, query, limit });
+}
+
 // ============================================================================
 // Cluster Commands
 // ============================================================================
@@ -217,10 +349,67 @@ export async function sendRconCommand(serverId: number, command: string): Promis
     return await invoke('send_rcon_command', { serverId, command });
 }
 
-export async function getOnlinePlayers(serverId: number): Promise<string[]> {
+export async function getOnlinePlayers(serverId: number): Promise<Player[]> {
     return await invoke('get_online_players', { serverId });
 }
 
+/** Everyone online on any server, as of the last session poll */
+export async function getAllOnlinePlayers(): Promise<PlayerSession[]> {
+    return await invoke('get_all_online_players');
+}
+
+/** Playtime and last seen per player, most playtime first */
+export async function getPlayerStats(serverId?: number): Promise<PlayerStats[]> {
+    return await invoke('get_player_stats', { serverId });
+}
+
+/** A player's sessions on any server, newest first */
+export async function getPlayerHistory(platformId: string, limit?: number): Promise<PlayerSession[]> {
+    return await invoke('get_player_history', { platformId, limit });
+}
+
+export async function getDailyPlayerPeaks(serverId?: number, days?: number): Promise<DailyPeak[]> {
+    return await invoke('get_daily_player_peaks', { serverId, days });
+}
+
+/** Captured chat, newest first; pass the smallest id seen as `beforeId` for older lines */
+export async function getChatMessages(
+    serverId?: number,
+    query?: string,
+    beforeId?: number,
+    limit?: number,
+): Promise<ChatMessage[]> {
+    return await invoke('get_chat_messages', { serverId, query, beforeId, limit });
+}
+
+export async function kickPlayer(serverId: number, platformId: string, reason?: string): Promise<void> {
+    return await invoke('kick_player', { serverId, platformId, reason });
+}
+
+/** Ban a player; `expiresAt` (RFC 3339) makes the ban temporary */
+export async function banPlayer(
+    serverId: number,
+    platformId: string,
+    playerName?: string,
+    reason?: string,
+    expiresAt?: string,
+): Promise<Ban> {
+    return await invoke('ban_player', { serverId, platformId, playerName, reason, expiresAt });
+}
+
+export async function unbanPlayer(serverId: number, platformId: string, reason?: string): Promise<void> {
+    return await invoke('unban_player', { serverId, platformId, reason });
+}
+
+export async function listBans(serverId: number, includeLifted?: boolean): Promise<Ban[]> {
+    return await invoke('list_bans', { serverId, includeLifted });
+}
+
+/** Kicks, bans and unbans with who made them, newest first */
+export async function getModerationLog(serverId?: number, limit?: number): Promise<ModerationAction[]> {
+    return await invoke('get_moderation_log', { serverId, limit });
+}
+
 export async function destroyWildDinos(serverId: number): Promise<string> {
     return await invoke('destroy_wild_dinos', { serverId });
 }
@@ -295,6 +484,16 @@ export async function setCrossplayEnabled(serverId: number, enabled: boolean): P
     return await invoke('set_crossplay_enabled', { serverId, enabled });
 }
 
+/** Clashes with other servers and with ports already bound on this machine */
+export async function checkPortConflicts(ports: ServerPorts, serverId?: number, multihomeIp?: string): Promise<PortConflict[]> {
+    return await invoke('check_port_conflicts', { serverId, ports, multihomeIp });
+}
+
+/** Free game/query/RCON port blocks, lowest first */
+export async function suggestServerPorts(count?: number, multihomeIp?: string): Promise<ServerPorts[]> {
+    return await invoke('suggest_server_ports', { multihomeIp, count });
+}
+
 // ============================================================================
 // File Manager Commands
 // ============================================================================
@@ -339,6 +538,103 @@ export async function setAutoUpdate(serverId: number, enabled: boolean): Promise
     return await invoke('set_auto_update', { serverId, enabled });
 }
 
+export async function setServerRunner(
+    serverId: number,
+    kind: RunnerKind,
+    path?: string,
+    prefix?: string,
+): Promise<void> {
+    return await invoke('set_server_runner', { serverId, kind, path, prefix });
+}
+
+export async function getLaunchProfile(serverId: number): Promise<LaunchProfile> {
+    return await invoke('get_launch_profile', { serverId });
+}
+
+export async function saveLaunchProfile(serverId: number, profile: LaunchProfile): Promise<void> {
+    return await invoke('save_launch_profile', { serverId, profile });
+}
+
+/** Pass `profile` to preview unsaved edits instead of the stored profile */
+export async function previewLaunchCommand(serverId: number, profile?: LaunchProfile): Promise<LaunchPreview> {
+    return await invoke('preview_launch_command', { serverId, profile });
+}
+
+export async function getReadinessConfig(serverId: number): Promise<ReadinessConfig> {
+    return await invoke('get_readiness_config', { serverId });
+}
+
+export async function saveReadinessConfig(serverId: number, config: ReadinessConfig): Promise<void> {
+    return await invoke('save_readiness_config', { serverId, config });
+}
+
+export async function getRestartPolicy(serverId: number): Promise<RestartPolicy> {
+    return await invoke('get_restart_policy', { serverId });
+}
+
+export async function saveRestartPolicy(serverId: number, policy: RestartPolicy): Promise<void> {
+    return await invoke('save_restart_policy', { serverId, policy });
+}
+
+/** Clear a `crash-looping` server back to `stopped` so auto-restart can try again */
+export async function resetCrashLoop(serverId: number): Promise<void> {
+    return await invoke('reset_crash_loop', { serverId });
+}
+
+/** Put a stopped server into `maintenance`, where nothing can start it, or release it */
+export async function setServerMaintenance(serverId: number, enabled: boolean): Promise<void> {
+    return await invoke('set_server_maintenance', { serverId, enabled });
+}
+
+/** Status changes with their causes, newest first */
+export async function getServerStatusHistory(serverId: number, limit?: number): Promise<StatusChange[]> {
+    return await invoke('get_server_status_history', { serverId, limit });
+}
+
+// ============================================================================
+// HTTP API
+// ============================================================================
+
+/** The API itself is turned on with the `http_api_enabled` and `http_api_bind` settings */
+export async function listApiTokens(): Promise<ApiToken[]> {
+    return await invoke('list_api_tokens');
+}
+
+export async function createApiToken(name: string, scopes: ApiScope[]): Promise<NewApiToken> {
+    return await invoke('create_api_token', { name, scopes });
+}
+
+export async function revokeApiToken(tokenId: number): Promise<void> {
+    return await invoke('revoke_api_token', { tokenId });
+}
+
+// ============================================================================
+// Webhooks
+// ============================================================================
+
+export async function listWebhooks(): Promise<Webhook[]> {
+    return await invoke('list_webhooks');
+}
+
+/** Creates the webhook when it has no id; returns it with its id and secret */
+export async function saveWebhook(webhook: Webhook): Promise<Webhook> {
+    return await invoke('save_webhook', { webhook });
+}
+
+export async function deleteWebhook(webhookId: number): Promise<void> {
+    return await invoke('delete_webhook', { webhookId });
+}
+
+/** Sends a `test` event once and rejects with the error if it isn't accepted */
+export async function testWebhook(webhookId: number): Promise<void> {
+    return await invoke('test_webhook', { webhookId });
+}
+
+/** Delivery attempts, newest first */
+export async function getWebhookDeliveries(webhookId: number, limit?: number): Promise<WebhookDelivery[]> {
+    return await invoke('get_webhook_deliveries', { webhookId, limit });
+}
+
 export async function updateServerGraceful(serverId: number): Promise<void> {
     return await invoke('update_server_graceful', { serverId });
 }